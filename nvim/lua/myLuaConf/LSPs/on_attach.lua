@@ -36,6 +36,15 @@ return function(_, bufnr)
   nmap("<leader>lm", ts_cmd("TSToolsAddMissingImports"), "[L]SP Add [M]issing Imports")
   nmap("gd", vim.lsp.buf.definition, "[G]oto [D]efinition")
   nmap("gD", plugin_util.goto_definition_other_window, "[G]oto [D]efinition (other window)")
+  nmap("<leader>lp", function()
+    plugin_util.goto_definition({ target = "float" })
+  end, "[L]SP [P]eek Definition")
+  nmap("<leader>lR", function()
+    plugin_util.goto_definition({ method = "references", target = "vsplit", multiple = "picker" })
+  end, "[L]SP [R]eferences (picker)")
+  nmap("<leader>lo", function()
+    plugin_util.goto_definition({ strategy = "ace" })
+  end, "[L]SP Definition in chosen [O]ther window")
   nmap("gI", vim.lsp.buf.implementation, "[G]oto [I]mplementation")
   nmap("gr", vim.lsp.buf.references, "[G]oto [R]eferences")
   nmap("<leader>ds", vim.lsp.buf.document_symbol, "[D]ocument [S]ymbols")
@@ -19,23 +19,45 @@ monokai.setup(monokai_opts)
 
 ---@type nvimrs_theme_switcher.ThemeSpec[]
 local CONFIGURED_THEMES = {
-  { name = "Monokai Pro", colorscheme = "monokai-pro" },
-  { name = "Kanagawa Wave", colorscheme = "kanagawa-wave" },
-  { name = "Kanagawa Dragon", colorscheme = "kanagawa-dragon" },
-  { name = "Kanagawa Lotus", colorscheme = "kanagawa-lotus" },
-  { name = "Modus Operandi", colorscheme = "modus_operandi" },
-  { name = "Modus Operandi Tinted", colorscheme = "modus_operandi_tinted" },
-  { name = "Modus Operandi Deuteranopia", colorscheme = "modus_operandi_deuteranopia" },
-  { name = "Modus Operandi Tritanopia", colorscheme = "modus_operandi_tritanopia" },
-  { name = "Modus Vivendi", colorscheme = "modus_vivendi" },
-  { name = "Modus Vivendi Tinted", colorscheme = "modus_vivendi_tinted" },
-  { name = "Modus Vivendi Deuteranopia", colorscheme = "modus_vivendi_deuteranopia" },
-  { name = "Modus Vivendi Tritanopia", colorscheme = "modus_vivendi_tritanopia" },
+  { name = "Monokai Pro", colorscheme = "monokai-pro", background = "dark" },
+  { name = "Kanagawa Wave", colorscheme = "kanagawa-wave", background = "dark" },
+  { name = "Kanagawa Dragon", colorscheme = "kanagawa-dragon", background = "dark" },
+  { name = "Kanagawa Lotus", colorscheme = "kanagawa-lotus", background = "light" },
+  { name = "Modus Operandi", colorscheme = "modus_operandi", background = "light" },
+  { name = "Modus Operandi Tinted", colorscheme = "modus_operandi_tinted", background = "light" },
+  { name = "Modus Operandi Deuteranopia", colorscheme = "modus_operandi_deuteranopia", background = "light" },
+  { name = "Modus Operandi Tritanopia", colorscheme = "modus_operandi_tritanopia", background = "light" },
+  { name = "Modus Vivendi", colorscheme = "modus_vivendi", background = "dark" },
+  { name = "Modus Vivendi Tinted", colorscheme = "modus_vivendi_tinted", background = "dark" },
+  { name = "Modus Vivendi Deuteranopia", colorscheme = "modus_vivendi_deuteranopia", background = "dark" },
+  { name = "Modus Vivendi Tritanopia", colorscheme = "modus_vivendi_tritanopia", background = "dark" },
 }
 
+--- Automatic switching: follow `NVIMRS_APPEARANCE` (e.g. exported by the
+--- terminal's theme hook) and fall back to a day/night schedule.
+---@type nvimrs_theme_switcher.AutoArgs
+local AUTO_SWITCHING = {
+  themes = {},
+  schedule = {
+    { at = "07:00", colorscheme = "kanagawa-lotus" },
+    { at = "19:00", colorscheme = "kanagawa-wave" },
+  },
+  appearance = {
+    env = "NVIMRS_APPEARANCE",
+    dark = "kanagawa-wave",
+    light = "kanagawa-lotus",
+  },
+}
+local AUTO_INTERVAL_MS = 60 * 1000
+local AUTO_AUGROUP = "NvimrsThemeAuto"
+
+---@type uv.uv_timer_t|nil
+local auto_timer = nil
+
 ---@return string
 local function state_path()
-  return vim.fn.stdpath("state") .. "/nvimrs-theme-switcher/colorscheme.txt"
+  -- A legacy colorscheme.txt next to it is migrated on first read.
+  return vim.fn.stdpath("state") .. "/nvimrs-theme-switcher/state.json"
 end
 
 ---@return table<string, true>
@@ -62,6 +84,7 @@ local function discover_themes()
       themes[#themes + 1] = {
         name = theme.name,
         colorscheme = theme.colorscheme,
+        background = theme.background,
       }
     end
   end
@@ -75,22 +98,25 @@ local function apply_colorscheme(colorscheme)
   return ok
 end
 
----@param path string
+--- Root of the current buffer's project, used for per-project theme pins.
 ---@return string|nil
-local function read_persisted_colorscheme(path)
-  local ok, lines = pcall(vim.fn.readfile, path)
-  if not ok or type(lines) ~= "table" or #lines == 0 then
-    return nil
-  end
-  local value = vim.trim(lines[1] or "")
-  if value == "" then
-    return nil
+local function project_root()
+  local ok, root = pcall(function()
+    return require("myLuaConf.project").project_root()
+  end)
+  if ok then
+    return root
   end
-  return value
+  return nil
+end
+
+---@return nvimrs_theme_switcher.StateArgs
+local function state_args()
+  return { state_path = state_path(), project_root = project_root() }
 end
 
 function M.apply()
-  local persisted = read_persisted_colorscheme(state_path())
+  local persisted = rust_switcher.persisted_colorscheme(state_args())
   local candidate = persisted or DEFAULT_COLORSCHEME
   if apply_colorscheme(candidate) then
     return
@@ -113,6 +139,8 @@ local function switcher_args(themes)
     themes = themes,
     current_colorscheme = vim.g.colors_name,
     state_path = state_path(),
+    project_root = project_root(),
+    live_preview = true,
   }
 end
 
@@ -150,4 +178,88 @@ function M.prev_theme()
   rust_switcher.cycle_prev(switcher_args(themes))
 end
 
+--- Step back to the previously used theme; repeat to walk further back.
+function M.older_theme()
+  local themes = configured_themes_or_warn()
+  if themes == nil then
+    return
+  end
+  rust_switcher.cycle_next(vim.tbl_extend("force", switcher_args(themes), { order = "history" }))
+end
+
+function M.newer_theme()
+  local themes = configured_themes_or_warn()
+  if themes == nil then
+    return
+  end
+  rust_switcher.cycle_prev(vim.tbl_extend("force", switcher_args(themes), { order = "history" }))
+end
+
+function M.pin_project_theme()
+  rust_switcher.pin_project(state_args())
+end
+
+function M.unpin_project_theme()
+  rust_switcher.unpin_project(state_args())
+end
+
+--- Apply the pinned (or global) theme when the current project changes.
+function M.follow_project_pins()
+  vim.api.nvim_create_autocmd("User", {
+    group = vim.api.nvim_create_augroup("NvimrsThemeProjectPins", { clear = true }),
+    pattern = "ProjectRootChanged",
+    callback = function(args)
+      if args.data.buf ~= vim.api.nvim_get_current_buf() then
+        return
+      end
+      local persisted =
+        rust_switcher.persisted_colorscheme({ state_path = state_path(), project_root = args.data.root })
+      if persisted and persisted ~= vim.g.colors_name then
+        apply_colorscheme(persisted)
+      end
+    end,
+  })
+end
+
+function M.start_auto()
+  local themes = configured_themes_or_warn()
+  if themes == nil then
+    return
+  end
+  local args = vim.tbl_extend("force", AUTO_SWITCHING, { themes = themes, state_path = state_path() })
+  rust_switcher.start_auto(args)
+
+  if auto_timer == nil then
+    auto_timer = vim.uv.new_timer()
+    auto_timer:start(AUTO_INTERVAL_MS, AUTO_INTERVAL_MS, vim.schedule_wrap(rust_switcher.auto_tick))
+  end
+  local group = vim.api.nvim_create_augroup(AUTO_AUGROUP, { clear = true })
+  vim.api.nvim_create_autocmd("FocusGained", {
+    group = group,
+    callback = function()
+      rust_switcher.auto_tick()
+    end,
+  })
+end
+
+function M.stop_auto()
+  rust_switcher.stop_auto()
+  if auto_timer ~= nil then
+    auto_timer:stop()
+    auto_timer:close()
+    auto_timer = nil
+  end
+  pcall(vim.api.nvim_del_augroup_by_name, AUTO_AUGROUP)
+end
+
+function M.toggle_auto()
+  if auto_timer == nil then
+    M.start_auto()
+    vim.notify("Automatic theme switching enabled", vim.log.levels.INFO)
+  else
+    M.stop_auto()
+    vim.notify("Automatic theme switching disabled", vim.log.levels.INFO)
+  end
+end
+
 return M
@@ -42,6 +42,15 @@ local function git_init()
   end
 end
 
+---@param name string
+---@param fn fun(opts?: nvimrs_text.RangeArgs)
+---@param desc string
+local function text_range_command(name, fn, desc)
+  vim.api.nvim_create_user_command(name, function(cmd)
+    fn({ start_line = cmd.line1, end_line = cmd.line2 })
+  end, { range = true, desc = desc })
+end
+
 local function reload_nixcats()
   if vim.fn.exists(":NixCatsReload") ~= 2 then
     vim.notify("NixCatsReload command unavailable", vim.log.levels.WARN)
@@ -98,13 +107,25 @@ function M.setup()
     end,
   })
 
-  -- Emacs/readline-style bindings (insert).
-  Snacks.keymap.set("i", "<C-a>", readline.beginning_of_line, { desc = "Beginning of line" })
-  Snacks.keymap.set("i", "<C-e>", readline.end_of_line, { desc = "End of line" })
-  Snacks.keymap.set("i", "<C-t>", readline.transpose_chars, { desc = "Transpose chars" })
-  Snacks.keymap.set("i", "<M-f>", readline.forward_word, { desc = "Forward word" })
-  Snacks.keymap.set("i", "<M-b>", readline.backward_word, { desc = "Backward word" })
-  Snacks.keymap.set("i", "<M-d>", readline.kill_word, { desc = "Kill word" })
+  -- Emacs/readline-style bindings (insert and command line). Terminal mode is
+  -- left unmapped: the shell owns its prompt line and runs its own readline.
+  Snacks.keymap.set({ "i", "c" }, "<C-a>", readline.beginning_of_line, { desc = "Beginning of line" })
+  Snacks.keymap.set({ "i", "c" }, "<C-e>", readline.end_of_line, { desc = "End of line" })
+  Snacks.keymap.set({ "i", "c" }, "<C-t>", readline.transpose_chars, { desc = "Transpose chars" })
+  Snacks.keymap.set({ "i", "c" }, "<M-t>", readline.transpose_words, { desc = "Transpose words" })
+  Snacks.keymap.set({ "i", "c" }, "<M-f>", readline.forward_word, { desc = "Forward word" })
+  Snacks.keymap.set({ "i", "c" }, "<M-b>", readline.backward_word, { desc = "Backward word" })
+  Snacks.keymap.set({ "i", "c" }, "<M-u>", readline.upcase_word, { desc = "Upcase word" })
+  Snacks.keymap.set({ "i", "c" }, "<M-l>", readline.downcase_word, { desc = "Downcase word" })
+  Snacks.keymap.set({ "i", "c" }, "<M-c>", readline.capitalize_word, { desc = "Capitalize word" })
+
+  -- Kill ring (insert and command line).
+  Snacks.keymap.set({ "i", "c" }, "<M-d>", readline.kill_word, { desc = "Kill word" })
+  Snacks.keymap.set({ "i", "c" }, "<M-BS>", readline.backward_kill_word, { desc = "Backward kill word" })
+  Snacks.keymap.set({ "i", "c" }, "<C-k>", readline.kill_line, { desc = "Kill line" })
+  Snacks.keymap.set({ "i", "c" }, "<C-u>", readline.unix_line_discard, { desc = "Kill to beginning of line" })
+  Snacks.keymap.set({ "i", "c" }, "<C-y>", readline.yank, { desc = "Yank" })
+  Snacks.keymap.set({ "i", "c" }, "<M-y>", readline.yank_pop, { desc = "Yank pop" })
 end
 
 ---@return wk.Spec
@@ -284,6 +305,13 @@ function M.list()
       end,
       desc = "Switch project",
     },
+    {
+      "<leader>pP",
+      function()
+        project.pick_project()
+      end,
+      desc = "Switch project (frecency)",
+    },
     {
       "<leader>pf",
       function()
@@ -428,6 +456,27 @@ function M.list()
     },
     { "<leader>bj", project.show_project_root, desc = "Project root" },
     { "<leader>bd", plugin_util.delete_current_buffer, desc = "Delete" },
+    {
+      "<leader>bo",
+      function()
+        plugin_util.only_other_buffers()
+      end,
+      desc = "Delete others",
+    },
+    {
+      "<leader>bh",
+      function()
+        plugin_util.delete_hidden_buffers()
+      end,
+      desc = "Delete hidden",
+    },
+    {
+      "<leader>bP",
+      function()
+        plugin_util.delete_buffers_outside_project()
+      end,
+      desc = "Delete outside project",
+    },
     { "<leader>bn", "<cmd>bnext<cr>", desc = "Next" },
     { "<leader>bp", "<cmd>bprev<cr>", desc = "Prev" },
     {
@@ -484,10 +533,116 @@ function M.list()
     },
   })
 
+  text_range_command("SortLines", text.sort_lines, "Sort lines")
+  text_range_command("SortLinesReverse", text.sort_lines_reverse, "Sort lines (reverse)")
+  text_range_command("UniquifyLines", text.uniquify_lines, "Uniquify lines")
+  text_range_command("RandomizeLines", text.randomize_lines, "Randomize lines")
+  text_range_command("ColumnizeLines", text.columnize_lines, "Columnize lines")
+  vim.api.nvim_create_user_command("Align", function(cmd)
+    text.align({
+      start_line = cmd.line1,
+      end_line = cmd.line2,
+      pattern = cmd.args,
+      every_column = cmd.bang,
+    })
+  end, { range = true, bang = true, nargs = 1, desc = "Align lines on pattern (! for every column)" })
+
   add({
     -- Text/Lines
     { "<leader>x", group = "text" },
     { "<leader>xl", group = "lines" },
+    { "<leader>xc", group = "case" },
+    {
+      "<leader>xa",
+      function()
+        -- Capture the range now; the prompt leaves visual mode.
+        local start_line, end_line = vim.fn.line("v"), vim.fn.line(".")
+        vim.ui.input({ prompt = "Align on: " }, function(pattern)
+          if pattern and pattern ~= "" then
+            text.align({
+              start_line = start_line,
+              end_line = end_line,
+              pattern = pattern,
+              every_column = true,
+            })
+          end
+        end)
+      end,
+      desc = "Align on pattern",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcc",
+      text.cycle_case,
+      desc = "Cycle identifier case",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcs",
+      function()
+        text.convert_case({ style = "snake" })
+      end,
+      desc = "Convert to snake_case",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xck",
+      function()
+        text.convert_case({ style = "kebab" })
+      end,
+      desc = "Convert to kebab-case",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcm",
+      function()
+        text.convert_case({ style = "camel" })
+      end,
+      desc = "Convert to camelCase",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcp",
+      function()
+        text.convert_case({ style = "pascal" })
+      end,
+      desc = "Convert to PascalCase",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcS",
+      function()
+        text.convert_case({ style = "screaming_snake" })
+      end,
+      desc = "Convert to SCREAMING_SNAKE_CASE",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xct",
+      function()
+        text.convert_case({ style = "title" })
+      end,
+      desc = "Convert to Title Case",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcu",
+      text.upcase,
+      desc = "Upcase",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcd",
+      text.downcase,
+      desc = "Downcase",
+      mode = { "n", "x" },
+    },
+    {
+      "<leader>xcC",
+      text.capitalize,
+      desc = "Capitalize",
+      mode = { "n", "x" },
+    },
     {
       "<leader>xlc",
       text.sort_lines_by_column,
@@ -506,6 +661,14 @@ function M.list()
       desc = "Kill back to indentation",
       mode = { "n", "x" },
     },
+    {
+      "<leader>xln",
+      function()
+        text.sort_lines_with_options({ order = "natural" })
+      end,
+      desc = "Sort lines (natural)",
+      mode = { "n", "x" },
+    },
     {
       "<leader>xlr",
       text.randomize_lines,
@@ -678,6 +841,11 @@ function M.list()
     { "<leader>Ts", colorscheme.open_switcher, desc = "Switcher" },
     { "<leader>Tn", colorscheme.next_theme, desc = "Next" },
     { "<leader>Tp", colorscheme.prev_theme, desc = "Previous" },
+    { "<leader>Ta", colorscheme.toggle_auto, desc = "Toggle auto switching" },
+    { "<leader>To", colorscheme.older_theme, desc = "Older (history)" },
+    { "<leader>Ti", colorscheme.newer_theme, desc = "Newer (history)" },
+    { "<leader>TP", colorscheme.pin_project_theme, desc = "Pin to project" },
+    { "<leader>TU", colorscheme.unpin_project_theme, desc = "Unpin from project" },
   })
 
   add({
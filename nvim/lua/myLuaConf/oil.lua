@@ -61,8 +61,8 @@ function M.open(path)
   return M.open_oil(path)
 end
 
-function M.oil_select_other_window()
-  plugin_util.oil_select_other_window()
+function M.oil_select_other_window(opts)
+  plugin_util.oil_select_other_window(opts)
 end
 
 function M.dashboard_recent_files_with_oil(opts)
@@ -9,10 +9,14 @@ local lze = require("lze")
 colorscheme.apply()
 snacks.setup()
 project.setup()
+colorscheme.follow_project_pins()
 require("myLuaConf.oil").setup()
 ---@module "nvimrs_autocmds"
 local nvimrs_autocmds = require("nvimrs_autocmds")
-nvimrs_autocmds.setup()
+nvimrs_autocmds.setup({
+  tab_title = "{project}[ ({branch:24})]",
+  tab_title_max_width = 48,
+})
 keymaps.setup()
 
 local function should_enable_smear_cursor()
@@ -17,4 +17,30 @@ function M.picker_preview(ctx)
   return ret
 end
 
+---@param picker snacks.Picker
+---@return integer|nil
+local function preview_win(picker)
+  local win = picker.preview and picker.preview.win and picker.preview.win.win
+  if type(win) == "number" and vim.api.nvim_win_is_valid(win) then
+    return win
+  end
+  return nil
+end
+
+---@param picker snacks.Picker
+function M.gallery_next(picker)
+  local win = preview_win(picker)
+  if win then
+    require("nvimrs_snacks_preview").gallery_next(win)
+  end
+end
+
+---@param picker snacks.Picker
+function M.gallery_prev(picker)
+  local win = preview_win(picker)
+  if win then
+    require("nvimrs_snacks_preview").gallery_prev(win)
+  end
+end
+
 return M
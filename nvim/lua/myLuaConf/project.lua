@@ -1,5 +1,9 @@
 ---@class ProjectRootConfig
----@field root_indicators? string[] List of files/dirs that mark the project root.
+---@field root_indicators? (string|nvimrs_project_root.Rule)[] Files/dirs/globs that mark the project root.
+---@field stop_at? string[] Directories or names that stop the upward search (e.g. "$HOME").
+---@field registry? boolean Record discovered roots in the project registry (default: true).
+---@field settings? boolean Load the trusted settings file at each project root (default: true).
+---@field settings_file? string Settings file name at the root (default: ".nvimrs.json").
 
 ---@class ProjectRootApi
 ---@field setup fun(config?: ProjectRootConfig)
@@ -7,14 +11,44 @@
 ---@field project_root fun(): string|nil
 ---@field project_root_or_warn fun(): string|nil
 ---@field show_project_root fun()
+---@field list_projects fun(): nvimrs_project_root.Project[]
+---@field forget_project fun(root: string): boolean
+---@field project_settings fun(buf?: integer): nvimrs_project_root.Settings|nil
+---@field trust_project_settings fun(root?: string): boolean
+---@field untrust_project_settings fun(root?: string): boolean
 
 ---@type ProjectRootApi
 local rust = require("nvimrs_project_root")
 
 local M = {}
 
+--- Apply indentation from trusted project settings to the buffer.
+---@param data nvimrs_project_root.RootChangedData
+local function apply_buffer_settings(data)
+  local settings = data.settings
+  if not settings or not vim.api.nvim_buf_is_valid(data.buf) then
+    return
+  end
+  local bo = vim.bo[data.buf]
+  if settings.tab_width then
+    bo.tabstop = settings.tab_width
+    bo.shiftwidth = settings.tab_width
+    bo.softtabstop = settings.tab_width
+  end
+  if settings.expand_tab ~= nil then
+    bo.expandtab = settings.expand_tab
+  end
+end
+
 ---@param config? ProjectRootConfig
 function M.setup(config)
+  vim.api.nvim_create_autocmd("User", {
+    group = vim.api.nvim_create_augroup("ProjectRootSettings", { clear = true }),
+    pattern = "ProjectRootChanged",
+    callback = function(args)
+      apply_buffer_settings(args.data)
+    end,
+  })
   rust.setup(config)
 end
 
@@ -37,4 +71,70 @@ function M.show_project_root()
   rust.show_project_root()
 end
 
+--- Registered projects, most frecent first.
+---@return nvimrs_project_root.Project[]
+function M.list_projects()
+  return rust.list_projects()
+end
+
+---@param root string
+---@return boolean
+function M.forget_project(root)
+  return rust.forget_project(root)
+end
+
+--- Trusted settings for the buffer's project root.
+---@param buf? integer
+---@return nvimrs_project_root.Settings|nil
+function M.project_settings(buf)
+  return rust.project_settings(buf)
+end
+
+---@param root? string
+---@return boolean
+function M.trust_project_settings(root)
+  return rust.trust_project_settings(root)
+end
+
+---@param root? string
+---@return boolean
+function M.untrust_project_settings(root)
+  return rust.untrust_project_settings(root)
+end
+
+--- Pick a registered project by frecency and open a file picker in it.
+--- `<C-x>` forgets the selected project.
+function M.pick_project()
+  local Snacks = require("snacks")
+  Snacks.picker.pick({
+    title = "Projects",
+    format = "file",
+    finder = function()
+      local items = {}
+      for idx, project in ipairs(rust.list_projects()) do
+        items[#items + 1] = { idx = idx, file = project.root, text = project.root, dir = true }
+      end
+      return items
+    end,
+    matcher = { sort_empty = false },
+    confirm = function(picker, item)
+      picker:close()
+      if item then
+        vim.cmd("lcd " .. vim.fn.fnameescape(item.file))
+        Snacks.picker.files({ cwd = item.file })
+      end
+    end,
+    actions = {
+      forget_project = function(picker, item)
+        if item and rust.forget_project(item.file) then
+          picker:find()
+        end
+      end,
+    },
+    win = {
+      input = { keys = { ["<C-x>"] = { "forget_project", mode = { "n", "i" } } } },
+    },
+  })
+end
+
 return M
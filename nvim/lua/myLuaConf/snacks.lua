@@ -8,10 +8,11 @@ local function snacks()
   return require("snacks")
 end
 
-local function reset_preview_state()
+local function setup_doc_preview()
   ---@module "nvimrs_snacks_preview"
   local preview = require("nvimrs_snacks_preview")
   preview.reset_state()
+  preview.setup({})
 end
 
 local function project_confirm_winlocal(picker, item)
@@ -142,10 +143,16 @@ local function opts()
     picker = {
       enabled = true,
       main = { current = true },
+      actions = {
+        doc_gallery_next = snacks_preview.gallery_next,
+        doc_gallery_prev = snacks_preview.gallery_prev,
+      },
       win = {
         input = {
           keys = {
             ["<Esc>"] = { "cancel", mode = { "i", "n" } },
+            ["<a-]>"] = { "doc_gallery_next", mode = { "i", "n" } },
+            ["<a-[>"] = { "doc_gallery_prev", mode = { "i", "n" } },
           },
         },
       },
@@ -171,7 +178,7 @@ local function opts()
 end
 
 function M.setup()
-  reset_preview_state()
+  setup_doc_preview()
   snacks().setup(opts())
 end
 
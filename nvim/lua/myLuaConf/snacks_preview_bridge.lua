@@ -3,6 +3,11 @@ local M = {}
 ---@class SnacksPreviewBridge.Args
 ---@field win integer
 ---@field src string
+---@field anchor? "win"|"cursor"
+
+---@class SnacksPreviewBridge.RenderArgs
+---@field cmd string[]
+---@field on_done fun(ok: boolean, stderr: string|nil)
 
 ---@class SnacksPreviewBridge.PlacementLoc
 ---@field width integer
@@ -57,6 +62,23 @@ local function install_image_patches()
   end
 end
 
+-- Place the float on the line below the cursor, or above it when the image
+-- would run past the bottom of the window.
+---@param win_id integer
+---@param height integer
+---@return integer row
+---@return integer col
+local function cursor_anchor(win_id, height)
+  local line, column = vim.api.nvim_win_call(win_id, function()
+    return vim.fn.winline(), vim.fn.wincol()
+  end)
+  local row = line
+  if row + height > vim.api.nvim_win_get_height(win_id) and line - 1 - height >= 0 then
+    row = line - 1 - height
+  end
+  return row, math.max(column - 1, 0)
+end
+
 ---@param args SnacksPreviewBridge.Args
 ---@return fun()|nil cleanup
 ---@return string|nil err
@@ -93,11 +115,15 @@ function M.snacks_open_preview(args)
   local max_height = tonumber(doc.max_height) or 40
   local width = math.min(max_width, base_width)
   local height = math.min(max_height, base_height)
+  local row, col = 1, 1
+  if args.anchor == "cursor" then
+    row, col = cursor_anchor(win_id, height)
+  end
   local resolved = win_module.resolve(doc, "snacks_image", {
     relative = "win",
     win = win_id,
-    row = 1,
-    col = 1,
+    row = row,
+    col = col,
     width = width,
     height = height,
     show = true,
@@ -167,4 +193,25 @@ function M.snacks_open_preview(args)
   return cleanup, nil
 end
 
+---@param args SnacksPreviewBridge.RenderArgs
+---@return boolean started
+---@return string|nil err
+function M.render_diagram(args)
+  local cmd = args.cmd
+  if type(cmd) ~= "table" or type(cmd[1]) ~= "string" or type(args.on_done) ~= "function" then
+    return false, "missing required args `cmd` or `on_done`"
+  end
+  if vim.fn.executable(cmd[1]) ~= 1 then
+    return false, "`" .. cmd[1] .. "` is not executable"
+  end
+  local ok, err = pcall(vim.system, cmd, { text = true }, vim.schedule_wrap(function(out)
+    local stderr = out.code ~= 0 and vim.trim(out.stderr or "") or nil
+    args.on_done(out.code == 0, stderr)
+  end))
+  if not ok then
+    return false, tostring(err)
+  end
+  return true, nil
+end
+
 return M
@@ -2,7 +2,48 @@
 
 local M = {}
 
-function M.setup() end
+---@alias nvimrs_autocmds.CwdPolicy "file_dir"|"project_root"|"tab_root"|"none"
+
+---@class nvimrs_autocmds.CwdPathRule
+---@field pattern string Path glob; leading `~` is $HOME and `*` also spans `/`
+---@field policy nvimrs_autocmds.CwdPolicy
+
+---Path rules are checked first (first match wins), then filetypes, then
+---`default`. Root policies fall back to "file_dir" without a project root.
+---@class nvimrs_autocmds.CwdOpts
+---@field default? nvimrs_autocmds.CwdPolicy (default: "file_dir")
+---@field filetypes? table<string, nvimrs_autocmds.CwdPolicy>
+---@field paths? nvimrs_autocmds.CwdPathRule[]
+
+---@class nvimrs_autocmds.SetupOpts
+---@field cwd? nvimrs_autocmds.CwdOpts
+---@field terminal_backend? "auto"|"off"|"wezterm"|"kitty"|"foot"|"ghostty"|"iterm2"|"tmux"
+---Template with {project}, {file}, {branch}, {modified} and {mode}; `[...]`
+---sections drop out when a placeholder inside is empty.
+---@field tab_title? string
+---@field tab_title_max_width? integer
+
+---@class nvimrs_autocmds.RecentFilesOpts
+---@field cwd_only? boolean Only files under the current working directory (default: false)
+---@field root? string Only files last visited under this project root
+---@field project_only? boolean Only files last visited under the current buffer's project root (default: false)
+---@field limit? integer Maximum number of files (default: all)
+
+---@class nvimrs_autocmds.RecentFile
+---@field path string
+---@field root? string Project root the file was last visited under
+---@field visits integer
+---@field last_visit integer Unix timestamp in seconds
+---@field score integer Frecency score
+
+---@param opts? nvimrs_autocmds.SetupOpts
+function M.setup(opts) end
+
+--- Visited files that still exist, most frecent first. Persisted across
+--- sessions and shared by every instance.
+---@param opts? nvimrs_autocmds.RecentFilesOpts
+---@return nvimrs_autocmds.RecentFile[]
+function M.recent_files(opts) end
 
 function M.switch_to_last_buffer() end
 
@@ -2,6 +2,24 @@
 
 ---@alias nvimrs_plugin_util.OptionValue nil|boolean|integer|number|string|table
 
+---@alias nvimrs_plugin_util.DefinitionTarget "other_window"|"split"|"vsplit"|"tab"|"float"
+---@alias nvimrs_plugin_util.DefinitionMethod "definition"|"references"|"implementation"|"type_definition"
+
+---@alias nvimrs_plugin_util.WindowStrategy "next"|"ace"|"largest"|"mru"
+---@alias nvimrs_plugin_util.SplitDirection "right"|"left"|"below"|"above"
+
+---@class nvimrs_plugin_util.WindowSelectOpts
+---@field strategy? nvimrs_plugin_util.WindowStrategy How the other window is picked; "ace" labels windows when there are more than two (default: "next")
+---@field split? nvimrs_plugin_util.SplitDirection Where a split opens when no other window exists (default: "right")
+
+---@class nvimrs_plugin_util.DefinitionOpts: nvimrs_plugin_util.WindowSelectOpts
+---@field target? nvimrs_plugin_util.DefinitionTarget Where the item opens (default: "other_window")
+---@field method? nvimrs_plugin_util.DefinitionMethod LSP request to list items with (default: "definition")
+---@field multiple? "quickfix"|"picker" Open the first item and fill quickfix, or pick one (default: "quickfix")
+
+---@class nvimrs_plugin_util.BulkDeleteOpts
+---@field force? boolean Also delete buffers with unsaved changes (default: false)
+
 local M = {}
 
 ---@param path? string
@@ -51,19 +69,45 @@ function M.open_oil(path) end
 ---@return string
 function M.oil_winbar() end
 
-function M.oil_select_other_window() end
+---@param opts? nvimrs_plugin_util.WindowSelectOpts
+function M.oil_select_other_window(opts) end
 
 function M.goto_definition_other_window() end
 
+--- Jump to the items an LSP request lists. Duplicate locations are dropped
+--- and an item in the current file is opened first.
+---@param opts? nvimrs_plugin_util.DefinitionOpts
+function M.goto_definition(opts) end
+
+--- Delete the current buffer; windows showing it switch to the most
+--- recently used buffer instead of closing.
 function M.delete_current_buffer() end
 
 function M.kill_window_and_buffer() end
 
----@return integer|nil
-function M.other_window() end
+--- Delete every listed buffer except the current one.
+---@param opts? nvimrs_plugin_util.BulkDeleteOpts
+function M.only_other_buffers(opts) end
+
+--- Delete listed buffers that no window shows.
+---@param opts? nvimrs_plugin_util.BulkDeleteOpts
+function M.delete_hidden_buffers(opts) end
 
----@return integer win
----@return boolean created
-function M.get_or_create_other_window() end
+--- Delete named buffers outside the current buffer's project root.
+---@param opts? nvimrs_plugin_util.BulkDeleteOpts
+function M.delete_buffers_outside_project(opts) end
+
+--- Existing window to use as "the other window"; nil when none exists or
+--- the ace prompt was cancelled.
+---@param opts? nvimrs_plugin_util.WindowSelectOpts
+---@return integer|nil
+function M.other_window(opts) end
+
+--- Like `other_window`, but splits when no other window exists. Returns
+--- nothing when the ace prompt was cancelled.
+---@param opts? nvimrs_plugin_util.WindowSelectOpts
+---@return integer|nil win
+---@return boolean|nil created
+function M.get_or_create_other_window(opts) end
 
 return M
@@ -1,7 +1,44 @@
 ---@meta
 
+---@class nvimrs_project_root.Rule
+---@field pattern string File/dir name; `*` and `?` make it a glob (e.g. "*.cabal")
+---@field priority? integer Higher wins over nearer matches (default: 0)
+---@field strategy? "innermost"|"outermost" Which matching ancestor to report (default: "innermost")
+
 ---@class nvimrs_project_root.Config
----@field root_indicators? string[]
+---@field root_indicators? (string|nvimrs_project_root.Rule)[] Default: `.git` (priority 1), then package.json, Cargo.toml, flake.nix, Makefile
+---@field stop_at? string[] Directories (`/...`, `~`, `$VAR`) or names that end the upward walk
+---@field registry? boolean Record discovered roots in the project registry (default: true)
+---@field settings? boolean Load the trusted settings file at each project root (default: true)
+---@field settings_file? string Settings file name at the root (default: ".nvimrs.json")
+
+---@class nvimrs_project_root.Project
+---@field root string
+---@field indicator string
+---@field rule string
+---@field visits integer
+---@field last_visit integer Unix timestamp in seconds
+---@field score integer Frecency score
+
+---@class nvimrs_project_root.SmearSettings
+---@field filetypes_disabled string[]
+
+---@class nvimrs_project_root.Settings
+---@field tab_width? integer
+---@field expand_tab? boolean
+---@field formatter? string
+---@field smear nvimrs_project_root.SmearSettings
+
+--- `data` of the `User ProjectRootChanged` autocmd. `previous_root == root`
+--- means the root is unchanged but its settings were trusted or revoked.
+---@class nvimrs_project_root.RootChangedData
+---@field buf integer
+---@field root string
+---@field previous_root? string
+---@field indicator string
+---@field rule string
+---@field settings_status "disabled"|"missing"|"untrusted"|"invalid"|"trusted"
+---@field settings? nvimrs_project_root.Settings Only set when trusted
 
 local M = {}
 
@@ -19,4 +56,26 @@ function M.project_root_or_warn() end
 
 function M.show_project_root() end
 
+--- Registered projects that still exist, most frecent first.
+---@return nvimrs_project_root.Project[]
+function M.list_projects() end
+
+---@param root string
+---@return boolean forgotten
+function M.forget_project(root) end
+
+--- Trusted settings for the buffer's project root.
+---@param buf? integer
+---@return nvimrs_project_root.Settings|nil
+function M.project_settings(buf) end
+
+--- Trust the current content of the settings file (default: current root).
+---@param root? string
+---@return boolean loaded
+function M.trust_project_settings(root) end
+
+---@param root? string
+---@return boolean revoked
+function M.untrust_project_settings(root) end
+
 return M
@@ -2,6 +2,14 @@
 
 local M = {}
 
+---@class nvimrs_readline.Config
+---@field register? string Register every kill is mirrored to (default: none)
+---@field ring_size? integer Maximum kill ring entries (default: 60)
+---@field word_chars? string Extra word characters besides letters and digits, e.g. "_"
+
+---@param opts? nvimrs_readline.Config
+function M.setup(opts) end
+
 function M.beginning_of_line() end
 
 function M.end_of_line() end
@@ -12,6 +20,24 @@ function M.backward_word() end
 
 function M.kill_word() end
 
+function M.backward_kill_word() end
+
+function M.kill_line() end
+
+function M.unix_line_discard() end
+
+function M.yank() end
+
+function M.yank_pop() end
+
 function M.transpose_chars() end
 
+function M.transpose_words() end
+
+function M.upcase_word() end
+
+function M.downcase_word() end
+
+function M.capitalize_word() end
+
 return M
@@ -14,16 +14,48 @@
 ---@field win integer
 ---@field path string
 
+---@class nvimrs_snacks_preview.SetupOpts
+---@field filetypes? string[] Filetypes that get a document preview (default: markdown, mdx, typst, tex, latex)
+---@field diagrams? boolean Render Mermaid/Graphviz code blocks into the gallery (default: true)
+---@field mermaid_cmd? string Mermaid CLI executable (default: "mmdc")
+---@field graphviz_cmd? string Graphviz executable (default: "dot")
+---@field follow_cursor? boolean Start with the inline image-under-cursor preview enabled (default: false)
+---@field follow_debounce_ms? integer Delay before the inline preview follows the cursor (default: 150)
+
+---@class nvimrs_snacks_preview.CloseCounts
+---@field buffer integer Previews closed for their buffer
+---@field token integer Previews closed by their window or autocmd token
+---@field reset integer Previews dropped by `reset_state`
+
+---@class nvimrs_snacks_preview.Stats
+---@field open_previews integer
+---@field follow_previews integer
+---@field registrations integer
+---@field replacements integer Previews displaced by a registration on the same buffer or window
+---@field stale_doc_find integer
+---@field stale_cleanup_opened integer
+---@field closes nvimrs_snacks_preview.CloseCounts
+---@field pending_cleanups integer
+---@field leaked_cleanups integer Registered cleanups that no preview owns
+
 ---@class nvimrs_snacks_preview
+---@field setup fun(opts?: nvimrs_snacks_preview.SetupOpts)
 ---@field on_doc_find fun(args: nvimrs_snacks_preview.DocFindArgs)
 ---@field attach_doc_preview fun(args: nvimrs_snacks_preview.AttachDocPreviewArgs)
 ---@field close_doc_preview fun(buf: integer)
 ---@field close_doc_preview_for_window fun(win: integer)
+---@field gallery_next fun(win?: integer)
+---@field gallery_prev fun(win?: integer)
+---@field follow_cursor fun(enabled: boolean)
+---@field stats fun(): nvimrs_snacks_preview.Stats
 ---@field reset_state fun()
 
 ---@type nvimrs_snacks_preview
 local M = {}
 
+---@param opts? nvimrs_snacks_preview.SetupOpts
+function M.setup(opts) end
+
 ---@param args nvimrs_snacks_preview.DocFindArgs
 function M.on_doc_find(args) end
 
@@ -36,6 +68,22 @@ function M.close_doc_preview(buf) end
 ---@param win integer
 function M.close_doc_preview_for_window(win) end
 
+--- Show the next image of the preview in `win`, or of the only open preview.
+---@param win? integer
+function M.gallery_next(win) end
+
+--- Show the previous image of the preview in `win`, or of the only open preview.
+---@param win? integer
+function M.gallery_prev(win) end
+
+--- Turn the inline preview of the image reference under the cursor on or off.
+---@param enabled boolean
+function M.follow_cursor(enabled) end
+
+--- Lifecycle counters and live preview counts, as shown by `:SnacksPreviewDiagnostics`.
+---@return nvimrs_snacks_preview.Stats
+function M.stats() end
+
 function M.reset_state() end
 
 return M
@@ -2,20 +2,83 @@
 
 local M = {}
 
-function M.sort_lines() end
+---@class nvimrs_text.RangeArgs
+---@field start_line? integer
+---@field end_line? integer
 
-function M.sort_lines_reverse() end
+---@class nvimrs_text.SortOptions: nvimrs_text.RangeArgs
+---@field order? "lexical"|"numeric"|"natural"
+---@field ignore_case? boolean
+---@field reverse? boolean
+---@field unique? boolean
+---@field separator? string
+---@field field? integer
+---@field column? integer
+---@field pattern? string
 
-function M.sort_lines_by_column() end
+---@class nvimrs_text.AlignArgs: nvimrs_text.RangeArgs
+---@field pattern string Vim regex (or plain text with `literal`)
+---@field literal? boolean
+---@field every_column? boolean
+---@field justify? "left"|"right"|"center"
+---@field spacing? integer
 
-function M.sort_lines_by_column_reverse() end
+---@class nvimrs_text.ColumnizeArgs: nvimrs_text.RangeArgs
+---@field justify? "left"|"right"|"center"
+---@field spacing? integer
 
-function M.randomize_lines() end
+---@alias nvimrs_text.CaseStyle "snake"|"camel"|"pascal"|"kebab"|"screaming_snake"|"title"
 
-function M.uniquify_lines() end
+---@class nvimrs_text.CaseArgs: nvimrs_text.RangeArgs
+---@field style nvimrs_text.CaseStyle
 
-function M.duplicate_line_or_region() end
+---@param opts? nvimrs_text.RangeArgs
+function M.sort_lines(opts) end
 
-function M.kill_back_to_indentation() end
+---@param opts? nvimrs_text.RangeArgs
+function M.sort_lines_reverse(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.sort_lines_by_column(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.sort_lines_by_column_reverse(opts) end
+
+---@param opts? nvimrs_text.SortOptions
+function M.sort_lines_with_options(opts) end
+
+---@param opts nvimrs_text.AlignArgs
+function M.align(opts) end
+
+---@param opts? nvimrs_text.ColumnizeArgs
+function M.columnize_lines(opts) end
+
+--- Without a range or selection these act on the identifier under the cursor.
+---@param opts? nvimrs_text.RangeArgs
+function M.cycle_case(opts) end
+
+---@param opts nvimrs_text.CaseArgs
+function M.convert_case(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.upcase(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.downcase(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.capitalize(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.randomize_lines(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.uniquify_lines(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.duplicate_line_or_region(opts) end
+
+---@param opts? nvimrs_text.RangeArgs
+function M.kill_back_to_indentation(opts) end
 
 return M
@@ -3,12 +3,38 @@
 ---@class nvimrs_theme_switcher.ThemeSpec
 ---@field name string
 ---@field colorscheme string
+---@field background? "light"|"dark" Used to group the picker list and by the background filter
 
 ---@class nvimrs_theme_switcher.OpenArgs
 ---@field themes nvimrs_theme_switcher.ThemeSpec[]
 ---@field title? string
 ---@field current_colorscheme? string
 ---@field state_path? string
+---@field project_root? string Reads and updates this project's pinned theme, if any
+---@field order? "catalog"|"history" Order used by cycle_next/cycle_prev (default: "catalog")
+---@field live_preview? boolean Apply themes while moving through the picker (default: true)
+---@field background? "all"|"light"|"dark" Initial background filter (default: "all")
+
+---@class nvimrs_theme_switcher.StateArgs
+---@field state_path string
+---@field project_root? string
+---@field colorscheme? string Theme to pin (default: the active colorscheme)
+
+---@class nvimrs_theme_switcher.ScheduleEntry
+---@field at string Local start time as "HH:MM"; holds until the next entry
+---@field colorscheme string Must be one of `themes`
+
+---@class nvimrs_theme_switcher.Appearance
+---@field env? string Environment variable holding "dark"/"light" (checked first)
+---@field file? string File whose first line holds "dark"/"light"
+---@field dark string Colorscheme used for a dark appearance
+---@field light string Colorscheme used for a light appearance
+
+---@class nvimrs_theme_switcher.AutoArgs
+---@field themes nvimrs_theme_switcher.ThemeSpec[]
+---@field state_path? string
+---@field schedule? nvimrs_theme_switcher.ScheduleEntry[]
+---@field appearance? nvimrs_theme_switcher.Appearance Wins over `schedule` when it reports a value
 
 ---@class nvimrs_theme_switcher
 ---@field open fun(args: nvimrs_theme_switcher.OpenArgs)
@@ -18,7 +44,16 @@
 ---@field move_prev fun()
 ---@field confirm fun()
 ---@field cancel fun()
+---@field toggle_live_preview fun()
+---@field cycle_background fun()
+---@field set_background fun(value: "all"|"light"|"dark")
 ---@field close fun()
+---@field persisted_colorscheme fun(args: nvimrs_theme_switcher.StateArgs): string|nil
+---@field pin_project fun(args: nvimrs_theme_switcher.StateArgs)
+---@field unpin_project fun(args: nvimrs_theme_switcher.StateArgs)
+---@field start_auto fun(args: nvimrs_theme_switcher.AutoArgs)
+---@field auto_tick fun()
+---@field stop_auto fun()
 
 ---@type nvimrs_theme_switcher
 local M = {}
@@ -40,6 +75,36 @@ function M.confirm() end
 
 function M.cancel() end
 
+function M.toggle_live_preview() end
+
+--- Cycle the picker filter: all -> dark -> light -> all.
+function M.cycle_background() end
+
+---@param value "all"|"light"|"dark"
+function M.set_background(value) end
+
 function M.close() end
 
+--- The project's pinned theme, else the global one. Legacy one-line state
+--- files are migrated on the next write.
+---@param args nvimrs_theme_switcher.StateArgs
+---@return string|nil
+function M.persisted_colorscheme(args) end
+
+---@param args nvimrs_theme_switcher.StateArgs
+function M.pin_project(args) end
+
+---@param args nvimrs_theme_switcher.StateArgs
+function M.unpin_project(args) end
+
+--- Start automatic switching and apply the current target immediately.
+---@param args nvimrs_theme_switcher.AutoArgs
+function M.start_auto(args) end
+
+--- Re-check the clock and appearance source. A theme is applied only when
+--- the target changed, so manual picks stick until the next change.
+function M.auto_tick() end
+
+function M.stop_auto() end
+
 return M
@@ -1,3 +1,4 @@
 mod wezterm;
 
+pub use wezterm::TerminalBackendChoice;
 pub use wezterm::setup_wezterm_autocmd;
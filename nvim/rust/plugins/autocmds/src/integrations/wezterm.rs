@@ -5,6 +5,7 @@ use std::process::ExitStatus;
 use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc::TrySendError;
@@ -12,6 +13,8 @@ use std::sync::mpsc::{self};
 use std::thread;
 use std::time::Instant;
 
+use crate::machines::wezterm::TabTitleField;
+use crate::machines::wezterm::TabTitleFormat;
 use crate::machines::wezterm::WeztermCommand;
 use crate::machines::wezterm::WeztermCompletion;
 use crate::machines::wezterm::WeztermEvent;
@@ -33,21 +36,26 @@ use nvimrs_support::TabTitle;
 
 use crate::types::AutocmdAction;
 
+mod backend;
 mod context;
 mod sync;
 mod terminal;
 mod work;
 
+use self::backend::TerminalBackend;
+pub use self::backend::TerminalBackendChoice;
+use self::backend::TerminalBackendKind;
+use self::backend::backend_for;
 use self::context::WeztermContext;
-use self::context::current_buf_project_root;
+use self::context::current_tab_title_inputs;
 use self::context::current_window_cwd;
+use self::context::invalidate_git_branches;
 use self::context::should_skip_sync_for_current_buffer;
 use self::sync::WeztermRuntimeMode;
 use self::sync::WeztermSyncGate;
 use self::sync::WeztermSyncPolicy;
 use self::sync::WeztermSyncSnapshot;
 use self::sync::WeztermSyncStats;
-use self::terminal::EscapeSequenceWeztermCommandRunner;
 use self::work::WeztermCommandBatch;
 use self::work::WeztermCommandResult;
 use self::work::WeztermWorkItem;
@@ -59,12 +67,15 @@ const WEZTERM_WORKER_QUEUE_CAPACITY: usize = 64;
 const WEZTERM_DEFAULT_COMPLETION_DRAIN_BATCH_SIZE: usize = 16;
 const WEZTERM_DEFAULT_SYNC_DEBOUNCE_WINDOW_MS: u64 = 75;
 
+static TERMINAL_BACKEND: OnceLock<TerminalBackendKind> = OnceLock::new();
 static WEZTERM_STATE: StateCell<WeztermState> = StateCell::new(WeztermState::new());
 static WEZTERM_DISPATCHER: LazyLock<WeztermDispatcher> = LazyLock::new(WeztermDispatcher::new);
 static WEZTERM_SYNC_POLICY: LazyLock<WeztermSyncPolicy> =
     LazyLock::new(WeztermSyncPolicy::default_policy);
 static WEZTERM_SYNC_GATE: LazyLock<Mutex<WeztermSyncGate>> =
     LazyLock::new(|| Mutex::new(WeztermSyncGate::new()));
+static TAB_TITLE_FORMAT: LazyLock<Mutex<TabTitleFormat>> =
+    LazyLock::new(|| Mutex::new(TabTitleFormat::default()));
 
 struct WeztermDispatcherShared {
     completed: Mutex<VecDeque<WeztermCommandResult>>,
@@ -256,11 +267,6 @@ impl WeztermDispatchError {
     }
 }
 
-trait WeztermCommandRunner: Send + Sync {
-    fn run_tab_title(&self, title: &TabTitle) -> std::io::Result<ExitStatus>;
-    fn run_working_dir(&self, cwd: &str) -> std::io::Result<ExitStatus>;
-}
-
 impl WeztermDispatcher {
     fn new() -> Self {
         let wakeup = match AsyncHandle::new(|| {
@@ -283,7 +289,8 @@ impl WeztermDispatcher {
             WeztermRuntimeMode::DegradedPolling
         };
         let shared = Arc::new(WeztermDispatcherShared::new(wakeup, initial_mode));
-        let runner: Arc<dyn WeztermCommandRunner> = Arc::new(EscapeSequenceWeztermCommandRunner);
+        let runner =
+            backend_for(selected_terminal_backend().unwrap_or(TerminalBackendKind::WezTerm));
 
         let (sender, receiver) = mpsc::sync_channel(WEZTERM_WORKER_QUEUE_CAPACITY);
         let worker_shared = Arc::clone(&shared);
@@ -349,7 +356,7 @@ impl WeztermDispatcher {
 fn run_wezterm_worker(
     receiver: mpsc::Receiver<WeztermWorkItem>,
     shared: Arc<WeztermDispatcherShared>,
-    runner: Arc<dyn WeztermCommandRunner>,
+    runner: Arc<dyn TerminalBackend>,
 ) {
     while let Ok(work_item) = receiver.recv() {
         run_wezterm_work_item(work_item, runner.as_ref(), &shared);
@@ -358,7 +365,7 @@ fn run_wezterm_worker(
 
 fn run_wezterm_work_item(
     work_item: WeztermWorkItem,
-    runner: &dyn WeztermCommandRunner,
+    runner: &dyn TerminalBackend,
     shared: &WeztermDispatcherShared,
 ) {
     match work_item {
@@ -396,15 +403,37 @@ where
     }
 }
 
+fn tab_title_format() -> TabTitleFormat {
+    match TAB_TITLE_FORMAT.lock() {
+        Ok(format) => format.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+fn set_tab_title_format(next: TabTitleFormat) {
+    match TAB_TITLE_FORMAT.lock() {
+        Ok(mut format) => *format = next,
+        Err(poisoned) => *poisoned.into_inner() = next,
+    }
+}
+
 fn should_coalesce_sync(now: Instant) -> bool {
     with_sync_gate(|gate| gate.should_coalesce(now, WEZTERM_SYNC_POLICY.autocmd_debounce_window))
 }
 
+fn selected_terminal_backend() -> Option<TerminalBackendKind> {
+    TERMINAL_BACKEND.get().copied()
+}
+
+fn backend_name() -> &'static str {
+    selected_terminal_backend().map_or("terminal", TerminalBackendKind::as_str)
+}
+
 fn warn_cli_unavailable(state: &mut WeztermState, err: &std::io::Error) {
     if !state.take_warn_cli_unavailable() {
         return;
     }
-    let message = format!("wezterm command unavailable: {err}");
+    let message = format!("{} command unavailable: {err}", backend_name());
     notify::warn(WEZTERM_LOG_CONTEXT, &message);
 }
 
@@ -436,7 +465,9 @@ fn on_wezterm_tab_title_result(
             if exit_status.success() {
                 WeztermCompletion::Success
             } else {
-                warn_title_failed(&mut state, || format_cli_failure(exit_status));
+                warn_title_failed(&mut state, || {
+                    format_cli_failure(backend_name(), exit_status)
+                });
                 WeztermCompletion::Failed
             }
         }
@@ -445,7 +476,9 @@ fn on_wezterm_tab_title_result(
                 warn_cli_unavailable(&mut state, &err);
                 WeztermCompletion::Unavailable
             } else {
-                warn_title_failed(&mut state, || format!("wezterm cli failed: {err}"));
+                warn_title_failed(&mut state, || {
+                    format!("{} tab title failed: {err}", backend_name())
+                });
                 WeztermCompletion::Failed
             }
         }
@@ -465,7 +498,9 @@ fn on_wezterm_working_dir_result(
             if exit_status.success() {
                 WeztermCompletion::Success
             } else {
-                warn_cwd_failed(&mut state, || format_set_working_dir_failure(exit_status));
+                warn_cwd_failed(&mut state, || {
+                    format_set_working_dir_failure(backend_name(), exit_status)
+                });
                 WeztermCompletion::Failed
             }
         }
@@ -475,7 +510,7 @@ fn on_wezterm_working_dir_result(
                 WeztermCompletion::Unavailable
             } else {
                 warn_cwd_failed(&mut state, || {
-                    format!("wezterm set-working-directory failed: {err}")
+                    format!("{} set-working-directory failed: {err}", backend_name())
                 });
                 WeztermCompletion::Failed
             }
@@ -550,8 +585,9 @@ fn drain_wezterm_completions() -> Result<AutocmdAction> {
 }
 
 fn next_wezterm_tab_title_command(context: &WeztermContext) -> Result<Option<WeztermCommand>> {
-    let root = current_buf_project_root()?;
-    let Some(title) = derive_tab_title(root, context.home.as_deref()) else {
+    let format = tab_title_format();
+    let inputs = current_tab_title_inputs(&format.template)?;
+    let Some(title) = derive_tab_title(&format, &inputs, context.home.as_deref()) else {
         return Ok(None);
     };
 
@@ -625,7 +661,10 @@ fn sync_wezterm_state_now() -> Result<AutocmdAction> {
 
 fn show_wezterm_sync_stats() -> Result<AutocmdAction> {
     let snapshot = WEZTERM_DISPATCHER.snapshot();
-    notify::info(WEZTERM_LOG_CONTEXT, &snapshot.render());
+    notify::info(
+        WEZTERM_LOG_CONTEXT,
+        &format!("backend={} {}", backend_name(), snapshot.render()),
+    );
     Ok(AutocmdAction::Keep)
 }
 
@@ -657,22 +696,54 @@ fn setup_wezterm_commands() -> Result<()> {
     Ok(())
 }
 
-pub fn setup_wezterm_autocmd() -> Result<()> {
-    if WeztermContext::detect().is_none() {
-        return Ok(());
+/// Autocmd events that can change the rendered tab title.
+fn tab_title_sync_events(format: &TabTitleFormat) -> Vec<&'static str> {
+    let mut events = vec!["VimEnter", "BufEnter", "DirChanged"];
+    let template = &format.template;
+    if template.uses(TabTitleField::Modified) {
+        events.push("BufModifiedSet");
+    }
+    if template.uses(TabTitleField::Mode) {
+        events.push("ModeChanged");
     }
+    if template.uses(TabTitleField::Branch) {
+        events.push("FocusGained");
+    }
+    events
+}
+
+pub fn setup_wezterm_autocmd(choice: TerminalBackendChoice, title: TabTitleFormat) -> Result<()> {
+    let Some(kind) = choice.resolve(|name| std::env::var(name).ok()) else {
+        return Ok(());
+    };
+    // The worker is bound to the first backend; later setups keep it.
+    let _ = TERMINAL_BACKEND.set(kind);
+    set_tab_title_format(title.clone());
 
     let group = api::create_augroup(
         "WeztermProjectTab",
         &CreateAugroupOpts::builder().clear(true).build(),
     )?;
+    if title.template.uses(TabTitleField::Branch) {
+        // Registered first so the sync on the same event reads a fresh branch.
+        let invalidate_opts = CreateAutocmdOpts::builder()
+            .group(group)
+            .callback(|_args: AutocmdCallbackArgs| {
+                crate::run_autocmd("wezterm_branch_cache", || {
+                    invalidate_git_branches();
+                    Ok(AutocmdAction::Keep)
+                })
+            })
+            .build();
+        api::create_autocmd(["FocusGained", "DirChanged"], &invalidate_opts)?;
+    }
     let opts = CreateAutocmdOpts::builder()
         .group(group)
         .callback(|_args: AutocmdCallbackArgs| {
             crate::run_autocmd("wezterm_sync", sync_wezterm_state)
         })
         .build();
-    api::create_autocmd(["VimEnter", "BufEnter", "DirChanged"], &opts)?;
+    api::create_autocmd(tab_title_sync_events(&title), &opts)?;
     setup_wezterm_commands()?;
     Ok(())
 }
@@ -727,7 +798,7 @@ mod tests {
         }
     }
 
-    impl WeztermCommandRunner for ScriptedRunner {
+    impl TerminalBackend for ScriptedRunner {
         fn run_tab_title(&self, _title: &TabTitle) -> std::io::Result<ExitStatus> {
             match self.title.lock() {
                 Ok(mut queue) => queue
@@ -808,7 +879,7 @@ mod tests {
             .map_err(|_| "failed to send cwd command")?;
         drop(sender);
 
-        let runner: Arc<dyn WeztermCommandRunner> = Arc::new(ScriptedRunner::new(
+        let runner: Arc<dyn TerminalBackend> = Arc::new(ScriptedRunner::new(
             vec![RunnerOutcome::Exit(0)],
             vec![RunnerOutcome::Io(ErrorKind::Other)],
         ));
@@ -0,0 +1,416 @@
+use std::io::ErrorKind;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use nvimrs_support::TabTitle;
+
+use super::terminal::encode_file_url_path;
+use super::terminal::local_hostname;
+use super::terminal::successful_exit_status;
+use super::terminal::tmux_passthrough_enabled;
+use super::terminal::with_tmux_passthrough;
+use super::terminal::write_terminal_sequence;
+
+/// Applies tab-title and working-directory updates for one terminal.
+///
+/// Runs on the sync worker thread; the returned status feeds the shared
+/// reducer, so `ErrorKind::NotFound` means "backend unavailable".
+pub(super) trait TerminalBackend: Send + Sync {
+    fn run_tab_title(&self, title: &TabTitle) -> std::io::Result<ExitStatus>;
+    fn run_working_dir(&self, cwd: &str) -> std::io::Result<ExitStatus>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalBackendKind {
+    WezTerm,
+    Kitty,
+    Foot,
+    Ghostty,
+    ITerm2,
+    /// Plain tmux with no recognised outer terminal: `rename-window` plus a
+    /// raw OSC 7 that tmux records as the pane path.
+    Tmux,
+}
+
+impl TerminalBackendKind {
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::WezTerm => "wezterm",
+            Self::Kitty => "kitty",
+            Self::Foot => "foot",
+            Self::Ghostty => "ghostty",
+            Self::ITerm2 => "iterm2",
+            Self::Tmux => "tmux",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "wezterm" => Some(Self::WezTerm),
+            "kitty" => Some(Self::Kitty),
+            "foot" => Some(Self::Foot),
+            "ghostty" => Some(Self::Ghostty),
+            "iterm2" => Some(Self::ITerm2),
+            "tmux" => Some(Self::Tmux),
+            _ => None,
+        }
+    }
+
+    /// Detect the terminal from its environment markers. The outer terminal
+    /// wins over tmux, whose sessions inherit those markers and get the
+    /// sequences through tmux passthrough.
+    pub(crate) fn detect<F>(env: F) -> Option<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let set = |name: &str| env(name).is_some_and(|value| !value.is_empty());
+        let term = env("TERM").unwrap_or_default();
+        let term_program = env("TERM_PROGRAM").unwrap_or_default();
+        if set("WEZTERM_PANE") {
+            Some(Self::WezTerm)
+        } else if set("KITTY_WINDOW_ID") || term == "xterm-kitty" {
+            Some(Self::Kitty)
+        } else if set("GHOSTTY_RESOURCES_DIR") || term_program == "ghostty" {
+            Some(Self::Ghostty)
+        } else if set("ITERM_SESSION_ID") || term_program == "iTerm.app" {
+            Some(Self::ITerm2)
+        } else if term == "foot" || term.starts_with("foot-") {
+            Some(Self::Foot)
+        } else if set("TMUX") {
+            Some(Self::Tmux)
+        } else {
+            None
+        }
+    }
+
+    /// Title escape sequence before any tmux passthrough wrapping. WezTerm and
+    /// iTerm2 name tabs from OSC 1; the others follow the window title.
+    fn title_sequence(self, title: &TabTitle) -> String {
+        let title = sanitize_title(title.as_str());
+        match self {
+            Self::WezTerm | Self::ITerm2 => format!("\u{1b}]1;{title}\u{1b}\\"),
+            Self::Kitty | Self::Foot | Self::Ghostty | Self::Tmux => {
+                format!("\u{1b}]2;{title}\u{1b}\\")
+            }
+        }
+    }
+
+    fn working_dir_sequence(self, cwd: &str, host: &str) -> std::io::Result<String> {
+        let encoded_path = encode_file_url_path(cwd)?;
+        Ok(match self {
+            // OSC 1337 takes the path verbatim, so a control character in it
+            // would end the sequence early; such paths are not reported.
+            Self::ITerm2 if cwd.chars().any(char::is_control) => {
+                return Err(std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "working directory contains control characters",
+                ));
+            }
+            Self::ITerm2 => format!("\u{1b}]1337;CurrentDir={cwd}\u{1b}\\"),
+            Self::WezTerm | Self::Kitty | Self::Foot | Self::Ghostty | Self::Tmux => {
+                format!("\u{1b}]7;file://{host}{encoded_path}\u{1b}\\")
+            }
+        })
+    }
+}
+
+/// Backend selection from config: `auto` (default) detects, `off` disables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TerminalBackendChoice {
+    #[default]
+    Auto,
+    Off,
+    Fixed(TerminalBackendKind),
+}
+
+impl TerminalBackendChoice {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(Self::Auto),
+            "off" => Some(Self::Off),
+            other => TerminalBackendKind::parse(other).map(Self::Fixed),
+        }
+    }
+
+    pub(crate) fn resolve<F>(self, env: F) -> Option<TerminalBackendKind>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        match self {
+            Self::Auto => TerminalBackendKind::detect(env),
+            Self::Off => None,
+            Self::Fixed(kind) => Some(kind),
+        }
+    }
+}
+
+/// Control characters would end the OSC early or smuggle in other sequences.
+fn sanitize_title(title: &str) -> String {
+    title.chars().filter(|ch| !ch.is_control()).collect()
+}
+
+/// Writes escape sequences to the controlling terminal, wrapped for tmux
+/// passthrough when running inside tmux.
+#[derive(Debug)]
+struct EscapeSequenceBackend {
+    kind: TerminalBackendKind,
+    passthrough: bool,
+}
+
+impl TerminalBackend for EscapeSequenceBackend {
+    fn run_tab_title(&self, title: &TabTitle) -> std::io::Result<ExitStatus> {
+        let sequence = with_tmux_passthrough(&self.kind.title_sequence(title), self.passthrough);
+        write_terminal_sequence(&sequence)?;
+        Ok(successful_exit_status())
+    }
+
+    fn run_working_dir(&self, cwd: &str) -> std::io::Result<ExitStatus> {
+        let osc = self.kind.working_dir_sequence(cwd, &local_hostname())?;
+        write_terminal_sequence(&with_tmux_passthrough(&osc, self.passthrough))?;
+        Ok(successful_exit_status())
+    }
+}
+
+fn tmux_rename_window_args(title: &TabTitle, pane: Option<&str>) -> Vec<String> {
+    let mut args = vec!["rename-window".to_string()];
+    if let Some(pane) = pane.filter(|pane| !pane.is_empty()) {
+        args.push("-t".to_string());
+        args.push(pane.to_string());
+    }
+    args.push(sanitize_title(title.as_str()));
+    args
+}
+
+/// Turns tmux's stderr into the error reported through the title warning,
+/// so a failed `rename-window` never writes into the TUI.
+fn tmux_failure(status: ExitStatus, stderr: &[u8]) -> std::io::Result<ExitStatus> {
+    let message = String::from_utf8_lossy(stderr);
+    let message = message.trim();
+    if status.success() || message.is_empty() {
+        return Ok(status);
+    }
+    Err(std::io::Error::other(format!(
+        "tmux rename-window failed: {message}"
+    )))
+}
+
+#[derive(Debug)]
+struct TmuxBackend {
+    pane: Option<String>,
+}
+
+impl TerminalBackend for TmuxBackend {
+    fn run_tab_title(&self, title: &TabTitle) -> std::io::Result<ExitStatus> {
+        let output = Command::new("tmux")
+            .args(tmux_rename_window_args(title, self.pane.as_deref()))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()?;
+        tmux_failure(output.status, &output.stderr)
+    }
+
+    fn run_working_dir(&self, cwd: &str) -> std::io::Result<ExitStatus> {
+        let osc = TerminalBackendKind::Tmux.working_dir_sequence(cwd, &local_hostname())?;
+        write_terminal_sequence(&osc)?;
+        Ok(successful_exit_status())
+    }
+}
+
+pub(super) fn backend_for(kind: TerminalBackendKind) -> Arc<dyn TerminalBackend> {
+    match kind {
+        TerminalBackendKind::Tmux => Arc::new(TmuxBackend {
+            pane: std::env::var("TMUX_PANE").ok(),
+        }),
+        kind => Arc::new(EscapeSequenceBackend {
+            kind,
+            passthrough: tmux_passthrough_enabled(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    type TestResult<T = ()> = std::result::Result<T, &'static str>;
+
+    fn title(value: &str) -> TestResult<TabTitle> {
+        TabTitle::try_new(value.to_string()).map_err(|_| "expected non-empty tab title")
+    }
+
+    fn detect(vars: &[(&str, &str)]) -> Option<TerminalBackendKind> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect();
+        TerminalBackendKind::detect(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn detect_prefers_outer_terminal_over_tmux() {
+        assert_eq!(
+            detect(&[("WEZTERM_PANE", "3"), ("TMUX", "/tmp/tmux")]),
+            Some(TerminalBackendKind::WezTerm)
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty")]),
+            Some(TerminalBackendKind::Kitty)
+        );
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "ghostty")]),
+            Some(TerminalBackendKind::Ghostty)
+        );
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "iTerm.app")]),
+            Some(TerminalBackendKind::ITerm2)
+        );
+        assert_eq!(
+            detect(&[("TERM", "foot-extra")]),
+            Some(TerminalBackendKind::Foot)
+        );
+        assert_eq!(
+            detect(&[("TMUX", "/tmp/tmux"), ("TERM", "tmux-256color")]),
+            Some(TerminalBackendKind::Tmux)
+        );
+        assert_eq!(detect(&[("WEZTERM_PANE", ""), ("TERM", "xterm")]), None);
+    }
+
+    #[test]
+    fn choice_parses_and_resolves() {
+        assert_eq!(
+            TerminalBackendChoice::parse("auto"),
+            Some(TerminalBackendChoice::Auto)
+        );
+        assert_eq!(
+            TerminalBackendChoice::parse("kitty"),
+            Some(TerminalBackendChoice::Fixed(TerminalBackendKind::Kitty))
+        );
+        assert_eq!(TerminalBackendChoice::parse("xterm"), None);
+        assert_eq!(TerminalBackendChoice::Off.resolve(|_| None), None);
+        assert_eq!(
+            TerminalBackendChoice::Fixed(TerminalBackendKind::Foot).resolve(|_| None),
+            Some(TerminalBackendKind::Foot)
+        );
+        assert_eq!(
+            TerminalBackendChoice::Auto
+                .resolve(|name| (name == "WEZTERM_PANE").then(|| "1".into())),
+            Some(TerminalBackendKind::WezTerm)
+        );
+    }
+
+    #[test]
+    fn wezterm_and_iterm2_set_tab_title_with_osc1() -> TestResult {
+        let tab = title("repo")?;
+        assert_eq!(
+            TerminalBackendKind::WezTerm.title_sequence(&tab),
+            "\u{1b}]1;repo\u{1b}\\"
+        );
+        assert_eq!(
+            TerminalBackendKind::ITerm2.title_sequence(&tab),
+            "\u{1b}]1;repo\u{1b}\\"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn kitty_foot_and_ghostty_set_window_title_with_osc2() -> TestResult {
+        let tab = title("repo")?;
+        for kind in [
+            TerminalBackendKind::Kitty,
+            TerminalBackendKind::Foot,
+            TerminalBackendKind::Ghostty,
+        ] {
+            assert_eq!(kind.title_sequence(&tab), "\u{1b}]2;repo\u{1b}\\");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn title_sequence_strips_control_characters() -> TestResult {
+        let tab = title("re\u{1b}]0;po\u{7}")?;
+        assert_eq!(
+            TerminalBackendKind::Kitty.title_sequence(&tab),
+            "\u{1b}]2;re]0;po\u{1b}\\"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn osc7_backends_encode_file_urls() -> TestResult {
+        for kind in [
+            TerminalBackendKind::WezTerm,
+            TerminalBackendKind::Kitty,
+            TerminalBackendKind::Foot,
+            TerminalBackendKind::Ghostty,
+            TerminalBackendKind::Tmux,
+        ] {
+            let sequence = kind
+                .working_dir_sequence("/tmp/my dir", "host")
+                .map_err(|_| "expected valid OSC 7 sequence")?;
+            assert_eq!(sequence, "\u{1b}]7;file://host/tmp/my%20dir\u{1b}\\");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn iterm2_reports_current_dir_with_osc1337() -> TestResult {
+        let sequence = TerminalBackendKind::ITerm2
+            .working_dir_sequence("/tmp/my dir", "host")
+            .map_err(|_| "expected valid OSC 1337 sequence")?;
+        assert_eq!(sequence, "\u{1b}]1337;CurrentDir=/tmp/my dir\u{1b}\\");
+        assert!(
+            TerminalBackendKind::ITerm2
+                .working_dir_sequence("relative", "host")
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn iterm2_rejects_working_dirs_with_control_characters() {
+        for cwd in ["/tmp/a\u{1b}\\\u{1b}]0;pwned", "/tmp/bell\u{7}"] {
+            assert!(
+                TerminalBackendKind::ITerm2
+                    .working_dir_sequence(cwd, "host")
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn tmux_renames_the_window_of_its_pane() -> TestResult {
+        let tab = title("repo")?;
+        assert_eq!(
+            tmux_rename_window_args(&tab, Some("%3")),
+            vec!["rename-window", "-t", "%3", "repo"]
+        );
+        assert_eq!(
+            tmux_rename_window_args(&tab, None),
+            vec!["rename-window", "repo"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tmux_failure_reports_stderr_as_the_error() {
+        let ok = successful_exit_status();
+        assert!(tmux_failure(ok, b"ignored").is_ok());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            let failed = ExitStatus::from_raw(1 << 8);
+            assert_eq!(
+                tmux_failure(failed, b"no server running on /tmp/tmux\n")
+                    .map_err(|err| err.to_string()),
+                Err("tmux rename-window failed: no server running on /tmp/tmux".to_string())
+            );
+            assert!(tmux_failure(failed, b"  \n").is_ok_and(|status| !status.success()));
+        }
+    }
+}
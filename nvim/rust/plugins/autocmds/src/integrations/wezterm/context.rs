@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
 
 use nvim_oxi::Array;
 use nvim_oxi::Result;
@@ -7,25 +11,66 @@ use nvim_oxi::api;
 use nvim_oxi::api::opts::OptionOpts;
 use nvimrs_support::ProjectRoot;
 
+use crate::machines::wezterm::TabTitleField;
+use crate::machines::wezterm::TabTitleInputs;
+use crate::machines::wezterm::TabTitleTemplate;
+use crate::machines::wezterm::parse_git_head;
+use crate::machines::wezterm::parse_gitdir_file;
+
 const PROJECT_ROOT_VAR: &str = "project_root";
 
+static GIT_BRANCHES: LazyLock<Mutex<GitBranchCache>> =
+    LazyLock::new(|| Mutex::new(GitBranchCache::default()));
+
+/// Branch per project root, so `BufEnter` does not walk `.git` and read
+/// `HEAD` on every buffer switch. Cleared on `FocusGained` and `DirChanged`,
+/// when a checkout may have happened outside the editor.
+#[derive(Debug, Default)]
+struct GitBranchCache {
+    branches: HashMap<PathBuf, Option<String>>,
+}
+
+impl GitBranchCache {
+    fn get_or_read<F>(&mut self, root: &Path, read: F) -> Option<String>
+    where
+        F: FnOnce(&Path) -> Option<String>,
+    {
+        self.branches
+            .entry(root.to_path_buf())
+            .or_insert_with(|| read(root))
+            .clone()
+    }
+}
+
+fn cached_git_branch(root: &Path) -> Option<String> {
+    match GIT_BRANCHES.lock() {
+        Ok(mut cache) => cache.get_or_read(root, read_git_branch),
+        Err(poisoned) => poisoned.into_inner().get_or_read(root, read_git_branch),
+    }
+}
+
+pub(super) fn invalidate_git_branches() {
+    match GIT_BRANCHES.lock() {
+        Ok(mut cache) => cache.branches.clear(),
+        Err(poisoned) => poisoned.into_inner().branches.clear(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(super) struct WeztermContext {
     pub(super) home: Option<PathBuf>,
 }
 
 impl WeztermContext {
+    /// `None` until setup selected a terminal backend, or when it selected none.
     pub(super) fn detect() -> Option<Self> {
-        let in_wezterm = std::env::var_os("WEZTERM_PANE").is_some_and(|value| !value.is_empty());
-        if !in_wezterm {
-            return None;
-        }
+        super::selected_terminal_backend()?;
         let home = std::env::var_os("HOME").map(PathBuf::from);
         Some(Self { home })
     }
 }
 
-pub(super) fn current_buf_project_root() -> Result<Option<ProjectRoot>> {
+fn current_buf_project_root() -> Result<Option<ProjectRoot>> {
     let buf = api::get_current_buf();
     if !buf.is_valid() {
         return Ok(None);
@@ -37,9 +82,67 @@ pub(super) fn current_buf_project_root() -> Result<Option<ProjectRoot>> {
     Ok(ProjectRoot::try_new(root.to_string_lossy().into_owned()).ok())
 }
 
+/// Collects what `template` renders; unused fields skip their lookups.
+pub(super) fn current_tab_title_inputs(template: &TabTitleTemplate) -> Result<TabTitleInputs> {
+    let root = current_buf_project_root()?;
+    let buf = api::get_current_buf();
+    let file = if template.uses(TabTitleField::File) {
+        buf.get_name()
+            .ok()
+            .map(|name| name.to_string_lossy().into_owned())
+            .filter(|name| !name.is_empty())
+            .map(PathBuf::from)
+    } else {
+        None
+    };
+    let branch = if template.uses(TabTitleField::Branch) {
+        root.as_ref()
+            .and_then(|root| cached_git_branch(root.as_path()))
+    } else {
+        None
+    };
+    let modified = template.uses(TabTitleField::Modified)
+        && api::get_option_value::<bool>("modified", &OptionOpts::builder().buf(buf).build())
+            .unwrap_or(false);
+    let mode = if template.uses(TabTitleField::Mode) {
+        let mode: NvimString = api::call_function("mode", Array::new())?;
+        Some(mode.to_string_lossy().into_owned())
+    } else {
+        None
+    };
+    Ok(TabTitleInputs {
+        root,
+        file,
+        branch,
+        modified,
+        mode,
+    })
+}
+
+/// Reads the branch from the nearest `.git` at or above `start` without
+/// spawning git. Follows `gitdir:` files used by worktrees and submodules.
+fn read_git_branch(start: &Path) -> Option<String> {
+    let dot_git = start
+        .ancestors()
+        .map(|dir| dir.join(".git"))
+        .find(|candidate| candidate.exists())?;
+    let git_dir = if dot_git.is_dir() {
+        dot_git
+    } else {
+        let contents = std::fs::read_to_string(&dot_git).ok()?;
+        let target = Path::new(parse_gitdir_file(&contents)?);
+        dot_git.parent()?.join(target)
+    };
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    parse_git_head(&head)
+}
+
+/// Follows the cwd policy so the terminal always reports the directory the
+/// window was (or is about to be) switched to.
 pub(super) fn current_window_cwd() -> Result<Option<String>> {
-    let cwd: NvimString = api::call_function("getcwd", Array::new())?;
-    let cwd = cwd.to_string_lossy().into_owned();
+    let Some(cwd) = crate::current_policy_cwd()? else {
+        return Ok(None);
+    };
     if !crate::is_dir(&cwd) {
         return Ok(None);
     }
@@ -62,6 +165,47 @@ fn buftype_requires_wezterm_sync(buftype: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
+    #[test]
+    fn read_git_branch_follows_worktree_gitdir_files() -> std::io::Result<()> {
+        let root = std::env::temp_dir().join(format!("nvimrs-git-head-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let main_git = root.join("main/.git");
+        let worktree_git = main_git.join("worktrees/feature");
+        fs::create_dir_all(&worktree_git)?;
+        fs::create_dir_all(root.join("feature/src"))?;
+        fs::write(main_git.join("HEAD"), "ref: refs/heads/main\n")?;
+        fs::write(worktree_git.join("HEAD"), "ref: refs/heads/feature\n")?;
+        fs::write(
+            root.join("feature/.git"),
+            "gitdir: ../main/.git/worktrees/feature\n",
+        )?;
+
+        let main = super::read_git_branch(&root.join("main"));
+        let feature = super::read_git_branch(&root.join("feature/src"));
+        fs::remove_dir_all(&root)?;
+        assert_eq!(main.as_deref(), Some("main"));
+        assert_eq!(feature.as_deref(), Some("feature"));
+        Ok(())
+    }
+
+    #[test]
+    fn git_branch_cache_reads_each_root_once_until_cleared() {
+        let mut cache = super::GitBranchCache::default();
+        let mut reads = 0;
+        let mut read = |_: &std::path::Path| {
+            reads += 1;
+            Some("main".to_string())
+        };
+        let root = std::path::Path::new("/repo");
+        assert_eq!(cache.get_or_read(root, &mut read).as_deref(), Some("main"));
+        assert_eq!(cache.get_or_read(root, &mut read).as_deref(), Some("main"));
+        cache.branches.clear();
+        assert_eq!(cache.get_or_read(root, &mut read).as_deref(), Some("main"));
+        assert_eq!(reads, 2);
+    }
+
     #[test]
     fn buftype_requires_wezterm_sync_allows_normal_and_acwrite_buffers() {
         assert!(super::buftype_requires_wezterm_sync(""));
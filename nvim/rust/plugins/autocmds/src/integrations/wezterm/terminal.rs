@@ -5,13 +5,10 @@ use std::process::ExitStatus;
 use std::thread;
 use std::time::Duration;
 
-use nvimrs_support::TabTitle;
 use percent_encoding::AsciiSet;
 use percent_encoding::NON_ALPHANUMERIC;
 use percent_encoding::percent_encode;
 
-use super::WeztermCommandRunner;
-
 const TERMINAL_WRITE_MAX_WOULD_BLOCK_RETRIES: usize = 16;
 const TERMINAL_WRITE_RETRY_DELAY: Duration = Duration::from_millis(5);
 const FILE_URL_PATH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
@@ -22,22 +19,22 @@ const FILE_URL_PATH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
     .remove(b'~');
 
 #[cfg(unix)]
-fn successful_exit_status() -> ExitStatus {
+pub(super) fn successful_exit_status() -> ExitStatus {
     use std::os::unix::process::ExitStatusExt;
     ExitStatus::from_raw(0)
 }
 
 #[cfg(windows)]
-fn successful_exit_status() -> ExitStatus {
+pub(super) fn successful_exit_status() -> ExitStatus {
     use std::os::windows::process::ExitStatusExt;
     ExitStatus::from_raw(0)
 }
 
-fn tmux_passthrough_enabled() -> bool {
+pub(super) fn tmux_passthrough_enabled() -> bool {
     std::env::var_os("TMUX").is_some()
 }
 
-fn with_tmux_passthrough(osc: &str, passthrough_enabled: bool) -> String {
+pub(super) fn with_tmux_passthrough(osc: &str, passthrough_enabled: bool) -> String {
     if !passthrough_enabled {
         return osc.to_string();
     }
@@ -53,12 +50,16 @@ fn with_tmux_passthrough(osc: &str, passthrough_enabled: bool) -> String {
     tmux_passthrough
 }
 
-fn build_tab_title_sequence(title: &TabTitle) -> String {
-    let osc = format!("\u{1b}]1;{}\u{1b}\\", title.as_str());
-    with_tmux_passthrough(&osc, tmux_passthrough_enabled())
+pub(super) fn local_hostname() -> String {
+    hostname::get()
+        .ok()
+        .and_then(|host| host.into_string().ok())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
 }
 
-fn build_working_dir_sequence(cwd: &str) -> std::io::Result<String> {
+/// Validate `cwd` and percent-encode it for a `file://` URL path.
+pub(super) fn encode_file_url_path(cwd: &str) -> std::io::Result<String> {
     let cwd = std::path::Path::new(cwd);
     if !cwd.is_absolute() {
         return Err(Error::new(
@@ -66,11 +67,6 @@ fn build_working_dir_sequence(cwd: &str) -> std::io::Result<String> {
             format!("cwd {cwd:?} is not an absolute path"),
         ));
     }
-    let host = hostname::get()
-        .ok()
-        .and_then(|host| host.into_string().ok())
-        .filter(|host| !host.is_empty())
-        .unwrap_or_else(|| "localhost".to_string());
     #[cfg(unix)]
     let encoded_path = {
         use std::os::unix::ffi::OsStrExt;
@@ -81,9 +77,7 @@ fn build_working_dir_sequence(cwd: &str) -> std::io::Result<String> {
         let normalized = cwd.to_string_lossy().replace('\\', "/");
         percent_encode(normalized.as_bytes(), FILE_URL_PATH_ENCODE_SET).to_string()
     };
-
-    let osc = format!("\u{1b}]7;file://{host}{encoded_path}\u{1b}\\");
-    Ok(with_tmux_passthrough(&osc, tmux_passthrough_enabled()))
+    Ok(encoded_path)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -191,7 +185,7 @@ fn write_sequence_to_stdout(sequence: &str) -> std::io::Result<()> {
 }
 
 #[cfg(unix)]
-fn write_terminal_sequence(sequence: &str) -> std::io::Result<()> {
+pub(super) fn write_terminal_sequence(sequence: &str) -> std::io::Result<()> {
     match std::fs::OpenOptions::new().write(true).open("/dev/tty") {
         Ok(mut tty) => write_sequence_to(&mut tty, sequence),
         Err(_) => write_sequence_to_stdout(sequence),
@@ -199,27 +193,10 @@ fn write_terminal_sequence(sequence: &str) -> std::io::Result<()> {
 }
 
 #[cfg(not(unix))]
-fn write_terminal_sequence(sequence: &str) -> std::io::Result<()> {
+pub(super) fn write_terminal_sequence(sequence: &str) -> std::io::Result<()> {
     write_sequence_to_stdout(sequence)
 }
 
-#[derive(Debug, Default)]
-pub(super) struct EscapeSequenceWeztermCommandRunner;
-
-impl WeztermCommandRunner for EscapeSequenceWeztermCommandRunner {
-    fn run_tab_title(&self, title: &TabTitle) -> std::io::Result<ExitStatus> {
-        let sequence = build_tab_title_sequence(title);
-        write_terminal_sequence(&sequence)?;
-        Ok(successful_exit_status())
-    }
-
-    fn run_working_dir(&self, cwd: &str) -> std::io::Result<ExitStatus> {
-        let sequence = build_working_dir_sequence(cwd)?;
-        write_terminal_sequence(&sequence)?;
-        Ok(successful_exit_status())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,10 +284,10 @@ mod tests {
     }
 
     #[test]
-    fn working_dir_sequence_contains_osc7_payload() -> TestResult {
-        let sequence =
-            build_working_dir_sequence("/tmp").map_err(|_| "expected valid OSC7 sequence")?;
-        assert!(sequence.contains("]7;file://"));
+    fn encode_file_url_path_escapes_and_rejects_relative_paths() -> TestResult {
+        let encoded = encode_file_url_path("/tmp/my dir").map_err(|_| "expected absolute path")?;
+        assert_eq!(encoded, "/tmp/my%20dir");
+        assert!(encode_file_url_path("relative").is_err());
         Ok(())
     }
 }
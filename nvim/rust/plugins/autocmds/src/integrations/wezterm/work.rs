@@ -3,7 +3,7 @@ use std::process::ExitStatus;
 use crate::machines::wezterm::WeztermCommand;
 use nvimrs_support::TabTitle;
 
-use super::WeztermCommandRunner;
+use super::backend::TerminalBackend;
 
 #[derive(Debug)]
 pub(super) enum WeztermCommandResult {
@@ -93,7 +93,7 @@ impl WeztermWorkItem {
 
 pub(super) fn run_wezterm_command(
     command: WeztermCommand,
-    runner: &dyn WeztermCommandRunner,
+    runner: &dyn TerminalBackend,
 ) -> WeztermCommandResult {
     match command {
         WeztermCommand::SetTabTitle(title) => WeztermCommandResult::TabTitle {
@@ -3,24 +3,46 @@ mod machines;
 mod types;
 
 use machines::buffer_mru::BufferMruState;
+use machines::buffer_mru::RECENT_FILES_FILE;
+use machines::buffer_mru::RecentFilesJournal;
+use machines::buffer_mru::RecentFilesQuery;
+use machines::buffer_mru::flush_recent_files;
+use machines::buffer_mru::load_recent_files;
+use machines::cwd_policy::CwdInputs;
+use machines::cwd_policy::CwdPathRule;
+use machines::cwd_policy::CwdPolicy;
+use machines::cwd_policy::CwdPolicyConfig;
+use machines::cwd_policy::CwdScope;
+use machines::cwd_policy::CwdTarget;
 use machines::oil_last_buf::OilLastBufEvent;
 use machines::oil_last_buf::OilLastBufState;
+use machines::oil_rename::BufferUpdate;
+use machines::oil_rename::OpenBuffer;
+use machines::oil_rename::plan_buffer_updates;
+use machines::wezterm::DEFAULT_TAB_TITLE_TEMPLATE;
+use machines::wezterm::TabTitleFormat;
+use machines::wezterm::TabTitleTemplate;
 use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Function;
+use nvim_oxi::Object;
 use nvim_oxi::Result;
 use nvim_oxi::String as NvimString;
 use nvim_oxi::api;
 use nvim_oxi::api::Buffer;
 use nvim_oxi::api::Window;
+use nvim_oxi::api::opts::BufDeleteOpts;
 use nvim_oxi::api::opts::CmdOpts;
 use nvim_oxi::api::opts::CreateAugroupOpts;
 use nvim_oxi::api::opts::CreateAutocmdOpts;
 use nvim_oxi::api::opts::OptionOpts;
 use nvim_oxi::api::types::AutocmdCallbackArgs;
 use nvim_oxi::api::types::CmdInfos;
+use nvim_oxi::conversion::FromObject;
 use nvim_oxi::mlua;
 use nvim_oxi::schedule;
+use nvimrs_nvim_oxi_utils::decode;
+use nvimrs_nvim_oxi_utils::dict;
 use nvimrs_nvim_oxi_utils::guard;
 use nvimrs_nvim_oxi_utils::handles::BufHandle;
 use nvimrs_nvim_oxi_utils::handles::WinHandle;
@@ -29,21 +51,28 @@ use nvimrs_nvim_oxi_utils::lua;
 use nvimrs_nvim_oxi_utils::notify;
 use nvimrs_nvim_oxi_utils::state::StateCell;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::LazyLock;
 use types::AutocmdAction;
 use types::OilAction;
 use types::OilActionsPostArgs;
 
+use nvimrs_nvim_utils::frecency::now_secs;
 use nvimrs_nvim_utils::path::has_uri_scheme;
 use nvimrs_nvim_utils::path::normalize_path;
 use nvimrs_nvim_utils::path::path_is_dir;
 use nvimrs_nvim_utils::path::strip_known_prefixes;
 
 const LOG_CONTEXT: &str = "autocmds";
+const PROJECT_ROOT_VAR: &str = "project_root";
+/// Set on buffers whose file oil deleted; holds the deleted path.
+const DELETED_FILE_VAR: &str = "nvimrs_deleted_file";
 
 #[derive(Default)]
 struct AutocmdState {
     buffer_mru: BufferMruState,
+    recent_files: RecentFilesJournal,
+    cwd_policy: CwdPolicyConfig,
     oil_last_buf: OilLastBufState,
 }
 
@@ -166,28 +195,43 @@ fn win_current_cwd(win: &Window) -> Result<Option<String>> {
     Ok(Some(cwd))
 }
 
-fn set_win_cwd(win: &Window, dir: &str) -> Result<()> {
+fn win_has_local_cwd(win: &Window) -> Result<bool> {
+    let local: i64 = win.call(|()| -> Result<i64> {
+        let local: i64 = api::call_function("haslocaldir", Array::new())?;
+        Ok(local)
+    })?;
+    Ok(local == 1)
+}
+
+/// Point `win` at `target` with `lcd`, or `tcd` for tab-scoped targets.
+fn set_win_cwd(win: &Window, target: &CwdTarget) -> Result<()> {
     if !win.is_valid() {
         return Ok(());
     }
-    if dir.is_empty() || !is_dir(dir) {
+    let dir = target.dir.to_string_lossy().into_owned();
+    if dir.is_empty() || !is_dir(&dir) {
         return Ok(());
     }
 
     if let Some(current) = win_current_cwd(win)? {
-        let unchanged = match (normalize_path(&current), normalize_path(dir)) {
+        let unchanged = match (normalize_path(&current), normalize_path(&dir)) {
             (Some(current), Some(target)) => current == target,
             _ => current == dir,
         };
-        if unchanged {
+        // `tcd` must also drop a window-local directory left by `lcd`.
+        let scoped = target.scope == CwdScope::Window || !win_has_local_cwd(win)?;
+        if unchanged && scoped {
             return Ok(());
         }
     }
 
-    let dir = dir.to_string();
+    let cmd = match target.scope {
+        CwdScope::Window => "lcd",
+        CwdScope::Tab => "tcd",
+    };
     let _: () = win.call(move |()| -> Result<()> {
         let mut infos_builder = CmdInfos::builder();
-        infos_builder.cmd("lcd").args([dir]);
+        infos_builder.cmd(cmd).args([dir]);
         let infos = infos_builder.build();
         let opts = CmdOpts::builder().build();
         let _ = api::cmd(&infos, &opts)?;
@@ -196,7 +240,7 @@ fn set_win_cwd(win: &Window, dir: &str) -> Result<()> {
     Ok(())
 }
 
-fn file_dir_for_buf_name(buf: &Buffer, name_str: &str) -> Result<Option<String>> {
+fn file_path_for_buf_name(buf: &Buffer, name_str: &str) -> Result<Option<PathBuf>> {
     if !buf.is_valid() {
         return Ok(None);
     }
@@ -211,12 +255,62 @@ fn file_dir_for_buf_name(buf: &Buffer, name_str: &str) -> Result<Option<String>>
     if has_uri_scheme(name_str) {
         return Ok(None);
     }
-    let dir: NvimString = api::call_function("fnamemodify", Array::from_iter([name_str, ":p:h"]))?;
-    let dir = dir.to_string_lossy().into_owned();
-    if dir.is_empty() {
+    let path: NvimString = api::call_function("fnamemodify", Array::from_iter([name_str, ":p"]))?;
+    let path = path.to_string_lossy().into_owned();
+    if path.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(PathBuf::from(path)))
+}
+
+/// Evaluate the cwd policy for `buf`, which shows `path`; `dir` is where
+/// the file-directory policy points.
+fn resolve_cwd_target(buf: &Buffer, path: &Path, dir: &Path) -> Result<Option<CwdTarget>> {
+    let filetype: NvimString =
+        api::get_option_value("filetype", &OptionOpts::builder().buf(buf.clone()).build())?;
+    let filetype = filetype.to_string_lossy();
+    let root = buf_project_root(buf);
+    let inputs = CwdInputs {
+        path,
+        dir,
+        filetype: filetype.as_ref(),
+        project_root: root.as_deref().map(Path::new),
+    };
+    let state = state_lock();
+    Ok(state.cwd_policy.resolve(&inputs))
+}
+
+/// Policy target for `buf`: oil buffers resolve against the directory they
+/// list, regular files against their path.
+fn cwd_target_for_buf(buf: &Buffer) -> Result<Option<CwdTarget>> {
+    if !buf.is_valid() {
+        return Ok(None);
+    }
+    let name = buf.get_name()?;
+    let name = name.to_string_lossy();
+    if is_oil_buffer_name(name.as_ref()) {
+        let Some(dir) = oil_current_dir(BufHandle::from_buffer(buf))? else {
+            return Ok(None);
+        };
+        let dir = PathBuf::from(dir);
+        return resolve_cwd_target(buf, &dir, &dir);
+    }
+    let Some(path) = file_path_for_buf_name(buf, name.as_ref())? else {
         return Ok(None);
+    };
+    let Some(dir) = path.parent() else {
+        return Ok(None);
+    };
+    resolve_cwd_target(buf, &path, dir)
+}
+
+/// Directory the terminal integration reports for the current window: the
+/// policy target when there is one, otherwise the window's cwd.
+pub(crate) fn current_policy_cwd() -> Result<Option<String>> {
+    if let Some(target) = cwd_target_for_buf(&api::get_current_buf())? {
+        return Ok(Some(target.dir.to_string_lossy().into_owned()));
     }
-    Ok(Some(dir))
+    win_current_cwd(&api::get_current_win())
 }
 
 fn win_for_buf(buf: &Buffer) -> Result<Option<Window>> {
@@ -283,8 +377,13 @@ fn track_buffer_mru(buf: &Buffer, name: &str) {
     if !should_track_buffer_mru(buf, name) {
         return;
     }
+    let recent = recent_file_path(buf, name);
+    let root = recent.as_ref().and_then(|_| buf_project_root(buf));
     let mut state = state_lock();
     state.buffer_mru.enter(BufHandle::from_buffer(buf));
+    if let Some(path) = recent {
+        state.recent_files.visit(&path, root.as_deref(), now_secs());
+    }
 }
 
 fn track_current_buffer_mru() {
@@ -323,6 +422,114 @@ fn switch_to_last_buffer() -> Result<()> {
     Ok(())
 }
 
+/// Normalized path of a regular file buffer; `None` for special buffers and
+/// URI names, which have no stable identity across sessions.
+fn recent_file_path(buf: &Buffer, name: &str) -> Option<String> {
+    if name.is_empty() || has_uri_scheme(name) || !buf.is_valid() {
+        return None;
+    }
+    let bt: NvimString =
+        api::get_option_value("buftype", &OptionOpts::builder().buf(buf.clone()).build()).ok()?;
+    if !bt.is_empty() {
+        return None;
+    }
+    let absolute: NvimString =
+        api::call_function("fnamemodify", Array::from_iter([name, ":p"])).ok()?;
+    normalize_path(absolute.to_string_lossy().as_ref())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+fn buf_project_root(buf: &Buffer) -> Option<String> {
+    buf.get_var::<NvimString>(PROJECT_ROOT_VAR)
+        .ok()
+        .map(|root| root.to_string_lossy().into_owned())
+        .filter(|root| !root.is_empty())
+}
+
+fn recent_files_path() -> Result<PathBuf> {
+    let dir: NvimString = api::call_function("stdpath", Array::from_iter(["data"]))?;
+    Ok(PathBuf::from(dir.to_string_lossy().into_owned()).join(RECENT_FILES_FILE))
+}
+
+/// Write pending visits to the shared store. On failure the journal is kept
+/// so the next flush retries it.
+fn flush_recent_files_journal() -> Result<()> {
+    let journal = std::mem::take(&mut state_lock().recent_files);
+    if journal.is_empty() {
+        return Ok(());
+    }
+    let path = recent_files_path()?;
+    if let Err(err) = flush_recent_files(&path, &journal, now_secs()) {
+        state_lock().recent_files.restore(journal);
+        notify::warn(LOG_CONTEXT, &format!("recent files flush failed: {err}"));
+    }
+    Ok(())
+}
+
+fn clamp_u64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Files that still exist, most frecent first, including visits not yet
+/// flushed. `cwd_only` restricts the list to files under the current
+/// working directory; `root`, or `project_only` for the current buffer's
+/// project, to files last visited under that project root.
+fn recent_files(opts: Option<&Dictionary>) -> Result<Array> {
+    let cwd_only = opts
+        .and_then(|opts| dict::get_object(opts, "cwd_only"))
+        .and_then(|value| bool::from_object(value).ok())
+        .unwrap_or(false);
+    let limit = opts
+        .and_then(|opts| dict::get_i64(opts, "limit"))
+        .and_then(|limit| usize::try_from(limit).ok())
+        .filter(|limit| *limit > 0);
+    let project_only = opts
+        .and_then(|opts| dict::get_object(opts, "project_only"))
+        .and_then(|value| bool::from_object(value).ok())
+        .unwrap_or(false);
+    let root = match opts.and_then(|opts| dict::get_string_nonempty(opts, "root")) {
+        Some(root) => {
+            Some(normalize_path(&root).map_or(root, |root| root.to_string_lossy().into_owned()))
+        }
+        None if project_only => match buf_project_root(&api::get_current_buf()) {
+            Some(root) => Some(root),
+            None => return Ok(Array::new()),
+        },
+        None => None,
+    };
+    let cwd = if cwd_only {
+        let cwd: NvimString = api::call_function("getcwd", Array::new())?;
+        normalize_path(cwd.to_string_lossy().as_ref()).map(|cwd| cwd.to_string_lossy().into_owned())
+    } else {
+        None
+    };
+
+    let path = recent_files_path()?;
+    let mut files = load_recent_files(&path)
+        .map_err(|err| nvim_oxi::Error::from(api::Error::Other(err.to_string())))?;
+    let now = now_secs();
+    state_lock().recent_files.apply_to(&mut files, now);
+    let query = RecentFilesQuery { cwd, root, limit };
+    let records: Array = files
+        .select(&query, now, |path| Path::new(path).is_file())
+        .into_iter()
+        .map(|record| {
+            let root = record
+                .root
+                .as_deref()
+                .map_or_else(Object::nil, Object::from);
+            Dictionary::from_iter([
+                ("path", Object::from(record.path.as_str())),
+                ("root", root),
+                ("visits", Object::from(clamp_u64(record.visits))),
+                ("last_visit", Object::from(clamp_u64(record.last_visit))),
+                ("score", Object::from(clamp_u64(record.frecency(now)))),
+            ])
+        })
+        .collect();
+    Ok(records)
+}
+
 fn apply_oil_last_buf_event(event: OilLastBufEvent) {
     let mut state = state_lock();
     let _ = state.oil_last_buf.reduce(event);
@@ -376,13 +583,24 @@ fn on_file_cwd(args: &AutocmdCallbackArgs) -> Result<AutocmdAction> {
         return on_oil_buf(args);
     }
 
-    let Some(dir) = file_dir_for_buf_name(&args.buffer, name.as_ref())? else {
+    let Some(target) = cwd_target_for_buf(&args.buffer)? else {
         return Ok(AutocmdAction::Keep);
     };
     let Some(win) = win_for_buf(&args.buffer)? else {
         return Ok(AutocmdAction::Keep);
     };
-    set_win_cwd(&win, &dir)?;
+    set_win_cwd(&win, &target)?;
+    Ok(AutocmdAction::Keep)
+}
+
+/// The project root is usually announced after `BufEnter`, so root-based
+/// policies are re-evaluated once it is known.
+fn on_project_root_changed() -> Result<AutocmdAction> {
+    let buf = api::get_current_buf();
+    let Some(target) = cwd_target_for_buf(&buf)? else {
+        return Ok(AutocmdAction::Keep);
+    };
+    set_win_cwd(&api::get_current_win(), &target)?;
     Ok(AutocmdAction::Keep)
 }
 
@@ -399,7 +617,10 @@ fn on_oil_buf(args: &AutocmdCallbackArgs) -> Result<AutocmdAction> {
         win: win_handle,
         buf: buf_handle,
     });
-    set_win_cwd(&win, &dir)?;
+    let dir = PathBuf::from(dir);
+    if let Some(target) = resolve_cwd_target(&args.buffer, &dir, &dir)? {
+        set_win_cwd(&win, &target)?;
+    }
     Ok(AutocmdAction::Keep)
 }
 
@@ -417,8 +638,16 @@ fn on_win_closed(args: &AutocmdCallbackArgs) -> AutocmdAction {
 fn on_buf_wipeout(args: &AutocmdCallbackArgs) -> AutocmdAction {
     let buf_handle = BufHandle::from_buffer(&args.buffer);
     apply_oil_last_buf_event(OilLastBufEvent::BufWiped { buf: buf_handle });
+    let recent = args
+        .buffer
+        .get_name()
+        .ok()
+        .and_then(|name| recent_file_path(&args.buffer, name.to_string_lossy().as_ref()));
     let mut state = state_lock();
     state.buffer_mru.wipe(buf_handle);
+    if let Some(path) = recent {
+        state.recent_files.forget(&path);
+    }
     AutocmdAction::Keep
 }
 
@@ -433,12 +662,133 @@ fn on_oil_actions_post(args: AutocmdCallbackArgs) -> Result<AutocmdAction> {
             return Ok(AutocmdAction::Keep);
         }
     };
-    if let OilAction::Move(action) = parsed.action {
-        snacks_rename_file(action.src_url.as_str(), action.dest_url.as_str())?;
+    for action in &parsed.actions {
+        if let OilAction::Move(action) = action {
+            snacks_rename_file(action.src_url.as_str(), action.dest_url.as_str())?;
+        }
+    }
+
+    let mut deleted = Vec::new();
+    for update in plan_buffer_updates(&parsed.actions, &open_file_buffers()) {
+        match update {
+            BufferUpdate::Rename { buf, from, to } => rename_buffer(buf, &from, &to)?,
+            BufferUpdate::Deleted { buf, path } => {
+                if let Some(mut buffer) = buf.valid_buffer() {
+                    buffer.set_var(DELETED_FILE_VAR, path.to_string_lossy().into_owned())?;
+                    deleted.push(buf);
+                }
+            }
+        }
+    }
+    if !deleted.is_empty() {
+        schedule(move |()| {
+            run_scheduled("offer_wipe_deleted_buffers", || {
+                offer_wipe_deleted_buffers(&deleted)
+            });
+        });
     }
     Ok(AutocmdAction::Keep)
 }
 
+fn open_file_buffers() -> Vec<OpenBuffer> {
+    api::list_bufs()
+        .filter(Buffer::is_valid)
+        .filter_map(|buf| {
+            let name = buf.get_name().ok()?;
+            let name = name.to_string_lossy();
+            if has_uri_scheme(name.as_ref()) {
+                return None;
+            }
+            Some(OpenBuffer {
+                buf: BufHandle::from_buffer(&buf),
+                path: normalize_path(name.as_ref())?,
+            })
+        })
+        .collect()
+}
+
+fn buffer_is_modified(buf: &Buffer) -> bool {
+    let opt_opts = OptionOpts::builder().buf(buf.clone()).build();
+    api::get_option_value::<bool>("modified", &opt_opts).unwrap_or(false)
+}
+
+/// Point `buf` at its moved file. `nvim_buf_set_name` leaves an unlisted
+/// placeholder under the old name, which is wiped, and an unmodified buffer
+/// is re-read so it no longer counts as a new file on the next write.
+fn rename_buffer(buf: BufHandle, from: &Path, to: &Path) -> Result<()> {
+    let Some(mut buffer) = buf.valid_buffer() else {
+        return Ok(());
+    };
+    buffer.set_name(to)?;
+
+    for stale in api::list_bufs() {
+        if stale.handle() == buffer.handle() || !stale.is_valid() || buffer_is_listed(&stale) {
+            continue;
+        }
+        let is_placeholder = stale
+            .get_name()
+            .ok()
+            .and_then(|name| normalize_path(name.to_string_lossy().as_ref()))
+            .is_some_and(|path| path == from);
+        if is_placeholder {
+            stale.delete(&BufDeleteOpts::builder().force(true).build())?;
+        }
+    }
+
+    if !buffer_is_modified(&buffer) {
+        let _: () = buffer.call(|()| -> Result<()> {
+            api::command("silent! edit")?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Ask once whether to wipe the buffers of files oil deleted. Modified
+/// buffers are kept so unsaved changes can still be written elsewhere.
+fn offer_wipe_deleted_buffers(bufs: &[BufHandle]) -> Result<()> {
+    let bufs: Vec<Buffer> = bufs
+        .iter()
+        .copied()
+        .filter_map(BufHandle::valid_buffer)
+        .collect();
+    if bufs.is_empty() {
+        return Ok(());
+    }
+    let prompt = if bufs.len() == 1 {
+        "Wipe the buffer of the deleted file?".to_string()
+    } else {
+        format!("Wipe {} buffers of deleted files?", bufs.len())
+    };
+    let choice: i64 = api::call_function(
+        "confirm",
+        Array::from_iter([
+            Object::from(prompt),
+            Object::from("&Yes\n&No"),
+            Object::from(2_i64),
+        ]),
+    )?;
+    if choice != 1 {
+        return Ok(());
+    }
+
+    let mut kept = 0_usize;
+    for buf in bufs {
+        if buffer_is_modified(&buf) {
+            kept += 1;
+            continue;
+        }
+        buf.delete(&BufDeleteOpts::builder().build())?;
+    }
+    if kept > 0 {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("kept {kept} modified buffer(s) of deleted files"),
+        );
+    }
+    Ok(())
+}
+
 fn setup_dashboard_autocmd() -> Result<()> {
     let group = api::create_augroup(
         "UserDashboard",
@@ -464,6 +814,15 @@ fn setup_file_cwd_autocmd() -> Result<()> {
         .callback(|args: AutocmdCallbackArgs| run_autocmd("on_file_cwd", || on_file_cwd(&args)))
         .build();
     api::create_autocmd(["BufEnter"], &opts)?;
+
+    let root_opts = CreateAutocmdOpts::builder()
+        .group(group)
+        .patterns(["ProjectRootChanged"])
+        .callback(|_args: AutocmdCallbackArgs| {
+            run_autocmd("on_project_root_changed", on_project_root_changed)
+        })
+        .build();
+    api::create_autocmd(["User"], &root_opts)?;
     Ok(())
 }
 
@@ -492,6 +851,24 @@ fn setup_oil_last_buf_autocmds() -> Result<()> {
     Ok(())
 }
 
+fn setup_recent_files_autocmd() -> Result<()> {
+    let group = api::create_augroup(
+        "UserRecentFiles",
+        &CreateAugroupOpts::builder().clear(true).build(),
+    )?;
+    let opts = CreateAutocmdOpts::builder()
+        .group(group)
+        .callback(|_args: AutocmdCallbackArgs| {
+            run_autocmd("flush_recent_files", || {
+                flush_recent_files_journal()?;
+                Ok(AutocmdAction::Keep)
+            })
+        })
+        .build();
+    api::create_autocmd(["FocusLost", "VimLeavePre"], &opts)?;
+    Ok(())
+}
+
 fn setup_oil_rename_autocmd() -> Result<()> {
     let group = api::create_augroup(
         "UserOilRename",
@@ -508,12 +885,112 @@ fn setup_oil_rename_autocmd() -> Result<()> {
     Ok(())
 }
 
-fn setup() -> Result<()> {
+fn terminal_backend_choice(opts: Option<&Dictionary>) -> integrations::TerminalBackendChoice {
+    let Some(raw) = opts.and_then(|opts| dict::get_string_nonempty(opts, "terminal_backend"))
+    else {
+        return integrations::TerminalBackendChoice::default();
+    };
+    integrations::TerminalBackendChoice::parse(&raw).unwrap_or_else(|| {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("unknown terminal_backend {raw:?}; falling back to auto"),
+        );
+        integrations::TerminalBackendChoice::default()
+    })
+}
+
+fn tab_title_format(opts: Option<&Dictionary>) -> TabTitleFormat {
+    let Some(opts) = opts else {
+        return TabTitleFormat::default();
+    };
+    let max_width = dict::get_i64(opts, "tab_title_max_width")
+        .and_then(|width| usize::try_from(width).ok())
+        .filter(|width| *width > 0);
+    let raw = dict::get_string_nonempty(opts, "tab_title")
+        .unwrap_or_else(|| DEFAULT_TAB_TITLE_TEMPLATE.to_string());
+    let template = TabTitleTemplate::parse(&raw).unwrap_or_else(|err| {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("invalid tab_title {raw:?}: {err}; using {DEFAULT_TAB_TITLE_TEMPLATE:?}"),
+        );
+        TabTitleTemplate::default()
+    });
+    TabTitleFormat {
+        template,
+        max_width,
+    }
+}
+
+fn parse_cwd_policy(value: &str, what: &str) -> Option<CwdPolicy> {
+    let policy = CwdPolicy::parse(value);
+    if policy.is_none() {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("unknown cwd policy {value:?} for {what}; ignoring it"),
+        );
+    }
+    policy
+}
+
+/// `cwd = { default?, filetypes? = { [ft] = policy }, paths? = { { pattern, policy } } }`
+fn cwd_policy_config(opts: Option<&Dictionary>) -> CwdPolicyConfig {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let Some(cwd) = opts
+        .and_then(|opts| dict::get_object(opts, "cwd"))
+        .and_then(|value| Dictionary::from_object(value).ok())
+    else {
+        return CwdPolicyConfig {
+            home,
+            ..CwdPolicyConfig::default()
+        };
+    };
+    let default = dict::get_string_nonempty(&cwd, "default")
+        .and_then(|value| parse_cwd_policy(&value, "default"))
+        .unwrap_or_default();
+    let filetypes = dict::get_object(&cwd, "filetypes")
+        .and_then(|value| Dictionary::from_object(value).ok())
+        .map(|filetypes| {
+            filetypes
+                .into_iter()
+                .filter_map(|(filetype, policy)| {
+                    let filetype = filetype.to_string_lossy().into_owned();
+                    let policy = NvimString::from_object(policy).ok()?;
+                    let policy = parse_cwd_policy(&policy.to_string_lossy(), &filetype)?;
+                    Some((filetype, policy))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let paths = dict::get_object(&cwd, "paths")
+        .and_then(|value| {
+            decode::parse_from_object::<Vec<Object>>(value, "cwd.paths", "array[table]").ok()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|rule| {
+            let rule = Dictionary::from_object(rule).ok()?;
+            let pattern = dict::get_string_nonempty(&rule, "pattern")?;
+            let policy = dict::get_string_nonempty(&rule, "policy")?;
+            let policy = parse_cwd_policy(&policy, &pattern)?;
+            Some(CwdPathRule { pattern, policy })
+        })
+        .collect();
+    CwdPolicyConfig {
+        default,
+        filetypes,
+        paths,
+        home,
+    }
+}
+
+fn setup(opts: Option<&Dictionary>) -> Result<()> {
+    state_lock().cwd_policy = cwd_policy_config(opts);
     setup_dashboard_autocmd()?;
     setup_file_cwd_autocmd()?;
     setup_oil_last_buf_autocmds()?;
+    setup_recent_files_autocmd()?;
     setup_oil_rename_autocmd()?;
-    integrations::setup_wezterm_autocmd()?;
+    integrations::setup_wezterm_autocmd(terminal_backend_choice(opts), tab_title_format(opts))?;
     track_current_buffer_mru();
     Ok(())
 }
@@ -521,11 +998,18 @@ fn setup() -> Result<()> {
 #[nvim_oxi::plugin]
 fn nvimrs_autocmds() -> Dictionary {
     let mut api = Dictionary::new();
-    api.insert("setup", Function::<(), ()>::from_fn(|()| setup()));
+    api.insert(
+        "setup",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| setup(opts.as_ref())),
+    );
     api.insert(
         "oil_last_buf_for_win",
         Function::<Option<i64>, Option<i64>>::from_fn(oil_last_buf_for_win),
     );
+    api.insert(
+        "recent_files",
+        Function::<Option<Dictionary>, Array>::from_fn(|opts| recent_files(opts.as_ref())),
+    );
     api.insert(
         "switch_to_last_buffer",
         Function::<(), ()>::from_fn(|()| switch_to_last_buffer()),
@@ -1,3 +1,9 @@
+mod recent;
 mod state;
 
+pub use recent::RECENT_FILES_FILE;
+pub use recent::RecentFilesJournal;
+pub use recent::RecentFilesQuery;
+pub use recent::flush as flush_recent_files;
+pub use recent::load as load_recent_files;
 pub use state::BufferMruState;
@@ -0,0 +1,433 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use nvimrs_nvim_utils::frecency::frecency;
+use nvimrs_nvim_utils::store;
+use nvimrs_nvim_utils::store::StoreError;
+use nvimrs_nvim_utils::store::StoreResult;
+use serde::Deserialize;
+use serde::Serialize;
+
+pub const RECENT_FILES_VERSION: u32 = 1;
+pub const RECENT_FILES_FILE: &str = "nvimrs/recent_files.json";
+/// Entries kept on disk; the least frecent are dropped beyond this.
+pub const MAX_RECENT_FILES: usize = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFileRecord {
+    pub path: String,
+    /// Project root the file was last visited under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub visits: u64,
+    /// Unix timestamp (seconds) of the most recent visit.
+    pub last_visit: u64,
+}
+
+impl RecentFileRecord {
+    pub const fn frecency(&self, now: u64) -> u64 {
+        frecency(self.visits, self.last_visit, now)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecentFilesFile {
+    version: u32,
+    files: Vec<RecentFileRecord>,
+}
+
+/// File-path keyed visit history shared by every instance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecentFiles {
+    files: BTreeMap<String, RecentFileRecord>,
+}
+
+impl RecentFiles {
+    /// Files ordered by frecency, then most recent visit, then path.
+    pub fn by_frecency(&self, now: u64) -> Vec<&RecentFileRecord> {
+        let mut records: Vec<&RecentFileRecord> = self.files.values().collect();
+        records.sort_by(|left, right| {
+            right
+                .frecency(now)
+                .cmp(&left.frecency(now))
+                .then_with(|| right.last_visit.cmp(&left.last_visit))
+                .then_with(|| left.path.cmp(&right.path))
+        });
+        records
+    }
+
+    /// Records matching `query` for which `keep` holds, most frecent first.
+    /// `keep` runs before the limit so stale entries do not eat into it.
+    pub fn select<F>(&self, query: &RecentFilesQuery, now: u64, keep: F) -> Vec<&RecentFileRecord>
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.by_frecency(now)
+            .into_iter()
+            .filter(|record| query.matches(record) && keep(&record.path))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Drops the least frecent entries beyond `MAX_RECENT_FILES`.
+    fn prune(&mut self, now: u64) {
+        if self.files.len() <= MAX_RECENT_FILES {
+            return;
+        }
+        let stale: Vec<String> = self
+            .by_frecency(now)
+            .into_iter()
+            .skip(MAX_RECENT_FILES)
+            .map(|record| record.path.clone())
+            .collect();
+        for path in stale {
+            self.files.remove(&path);
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let file = RecentFilesFile {
+            version: RECENT_FILES_VERSION,
+            files: self.files.values().cloned().collect(),
+        };
+        serde_json::to_string_pretty(&file).unwrap_or_default()
+    }
+
+    /// Parse a recent files store. Unknown versions and malformed content
+    /// yield `None` so a newer or corrupt file is never silently rewritten.
+    pub fn decode(raw: &str) -> Option<Self> {
+        let file: RecentFilesFile = serde_json::from_str(raw).ok()?;
+        if file.version != RECENT_FILES_VERSION {
+            return None;
+        }
+        let files = file
+            .files
+            .into_iter()
+            .filter(|record| !record.path.is_empty())
+            .map(|record| (record.path.clone(), record))
+            .collect();
+        Some(Self { files })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingVisit {
+    root: Option<String>,
+    visits: u64,
+    last_visit: u64,
+}
+
+/// Visits and removals since the last flush. Applied on top of whatever is
+/// on disk at flush time so concurrent instances add up instead of
+/// overwriting each other.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecentFilesJournal {
+    visits: BTreeMap<String, PendingVisit>,
+    forgotten: BTreeSet<String>,
+}
+
+impl RecentFilesJournal {
+    pub fn is_empty(&self) -> bool {
+        self.visits.is_empty() && self.forgotten.is_empty()
+    }
+
+    pub fn visit(&mut self, path: &str, root: Option<&str>, now: u64) {
+        self.forgotten.remove(path);
+        let pending = self
+            .visits
+            .entry(path.to_string())
+            .or_insert_with(|| PendingVisit {
+                root: None,
+                visits: 0,
+                last_visit: 0,
+            });
+        if let Some(root) = root {
+            pending.root = Some(root.to_string());
+        }
+        pending.visits = pending.visits.saturating_add(1);
+        pending.last_visit = pending.last_visit.max(now);
+    }
+
+    pub fn forget(&mut self, path: &str) {
+        self.visits.remove(path);
+        self.forgotten.insert(path.to_string());
+    }
+
+    /// Folds an older journal back in, e.g. after a failed flush. Entries
+    /// recorded in `self` since then win.
+    pub fn restore(&mut self, older: Self) {
+        for path in older.forgotten {
+            if !self.visits.contains_key(&path) {
+                self.forgotten.insert(path);
+            }
+        }
+        for (path, visit) in older.visits {
+            if self.forgotten.contains(&path) {
+                continue;
+            }
+            let pending = self.visits.entry(path).or_insert_with(|| PendingVisit {
+                root: None,
+                visits: 0,
+                last_visit: 0,
+            });
+            if pending.root.is_none() {
+                pending.root = visit.root;
+            }
+            pending.visits = pending.visits.saturating_add(visit.visits);
+            pending.last_visit = pending.last_visit.max(visit.last_visit);
+        }
+    }
+
+    pub fn apply_to(&self, files: &mut RecentFiles, now: u64) {
+        for path in &self.forgotten {
+            files.files.remove(path);
+        }
+        for (path, visit) in &self.visits {
+            let record = files
+                .files
+                .entry(path.clone())
+                .or_insert_with(|| RecentFileRecord {
+                    path: path.clone(),
+                    root: None,
+                    visits: 0,
+                    last_visit: 0,
+                });
+            if visit.root.is_some() {
+                record.root.clone_from(&visit.root);
+            }
+            record.visits = record.visits.saturating_add(visit.visits);
+            record.last_visit = record.last_visit.max(visit.last_visit);
+        }
+        files.prune(now);
+    }
+}
+
+/// Filter for `recent_files`: restrict to files under `cwd`, to files last
+/// visited under project `root`, and cap the result at `limit` entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecentFilesQuery {
+    pub cwd: Option<String>,
+    pub root: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl RecentFilesQuery {
+    fn matches(&self, record: &RecentFileRecord) -> bool {
+        self.cwd
+            .as_deref()
+            .is_none_or(|cwd| Path::new(&record.path).starts_with(cwd))
+            && self
+                .root
+                .as_deref()
+                .is_none_or(|root| record.root.as_deref() == Some(root))
+    }
+}
+
+pub fn load(path: &Path) -> StoreResult<RecentFiles> {
+    let Some(raw) = store::read_optional(path)? else {
+        return Ok(RecentFiles::default());
+    };
+    RecentFiles::decode(&raw).ok_or_else(|| StoreError::Incompatible {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Apply `journal` to the store at `path` through [`store::locked_update`].
+pub fn flush(path: &Path, journal: &RecentFilesJournal, now: u64) -> StoreResult<()> {
+    if journal.is_empty() {
+        return Ok(());
+    }
+    store::locked_update(path, || {
+        let mut files = load(path)?;
+        journal.apply_to(&mut files, now);
+        Ok(((), Some(files.encode())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    const NOW: u64 = 1_000 * DAY;
+
+    fn paths(records: &[&RecentFileRecord]) -> Vec<String> {
+        records.iter().map(|record| record.path.clone()).collect()
+    }
+
+    fn files_from(journal: &RecentFilesJournal) -> RecentFiles {
+        let mut files = RecentFiles::default();
+        journal.apply_to(&mut files, NOW);
+        files
+    }
+
+    #[test]
+    fn visits_accumulate_and_rank_by_frecency() {
+        let mut journal = RecentFilesJournal::default();
+        for _ in 0..5 {
+            journal.visit("/repo/old.rs", Some("/repo"), NOW - 200 * DAY);
+        }
+        journal.visit("/repo/new.rs", Some("/repo"), NOW - HOUR);
+        journal.visit("/notes/week.md", None, NOW - 3 * DAY);
+        let files = files_from(&journal);
+        let ranked = files.by_frecency(NOW);
+        assert_eq!(
+            paths(&ranked),
+            vec!["/repo/new.rs", "/notes/week.md", "/repo/old.rs"]
+        );
+        assert_eq!(ranked[2].visits, 5);
+        assert_eq!(ranked[2].root.as_deref(), Some("/repo"));
+    }
+
+    #[test]
+    fn journal_merges_with_records_from_other_instances() {
+        let mut disk = RecentFiles::default();
+        let mut other = RecentFilesJournal::default();
+        other.visit("/repo/a.rs", Some("/repo"), NOW - DAY);
+        other.visit("/repo/gone.rs", Some("/repo"), NOW - DAY);
+        other.apply_to(&mut disk, NOW);
+
+        let mut ours = RecentFilesJournal::default();
+        ours.visit("/repo/a.rs", None, NOW);
+        ours.forget("/repo/gone.rs");
+        ours.apply_to(&mut disk, NOW);
+
+        let ranked = disk.by_frecency(NOW);
+        assert_eq!(paths(&ranked), vec!["/repo/a.rs"]);
+        assert_eq!(ranked[0].visits, 2);
+        assert_eq!(ranked[0].last_visit, NOW);
+        assert_eq!(ranked[0].root.as_deref(), Some("/repo"));
+    }
+
+    #[test]
+    fn visit_after_forget_revives_the_file() {
+        let mut journal = RecentFilesJournal::default();
+        journal.visit("/a", None, NOW);
+        journal.forget("/a");
+        assert!(paths(&files_from(&journal).by_frecency(NOW)).is_empty());
+        journal.visit("/a", None, NOW);
+        assert_eq!(paths(&files_from(&journal).by_frecency(NOW)), vec!["/a"]);
+    }
+
+    #[test]
+    fn restore_keeps_newer_entries_authoritative() {
+        let mut older = RecentFilesJournal::default();
+        older.visit("/a", Some("/root"), NOW - DAY);
+        older.visit("/b", None, NOW - DAY);
+        older.forget("/c");
+
+        let mut newer = RecentFilesJournal::default();
+        newer.forget("/b");
+        newer.visit("/c", None, NOW);
+        newer.visit("/a", None, NOW);
+        newer.restore(older);
+
+        let files = files_from(&newer);
+        let ranked = files.by_frecency(NOW);
+        assert_eq!(paths(&ranked), vec!["/a", "/c"]);
+        assert_eq!(ranked[0].visits, 2);
+        assert_eq!(ranked[0].root.as_deref(), Some("/root"));
+    }
+
+    #[test]
+    fn select_filters_by_cwd_and_limits() {
+        let mut journal = RecentFilesJournal::default();
+        journal.visit("/repo/src/lib.rs", None, NOW);
+        journal.visit("/repo/README.md", None, NOW - HOUR);
+        journal.visit("/repository/other.rs", None, NOW);
+        let files = files_from(&journal);
+        let query = RecentFilesQuery {
+            cwd: Some("/repo".to_string()),
+            root: None,
+            limit: None,
+        };
+        assert_eq!(
+            paths(&files.select(&query, NOW, |_| true)),
+            vec!["/repo/src/lib.rs", "/repo/README.md"]
+        );
+        let query = RecentFilesQuery {
+            cwd: None,
+            root: None,
+            limit: Some(1),
+        };
+        assert_eq!(
+            paths(&files.select(&query, NOW, |_| true)),
+            vec!["/repo/src/lib.rs"]
+        );
+        assert_eq!(
+            paths(&files.select(&query, NOW, |path| path != "/repo/src/lib.rs")),
+            vec!["/repository/other.rs"]
+        );
+    }
+
+    #[test]
+    fn select_filters_by_recorded_root_not_path() {
+        let mut journal = RecentFilesJournal::default();
+        journal.visit("/repo/src/lib.rs", Some("/repo"), NOW);
+        journal.visit("/shared/notes.md", Some("/repo"), NOW - HOUR);
+        journal.visit("/repo/vendor/dep.rs", Some("/repo/vendor"), NOW);
+        journal.visit("/repo/scratch.rs", None, NOW);
+        let files = files_from(&journal);
+        let query = RecentFilesQuery {
+            cwd: None,
+            root: Some("/repo".to_string()),
+            limit: None,
+        };
+        assert_eq!(
+            paths(&files.select(&query, NOW, |_| true)),
+            vec!["/repo/src/lib.rs", "/shared/notes.md"]
+        );
+    }
+
+    #[test]
+    fn prune_caps_the_store() {
+        let mut journal = RecentFilesJournal::default();
+        for index in 0..MAX_RECENT_FILES as u64 + 3 {
+            journal.visit(&format!("/f{index}"), None, NOW - index);
+        }
+        let files = files_from(&journal);
+        let ranked = files.by_frecency(NOW);
+        assert_eq!(ranked.len(), MAX_RECENT_FILES);
+        assert!(!paths(&ranked).contains(&format!("/f{}", MAX_RECENT_FILES + 2)));
+    }
+
+    #[test]
+    fn encode_decode_roundtrip_and_version_check() {
+        let mut journal = RecentFilesJournal::default();
+        journal.visit("/repo/a.rs", Some("/repo"), NOW);
+        journal.visit("/b.md", None, NOW - DAY);
+        let files = files_from(&journal);
+        assert_eq!(RecentFiles::decode(&files.encode()), Some(files));
+        assert_eq!(RecentFiles::decode(r#"{"version":2,"files":[]}"#), None);
+        assert_eq!(RecentFiles::decode("not json"), None);
+    }
+
+    #[test]
+    fn flush_merges_journals_into_the_store() -> Result<(), StoreError> {
+        let dir = std::env::temp_dir().join(format!(
+            "nvimrs-recent-files-{}-{}",
+            std::process::id(),
+            nvimrs_nvim_utils::frecency::now_secs()
+        ));
+        let path = dir.join("recent_files.json");
+        let mut first = RecentFilesJournal::default();
+        first.visit("/a", None, NOW);
+        first.visit("/b", None, NOW);
+        flush(&path, &first, NOW)?;
+        let mut second = RecentFilesJournal::default();
+        second.visit("/a", None, NOW);
+        second.forget("/b");
+        flush(&path, &second, NOW)?;
+
+        let files = load(&path)?;
+        let ranked = files.by_frecency(NOW);
+        assert_eq!(paths(&ranked), vec!["/a"]);
+        assert_eq!(ranked[0].visits, 2);
+        assert!(!dir.join("recent_files.json.lock").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+}
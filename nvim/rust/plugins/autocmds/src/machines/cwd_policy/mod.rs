@@ -0,0 +1,8 @@
+mod policy;
+
+pub use policy::CwdInputs;
+pub use policy::CwdPathRule;
+pub use policy::CwdPolicy;
+pub use policy::CwdPolicyConfig;
+pub use policy::CwdScope;
+pub use policy::CwdTarget;
@@ -0,0 +1,241 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use nvimrs_nvim_utils::path::glob_matches;
+
+/// How a window picks its working directory when it enters a buffer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CwdPolicy {
+    /// `lcd` to the directory of the file (the historical behaviour).
+    #[default]
+    FileDir,
+    /// `lcd` to the buffer's project root, or the file directory without one.
+    ProjectRoot,
+    /// `tcd` to the buffer's project root so every window in the tab shares
+    /// it; falls back to `FileDir` without a root.
+    TabRoot,
+    /// Leave the working directory alone.
+    None,
+}
+
+impl CwdPolicy {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "file_dir" => Some(Self::FileDir),
+            "project_root" => Some(Self::ProjectRoot),
+            "tab_root" => Some(Self::TabRoot),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+/// Applies `policy` to buffers whose path matches `pattern`. A leading `~`
+/// is the home directory and `*` spans any characters, `/` included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CwdPathRule {
+    pub pattern: String,
+    pub policy: CwdPolicy,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CwdPolicyConfig {
+    pub default: CwdPolicy,
+    pub filetypes: HashMap<String, CwdPolicy>,
+    /// Checked in order before `filetypes`; the first match wins.
+    pub paths: Vec<CwdPathRule>,
+    pub home: Option<PathBuf>,
+}
+
+/// What the policy looks at for one buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct CwdInputs<'a> {
+    /// Absolute path of the file, or the directory an oil buffer shows.
+    pub path: &'a Path,
+    /// Directory `FileDir` switches to.
+    pub dir: &'a Path,
+    pub filetype: &'a str,
+    pub project_root: Option<&'a Path>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CwdScope {
+    Window,
+    Tab,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CwdTarget {
+    pub dir: PathBuf,
+    pub scope: CwdScope,
+}
+
+impl CwdPolicyConfig {
+    fn expand_pattern<'a>(&self, pattern: &'a str) -> Cow<'a, str> {
+        match (pattern.strip_prefix('~'), self.home.as_deref()) {
+            (Some(rest), Some(home)) => format!("{}{rest}", home.display()).into(),
+            _ => pattern.into(),
+        }
+    }
+
+    pub fn policy_for(&self, path: &Path, filetype: &str) -> CwdPolicy {
+        let path = path.to_string_lossy();
+        self.paths
+            .iter()
+            .find(|rule| glob_matches(&self.expand_pattern(&rule.pattern), &path))
+            .map(|rule| rule.policy)
+            .or_else(|| self.filetypes.get(filetype).copied())
+            .unwrap_or(self.default)
+    }
+
+    /// Where the current window should point for `inputs`, or `None` when
+    /// the policy leaves the working directory alone.
+    pub fn resolve(&self, inputs: &CwdInputs<'_>) -> Option<CwdTarget> {
+        let file_dir = || CwdTarget {
+            dir: inputs.dir.to_path_buf(),
+            scope: CwdScope::Window,
+        };
+        let root = |scope| {
+            inputs.project_root.map(|root| CwdTarget {
+                dir: root.to_path_buf(),
+                scope,
+            })
+        };
+        match self.policy_for(inputs.path, inputs.filetype) {
+            CwdPolicy::FileDir => Some(file_dir()),
+            CwdPolicy::ProjectRoot => root(CwdScope::Window).or_else(|| Some(file_dir())),
+            CwdPolicy::TabRoot => root(CwdScope::Tab).or_else(|| Some(file_dir())),
+            CwdPolicy::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn inputs<'a>(path: &'a str, filetype: &'a str, root: Option<&'a str>) -> CwdInputs<'a> {
+        let path = Path::new(path);
+        CwdInputs {
+            path,
+            dir: path.parent().unwrap_or(path),
+            filetype,
+            project_root: root.map(Path::new),
+        }
+    }
+
+    fn target(dir: &str, scope: CwdScope) -> Option<CwdTarget> {
+        Some(CwdTarget {
+            dir: PathBuf::from(dir),
+            scope,
+        })
+    }
+
+    fn config() -> CwdPolicyConfig {
+        CwdPolicyConfig {
+            default: CwdPolicy::ProjectRoot,
+            filetypes: HashMap::from([
+                ("gitcommit".to_string(), CwdPolicy::None),
+                ("rust".to_string(), CwdPolicy::TabRoot),
+            ]),
+            paths: vec![
+                CwdPathRule {
+                    pattern: "~/notes/*".to_string(),
+                    policy: CwdPolicy::FileDir,
+                },
+                CwdPathRule {
+                    pattern: "*/vendor/*".to_string(),
+                    policy: CwdPolicy::None,
+                },
+            ],
+            home: Some(PathBuf::from("/home/me")),
+        }
+    }
+
+    #[test]
+    fn parse_accepts_known_policies() {
+        assert_eq!(CwdPolicy::parse("file_dir"), Some(CwdPolicy::FileDir));
+        assert_eq!(
+            CwdPolicy::parse("project_root"),
+            Some(CwdPolicy::ProjectRoot)
+        );
+        assert_eq!(CwdPolicy::parse("tab_root"), Some(CwdPolicy::TabRoot));
+        assert_eq!(CwdPolicy::parse("none"), Some(CwdPolicy::None));
+        assert_eq!(CwdPolicy::parse("project"), None);
+    }
+
+    #[test]
+    fn default_config_keeps_file_dir_behaviour() {
+        let config = CwdPolicyConfig::default();
+        assert_eq!(
+            config.resolve(&inputs("/repo/src/main.rs", "rust", Some("/repo"))),
+            target("/repo/src", CwdScope::Window)
+        );
+    }
+
+    #[test]
+    fn path_rules_win_over_filetypes_and_default() {
+        let config = config();
+        assert_eq!(
+            config.resolve(&inputs(
+                "/home/me/notes/rust/todo.rs",
+                "rust",
+                Some("/home/me")
+            )),
+            target("/home/me/notes/rust", CwdScope::Window)
+        );
+        assert_eq!(
+            config.resolve(&inputs("/repo/vendor/dep/lib.rs", "rust", Some("/repo"))),
+            None
+        );
+    }
+
+    #[test]
+    fn filetypes_win_over_default() {
+        let config = config();
+        assert_eq!(
+            config.resolve(&inputs(
+                "/repo/.git/COMMIT_EDITMSG",
+                "gitcommit",
+                Some("/repo")
+            )),
+            None
+        );
+        assert_eq!(
+            config.resolve(&inputs("/repo/src/main.rs", "rust", Some("/repo"))),
+            target("/repo", CwdScope::Tab)
+        );
+        assert_eq!(
+            config.resolve(&inputs("/repo/README.md", "markdown", Some("/repo"))),
+            target("/repo", CwdScope::Window)
+        );
+    }
+
+    #[test]
+    fn root_policies_fall_back_to_file_dir_without_a_root() {
+        let config = config();
+        assert_eq!(
+            config.resolve(&inputs("/tmp/scratch.rs", "rust", None)),
+            target("/tmp", CwdScope::Window)
+        );
+        assert_eq!(
+            config.resolve(&inputs("/tmp/scratch.md", "markdown", None)),
+            target("/tmp", CwdScope::Window)
+        );
+    }
+
+    #[test]
+    fn tilde_is_literal_without_home() {
+        let config = CwdPolicyConfig {
+            home: None,
+            ..config()
+        };
+        assert_eq!(
+            config.policy_for(Path::new("/home/me/notes/a.md"), "markdown"),
+            CwdPolicy::ProjectRoot
+        );
+    }
+}
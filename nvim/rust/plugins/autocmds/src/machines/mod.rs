@@ -1,3 +1,5 @@
 pub mod buffer_mru;
+pub mod cwd_policy;
 pub mod oil_last_buf;
+pub mod oil_rename;
 pub mod wezterm;
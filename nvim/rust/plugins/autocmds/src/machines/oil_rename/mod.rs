@@ -0,0 +1,5 @@
+mod plan;
+
+pub use plan::BufferUpdate;
+pub use plan::OpenBuffer;
+pub use plan::plan_buffer_updates;
@@ -0,0 +1,278 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use nvimrs_nvim_oxi_utils::handles::BufHandle;
+use nvimrs_nvim_utils::path::normalize_path;
+use nvimrs_nvim_utils::path::split_uri_scheme_and_rest;
+
+use crate::types::OilAction;
+
+/// A named buffer open at the time oil finished its actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenBuffer {
+    pub buf: BufHandle,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferUpdate {
+    /// The file behind `buf` now lives at `to`.
+    Rename {
+        buf: BufHandle,
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// The file behind `buf` was deleted, directly or with its directory.
+    Deleted { buf: BufHandle, path: PathBuf },
+}
+
+/// Local path of an `oil://` url. Remote adapters (`oil-ssh://`, ...) have
+/// no buffers to re-point and yield `None`.
+fn local_path(url: &str) -> Option<PathBuf> {
+    let (scheme, _) = split_uri_scheme_and_rest(url)?;
+    if scheme != "oil" {
+        return None;
+    }
+    normalize_path(url)
+}
+
+/// `path` re-rooted from `src` to `dest` when it is `src` or lies under it.
+fn moved_path(path: &Path, src: &Path, dest: &Path) -> Option<PathBuf> {
+    let rest = path.strip_prefix(src).ok()?;
+    if rest.as_os_str().is_empty() {
+        return Some(dest.to_path_buf());
+    }
+    Some(dest.join(rest))
+}
+
+#[derive(Debug)]
+struct Tracked<'a> {
+    open: &'a OpenBuffer,
+    path: PathBuf,
+    deleted: bool,
+}
+
+/// Buffer updates implied by `actions`, applied in order so chained moves
+/// compose and a move followed by a delete reports the deletion. Copies
+/// leave the source buffers alone.
+pub fn plan_buffer_updates(actions: &[OilAction], buffers: &[OpenBuffer]) -> Vec<BufferUpdate> {
+    let mut tracked: Vec<Tracked<'_>> = buffers
+        .iter()
+        .map(|open| Tracked {
+            open,
+            path: open.path.clone(),
+            deleted: false,
+        })
+        .collect();
+
+    for action in actions {
+        match action {
+            OilAction::Move(action) => {
+                let (Some(src), Some(dest)) = (
+                    local_path(action.src_url.as_str()),
+                    local_path(action.dest_url.as_str()),
+                ) else {
+                    continue;
+                };
+                for entry in tracked.iter_mut().filter(|entry| !entry.deleted) {
+                    if let Some(path) = moved_path(&entry.path, &src, &dest) {
+                        entry.path = path;
+                    }
+                }
+            }
+            OilAction::Delete(action) => {
+                let Some(path) = local_path(action.url.as_str()) else {
+                    continue;
+                };
+                for entry in tracked.iter_mut() {
+                    if entry.path.starts_with(&path) {
+                        entry.deleted = true;
+                    }
+                }
+            }
+            OilAction::Copy(_) | OilAction::Other => {}
+        }
+    }
+
+    tracked
+        .into_iter()
+        .filter_map(|entry| {
+            if entry.deleted {
+                Some(BufferUpdate::Deleted {
+                    buf: entry.open.buf,
+                    path: entry.path,
+                })
+            } else if entry.path != entry.open.path {
+                Some(BufferUpdate::Rename {
+                    buf: entry.open.buf,
+                    from: entry.open.path.clone(),
+                    to: entry.path,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OilActionsPostArgs;
+    use nvim_oxi::Dictionary;
+    use nvim_oxi::Object;
+    use pretty_assertions::assert_eq;
+
+    fn action(kind: &str, fields: &[(&str, &str)]) -> Dictionary {
+        let mut action = Dictionary::new();
+        action.insert("type", kind);
+        for (key, value) in fields {
+            action.insert(*key, *value);
+        }
+        action
+    }
+
+    fn mv(src: &str, dest: &str) -> Dictionary {
+        action("move", &[("src_url", src), ("dest_url", dest)])
+    }
+
+    fn cp(src: &str, dest: &str) -> Dictionary {
+        action("copy", &[("src_url", src), ("dest_url", dest)])
+    }
+
+    fn rm(url: &str) -> Dictionary {
+        action("delete", &[("url", url)])
+    }
+
+    fn parse(actions: Vec<Dictionary>) -> Result<Vec<OilAction>, &'static str> {
+        let mut payload = Dictionary::new();
+        payload.insert("actions", nvim_oxi::Array::from_iter(actions));
+        OilActionsPostArgs::parse(Object::from(payload))
+            .map(|args| args.actions)
+            .map_err(|_| "parse failed")
+    }
+
+    fn buffers(paths: &[(i64, &str)]) -> Result<Vec<OpenBuffer>, &'static str> {
+        paths
+            .iter()
+            .map(|(id, path)| {
+                Ok(OpenBuffer {
+                    buf: BufHandle::try_from_i64(*id).ok_or("expected valid buffer handle")?,
+                    path: PathBuf::from(path),
+                })
+            })
+            .collect()
+    }
+
+    fn rename(id: i64, from: &str, to: &str) -> Result<BufferUpdate, &'static str> {
+        Ok(BufferUpdate::Rename {
+            buf: BufHandle::try_from_i64(id).ok_or("expected valid buffer handle")?,
+            from: PathBuf::from(from),
+            to: PathBuf::from(to),
+        })
+    }
+
+    fn deleted(id: i64, path: &str) -> Result<BufferUpdate, &'static str> {
+        Ok(BufferUpdate::Deleted {
+            buf: BufHandle::try_from_i64(id).ok_or("expected valid buffer handle")?,
+            path: PathBuf::from(path),
+        })
+    }
+
+    #[test]
+    fn file_move_renames_only_that_buffer() -> Result<(), &'static str> {
+        let actions = parse(vec![mv("oil:///repo/a.rs", "oil:///repo/b.rs")])?;
+        let open = buffers(&[(1, "/repo/a.rs"), (2, "/repo/a.rs.bak")])?;
+        assert_eq!(
+            plan_buffer_updates(&actions, &open),
+            vec![rename(1, "/repo/a.rs", "/repo/b.rs")?]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn directory_move_repoints_every_buffer_under_it() -> Result<(), &'static str> {
+        let actions = parse(vec![mv("oil:///repo/src/", "oil:///repo/lib/")])?;
+        let open = buffers(&[
+            (1, "/repo/src/main.rs"),
+            (2, "/repo/src/deep/mod.rs"),
+            (3, "/repo/srcs/other.rs"),
+            (4, "/repo/README.md"),
+        ])?;
+        assert_eq!(
+            plan_buffer_updates(&actions, &open),
+            vec![
+                rename(1, "/repo/src/main.rs", "/repo/lib/main.rs")?,
+                rename(2, "/repo/src/deep/mod.rs", "/repo/lib/deep/mod.rs")?,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deletes_mark_files_and_directory_contents() -> Result<(), &'static str> {
+        let actions = parse(vec![rm("oil:///repo/old.rs"), rm("oil:///repo/tmp/")])?;
+        let open = buffers(&[
+            (1, "/repo/old.rs"),
+            (2, "/repo/tmp/scratch.txt"),
+            (3, "/repo/keep.rs"),
+        ])?;
+        assert_eq!(
+            plan_buffer_updates(&actions, &open),
+            vec![
+                deleted(1, "/repo/old.rs")?,
+                deleted(2, "/repo/tmp/scratch.txt")?,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn copies_leave_buffers_alone() -> Result<(), &'static str> {
+        let actions = parse(vec![
+            cp("oil:///repo/a.rs", "oil:///repo/b.rs"),
+            cp("oil:///repo/src/", "oil:///repo/src2/"),
+        ])?;
+        let open = buffers(&[(1, "/repo/a.rs"), (2, "/repo/src/main.rs")])?;
+        assert_eq!(plan_buffer_updates(&actions, &open), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn actions_apply_in_order() -> Result<(), &'static str> {
+        let actions = parse(vec![
+            mv("oil:///repo/a.rs", "oil:///repo/b.rs"),
+            mv("oil:///repo/", "oil:///work/"),
+            rm("oil:///work/gone.rs"),
+        ])?;
+        let open = buffers(&[(1, "/repo/a.rs"), (2, "/repo/gone.rs")])?;
+        assert_eq!(
+            plan_buffer_updates(&actions, &open),
+            vec![
+                rename(1, "/repo/a.rs", "/work/b.rs")?,
+                deleted(2, "/work/gone.rs")?,
+            ]
+        );
+
+        let actions = parse(vec![
+            mv("oil:///repo/a.rs", "oil:///repo/b.rs"),
+            rm("oil:///repo/b.rs"),
+        ])?;
+        assert_eq!(
+            plan_buffer_updates(&actions, &open),
+            vec![deleted(1, "/repo/b.rs")?]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn remote_urls_are_ignored() -> Result<(), &'static str> {
+        let actions = parse(vec![
+            mv("oil-ssh://host//repo/a.rs", "oil-ssh://host//repo/b.rs"),
+            rm("oil-ssh://host//repo/c.rs"),
+        ])?;
+        let open = buffers(&[(1, "/repo/a.rs"), (2, "/repo/c.rs")])?;
+        assert_eq!(plan_buffer_updates(&actions, &open), Vec::new());
+        Ok(())
+    }
+}
@@ -1,10 +1,15 @@
 use std::path::Path;
+use std::path::PathBuf;
 use std::process::ExitStatus;
 
 use nvimrs_support::NonEmptyString;
 use nvimrs_support::ProjectRoot;
 use nvimrs_support::TabTitle;
 
+use super::title::TabTitleFormat;
+use super::title::TabTitleValues;
+use super::title::mode_label;
+
 fn path_basename(path: &Path) -> Option<NonEmptyString> {
     let name = path.file_name()?.to_string_lossy().into_owned();
     NonEmptyString::try_new(name).ok()
@@ -26,28 +31,66 @@ pub(super) fn tilde_path(path: &Path, home: Option<&Path>) -> String {
     path.to_string_lossy().into_owned()
 }
 
-fn tab_title_for_root(root: &ProjectRoot, home: Option<&Path>) -> Option<TabTitle> {
+fn project_label(root: &ProjectRoot, home: Option<&Path>) -> String {
     let path = root.as_path();
-    path_basename(path)
-        .map(TabTitle::from)
-        .or_else(|| TabTitle::try_new(tilde_path(path, home)).ok())
+    path_basename(path).map_or_else(|| tilde_path(path, home), String::from)
+}
+
+/// Buffer path relative to the project root, or tilde-shortened when the
+/// buffer lives outside it.
+fn relative_file(file: &Path, root: Option<&ProjectRoot>, home: Option<&Path>) -> String {
+    root.and_then(|root| file.strip_prefix(root.as_path()).ok())
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .map_or_else(
+            || tilde_path(file, home),
+            |relative| relative.to_string_lossy().into_owned(),
+        )
 }
 
-pub fn derive_tab_title(root: Option<ProjectRoot>, home: Option<&Path>) -> Option<TabTitle> {
-    root.and_then(|root| tab_title_for_root(&root, home))
+/// Editor state a tab title template can draw from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabTitleInputs {
+    pub root: Option<ProjectRoot>,
+    pub file: Option<PathBuf>,
+    pub branch: Option<String>,
+    pub modified: bool,
+    /// Raw `mode()` code.
+    pub mode: Option<String>,
 }
 
-pub fn format_cli_failure(status: ExitStatus) -> String {
+pub fn derive_tab_title(
+    format: &TabTitleFormat,
+    inputs: &TabTitleInputs,
+    home: Option<&Path>,
+) -> Option<TabTitle> {
+    let values = TabTitleValues {
+        project: inputs.root.as_ref().map(|root| project_label(root, home)),
+        file: inputs
+            .file
+            .as_deref()
+            .map(|file| relative_file(file, inputs.root.as_ref(), home)),
+        branch: inputs.branch.clone(),
+        modified: inputs.modified,
+        mode: inputs
+            .mode
+            .as_deref()
+            .and_then(mode_label)
+            .map(ToOwned::to_owned),
+    };
+    TabTitle::try_new(format.render(&values)).ok()
+}
+
+pub fn format_cli_failure(backend: &str, status: ExitStatus) -> String {
     status.code().map_or_else(
-        || "wezterm cli failed with signal".to_string(),
-        |code| format!("wezterm cli failed with exit code {code}"),
+        || format!("{backend} tab title failed with signal"),
+        |code| format!("{backend} tab title failed with exit code {code}"),
     )
 }
 
-pub fn format_set_working_dir_failure(status: ExitStatus) -> String {
+pub fn format_set_working_dir_failure(backend: &str, status: ExitStatus) -> String {
     status.code().map_or_else(
-        || "wezterm set-working-directory failed with signal".to_string(),
-        |code| format!("wezterm set-working-directory failed with exit code {code}"),
+        || format!("{backend} set-working-directory failed with signal"),
+        |code| format!("{backend} set-working-directory failed with exit code {code}"),
     )
 }
 
@@ -57,12 +100,43 @@ mod tests {
 
     #[test]
     fn derive_tab_title_prefers_basename() -> Result<(), &'static str> {
-        let root = ProjectRoot::try_new("/tmp/root".to_string()).ok();
-        let title = derive_tab_title(root, None).ok_or("expected tab title")?;
+        let inputs = TabTitleInputs {
+            root: ProjectRoot::try_new("/tmp/root".to_string()).ok(),
+            ..TabTitleInputs::default()
+        };
+        let title = derive_tab_title(&TabTitleFormat::default(), &inputs, None)
+            .ok_or("expected tab title")?;
         assert_eq!(title.as_str(), "root");
         Ok(())
     }
 
+    #[test]
+    fn derive_tab_title_default_template_needs_a_root() {
+        let inputs = TabTitleInputs {
+            file: Some(PathBuf::from("/tmp/file.rs")),
+            ..TabTitleInputs::default()
+        };
+        assert_eq!(
+            derive_tab_title(&TabTitleFormat::default(), &inputs, None),
+            None
+        );
+    }
+
+    #[test]
+    fn relative_file_strips_root_or_falls_back_to_tilde() -> Result<(), &'static str> {
+        let root = ProjectRoot::try_new("/tmp/home/repo".to_string()).map_err(|_| "root")?;
+        let home = Some(Path::new("/tmp/home"));
+        assert_eq!(
+            relative_file(Path::new("/tmp/home/repo/src/lib.rs"), Some(&root), home),
+            "src/lib.rs"
+        );
+        assert_eq!(
+            relative_file(Path::new("/tmp/home/notes.md"), Some(&root), home),
+            format!("~{}notes.md", std::path::MAIN_SEPARATOR)
+        );
+        Ok(())
+    }
+
     #[test]
     fn tilde_path_rewrites_home() {
         let home = Path::new("/tmp/home");
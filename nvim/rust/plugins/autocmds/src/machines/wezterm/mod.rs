@@ -2,7 +2,9 @@ mod domain;
 mod events;
 mod reducer;
 mod state;
+mod title;
 
+pub use domain::TabTitleInputs;
 pub use domain::derive_tab_title;
 pub use domain::format_cli_failure;
 pub use domain::format_set_working_dir_failure;
@@ -10,3 +12,9 @@ pub use events::WeztermCommand;
 pub use events::WeztermCompletion;
 pub use events::WeztermEvent;
 pub use state::WeztermState;
+pub use title::DEFAULT_TAB_TITLE_TEMPLATE;
+pub use title::TabTitleField;
+pub use title::TabTitleFormat;
+pub use title::TabTitleTemplate;
+pub use title::parse_git_head;
+pub use title::parse_gitdir_file;
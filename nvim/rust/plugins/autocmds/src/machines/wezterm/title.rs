@@ -0,0 +1,482 @@
+use std::fmt;
+
+const ELLIPSIS: char = '…';
+pub const DEFAULT_TAB_TITLE_TEMPLATE: &str = "{project}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabTitleField {
+    Project,
+    File,
+    Branch,
+    Modified,
+    Mode,
+}
+
+impl TabTitleField {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "project" => Some(Self::Project),
+            "file" => Some(Self::File),
+            "branch" => Some(Self::Branch),
+            "modified" => Some(Self::Modified),
+            "mode" => Some(Self::Mode),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TabTitleTemplateError {
+    UnknownPlaceholder(String),
+    InvalidWidth(String),
+    UnclosedPlaceholder,
+    UnbalancedSection,
+}
+
+impl fmt::Display for TabTitleTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{name}}}"),
+            Self::InvalidWidth(raw) => write!(f, "invalid placeholder width {raw:?}"),
+            Self::UnclosedPlaceholder => f.write_str("unclosed placeholder"),
+            Self::UnbalancedSection => f.write_str("unbalanced [ ] section"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field {
+        field: TabTitleField,
+        max_width: Option<usize>,
+    },
+    /// Dropped entirely when any field inside it renders empty.
+    Section(Vec<Segment>),
+}
+
+/// Parsed tab title template.
+///
+/// Syntax:
+/// - `{project}`, `{file}`, `{branch}`, `{modified}` and `{mode}` are
+///   placeholders; `{file:24}` caps that field at 24 characters.
+/// - `[...]` is an optional section, omitted when any placeholder inside it is
+///   empty, e.g. `{project}[ ({branch})]`.
+/// - a backslash escapes the next character, e.g. `\[` for a literal `[`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabTitleTemplate {
+    segments: Vec<Segment>,
+}
+
+impl TabTitleTemplate {
+    pub fn parse(raw: &str) -> Result<Self, TabTitleTemplateError> {
+        let mut chars = raw.chars();
+        let segments = parse_segments(&mut chars, false)?;
+        Ok(Self { segments })
+    }
+
+    pub fn uses(&self, field: TabTitleField) -> bool {
+        segments_use(&self.segments, field)
+    }
+}
+
+impl Default for TabTitleTemplate {
+    fn default() -> Self {
+        Self {
+            segments: vec![Segment::Field {
+                field: TabTitleField::Project,
+                max_width: None,
+            }],
+        }
+    }
+}
+
+fn segments_use(segments: &[Segment], wanted: TabTitleField) -> bool {
+    segments.iter().any(|segment| match segment {
+        Segment::Literal(_) => false,
+        Segment::Field { field, .. } => *field == wanted,
+        Segment::Section(inner) => segments_use(inner, wanted),
+    })
+}
+
+fn parse_segments(
+    chars: &mut std::str::Chars<'_>,
+    in_section: bool,
+) -> Result<Vec<Segment>, TabTitleTemplateError> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => literal.push(chars.next().unwrap_or('\\')),
+            '{' => {
+                flush_literal(&mut segments, &mut literal);
+                segments.push(parse_field(chars)?);
+            }
+            '[' => {
+                flush_literal(&mut segments, &mut literal);
+                segments.push(Segment::Section(parse_segments(chars, true)?));
+            }
+            ']' if in_section => {
+                flush_literal(&mut segments, &mut literal);
+                return Ok(segments);
+            }
+            ']' => return Err(TabTitleTemplateError::UnbalancedSection),
+            '}' => return Err(TabTitleTemplateError::UnclosedPlaceholder),
+            _ => literal.push(ch),
+        }
+    }
+    if in_section {
+        return Err(TabTitleTemplateError::UnbalancedSection);
+    }
+    flush_literal(&mut segments, &mut literal);
+    Ok(segments)
+}
+
+fn flush_literal(segments: &mut Vec<Segment>, literal: &mut String) {
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(std::mem::take(literal)));
+    }
+}
+
+fn parse_field(chars: &mut std::str::Chars<'_>) -> Result<Segment, TabTitleTemplateError> {
+    let mut body = String::new();
+    loop {
+        match chars.next() {
+            Some('}') => break,
+            Some(ch) => body.push(ch),
+            None => return Err(TabTitleTemplateError::UnclosedPlaceholder),
+        }
+    }
+    let (name, width) = match body.split_once(':') {
+        Some((name, width)) => (name.trim(), Some(width.trim())),
+        None => (body.trim(), None),
+    };
+    let field = TabTitleField::parse(name)
+        .ok_or_else(|| TabTitleTemplateError::UnknownPlaceholder(name.to_string()))?;
+    let max_width = width
+        .map(|raw| {
+            raw.parse::<usize>()
+                .ok()
+                .filter(|width| *width > 0)
+                .ok_or_else(|| TabTitleTemplateError::InvalidWidth(raw.to_string()))
+        })
+        .transpose()?;
+    Ok(Segment::Field { field, max_width })
+}
+
+/// Resolved placeholder values; `None` renders as empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabTitleValues {
+    pub project: Option<String>,
+    pub file: Option<String>,
+    pub branch: Option<String>,
+    pub modified: bool,
+    pub mode: Option<String>,
+}
+
+impl TabTitleValues {
+    fn get(&self, field: TabTitleField) -> &str {
+        match field {
+            TabTitleField::Project => self.project.as_deref().unwrap_or_default(),
+            TabTitleField::File => self.file.as_deref().unwrap_or_default(),
+            TabTitleField::Branch => self.branch.as_deref().unwrap_or_default(),
+            TabTitleField::Modified => {
+                if self.modified {
+                    "+"
+                } else {
+                    ""
+                }
+            }
+            TabTitleField::Mode => self.mode.as_deref().unwrap_or_default(),
+        }
+    }
+}
+
+/// Template plus the overall width cap applied after rendering.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabTitleFormat {
+    pub template: TabTitleTemplate,
+    pub max_width: Option<usize>,
+}
+
+impl TabTitleFormat {
+    /// Renders the title, trimming surrounding whitespace. Returns an empty
+    /// string when nothing useful is left.
+    pub fn render(&self, values: &TabTitleValues) -> String {
+        let mut out = String::new();
+        render_segments(&self.template.segments, values, &mut out);
+        let title = out.trim();
+        match self.max_width {
+            Some(width) => truncate_end(title, width),
+            None => title.to_string(),
+        }
+    }
+}
+
+/// Appends the segments and reports whether every field rendered non-empty.
+fn render_segments(segments: &[Segment], values: &TabTitleValues, out: &mut String) -> bool {
+    let mut complete = true;
+    for segment in segments {
+        match segment {
+            Segment::Literal(text) => out.push_str(text),
+            Segment::Field { field, max_width } => {
+                let value = values.get(*field);
+                complete &= !value.is_empty();
+                match (field, max_width) {
+                    (_, None) => out.push_str(value),
+                    (TabTitleField::File, Some(width)) => {
+                        out.push_str(&truncate_path(value, *width));
+                    }
+                    (_, Some(width)) => out.push_str(&truncate_end(value, *width)),
+                }
+            }
+            Segment::Section(inner) => {
+                let mut section = String::new();
+                if render_segments(inner, values, &mut section) {
+                    out.push_str(&section);
+                }
+            }
+        }
+    }
+    complete
+}
+
+/// Keeps the start of `value`, ending in `…` when it had to cut.
+pub fn truncate_end(value: &str, width: usize) -> String {
+    if value.chars().count() <= width {
+        return value.to_string();
+    }
+    let mut out: String = value.chars().take(width.saturating_sub(1)).collect();
+    if width > 0 {
+        out.push(ELLIPSIS);
+    }
+    out
+}
+
+/// Keeps the trailing path components that fit behind a `…/` marker, so the
+/// file name survives; falls back to the tail characters of the last
+/// component when even that does not fit.
+pub fn truncate_path(value: &str, width: usize) -> String {
+    let total = value.chars().count();
+    if total <= width {
+        return value.to_string();
+    }
+    let mut kept = 0;
+    for (index, _) in value
+        .match_indices('/')
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+    {
+        let tail = &value[index + 1..];
+        if tail.chars().count() + 2 > width {
+            break;
+        }
+        kept = index + 1;
+    }
+    if kept > 0 {
+        return format!("{ELLIPSIS}/{}", &value[kept..]);
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let tail: String = value.chars().skip(total - (width - 1)).collect();
+    format!("{ELLIPSIS}{tail}")
+}
+
+/// Display name for a `mode()` code, keyed on its first character.
+pub fn mode_label(code: &str) -> Option<&'static str> {
+    let label = match code.chars().next()? {
+        'n' => "NORMAL",
+        'i' => "INSERT",
+        'v' | 'V' | '\u{16}' => "VISUAL",
+        's' | 'S' | '\u{13}' => "SELECT",
+        'R' => "REPLACE",
+        'c' => "COMMAND",
+        't' => "TERMINAL",
+        _ => return None,
+    };
+    Some(label)
+}
+
+/// Branch name from the contents of `.git/HEAD`, or the abbreviated commit
+/// when HEAD is detached.
+pub fn parse_git_head(contents: &str) -> Option<String> {
+    let head = contents.trim();
+    if let Some(reference) = head.strip_prefix("ref:") {
+        let reference = reference.trim();
+        let branch = reference.strip_prefix("refs/heads/").unwrap_or(reference);
+        return (!branch.is_empty()).then(|| branch.to_string());
+    }
+    let is_commit = head.len() >= 7 && head.chars().all(|ch| ch.is_ascii_hexdigit());
+    is_commit.then(|| head[..7].to_string())
+}
+
+/// Git directory named by a `.git` file (`gitdir: <path>`), as used by
+/// worktrees and submodules.
+pub fn parse_gitdir_file(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    type TestResult<T = ()> = std::result::Result<T, TabTitleTemplateError>;
+
+    fn values() -> TabTitleValues {
+        TabTitleValues {
+            project: Some("nvimrs".to_string()),
+            file: Some("plugins/autocmds/src/machines/wezterm/title.rs".to_string()),
+            branch: Some("feature/tab-titles".to_string()),
+            modified: true,
+            mode: Some("INSERT".to_string()),
+        }
+    }
+
+    fn render(
+        template: &str,
+        max_width: Option<usize>,
+        values: &TabTitleValues,
+    ) -> TestResult<String> {
+        let format = TabTitleFormat {
+            template: TabTitleTemplate::parse(template)?,
+            max_width,
+        };
+        Ok(format.render(values))
+    }
+
+    #[test]
+    fn default_template_renders_project_only() -> TestResult {
+        assert_eq!(
+            TabTitleTemplate::parse(DEFAULT_TAB_TITLE_TEMPLATE)?,
+            TabTitleTemplate::default()
+        );
+        insta::assert_snapshot!(TabTitleFormat::default().render(&values()));
+        Ok(())
+    }
+
+    #[test]
+    fn full_template_renders_every_field() -> TestResult {
+        insta::assert_snapshot!(render(
+            "{project}[ ({branch})]: {file}{modified}[ | {mode}]",
+            None,
+            &values()
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn sections_drop_when_fields_are_empty() -> TestResult {
+        let values = TabTitleValues {
+            project: Some("nvimrs".to_string()),
+            ..TabTitleValues::default()
+        };
+        insta::assert_snapshot!(render(
+            "{project}[ ({branch})][ {file}[ {modified}]] {mode}",
+            None,
+            &values
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn field_widths_truncate_names_and_paths() -> TestResult {
+        insta::assert_snapshot!(render(
+            "{project:4} {branch:10} {file:20}",
+            None,
+            &values()
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn overall_width_truncates_the_rendered_title() -> TestResult {
+        insta::assert_snapshot!(render("{project} {branch} {file}", Some(24), &values())?);
+        Ok(())
+    }
+
+    #[test]
+    fn escapes_produce_literal_delimiters() -> TestResult {
+        insta::assert_snapshot!(render(r"\[{project}\] \{x\} \\", None, &values())?);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_rejects_malformed_templates() {
+        assert_eq!(
+            TabTitleTemplate::parse("{nope}"),
+            Err(TabTitleTemplateError::UnknownPlaceholder(
+                "nope".to_string()
+            ))
+        );
+        assert_eq!(
+            TabTitleTemplate::parse("{file:0}"),
+            Err(TabTitleTemplateError::InvalidWidth("0".to_string()))
+        );
+        assert_eq!(
+            TabTitleTemplate::parse("{project"),
+            Err(TabTitleTemplateError::UnclosedPlaceholder)
+        );
+        assert_eq!(
+            TabTitleTemplate::parse("[{project}"),
+            Err(TabTitleTemplateError::UnbalancedSection)
+        );
+        assert_eq!(
+            TabTitleTemplate::parse("{project}]"),
+            Err(TabTitleTemplateError::UnbalancedSection)
+        );
+    }
+
+    #[test]
+    fn uses_reports_fields_inside_sections() -> TestResult {
+        let template = TabTitleTemplate::parse("{project}[ {mode}]")?;
+        assert!(template.uses(TabTitleField::Mode));
+        assert!(!template.uses(TabTitleField::Branch));
+        Ok(())
+    }
+
+    #[test]
+    fn truncate_path_keeps_trailing_components() {
+        assert_eq!(truncate_path("a/b/c.rs", 8), "a/b/c.rs");
+        assert_eq!(truncate_path("src/machines/title.rs", 12), "…/title.rs");
+        assert_eq!(truncate_path("src/very_long_file_name.rs", 8), "…name.rs");
+        assert_eq!(truncate_end("kanagawa", 5), "kana…");
+        assert_eq!(truncate_end("kanagawa", 0), "");
+    }
+
+    #[test]
+    fn mode_labels_cover_common_modes() {
+        assert_eq!(mode_label("n"), Some("NORMAL"));
+        assert_eq!(mode_label("niI"), Some("NORMAL"));
+        assert_eq!(mode_label("\u{16}"), Some("VISUAL"));
+        assert_eq!(mode_label("r?"), None);
+    }
+
+    #[test]
+    fn parse_git_head_reads_branches_and_detached_commits() {
+        assert_eq!(
+            parse_git_head("ref: refs/heads/main\n"),
+            Some("main".to_string())
+        );
+        assert_eq!(
+            parse_git_head("ref: refs/remotes/origin/dev"),
+            Some("refs/remotes/origin/dev".to_string())
+        );
+        assert_eq!(
+            parse_git_head("0123456789abcdef0123456789abcdef01234567\n"),
+            Some("0123456".to_string())
+        );
+        assert_eq!(parse_git_head("garbage"), None);
+        assert_eq!(
+            parse_gitdir_file("gitdir: ../.git/worktrees/feature\n"),
+            Some("../.git/worktrees/feature")
+        );
+        assert_eq!(parse_gitdir_file("nothing"), None);
+    }
+}
@@ -12,15 +12,31 @@ pub struct OilMoveAction {
     pub dest_url: NonEmptyString,
 }
 
+#[derive(Debug)]
+pub struct OilCopyAction {
+    pub src_url: NonEmptyString,
+    pub dest_url: NonEmptyString,
+}
+
+#[derive(Debug)]
+pub struct OilDeleteAction {
+    pub url: NonEmptyString,
+}
+
+/// One entry of oil's `actions` list. Creates and permission changes do not
+/// affect existing buffers and collapse into `Other`.
 #[derive(Debug)]
 pub enum OilAction {
     Move(OilMoveAction),
+    Copy(OilCopyAction),
+    Delete(OilDeleteAction),
     Other,
 }
 
 #[derive(Debug)]
 pub struct OilActionsPostArgs {
-    pub action: OilAction,
+    /// Actions in the order oil applied them.
+    pub actions: Vec<OilAction>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,24 +79,37 @@ impl OilActionsPostArgs {
                     expected: "array of dictionaries",
                 }
             })?;
-        let first = actions
+        if actions.is_empty() {
+            return Err(OilActionsPostParseError::EmptyActions);
+        }
+        let actions = actions
             .into_iter()
-            .next()
-            .ok_or(OilActionsPostParseError::EmptyActions)?;
-        let action = OilAction::parse(first)?;
-        Ok(Self { action })
+            .map(OilAction::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { actions })
     }
 }
 
 impl OilAction {
     fn parse(action: RawOilAction) -> Result<Self, OilActionsPostParseError> {
         let action_type = require_nonempty_field(action.action_type, "type")?;
-        if action_type.as_str() != "move" {
-            return Ok(Self::Other);
+        match action_type.as_str() {
+            "move" => {
+                let src_url = require_nonempty_field(action.src_url, "src_url")?;
+                let dest_url = require_nonempty_field(action.dest_url, "dest_url")?;
+                Ok(Self::Move(OilMoveAction { src_url, dest_url }))
+            }
+            "copy" => {
+                let src_url = require_nonempty_field(action.src_url, "src_url")?;
+                let dest_url = require_nonempty_field(action.dest_url, "dest_url")?;
+                Ok(Self::Copy(OilCopyAction { src_url, dest_url }))
+            }
+            "delete" => {
+                let url = require_nonempty_field(action.url, "url")?;
+                Ok(Self::Delete(OilDeleteAction { url }))
+            }
+            _ => Ok(Self::Other),
         }
-        let src_url = require_nonempty_field(action.src_url, "src_url")?;
-        let dest_url = require_nonempty_field(action.dest_url, "dest_url")?;
-        Ok(Self::Move(OilMoveAction { src_url, dest_url }))
     }
 }
 
@@ -119,6 +148,8 @@ struct RawOilAction {
     #[serde(rename = "type", default)]
     action_type: Option<Object>,
     #[serde(default)]
+    url: Option<Object>,
+    #[serde(default)]
     src_url: Option<Object>,
     #[serde(default)]
     dest_url: Option<Object>,
@@ -136,6 +167,23 @@ mod tests {
         action
     }
 
+    fn copy_action_dict(src: &str, dest: &str) -> Dictionary {
+        let mut action = Dictionary::new();
+        action.insert("type", "copy");
+        action.insert("entry_type", "file");
+        action.insert("src_url", src);
+        action.insert("dest_url", dest);
+        action
+    }
+
+    fn delete_action_dict(url: &str) -> Dictionary {
+        let mut action = Dictionary::new();
+        action.insert("type", "delete");
+        action.insert("entry_type", "file");
+        action.insert("url", url);
+        action
+    }
+
     fn actions_post_payload(actions: Vec<Dictionary>) -> Object {
         let mut payload = Dictionary::new();
         payload.insert("actions", nvim_oxi::Array::from_iter(actions));
@@ -146,12 +194,12 @@ mod tests {
     fn oil_actions_post_parse_move_action() -> Result<(), &'static str> {
         let payload = actions_post_payload(vec![move_action_dict("a", "b")]);
         let parsed = OilActionsPostArgs::parse(payload).map_err(|_| "parse failed")?;
-        match parsed.action {
-            OilAction::Move(action) => {
+        match parsed.actions.as_slice() {
+            [OilAction::Move(action)] => {
                 assert_eq!(action.src_url.as_str(), "a");
                 assert_eq!(action.dest_url.as_str(), "b");
             }
-            OilAction::Other => return Err("expected move action"),
+            _ => return Err("expected move action"),
         }
         Ok(())
     }
@@ -159,13 +207,66 @@ mod tests {
     #[test]
     fn oil_actions_post_parse_non_move_action() -> Result<(), &'static str> {
         let mut action = Dictionary::new();
-        action.insert("type", "delete");
+        action.insert("type", "create");
+        action.insert("url", "oil:///tmp/new");
         let payload = actions_post_payload(vec![action]);
         let parsed = OilActionsPostArgs::parse(payload).map_err(|_| "parse failed")?;
-        assert!(matches!(parsed.action, OilAction::Other));
+        assert!(matches!(parsed.actions.as_slice(), [OilAction::Other]));
         Ok(())
     }
 
+    #[test]
+    fn oil_actions_post_parse_copy_action() -> Result<(), &'static str> {
+        let payload = actions_post_payload(vec![copy_action_dict("oil:///a", "oil:///b")]);
+        let parsed = OilActionsPostArgs::parse(payload).map_err(|_| "parse failed")?;
+        match parsed.actions.as_slice() {
+            [OilAction::Copy(action)] => {
+                assert_eq!(action.src_url.as_str(), "oil:///a");
+                assert_eq!(action.dest_url.as_str(), "oil:///b");
+            }
+            _ => return Err("expected copy action"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn oil_actions_post_parse_delete_action() -> Result<(), &'static str> {
+        let payload = actions_post_payload(vec![delete_action_dict("oil:///tmp/gone.rs")]);
+        let parsed = OilActionsPostArgs::parse(payload).map_err(|_| "parse failed")?;
+        match parsed.actions.as_slice() {
+            [OilAction::Delete(action)] => assert_eq!(action.url.as_str(), "oil:///tmp/gone.rs"),
+            _ => return Err("expected delete action"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn oil_actions_post_parse_keeps_every_action_in_order() -> Result<(), &'static str> {
+        let payload = actions_post_payload(vec![
+            move_action_dict("oil:///src/", "oil:///dest/"),
+            delete_action_dict("oil:///old.rs"),
+            copy_action_dict("oil:///a", "oil:///b"),
+        ]);
+        let parsed = OilActionsPostArgs::parse(payload).map_err(|_| "parse failed")?;
+        assert!(matches!(
+            parsed.actions.as_slice(),
+            [OilAction::Move(_), OilAction::Delete(_), OilAction::Copy(_)]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn oil_actions_post_parse_rejects_delete_without_url() {
+        let mut action = Dictionary::new();
+        action.insert("type", "delete");
+        let payload = actions_post_payload(vec![action]);
+        let parsed = OilActionsPostArgs::parse(payload);
+        assert!(matches!(
+            parsed,
+            Err(OilActionsPostParseError::EmptyValue { key }) if key == "url"
+        ));
+    }
+
     #[test]
     fn oil_actions_post_parse_rejects_non_dict_payload() {
         let parsed = OilActionsPostArgs::parse(Object::from(123_i64));
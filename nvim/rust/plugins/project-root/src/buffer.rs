@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::core::RootMatch;
 use nvim_oxi::Result;
 use nvim_oxi::String as NvimString;
 use nvim_oxi::api;
@@ -14,7 +15,7 @@ use nvimrs_nvim_utils::path::strip_known_prefixes;
 
 const ROOT_VAR: &str = "project_root";
 const ROOT_CACHE_VAR: &str = "project_root_cache";
-const ROOT_CACHE_VERSION: &str = "v2";
+const ROOT_CACHE_VERSION: &str = "v3";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NormalizedPathKey(String);
@@ -38,24 +39,31 @@ struct RootCacheRecord {
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum RootCacheValue {
     Missing,
-    Found { root: String, indicator: String },
+    Found {
+        root: String,
+        indicator: String,
+        rule: String,
+    },
 }
 
 impl RootCacheRecord {
-    fn new(key: &NormalizedPathKey, root: Option<&str>, indicator: Option<&str>) -> Option<Self> {
+    fn new(key: &NormalizedPathKey, found: Option<&RootMatch>) -> Option<Self> {
         if key.as_str().is_empty() {
             return None;
         }
-        let root = root
-            .filter(|value| !value.is_empty())
-            .map(ToOwned::to_owned);
-        let indicator = indicator
-            .filter(|value| !value.is_empty())
-            .map(ToOwned::to_owned);
-        let value = match (root, indicator) {
-            (None, None) => RootCacheValue::Missing,
-            (Some(root), Some(indicator)) => RootCacheValue::Found { root, indicator },
-            _ => return None,
+        let value = match found {
+            None => RootCacheValue::Missing,
+            Some(found) => {
+                let root = found.root.to_string_lossy().into_owned();
+                if root.is_empty() || found.indicator.is_empty() || found.rule.is_empty() {
+                    return None;
+                }
+                RootCacheValue::Found {
+                    root,
+                    indicator: found.indicator.clone(),
+                    rule: found.rule.clone(),
+                }
+            }
         };
         Some(Self {
             key: key.clone(),
@@ -64,18 +72,22 @@ impl RootCacheRecord {
     }
 
     fn encode(&self) -> String {
-        let (has_root, root, indicator) = match &self.value {
-            RootCacheValue::Missing => ("0", "", ""),
-            RootCacheValue::Found { root, indicator } => ("1", root.as_str(), indicator.as_str()),
+        let (has_root, root, indicator, rule) = match &self.value {
+            RootCacheValue::Missing => ("0", "", "", ""),
+            RootCacheValue::Found {
+                root,
+                indicator,
+                rule,
+            } => ("1", root.as_str(), indicator.as_str(), rule.as_str()),
         };
         format!(
-            "{ROOT_CACHE_VERSION}\0{}\0{has_root}\0{root}\0{indicator}",
+            "{ROOT_CACHE_VERSION}\0{}\0{has_root}\0{root}\0{indicator}\0{rule}",
             self.key.as_str()
         )
     }
 
     fn decode(raw: &str) -> Option<Self> {
-        let mut parts = raw.splitn(5, '\0');
+        let mut parts = raw.splitn(6, '\0');
         if parts.next()? != ROOT_CACHE_VERSION {
             return None;
         }
@@ -86,12 +98,18 @@ impl RootCacheRecord {
         let has_root = parts.next()?;
         let root_part = parts.next().unwrap_or_default();
         let indicator_part = parts.next().unwrap_or_default();
+        let rule_part = parts.next().unwrap_or_default();
         let value = match has_root {
-            "0" if root_part.is_empty() && indicator_part.is_empty() => RootCacheValue::Missing,
-            "1" if !root_part.is_empty() && !indicator_part.is_empty() => RootCacheValue::Found {
-                root: root_part.to_owned(),
-                indicator: indicator_part.to_owned(),
-            },
+            "0" if root_part.is_empty() && indicator_part.is_empty() && rule_part.is_empty() => {
+                RootCacheValue::Missing
+            }
+            "1" if !root_part.is_empty() && !indicator_part.is_empty() && !rule_part.is_empty() => {
+                RootCacheValue::Found {
+                    root: root_part.to_owned(),
+                    indicator: indicator_part.to_owned(),
+                    rule: rule_part.to_owned(),
+                }
+            }
             _ => return None,
         };
         Some(Self {
@@ -130,21 +148,29 @@ fn set_buf_var(buf: &mut Buffer, var: &str, value: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// Root last stored on `buf` by [`set_buf_root`], if any.
+pub fn buf_root(buf: &Buffer) -> Option<String> {
+    get_buf_var(buf, ROOT_VAR).filter(|root| !root.is_empty())
+}
+
+/// Store the resolved root (or its absence) on `buf`. The cache record keeps
+/// the matched indicator and rule so a cached root can be revalidated.
 pub fn set_buf_root(
     buf: &Buffer,
-    root: Option<&str>,
+    found: Option<&RootMatch>,
     key: Option<&NormalizedPathKey>,
-    indicator: Option<&str>,
 ) -> Result<()> {
     if !buf.is_valid() {
         return Ok(());
     }
 
-    let root = root.filter(|value| !value.is_empty());
-    let cache_record = key.and_then(|key| RootCacheRecord::new(key, root, indicator));
+    let root = found
+        .map(|found| found.root.to_string_lossy().into_owned())
+        .filter(|value| !value.is_empty());
+    let cache_record = key.and_then(|key| RootCacheRecord::new(key, found));
     let cache_payload = cache_record.map(|record| record.encode());
     let mut buf = buf.clone();
-    set_buf_var(&mut buf, ROOT_VAR, root)?;
+    set_buf_var(&mut buf, ROOT_VAR, root.as_deref())?;
     set_buf_var(&mut buf, ROOT_CACHE_VAR, cache_payload.as_deref())?;
     Ok(())
 }
@@ -233,7 +259,9 @@ pub fn cached_root_by_key(buf: &Buffer, key: &NormalizedPathKey) -> Option<Strin
     }
     let (root, indicator) = match record.value {
         RootCacheValue::Missing => return None,
-        RootCacheValue::Found { root, indicator } => (root, indicator),
+        RootCacheValue::Found {
+            root, indicator, ..
+        } => (root, indicator),
     };
     let root_path = Path::new(&root);
     if !path_is_dir(root_path) {
@@ -279,10 +307,18 @@ mod tests {
         Ok(())
     }
 
+    fn found(root: &str, indicator: &str, rule: &str) -> RootMatch {
+        RootMatch {
+            root: PathBuf::from(root),
+            indicator: indicator.to_string(),
+            rule: rule.to_string(),
+        }
+    }
+
     #[test]
     fn root_cache_record_roundtrip_with_root() -> std::result::Result<(), &'static str> {
         let key = normalized_path_key(Path::new("/tmp/workspace"));
-        let record = RootCacheRecord::new(&key, Some("/tmp"), Some(".git"))
+        let record = RootCacheRecord::new(&key, Some(&found("/tmp", "app.cabal", "*.cabal")))
             .ok_or("expected valid root cache record")?;
         let encoded = record.encode();
         let decoded = RootCacheRecord::decode(&encoded).ok_or("expected valid encoded payload")?;
@@ -293,8 +329,7 @@ mod tests {
     #[test]
     fn root_cache_record_roundtrip_without_root() -> std::result::Result<(), &'static str> {
         let key = normalized_path_key(Path::new("/tmp/workspace"));
-        let record =
-            RootCacheRecord::new(&key, None, None).ok_or("expected valid root cache record")?;
+        let record = RootCacheRecord::new(&key, None).ok_or("expected valid root cache record")?;
         let encoded = record.encode();
         let decoded = RootCacheRecord::decode(&encoded).ok_or("expected valid encoded payload")?;
         assert_eq!(decoded, record);
@@ -305,36 +340,46 @@ mod tests {
     fn root_cache_record_decode_rejects_invalid_payload() {
         assert_eq!(RootCacheRecord::decode(""), None);
         assert_eq!(
-            RootCacheRecord::decode(concat!("v1\0k\0", "1\0/root")),
+            RootCacheRecord::decode(concat!("v2\0k\0", "1\0/root\0.git")),
             None
         );
         assert_eq!(
-            RootCacheRecord::decode(concat!("v2\0\0", "1\0/root\0.git")),
+            RootCacheRecord::decode(concat!("v3\0\0", "1\0/root\0.git\0.git")),
             None
         );
         assert_eq!(
-            RootCacheRecord::decode(concat!("v2\0k\0", "1\0\0.git")),
+            RootCacheRecord::decode(concat!("v3\0k\0", "1\0\0.git\0.git")),
             None
         );
         assert_eq!(
-            RootCacheRecord::decode(concat!("v2\0k\0", "0\0/root\0")),
+            RootCacheRecord::decode(concat!("v3\0k\0", "0\0/root\0\0")),
             None
         );
         assert_eq!(
-            RootCacheRecord::decode(concat!("v2\0k\0", "1\0/root\0")),
+            RootCacheRecord::decode(concat!("v3\0k\0", "1\0/root\0.git")),
             None
         );
         assert_eq!(
-            RootCacheRecord::decode(concat!("v2\0k\0", "0\0\0.git")),
+            RootCacheRecord::decode(concat!("v3\0k\0", "0\0\0\0.git")),
             None
         );
-        assert_eq!(RootCacheRecord::decode("v2\0k\0x\0/root\0.git"), None);
+        assert_eq!(RootCacheRecord::decode("v3\0k\0x\0/root\0.git\0.git"), None);
     }
 
     #[test]
-    fn root_cache_record_requires_indicator_with_root() {
+    fn root_cache_record_requires_indicator_and_rule_with_root() {
         let key = normalized_path_key(Path::new("/tmp/workspace"));
-        assert_eq!(RootCacheRecord::new(&key, Some("/tmp"), None), None);
-        assert_eq!(RootCacheRecord::new(&key, None, Some(".git")), None);
+        assert_eq!(
+            RootCacheRecord::new(&key, Some(&found("/tmp", "", ".git"))),
+            None
+        );
+        assert_eq!(
+            RootCacheRecord::new(&key, Some(&found("/tmp", ".git", ""))),
+            None
+        );
+        assert_eq!(
+            RootCacheRecord::new(&key, Some(&found("", ".git", ".git"))),
+            None
+        );
     }
 }
@@ -1,33 +1,115 @@
+use std::path::PathBuf;
+
+use crate::core::MatchStrategy;
 use crate::core::RootIndicators;
+use crate::core::RootRule;
+use crate::core::RootRules;
 use crate::core::default_root_indicators;
+use crate::core::expand_stop_dir;
 use crate::core::root_indicators_from_vec;
+use crate::settings::DEFAULT_SETTINGS_FILE;
 use nvim_oxi::Dictionary;
+use nvim_oxi::Object;
 use nvim_oxi::String as NvimString;
+use nvim_oxi::conversion::FromObject;
 use nvimrs_nvim_oxi_utils::decode;
+use nvimrs_nvim_oxi_utils::dict;
+use nvimrs_nvim_utils::path::normalize_path;
 
 #[derive(Debug, Clone)]
 pub struct ProjectRootConfig {
-    pub root_indicators: RootIndicators,
+    pub root_rules: RootRules,
+    /// Record discovered roots in the persistent project registry.
+    pub registry: bool,
+    /// Load the trusted settings file at each resolved root.
+    pub settings: bool,
+    pub settings_file: String,
 }
 
 impl ProjectRootConfig {
+    /// An indicator is either a plain name (`".git"`, `"*.cabal"`) or a table
+    /// `{ pattern, priority?, strategy? }`.
+    fn parse_rule(value: Object) -> Option<RootRule> {
+        if let Ok(pattern) = NvimString::from_object(value.clone()) {
+            let pattern = pattern.to_string_lossy().into_owned();
+            return (!pattern.is_empty()).then(|| RootRule::new(pattern));
+        }
+        let table = Dictionary::from_object(value).ok()?;
+        let pattern = dict::get_string_nonempty(&table, "pattern")?;
+        let strategy = match dict::get_string(&table, "strategy") {
+            Some(value) => MatchStrategy::parse(&value)?,
+            None => MatchStrategy::default(),
+        };
+        Some(RootRule {
+            pattern,
+            priority: dict::get_i64(&table, "priority").unwrap_or_default(),
+            strategy,
+        })
+    }
+
     fn parse_root_indicators(config: &Dictionary) -> Option<RootIndicators> {
         let value = decode::get_object(config, "root_indicators")?;
-        let values =
-            decode::parse_from_object::<Vec<NvimString>>(value, "root_indicators", "array[string]")
-                .ok()?;
-        let strings: Vec<String> = values
+        let values = decode::parse_from_object::<Vec<Object>>(
+            value,
+            "root_indicators",
+            "array[string|table]",
+        )
+        .ok()?;
+        root_indicators_from_vec(values.into_iter().filter_map(Self::parse_rule).collect())
+    }
+
+    fn parse_stop_at(config: &Dictionary) -> Vec<String> {
+        decode::get_object(config, "stop_at")
+            .and_then(|value| {
+                decode::parse_from_object::<Vec<NvimString>>(value, "stop_at", "array[string]").ok()
+            })
+            .unwrap_or_default()
             .into_iter()
-            .map(|val| val.to_string_lossy().into_owned())
-            .filter(|val| !val.is_empty())
-            .collect();
-        root_indicators_from_vec(strings)
+            .map(|value| value.to_string_lossy().into_owned())
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    /// Split `stop_at` into directories (absolute, `~` or `$VAR` prefixed)
+    /// and names that stop the walk wherever they appear.
+    fn split_stop_at(values: Vec<String>) -> (Vec<PathBuf>, Vec<String>) {
+        let mut dirs = Vec::new();
+        let mut names = Vec::new();
+        for value in values {
+            if value.starts_with(['/', '~', '$']) {
+                let dir = expand_stop_dir(&value, |name| std::env::var(name).ok())
+                    .and_then(|dir| normalize_path(&dir.to_string_lossy()));
+                dirs.extend(dir);
+            } else {
+                names.push(value);
+            }
+        }
+        (dirs, names)
     }
 
     pub fn from_dict(config: Option<&Dictionary>) -> Self {
-        let root_indicators = config
+        let indicators = config
             .and_then(Self::parse_root_indicators)
             .unwrap_or_else(default_root_indicators);
-        Self { root_indicators }
+        let (stop_dirs, stop_indicators) =
+            Self::split_stop_at(config.map(Self::parse_stop_at).unwrap_or_default());
+        Self {
+            root_rules: RootRules {
+                indicators,
+                stop_dirs,
+                stop_indicators,
+            },
+            registry: config
+                .and_then(|config| dict::get_object(config, "registry"))
+                .and_then(|value| bool::from_object(value).ok())
+                .unwrap_or(true),
+            settings: config
+                .and_then(|config| dict::get_object(config, "settings"))
+                .and_then(|value| bool::from_object(value).ok())
+                .unwrap_or(true),
+            settings_file: config
+                .and_then(|config| dict::get_string_nonempty(config, "settings_file"))
+                .unwrap_or_else(|| DEFAULT_SETTINGS_FILE.to_string()),
+        }
     }
 }
@@ -2,79 +2,313 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use nonempty::NonEmpty;
+use nvimrs_nvim_utils::path::glob_matches;
 
-pub type RootIndicators = NonEmpty<String>;
+/// Which ancestor a rule reports when several directories on the walk match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchStrategy {
+    /// The directory nearest to the buffer (the historical behaviour).
+    #[default]
+    Innermost,
+    /// The directory furthest from the buffer, e.g. the workspace root of a
+    /// monorepo with a `package.json` in every package.
+    Outermost,
+}
+
+impl MatchStrategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "innermost" => Some(Self::Innermost),
+            "outermost" => Some(Self::Outermost),
+            _ => None,
+        }
+    }
+}
+
+/// One root indicator. `pattern` is a file or directory name; `*` and `?`
+/// make it a glob matched against the entries of each directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootRule {
+    pub pattern: String,
+    pub priority: i64,
+    pub strategy: MatchStrategy,
+}
+
+impl RootRule {
+    pub const fn new(pattern: String) -> Self {
+        Self {
+            pattern,
+            priority: 0,
+            strategy: MatchStrategy::Innermost,
+        }
+    }
+
+    fn find_in<G>(&self, dir: &Path, exists: &G, names: Option<&[String]>) -> Option<String>
+    where
+        G: Fn(&Path) -> bool,
+    {
+        find_indicator(&self.pattern, dir, exists, names)
+    }
+}
+
+pub type RootIndicators = NonEmpty<RootRule>;
+
+/// Root indicators plus the negative indicators that end the upward walk.
+/// A directory in `stop_dirs`, or one containing a `stop_indicators` entry,
+/// is neither examined nor walked past.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootRules {
+    pub indicators: RootIndicators,
+    pub stop_dirs: Vec<PathBuf>,
+    pub stop_indicators: Vec<String>,
+}
+
+impl RootRules {
+    pub const fn new(indicators: RootIndicators) -> Self {
+        Self {
+            indicators,
+            stop_dirs: Vec::new(),
+            stop_indicators: Vec::new(),
+        }
+    }
+
+    fn needs_listing(&self) -> bool {
+        self.indicators.iter().any(|rule| is_glob(&rule.pattern))
+            || self.stop_indicators.iter().any(|pattern| is_glob(pattern))
+    }
+}
+
+impl Default for RootRules {
+    fn default() -> Self {
+        Self::new(default_root_indicators())
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RootMatch {
     pub root: PathBuf,
+    /// The entry found in `root`; for glob rules this is the matched name.
     pub indicator: String,
+    /// The pattern of the rule that matched.
+    pub rule: String,
 }
 
+/// `.git` outranks the build-file indicators so a `package.json` or
+/// `Cargo.toml` inside a workspace member still resolves to the repository.
 pub fn default_root_indicators() -> RootIndicators {
     RootIndicators::from((
-        ".git".to_string(),
-        vec![
-            "package.json".to_string(),
-            "Cargo.toml".to_string(),
-            "flake.nix".to_string(),
-            "Makefile".to_string(),
-        ],
+        RootRule {
+            priority: 1,
+            ..RootRule::new(".git".to_string())
+        },
+        ["package.json", "Cargo.toml", "flake.nix", "Makefile"]
+            .into_iter()
+            .map(|pattern| RootRule::new(pattern.to_string()))
+            .collect(),
     ))
 }
 
-pub fn root_indicators_from_vec(values: Vec<String>) -> Option<RootIndicators> {
+pub fn root_indicators_from_vec(values: Vec<RootRule>) -> Option<RootIndicators> {
     RootIndicators::from_vec(values)
 }
 
-pub fn root_match_from_path_with<F, G>(
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+fn find_indicator<G>(
+    pattern: &str,
+    dir: &Path,
+    exists: &G,
+    names: Option<&[String]>,
+) -> Option<String>
+where
+    G: Fn(&Path) -> bool,
+{
+    if !is_glob(pattern) {
+        return exists(&dir.join(pattern)).then(|| pattern.to_string());
+    }
+    names?
+        .iter()
+        .find(|name| glob_matches(pattern, name))
+        .cloned()
+}
+
+#[derive(Debug)]
+struct Candidate {
+    depth: usize,
+    root: PathBuf,
+    indicator: String,
+}
+
+/// Walk upward from `path` and pick the root.
+///
+/// Every rule records its innermost or outermost matching ancestor. The
+/// winner is the rule with the highest priority; ties go to the match
+/// nearest to `path`, then to the rule listed first. With every rule at the
+/// same priority and innermost, this is the plain "nearest indicator" walk.
+/// `list_names` returns the entry names of a directory and is only called
+/// when a glob is configured.
+///
+/// Once a top-priority innermost rule has matched, no ancestor can win any
+/// more, so the walk stops there instead of probing every directory up to
+/// `/` (which may sit on slow network mounts).
+pub fn root_match_from_path_with<F, G, H>(
     path: &Path,
-    indicators: &RootIndicators,
+    rules: &RootRules,
     is_dir: F,
     exists: G,
+    list_names: H,
 ) -> Option<RootMatch>
 where
     F: Fn(&Path) -> bool,
     G: Fn(&Path) -> bool,
+    H: Fn(&Path) -> Vec<String>,
 {
     let mut current = path.to_path_buf();
     if !is_dir(&current) {
         current = current.parent()?.to_path_buf();
     }
 
+    let needs_listing = rules.needs_listing();
+    let top_priority = rules.indicators.iter().map(|rule| rule.priority).max();
+    let top_outermost = rules.indicators.iter().any(|rule| {
+        Some(rule.priority) == top_priority && rule.strategy == MatchStrategy::Outermost
+    });
+    let mut candidates: Vec<Option<Candidate>> = rules.indicators.iter().map(|_| None).collect();
+    let mut depth = 0;
     loop {
-        for indicator in indicators {
-            let candidate = current.join(indicator);
-            if exists(&candidate) {
-                return Some(RootMatch {
+        if rules.stop_dirs.contains(&current) {
+            break;
+        }
+        let names = needs_listing.then(|| list_names(&current));
+        let stopped = rules
+            .stop_indicators
+            .iter()
+            .any(|pattern| find_indicator(pattern, &current, &exists, names.as_deref()).is_some());
+        if stopped {
+            break;
+        }
+        for (rule, candidate) in rules.indicators.iter().zip(candidates.iter_mut()) {
+            if candidate.is_some() && rule.strategy == MatchStrategy::Innermost {
+                continue;
+            }
+            if let Some(indicator) = rule.find_in(&current, &exists, names.as_deref()) {
+                *candidate = Some(Candidate {
+                    depth,
                     root: current.clone(),
-                    indicator: indicator.clone(),
+                    indicator,
                 });
             }
         }
-        if !current.pop() {
+        let settled = !top_outermost
+            && rules
+                .indicators
+                .iter()
+                .zip(&candidates)
+                .any(|(rule, candidate)| {
+                    Some(rule.priority) == top_priority && candidate.is_some()
+                });
+        if settled || !current.pop() {
             break;
         }
+        depth += 1;
     }
 
-    None
+    let (rule, candidate) = rules
+        .indicators
+        .iter()
+        .zip(candidates)
+        .enumerate()
+        .filter_map(|(idx, (rule, candidate))| Some((idx, rule, candidate?)))
+        .max_by_key(|(idx, rule, candidate)| {
+            (
+                rule.priority,
+                std::cmp::Reverse(candidate.depth),
+                std::cmp::Reverse(*idx),
+            )
+        })
+        .map(|(_, rule, candidate)| (rule, candidate))?;
+    Some(RootMatch {
+        root: candidate.root,
+        indicator: candidate.indicator,
+        rule: rule.pattern.clone(),
+    })
+}
+
+/// Expand a leading `~` or `$VAR` in a stop directory. Returns `None` when
+/// the variable is unset or the result is not absolute.
+pub fn expand_stop_dir<E>(value: &str, env: E) -> Option<PathBuf>
+where
+    E: Fn(&str) -> Option<String>,
+{
+    let (base, rest) = if let Some(rest) = value.strip_prefix('~') {
+        (env("HOME")?, rest)
+    } else if let Some(var) = value.strip_prefix('$') {
+        let end = var
+            .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+            .unwrap_or(var.len());
+        (env(&var[..end])?, &var[end..])
+    } else {
+        (String::new(), value)
+    };
+    let expanded = PathBuf::from(format!("{base}{rest}"));
+    expanded.is_absolute().then_some(expanded)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::collections::HashSet;
 
+    fn rules(patterns: &[&str]) -> RootRules {
+        let rules = patterns
+            .iter()
+            .map(|pattern| RootRule::new((*pattern).to_string()))
+            .collect();
+        RootRules::new(root_indicators_from_vec(rules).unwrap_or_else(default_root_indicators))
+    }
+
+    fn find(path: &str, rules: &RootRules, existing: &[&str]) -> Option<RootMatch> {
+        let existing: HashSet<PathBuf> = existing.iter().map(PathBuf::from).collect();
+        let mut listing: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        for entry in &existing {
+            if let (Some(parent), Some(name)) = (entry.parent(), entry.file_name()) {
+                listing
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .push(name.to_string_lossy().into_owned());
+            }
+        }
+        root_match_from_path_with(
+            Path::new(path),
+            rules,
+            |_| true,
+            |candidate| existing.contains(candidate),
+            |dir| {
+                let mut names = listing.get(dir).cloned().unwrap_or_default();
+                names.sort();
+                names
+            },
+        )
+    }
+
+    fn root_of(found: Option<RootMatch>) -> Option<PathBuf> {
+        found.map(|value| value.root)
+    }
+
     #[test]
     fn root_from_path_with_finds_nearest_indicator() {
-        let indicators = RootIndicators::new("root".to_string());
+        let indicators = RootIndicators::new(RootRule::new("root".to_string()));
         let mut existing = HashSet::new();
         existing.insert(PathBuf::from("/a/root"));
         let root = root_match_from_path_with(
             Path::new("/a/b/c"),
-            &indicators,
+            &RootRules::new(indicators),
             |_| true,
             |candidate| existing.contains(candidate),
+            |_| Vec::new(),
         )
         .map(|value| value.root);
         assert_eq!(root, Some(PathBuf::from("/a")));
@@ -82,12 +316,13 @@ mod tests {
 
     #[test]
     fn root_from_path_with_none_when_missing() {
-        let indicators = RootIndicators::new("root".to_string());
+        let indicators = RootIndicators::new(RootRule::new("root".to_string()));
         let root = root_match_from_path_with(
             Path::new("/a/b/c"),
-            &indicators,
+            &RootRules::new(indicators),
             |_| true,
             |_candidate| false,
+            |_| Vec::new(),
         )
         .map(|value| value.root);
         assert_eq!(root, None);
@@ -95,18 +330,157 @@ mod tests {
 
     #[test]
     fn root_match_from_path_with_reports_indicator() -> Result<(), &'static str> {
-        let indicators = RootIndicators::from(("x".to_string(), vec!["root".to_string()]));
+        let indicators = RootIndicators::from((
+            RootRule::new("x".to_string()),
+            vec![RootRule::new("root".to_string())],
+        ));
         let mut existing = HashSet::new();
         existing.insert(PathBuf::from("/a/root"));
         let found = root_match_from_path_with(
             Path::new("/a/b/c"),
-            &indicators,
+            &RootRules::new(indicators),
             |_| true,
             |candidate| existing.contains(candidate),
+            |_| Vec::new(),
         );
         let found = found.ok_or("expected root match")?;
         assert_eq!(found.root, PathBuf::from("/a"));
         assert_eq!(found.indicator, "root");
         Ok(())
     }
+
+    #[test]
+    fn root_match_reports_the_matching_rule_pattern() {
+        let found = find("/a/b/c", &rules(&["x", "root"]), &["/a/root"]);
+        assert_eq!(found.map(|value| value.rule), Some("root".to_string()));
+    }
+
+    #[test]
+    fn equal_priorities_prefer_nearest_then_list_order() {
+        let existing = ["/repo/.git", "/repo/pkg/package.json", "/repo/pkg/Makefile"];
+        let found = find(
+            "/repo/pkg/src",
+            &rules(&[".git", "Makefile", "package.json"]),
+            &existing,
+        );
+        assert_eq!(
+            found.map(|value| (value.root, value.rule)),
+            Some((PathBuf::from("/repo/pkg"), "Makefile".to_string()))
+        );
+    }
+
+    #[test]
+    fn higher_priority_wins_over_nearer_match() {
+        let mut rules = rules(&[".git", "package.json"]);
+        rules.indicators.head.priority = 10;
+        let existing = ["/repo/.git", "/repo/pkg/package.json"];
+        let found = find("/repo/pkg/src", &rules, &existing);
+        assert_eq!(root_of(found), Some(PathBuf::from("/repo")));
+    }
+
+    #[test]
+    fn default_rules_prefer_the_git_root_over_a_nested_package() {
+        let existing = ["/repo/.git", "/repo/packages/app/package.json"];
+        let found = find("/repo/packages/app/src", &RootRules::default(), &existing);
+        assert_eq!(
+            found.map(|value| (value.root, value.rule)),
+            Some((PathBuf::from("/repo"), ".git".to_string()))
+        );
+        let existing = ["/pkg/package.json"];
+        let found = find("/pkg/src", &RootRules::default(), &existing);
+        assert_eq!(root_of(found), Some(PathBuf::from("/pkg")));
+    }
+
+    #[test]
+    fn walk_stops_once_a_top_priority_rule_matches() {
+        let probed = std::cell::RefCell::new(Vec::new());
+        let found = root_match_from_path_with(
+            Path::new("/mnt/share/repo/pkg/src"),
+            &RootRules::default(),
+            |_| true,
+            |candidate| {
+                probed.borrow_mut().push(candidate.to_path_buf());
+                candidate == Path::new("/mnt/share/repo/.git")
+            },
+            |_| Vec::new(),
+        );
+        assert_eq!(root_of(found), Some(PathBuf::from("/mnt/share/repo")));
+        assert!(
+            probed
+                .borrow()
+                .iter()
+                .all(|probe| probe.starts_with("/mnt/share/repo")),
+            "walked past the root: {:?}",
+            probed.borrow()
+        );
+
+        let mut outer = rules(&["package.json"]);
+        outer.indicators.head.strategy = MatchStrategy::Outermost;
+        let existing = ["/ws/package.json", "/ws/app/package.json"];
+        assert_eq!(
+            root_of(find("/ws/app/src", &outer, &existing)),
+            Some(PathBuf::from("/ws"))
+        );
+    }
+
+    #[test]
+    fn outermost_strategy_reports_furthest_match() {
+        let mut rules = rules(&["package.json"]);
+        rules.indicators.head.strategy = MatchStrategy::Outermost;
+        let existing = ["/ws/package.json", "/ws/packages/app/package.json"];
+        let found = find("/ws/packages/app/src", &rules, &existing);
+        assert_eq!(root_of(found), Some(PathBuf::from("/ws")));
+    }
+
+    #[test]
+    fn glob_indicators_match_directory_entries() {
+        let existing = ["/proj/app.cabal", "/proj/src/Main.hs"];
+        let found = find("/proj/src/Main.hs", &rules(&["*.cabal"]), &existing);
+        assert_eq!(
+            found.map(|value| (value.root, value.indicator, value.rule)),
+            Some((
+                PathBuf::from("/proj"),
+                "app.cabal".to_string(),
+                "*.cabal".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn stop_dirs_and_stop_indicators_end_the_walk() {
+        let mut home_rules = rules(&[".git"]);
+        home_rules.stop_dirs = vec![PathBuf::from("/home/me")];
+        let existing = ["/home/me/.git"];
+        assert_eq!(find("/home/me/notes/todo", &home_rules, &existing), None);
+
+        let mut marker_rules = rules(&[".git"]);
+        marker_rules.stop_indicators = vec![".noroot".to_string()];
+        let existing = ["/repo/.git", "/repo/vendor/.noroot"];
+        assert_eq!(find("/repo/vendor/lib", &marker_rules, &existing), None);
+        assert_eq!(
+            root_of(find("/repo/src", &marker_rules, &existing)),
+            Some(PathBuf::from("/repo"))
+        );
+    }
+
+    #[test]
+    fn expand_stop_dir_handles_home_and_variables() {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "WORK" => Some("/srv/work".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand_stop_dir("~", env), Some(PathBuf::from("/home/me")));
+        assert_eq!(
+            expand_stop_dir("$HOME/src", env),
+            Some(PathBuf::from("/home/me/src"))
+        );
+        assert_eq!(
+            expand_stop_dir("$WORK", env),
+            Some(PathBuf::from("/srv/work"))
+        );
+        assert_eq!(expand_stop_dir("$MISSING/x", env), None);
+        assert_eq!(expand_stop_dir("relative", env), None);
+        assert_eq!(expand_stop_dir("/opt", env), Some(PathBuf::from("/opt")));
+    }
 }
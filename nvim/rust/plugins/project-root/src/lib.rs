@@ -2,6 +2,9 @@ mod buffer;
 mod config;
 mod core;
 mod plugin;
+mod registry;
+mod settings;
+mod trust;
 mod types;
 
 use nvim_oxi::Dictionary;
@@ -6,30 +6,50 @@ use crate::core::RootMatch;
 use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Function;
+use nvim_oxi::Object;
 use nvim_oxi::Result;
 use nvim_oxi::String as NvimString;
 use nvim_oxi::api;
 use nvim_oxi::api::Buffer;
 use nvim_oxi::api::opts::CreateAugroupOpts;
 use nvim_oxi::api::opts::CreateAutocmdOpts;
+use nvim_oxi::api::opts::CreateCommandOpts;
+use nvim_oxi::api::opts::ExecAutocmdsOpts;
 use nvim_oxi::api::types::AutocmdCallbackArgs;
+use nvim_oxi::api::types::CommandArgs;
+use nvim_oxi::api::types::CommandComplete;
+use nvim_oxi::api::types::CommandNArgs;
+use nvim_oxi::schedule;
 use nvimrs_nvim_oxi_utils::guard;
 use nvimrs_nvim_oxi_utils::handles;
 use nvimrs_nvim_oxi_utils::notify;
 use nvimrs_nvim_oxi_utils::state::StateCell;
 use nvimrs_nvim_utils::path::path_is_dir;
+use nvimrs_nvim_utils::store;
+use nvimrs_nvim_utils::store::StoreError;
 
 use crate::buffer::NormalizedPathKey;
+use crate::buffer::buf_root;
 use crate::buffer::cached_root_by_key;
 use crate::buffer::debug_log;
 use crate::buffer::get_path_from_buffer;
 use crate::buffer::normalized_path_key;
 use crate::buffer::set_buf_root;
 use crate::config::ProjectRootConfig;
+use crate::registry;
+use crate::registry::REGISTRY_FILE;
+use crate::settings::ProjectSettings;
+use crate::settings::SettingsStatus;
+use crate::settings::resolve_settings;
+use crate::trust;
+use crate::trust::TRUST_FILE;
 use crate::types::State;
 
 const PROJECT_ROOT_GROUP: &str = "ProjectRoot";
+const ROOT_CHANGED_PATTERN: &str = "ProjectRootChanged";
 const LOG_CONTEXT: &str = "project_root";
+/// Minimum seconds between registry writes for the same root.
+const RECORD_INTERVAL_SECS: u64 = 10 * 60;
 
 #[derive(Debug)]
 struct ProjectRootContext {
@@ -72,7 +92,7 @@ fn buffer_path_and_key(buf: &Buffer, label: &str) -> Result<Option<(PathBuf, Nor
 
     let Some(path) = get_path_from_buffer(buf)? else {
         debug_log(|| format!("{label}: buf={} no path", buf.handle()));
-        set_buf_root(buf, None, None, None)?;
+        set_buf_root(buf, None, None)?;
         return Ok(None);
     };
     let key = normalized_path_key(&path);
@@ -91,31 +111,51 @@ fn cached_or_refresh_root(buf: &Buffer, context: &ProjectRootContext) -> Result<
     refresh_root_for_path(buf, &path, &key, context)
 }
 
+/// Sorted entry names of `dir`, used to match glob indicators.
+fn dir_entry_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
 fn root_from_path(path: &Path, context: &ProjectRootContext) -> Option<PathBuf> {
     root_match_from_path(path, context).map(|value| value.root)
 }
 
 fn root_match_from_path(path: &Path, context: &ProjectRootContext) -> Option<RootMatch> {
-    let indicators = {
+    let rules = {
         let state = context.state_lock();
-        state.root_indicators.clone()
+        state.root_rules.clone()
     };
-    let root = crate::core::root_match_from_path_with(path, &indicators, path_is_dir, Path::exists);
+    let root = crate::core::root_match_from_path_with(
+        path,
+        &rules,
+        path_is_dir,
+        Path::exists,
+        dir_entry_names,
+    );
     debug_log(|| {
         root.as_ref().map_or_else(
             || {
                 format!(
-                    "root_from_path: path='{}' no root (indicators={:?})",
+                    "root_from_path: path='{}' no root (rules={:?})",
                     path.display(),
-                    indicators
+                    rules
                 )
             },
             |root| {
                 format!(
-                    "root_from_path: path='{}' found='{}' indicator='{}'",
+                    "root_from_path: path='{}' found='{}' indicator='{}' rule='{}'",
                     path.display(),
                     root.root.display(),
-                    root.indicator
+                    root.indicator,
+                    root.rule
                 )
             },
         )
@@ -123,6 +163,215 @@ fn root_match_from_path(path: &Path, context: &ProjectRootContext) -> Option<Roo
     root
 }
 
+fn store_error_to_nvim(err: &StoreError) -> nvim_oxi::Error {
+    nvim_oxi::api::Error::Other(err.to_string()).into()
+}
+
+fn stdpath(kind: &str, file: &str) -> Result<PathBuf> {
+    let dir: NvimString = api::call_function("stdpath", Array::from_iter([kind]))?;
+    Ok(PathBuf::from(dir.to_string_lossy().into_owned()).join(file))
+}
+
+fn registry_path() -> Result<PathBuf> {
+    stdpath("data", REGISTRY_FILE)
+}
+
+fn trust_path() -> Result<PathBuf> {
+    stdpath("state", TRUST_FILE)
+}
+
+fn record_project_visit(found: &RootMatch, context: &ProjectRootContext) {
+    let root = found.root.to_string_lossy().into_owned();
+    let now = registry::now_secs();
+    let mut state = context.state_lock();
+    if !state.registry_enabled {
+        return;
+    }
+    if let Some((last_root, at)) = &state.last_recorded
+        && *last_root == root
+        && now.saturating_sub(*at) < RECORD_INTERVAL_SECS
+    {
+        return;
+    }
+    state.last_recorded = Some((root.clone(), now));
+    state
+        .pending_visits
+        .record(&root, &found.indicator, &found.rule, now);
+    if !std::mem::replace(&mut state.flush_scheduled, true) {
+        schedule(|()| {
+            guard::with_panic(
+                (),
+                || flush_pending_visits(context()),
+                |info| report_panic("flush_pending_visits", &info),
+            );
+        });
+    }
+}
+
+/// Write queued visits to the registry outside the autocmd that recorded
+/// them. On failure the visits stay queued and the next flush retries them.
+fn flush_pending_visits(context: &ProjectRootContext) {
+    let pending = {
+        let mut state = context.state_lock();
+        state.flush_scheduled = false;
+        std::mem::take(&mut state.pending_visits)
+    };
+    if pending.is_empty() {
+        return;
+    }
+    let written = registry_path().and_then(|path| {
+        registry::update(&path, |registry| {
+            pending.apply_to(registry);
+            ((), true)
+        })
+        .map_err(|err| store_error_to_nvim(&err))
+    });
+    if let Err(err) = written {
+        context.state_lock().pending_visits.restore(pending);
+        notify::warn(LOG_CONTEXT, &format!("record project failed: {err}"));
+    }
+}
+
+/// Settings file for `root`, or `None` when settings loading is disabled.
+fn settings_file_for(root: &str, context: &ProjectRootContext) -> Option<PathBuf> {
+    let state = context.state_lock();
+    state
+        .settings_enabled
+        .then(|| Path::new(root).join(&state.settings_file))
+}
+
+fn content_hash(raw: &str) -> Result<String> {
+    let hash: NvimString = api::call_function("sha256", Array::from_iter([raw]))?;
+    Ok(hash.to_string_lossy().into_owned())
+}
+
+fn read_settings_status(file: &Path) -> Result<SettingsStatus> {
+    let Some(raw) = store::read_optional(file).map_err(|err| store_error_to_nvim(&err))? else {
+        return Ok(SettingsStatus::Missing);
+    };
+    let hash = content_hash(&raw)?;
+    let key = file.to_string_lossy();
+    let trusted = match trust::load(&trust_path()?) {
+        Ok(db) => db.is_trusted(&key, &hash),
+        Err(err) => {
+            notify::warn(LOG_CONTEXT, &err.to_string());
+            false
+        }
+    };
+    Ok(resolve_settings(Some((&raw, hash)), |_| trusted))
+}
+
+/// Re-read the settings file at `root` and cache its status. Untrusted or
+/// invalid files are reported once per content hash.
+fn load_project_settings(root: &str, context: &ProjectRootContext) -> Option<SettingsStatus> {
+    let file = settings_file_for(root, context)?;
+    let status = read_settings_status(&file).unwrap_or_else(|err| {
+        notify::warn(LOG_CONTEXT, &format!("load project settings failed: {err}"));
+        SettingsStatus::Missing
+    });
+    let previous = context
+        .state_lock()
+        .settings
+        .insert(root.to_string(), status.clone());
+    if previous.as_ref().and_then(SettingsStatus::hash) != status.hash() {
+        match &status {
+            SettingsStatus::Untrusted { .. } => notify::warn(
+                LOG_CONTEXT,
+                &format!(
+                    "{} is not trusted; run :ProjectTrust to load it",
+                    file.display()
+                ),
+            ),
+            SettingsStatus::Invalid { message, .. } => notify::warn(
+                LOG_CONTEXT,
+                &format!("invalid project settings in {}: {message}", file.display()),
+            ),
+            SettingsStatus::Missing | SettingsStatus::Trusted { .. } => {}
+        }
+    }
+    Some(status)
+}
+
+fn cached_project_settings(root: &str, context: &ProjectRootContext) -> Option<SettingsStatus> {
+    let cached = context.state_lock().settings.get(root).cloned();
+    cached.or_else(|| load_project_settings(root, context))
+}
+
+fn settings_to_object(settings: &ProjectSettings) -> Object {
+    let mut smear = Dictionary::new();
+    smear.insert(
+        "filetypes_disabled",
+        settings
+            .smear
+            .filetypes_disabled
+            .iter()
+            .map(String::as_str)
+            .collect::<Array>(),
+    );
+    let mut dict = Dictionary::new();
+    if let Some(tab_width) = settings.tab_width {
+        dict.insert("tab_width", i64::from(tab_width));
+    }
+    if let Some(expand_tab) = settings.expand_tab {
+        dict.insert("expand_tab", expand_tab);
+    }
+    if let Some(formatter) = settings.formatter.as_deref() {
+        dict.insert("formatter", formatter);
+    }
+    dict.insert("smear", smear);
+    Object::from(dict)
+}
+
+fn status_settings_object(status: Option<&SettingsStatus>) -> Object {
+    status
+        .and_then(SettingsStatus::settings)
+        .map_or_else(Object::nil, settings_to_object)
+}
+
+/// Fire `User ProjectRootChanged` with the new root and its settings.
+fn emit_root_changed(
+    buf: &Buffer,
+    found: &RootMatch,
+    previous: Option<&str>,
+    status: Option<&SettingsStatus>,
+) -> Result<()> {
+    let data = Dictionary::from_iter([
+        ("buf", Object::from(i64::from(buf.handle()))),
+        ("root", Object::from(found.root.to_string_lossy().as_ref())),
+        (
+            "previous_root",
+            previous.map_or_else(Object::nil, Object::from),
+        ),
+        ("indicator", Object::from(found.indicator.as_str())),
+        ("rule", Object::from(found.rule.as_str())),
+        (
+            "settings_status",
+            Object::from(status.map_or("disabled", SettingsStatus::label)),
+        ),
+        ("settings", status_settings_object(status)),
+    ]);
+    let opts = ExecAutocmdsOpts::builder()
+        .patterns(ROOT_CHANGED_PATTERN)
+        .modeline(false)
+        .data(data)
+        .build();
+    api::exec_autocmds(["User"], &opts)?;
+    Ok(())
+}
+
+fn on_root_changed(
+    buf: &Buffer,
+    found: &RootMatch,
+    previous: Option<&str>,
+    context: &ProjectRootContext,
+) {
+    let root = found.root.to_string_lossy();
+    let status = load_project_settings(&root, context);
+    if let Err(err) = emit_root_changed(buf, found, previous, status.as_ref()) {
+        notify::warn(LOG_CONTEXT, &format!("ProjectRootChanged failed: {err}"));
+    }
+}
+
 fn refresh_root_for_path(
     buf: &Buffer,
     path: &Path,
@@ -133,10 +382,14 @@ fn refresh_root_for_path(
     let root = root_match
         .as_ref()
         .map(|match_value| match_value.root.to_string_lossy().into_owned());
-    let indicator = root_match
-        .as_ref()
-        .map(|match_value| match_value.indicator.as_str());
-    set_buf_root(buf, root.as_deref(), Some(key), indicator)?;
+    let previous = buf_root(buf);
+    set_buf_root(buf, root_match.as_ref(), Some(key))?;
+    if let Some(found) = root_match.as_ref() {
+        record_project_visit(found, context);
+        if previous != root {
+            on_root_changed(buf, found, previous.as_deref(), context);
+        }
+    }
     debug_log(|| {
         let root_value = root.as_deref().unwrap_or("<none>");
         format!(
@@ -243,6 +496,72 @@ fn setup_autocmd(context: &'static ProjectRootContext) -> Result<()> {
         .build();
 
     api::create_autocmd(["BufEnter", "BufFilePost"], &opts)?;
+
+    let flush_opts = CreateAutocmdOpts::builder()
+        .group(group)
+        .callback(move |_args: AutocmdCallbackArgs| {
+            guard::with_panic(
+                false,
+                || {
+                    flush_pending_visits(context);
+                    false
+                },
+                |info| report_panic("flush_pending_visits", &info),
+            )
+        })
+        .build();
+    api::create_autocmd(["VimLeavePre"], &flush_opts)?;
+    Ok(())
+}
+
+fn run_command(label: &str, command: impl FnOnce() -> Result<()>) {
+    guard::with_panic(
+        (),
+        || {
+            if let Err(err) = command() {
+                notify::warn(LOG_CONTEXT, &format!("{label} failed: {err}"));
+            }
+        },
+        |info| report_panic(label, &info),
+    );
+}
+
+fn command_root(args: &CommandArgs) -> Option<String> {
+    args.args.clone().filter(|value| !value.is_empty())
+}
+
+fn setup_commands() -> Result<()> {
+    let trust_opts = CreateCommandOpts::builder()
+        .force(true)
+        .nargs(CommandNArgs::ZeroOrOne)
+        .complete(CommandComplete::Dir)
+        .desc("Trust the project settings file")
+        .build();
+    api::create_user_command(
+        "ProjectTrust",
+        |args: CommandArgs| {
+            run_command("ProjectTrust", || {
+                trust_project_settings(command_root(&args)).map(|_| ())
+            });
+        },
+        &trust_opts,
+    )?;
+
+    let untrust_opts = CreateCommandOpts::builder()
+        .force(true)
+        .nargs(CommandNArgs::ZeroOrOne)
+        .complete(CommandComplete::Dir)
+        .desc("Revoke trust in the project settings file")
+        .build();
+    api::create_user_command(
+        "ProjectUntrust",
+        |args: CommandArgs| {
+            run_command("ProjectUntrust", || {
+                untrust_project_settings(command_root(&args)).map(|_| ())
+            });
+        },
+        &untrust_opts,
+    )?;
     Ok(())
 }
 
@@ -251,7 +570,7 @@ fn invalidate_cached_roots() {
         if !buf.is_valid() {
             continue;
         }
-        if let Err(err) = set_buf_root(&buf, None, None, None) {
+        if let Err(err) = set_buf_root(&buf, None, None) {
             notify::warn(
                 LOG_CONTEXT,
                 &format!("clear root cache failed for buf {}: {err}", buf.handle()),
@@ -263,10 +582,16 @@ fn invalidate_cached_roots() {
 fn apply_config(config: Option<&Dictionary>, context: &ProjectRootContext) -> bool {
     let config = ProjectRootConfig::from_dict(config);
     let mut state = context.state_lock();
-    if state.root_indicators == config.root_indicators {
+    state.registry_enabled = config.registry;
+    if state.settings_enabled != config.settings || state.settings_file != config.settings_file {
+        state.settings_enabled = config.settings;
+        state.settings_file = config.settings_file;
+        state.settings.clear();
+    }
+    if state.root_rules == config.root_rules {
         return false;
     }
-    state.root_indicators = config.root_indicators;
+    state.root_rules = config.root_rules;
     true
 }
 
@@ -293,6 +618,7 @@ fn setup(config: Option<Dictionary>) -> Result<()> {
 
     if should_setup {
         setup_autocmd(context)?;
+        setup_commands()?;
     }
 
     if let Err(err) = refresh_root_for_buffer(&api::get_current_buf(), context) {
@@ -339,6 +665,171 @@ fn show_project_root() -> Result<()> {
     Ok(())
 }
 
+fn clamp_u64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Registered projects whose root still exists, most frecent first.
+fn list_projects() -> Result<Array> {
+    let path = registry_path()?;
+    let mut registry = registry::load(&path).map_err(|err| store_error_to_nvim(&err))?;
+    context()
+        .state_lock()
+        .pending_visits
+        .apply_to(&mut registry);
+    let now = registry::now_secs();
+    let projects: Array = registry
+        .by_frecency(now)
+        .into_iter()
+        .filter(|record| path_is_dir(Path::new(&record.root)))
+        .map(|record| {
+            Dictionary::from_iter([
+                ("root", Object::from(record.root.as_str())),
+                ("indicator", Object::from(record.indicator.as_str())),
+                ("rule", Object::from(record.rule.as_str())),
+                ("visits", Object::from(clamp_u64(record.visits))),
+                ("last_visit", Object::from(clamp_u64(record.last_visit))),
+                ("score", Object::from(clamp_u64(record.frecency(now)))),
+            ])
+        })
+        .collect();
+    Ok(projects)
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn forget_project(root: String) -> Result<bool> {
+    let queued = {
+        let mut state = context().state_lock();
+        if state
+            .last_recorded
+            .as_ref()
+            .is_some_and(|(last_root, _)| *last_root == root)
+        {
+            state.last_recorded = None;
+        }
+        state.pending_visits.forget(&root)
+    };
+    // Like a failed flush, a failed write puts the visits back in the queue
+    // rather than losing them.
+    let forgotten = registry_path().and_then(|path| {
+        registry::update(&path, |registry| {
+            let removed = registry.forget(&root);
+            (removed, removed)
+        })
+        .map_err(|err| store_error_to_nvim(&err))
+    });
+    match forgotten {
+        Ok(forgotten) => Ok(forgotten || !queued.is_empty()),
+        Err(err) => {
+            context().state_lock().pending_visits.restore(queued);
+            Err(err)
+        }
+    }
+}
+
+/// Trusted settings for `buf` (default: current buffer), or `nil` when the
+/// buffer has no root or the root has no trusted settings file.
+fn project_settings(buf: Option<Buffer>) -> Result<Object> {
+    let buf = buf.unwrap_or_else(api::get_current_buf);
+    let context = context();
+    let Some(root) = cached_or_refresh_root(&buf, context)? else {
+        return Ok(Object::nil());
+    };
+    Ok(status_settings_object(
+        cached_project_settings(&root, context).as_ref(),
+    ))
+}
+
+fn settings_root(root: Option<String>) -> Result<Option<String>> {
+    match root.filter(|root| !root.is_empty()) {
+        Some(root) => Ok(nvimrs_nvim_utils::path::normalize_path(&root)
+            .map(|path| path.to_string_lossy().into_owned())),
+        None => get_project_root(context()),
+    }
+}
+
+/// Re-announce `root` for the current buffer so listeners pick up a trust
+/// change without having to leave and re-enter the project.
+fn reannounce_root(root: &str, context: &ProjectRootContext) -> Result<()> {
+    let buf = api::get_current_buf();
+    if buf_root(&buf).as_deref() != Some(root) {
+        return Ok(());
+    }
+    let Some((path, _)) = buffer_path_and_key(&buf, "reannounce_root")? else {
+        return Ok(());
+    };
+    if let Some(found) = root_match_from_path(&path, context) {
+        on_root_changed(&buf, &found, Some(root), context);
+    }
+    Ok(())
+}
+
+/// Trust the current content of the settings file at `root` (default: the
+/// current project root). Returns `true` when the settings are now loaded.
+fn trust_project_settings(root: Option<String>) -> Result<bool> {
+    let context = context();
+    let Some(root) = settings_root(root)? else {
+        notify::warn("", "No project root found");
+        return Ok(false);
+    };
+    let Some(file) = settings_file_for(&root, context) else {
+        notify::warn(LOG_CONTEXT, "project settings are disabled");
+        return Ok(false);
+    };
+    let Some(raw) = store::read_optional(&file).map_err(|err| store_error_to_nvim(&err))? else {
+        notify::warn(LOG_CONTEXT, &format!("{} does not exist", file.display()));
+        return Ok(false);
+    };
+    let hash = content_hash(&raw)?;
+    let key = file.to_string_lossy().into_owned();
+    trust::update(&trust_path()?, |db| {
+        let changed = db.trust(&key, &hash);
+        ((), changed)
+    })
+    .map_err(|err| store_error_to_nvim(&err))?;
+    context.state_lock().settings.remove(&root);
+    reannounce_root(&root, context)?;
+    let trusted = cached_project_settings(&root, context)
+        .as_ref()
+        .is_some_and(|status| status.settings().is_some());
+    if trusted {
+        notify::info(LOG_CONTEXT, &format!("trusted {}", file.display()));
+    }
+    Ok(trusted)
+}
+
+/// Forget the trust entry for the settings file at `root` (default: the
+/// current project root). Returns `true` when an entry was removed.
+fn untrust_project_settings(root: Option<String>) -> Result<bool> {
+    let context = context();
+    let Some(root) = settings_root(root)? else {
+        notify::warn("", "No project root found");
+        return Ok(false);
+    };
+    let file = {
+        let state = context.state_lock();
+        Path::new(&root).join(&state.settings_file)
+    };
+    let key = file.to_string_lossy().into_owned();
+    let revoked = trust::update(&trust_path()?, |db| {
+        let revoked = db.revoke(&key);
+        (revoked, revoked)
+    })
+    .map_err(|err| store_error_to_nvim(&err))?;
+    if revoked {
+        context.state_lock().settings.remove(&root);
+        reannounce_root(&root, context)?;
+        notify::info(
+            LOG_CONTEXT,
+            &format!("revoked trust for {}", file.display()),
+        );
+    }
+    Ok(revoked)
+}
+
 pub fn build_api() -> Dictionary {
     let mut api = Dictionary::new();
     api.insert("setup", Function::<Option<Dictionary>, ()>::from_fn(setup));
@@ -358,5 +849,25 @@ pub fn build_api() -> Dictionary {
         "show_project_root",
         Function::<(), ()>::from_fn(|()| show_project_root()),
     );
+    api.insert(
+        "list_projects",
+        Function::<(), Array>::from_fn(|()| list_projects()),
+    );
+    api.insert(
+        "forget_project",
+        Function::<String, bool>::from_fn(forget_project),
+    );
+    api.insert(
+        "project_settings",
+        Function::<Option<Buffer>, Object>::from_fn(project_settings),
+    );
+    api.insert(
+        "trust_project_settings",
+        Function::<Option<String>, bool>::from_fn(trust_project_settings),
+    );
+    api.insert(
+        "untrust_project_settings",
+        Function::<Option<String>, bool>::from_fn(untrust_project_settings),
+    );
     api
 }
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use nvimrs_nvim_utils::frecency;
+pub use nvimrs_nvim_utils::frecency::now_secs;
+use nvimrs_nvim_utils::store;
+use nvimrs_nvim_utils::store::StoreError;
+use nvimrs_nvim_utils::store::StoreResult;
+use serde::Deserialize;
+use serde::Serialize;
+
+pub const REGISTRY_VERSION: u32 = 1;
+pub const REGISTRY_FILE: &str = "nvimrs/projects.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectRecord {
+    pub root: String,
+    pub indicator: String,
+    pub rule: String,
+    pub visits: u64,
+    /// Unix timestamp (seconds) of the most recent visit.
+    pub last_visit: u64,
+}
+
+impl ProjectRecord {
+    /// Visit count weighted by how recently the project was used.
+    pub const fn frecency(&self, now: u64) -> u64 {
+        frecency::frecency(self.visits, self.last_visit, now)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RegistryFile {
+    version: u32,
+    projects: Vec<ProjectRecord>,
+}
+
+/// Every project root seen by any instance, keyed by root path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectRegistry {
+    projects: BTreeMap<String, ProjectRecord>,
+}
+
+impl ProjectRegistry {
+    pub fn record_visit(&mut self, root: &str, indicator: &str, rule: &str, now: u64) {
+        let record = self
+            .projects
+            .entry(root.to_string())
+            .or_insert_with(|| ProjectRecord {
+                root: root.to_string(),
+                indicator: String::new(),
+                rule: String::new(),
+                visits: 0,
+                last_visit: 0,
+            });
+        indicator.clone_into(&mut record.indicator);
+        rule.clone_into(&mut record.rule);
+        record.visits = record.visits.saturating_add(1);
+        record.last_visit = record.last_visit.max(now);
+    }
+
+    pub fn forget(&mut self, root: &str) -> bool {
+        self.projects.remove(root).is_some()
+    }
+
+    /// Projects ordered by frecency, then most recent visit, then path.
+    pub fn by_frecency(&self, now: u64) -> Vec<&ProjectRecord> {
+        let mut records: Vec<&ProjectRecord> = self.projects.values().collect();
+        records.sort_by(|left, right| {
+            right
+                .frecency(now)
+                .cmp(&left.frecency(now))
+                .then_with(|| right.last_visit.cmp(&left.last_visit))
+                .then_with(|| left.root.cmp(&right.root))
+        });
+        records
+    }
+
+    pub fn encode(&self) -> String {
+        let file = RegistryFile {
+            version: REGISTRY_VERSION,
+            projects: self.projects.values().cloned().collect(),
+        };
+        serde_json::to_string_pretty(&file).unwrap_or_default()
+    }
+
+    /// Parse a registry file. Unknown versions and malformed content yield
+    /// `None` so a newer or corrupt file is never silently rewritten.
+    pub fn decode(raw: &str) -> Option<Self> {
+        let file: RegistryFile = serde_json::from_str(raw).ok()?;
+        if file.version != REGISTRY_VERSION {
+            return None;
+        }
+        let projects = file
+            .projects
+            .into_iter()
+            .filter(|record| !record.root.is_empty())
+            .map(|record| (record.root.clone(), record))
+            .collect();
+        Some(Self { projects })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingVisit {
+    root: String,
+    indicator: String,
+    rule: String,
+    at: u64,
+}
+
+/// Visits recorded by this instance but not yet written. `BufEnter` only
+/// queues here, so it never waits on the registry lock.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingVisits {
+    visits: Vec<PendingVisit>,
+}
+
+impl PendingVisits {
+    pub fn is_empty(&self) -> bool {
+        self.visits.is_empty()
+    }
+
+    pub fn record(&mut self, root: &str, indicator: &str, rule: &str, now: u64) {
+        self.visits.push(PendingVisit {
+            root: root.to_string(),
+            indicator: indicator.to_string(),
+            rule: rule.to_string(),
+            at: now,
+        });
+    }
+
+    /// Removes the visits queued for `root` and returns them, so a caller
+    /// whose registry write fails can [`restore`](Self::restore) them.
+    pub fn forget(&mut self, root: &str) -> Self {
+        let (forgotten, kept) = std::mem::take(&mut self.visits)
+            .into_iter()
+            .partition(|visit| visit.root == root);
+        self.visits = kept;
+        Self { visits: forgotten }
+    }
+
+    pub fn apply_to(&self, registry: &mut ProjectRegistry) {
+        for visit in &self.visits {
+            registry.record_visit(&visit.root, &visit.indicator, &visit.rule, visit.at);
+        }
+    }
+
+    /// Puts a batch that failed to write back in front of visits queued
+    /// since, so the next flush retries it in order.
+    pub fn restore(&mut self, older: Self) {
+        let newer = std::mem::replace(&mut self.visits, older.visits);
+        self.visits.extend(newer);
+    }
+}
+
+pub fn load(path: &Path) -> StoreResult<ProjectRegistry> {
+    let Some(raw) = store::read_optional(path)? else {
+        return Ok(ProjectRegistry::default());
+    };
+    ProjectRegistry::decode(&raw).ok_or_else(|| StoreError::Incompatible {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Read the registry, apply `update` and write it back through
+/// [`store::locked_update`]. Returns the value produced by `update`; nothing
+/// is written when `update` reports no change.
+pub fn update<T, F>(path: &Path, update: F) -> StoreResult<T>
+where
+    F: FnOnce(&mut ProjectRegistry) -> (T, bool),
+{
+    store::locked_update(path, || {
+        let mut registry = load(path)?;
+        let (value, changed) = update(&mut registry);
+        Ok((value, changed.then(|| registry.encode())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    const NOW: u64 = 1_000 * DAY;
+
+    #[test]
+    fn record_visit_counts_and_keeps_latest_timestamp() {
+        let mut registry = ProjectRegistry::default();
+        registry.record_visit("/repo", ".git", ".git", NOW - DAY);
+        registry.record_visit("/repo", "Cargo.toml", "Cargo.toml", NOW - 2 * DAY);
+        let records = registry.by_frecency(NOW);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].visits, 2);
+        assert_eq!(records[0].last_visit, NOW - DAY);
+        assert_eq!(records[0].indicator, "Cargo.toml");
+    }
+
+    #[test]
+    fn frecency_prefers_recent_over_stale_frequent() {
+        let mut registry = ProjectRegistry::default();
+        for _ in 0..5 {
+            registry.record_visit("/old", ".git", ".git", NOW - 200 * DAY);
+        }
+        registry.record_visit("/new", ".git", ".git", NOW - HOUR);
+        registry.record_visit("/week", ".git", ".git", NOW - 3 * DAY);
+        let roots: Vec<&str> = registry
+            .by_frecency(NOW)
+            .into_iter()
+            .map(|record| record.root.as_str())
+            .collect();
+        assert_eq!(roots, vec!["/new", "/week", "/old"]);
+    }
+
+    #[test]
+    fn forget_removes_only_known_roots() {
+        let mut registry = ProjectRegistry::default();
+        registry.record_visit("/repo", ".git", ".git", NOW);
+        assert!(!registry.forget("/other"));
+        assert!(registry.forget("/repo"));
+        assert!(registry.by_frecency(NOW).is_empty());
+    }
+
+    #[test]
+    fn pending_visits_restore_keeps_failed_batch_first() {
+        let mut failed = PendingVisits::default();
+        failed.record("/a", ".git", ".git", NOW - DAY);
+        let mut pending = PendingVisits::default();
+        pending.record("/b", "Cargo.toml", "Cargo.toml", NOW);
+        pending.record("/a", "Makefile", "Makefile", NOW);
+        pending.restore(failed);
+        assert!(!pending.forget("/b").is_empty());
+        assert!(pending.forget("/b").is_empty());
+
+        let mut registry = ProjectRegistry::default();
+        pending.apply_to(&mut registry);
+        let records = registry.by_frecency(NOW);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].visits, 2);
+        assert_eq!(records[0].indicator, "Makefile");
+        assert_eq!(records[0].last_visit, NOW);
+    }
+
+    #[test]
+    fn pending_visits_forget_returns_what_it_removed() {
+        let mut pending = PendingVisits::default();
+        pending.record("/a", ".git", ".git", NOW - DAY);
+        pending.record("/b", ".git", ".git", NOW - HOUR);
+        pending.record("/a", ".git", ".git", NOW);
+        let forgotten = pending.forget("/a");
+
+        let mut registry = ProjectRegistry::default();
+        pending.apply_to(&mut registry);
+        assert_eq!(registry.by_frecency(NOW).len(), 1);
+
+        pending.restore(forgotten);
+        let mut registry = ProjectRegistry::default();
+        pending.apply_to(&mut registry);
+        let records = registry.by_frecency(NOW);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].root, "/a");
+        assert_eq!(records[0].visits, 2);
+    }
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        let mut registry = ProjectRegistry::default();
+        registry.record_visit("/a", ".git", ".git", NOW);
+        registry.record_visit("/b", "app.cabal", "*.cabal", NOW - DAY);
+        assert_eq!(ProjectRegistry::decode(&registry.encode()), Some(registry));
+    }
+
+    #[test]
+    fn decode_rejects_unknown_versions_and_garbage() {
+        assert_eq!(
+            ProjectRegistry::decode(r#"{"version":99,"projects":[]}"#),
+            None
+        );
+        assert_eq!(ProjectRegistry::decode("not json"), None);
+        assert_eq!(
+            ProjectRegistry::decode(r#"{"version":1,"projects":[]}"#),
+            Some(ProjectRegistry::default())
+        );
+    }
+
+    #[test]
+    fn update_writes_atomically_and_merges_with_disk() -> Result<(), StoreError> {
+        let dir = std::env::temp_dir().join(format!(
+            "nvimrs-project-registry-{}-{}",
+            std::process::id(),
+            now_secs()
+        ));
+        let path = dir.join("projects.json");
+        update(&path, |registry| {
+            registry.record_visit("/a", ".git", ".git", NOW);
+            ((), true)
+        })?;
+        update(&path, |registry| {
+            registry.record_visit("/b", ".git", ".git", NOW);
+            ((), true)
+        })?;
+        let removed = update(&path, |registry| {
+            let removed = registry.forget("/missing");
+            (removed, removed)
+        })?;
+        assert!(!removed);
+        let registry = load(&path)?;
+        assert_eq!(registry.by_frecency(NOW).len(), 2);
+        assert!(!dir.join("projects.json.lock").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+}
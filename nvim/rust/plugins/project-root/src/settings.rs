@@ -0,0 +1,161 @@
+use serde::Deserialize;
+
+pub const DEFAULT_SETTINGS_FILE: &str = ".nvimrs.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SmearSettings {
+    pub filetypes_disabled: Vec<String>,
+}
+
+/// Project-local settings read from the settings file at a project root.
+/// Unknown keys are ignored so newer files still load in older builds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub tab_width: Option<u32>,
+    pub expand_tab: Option<bool>,
+    pub formatter: Option<String>,
+    pub smear: SmearSettings,
+}
+
+impl ProjectSettings {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let mut settings: Self = serde_json::from_str(raw).map_err(|err| err.to_string())?;
+        settings.tab_width = settings.tab_width.filter(|width| *width > 0);
+        settings.formatter = settings.formatter.filter(|name| !name.is_empty());
+        settings
+            .smear
+            .filetypes_disabled
+            .retain(|filetype| !filetype.is_empty());
+        Ok(settings)
+    }
+}
+
+/// Outcome of looking for a settings file at a project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsStatus {
+    Missing,
+    /// The file exists but its content hash is not in the trust database.
+    Untrusted {
+        hash: String,
+    },
+    Invalid {
+        hash: String,
+        message: String,
+    },
+    Trusted {
+        hash: String,
+        settings: ProjectSettings,
+    },
+}
+
+impl SettingsStatus {
+    pub fn hash(&self) -> Option<&str> {
+        match self {
+            Self::Missing => None,
+            Self::Untrusted { hash } | Self::Invalid { hash, .. } | Self::Trusted { hash, .. } => {
+                Some(hash)
+            }
+        }
+    }
+
+    pub const fn settings(&self) -> Option<&ProjectSettings> {
+        match self {
+            Self::Trusted { settings, .. } => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::Untrusted { .. } => "untrusted",
+            Self::Invalid { .. } => "invalid",
+            Self::Trusted { .. } => "trusted",
+        }
+    }
+}
+
+/// Resolve the settings status from the file contents (if any), its hash and
+/// a trust check. Parsing only happens once the hash is trusted.
+pub fn resolve_settings<F>(contents: Option<(&str, String)>, is_trusted: F) -> SettingsStatus
+where
+    F: FnOnce(&str) -> bool,
+{
+    let Some((raw, hash)) = contents else {
+        return SettingsStatus::Missing;
+    };
+    if !is_trusted(&hash) {
+        return SettingsStatus::Untrusted { hash };
+    }
+    match ProjectSettings::parse(raw) {
+        Ok(settings) => SettingsStatus::Trusted { hash, settings },
+        Err(message) => SettingsStatus::Invalid { hash, message },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_known_keys_and_ignores_unknown() -> Result<(), String> {
+        let settings = ProjectSettings::parse(
+            r#"{
+                "tab_width": 2,
+                "expand_tab": true,
+                "formatter": "prettier",
+                "smear": { "filetypes_disabled": ["markdown", ""] },
+                "future_key": 1
+            }"#,
+        )?;
+        assert_eq!(
+            settings,
+            ProjectSettings {
+                tab_width: Some(2),
+                expand_tab: Some(true),
+                formatter: Some("prettier".to_string()),
+                smear: SmearSettings {
+                    filetypes_disabled: vec!["markdown".to_string()],
+                },
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_drops_empty_values_and_rejects_bad_types() -> Result<(), String> {
+        let settings = ProjectSettings::parse(r#"{"tab_width":0,"formatter":""}"#)?;
+        assert_eq!(settings, ProjectSettings::default());
+        assert!(ProjectSettings::parse(r#"{"tab_width":"four"}"#).is_err());
+        assert!(ProjectSettings::parse("not json").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_settings_only_parses_trusted_files() {
+        assert_eq!(resolve_settings(None, |_| true), SettingsStatus::Missing);
+
+        let untrusted = resolve_settings(Some(("{}", "abc".to_string())), |_| false);
+        assert_eq!(
+            untrusted,
+            SettingsStatus::Untrusted {
+                hash: "abc".to_string()
+            }
+        );
+        assert_eq!(untrusted.settings(), None);
+
+        let invalid = resolve_settings(Some(("{", "abc".to_string())), |hash| hash == "abc");
+        assert_eq!(invalid.label(), "invalid");
+
+        let trusted = resolve_settings(Some((r#"{"tab_width":4}"#, "abc".to_string())), |hash| {
+            hash == "abc"
+        });
+        assert_eq!(trusted.hash(), Some("abc"));
+        assert_eq!(
+            trusted.settings().and_then(|settings| settings.tab_width),
+            Some(4)
+        );
+    }
+}
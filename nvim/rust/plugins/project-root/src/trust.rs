@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use nvimrs_nvim_utils::store;
+use nvimrs_nvim_utils::store::StoreError;
+use nvimrs_nvim_utils::store::StoreResult;
+use serde::Deserialize;
+use serde::Serialize;
+
+pub const TRUST_VERSION: u32 = 1;
+pub const TRUST_FILE: &str = "nvimrs/trust.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrustFile {
+    version: u32,
+    /// Settings file path mapped to the sha256 of the trusted content.
+    trusted: BTreeMap<String, String>,
+}
+
+/// Settings files the user has reviewed. Trust is pinned to the content hash,
+/// so any edit to a trusted file has to be trusted again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustDb {
+    trusted: BTreeMap<String, String>,
+}
+
+impl TrustDb {
+    pub fn is_trusted(&self, path: &str, hash: &str) -> bool {
+        self.trusted
+            .get(path)
+            .is_some_and(|trusted| trusted == hash)
+    }
+
+    /// Returns `true` when the stored hash changed.
+    pub fn trust(&mut self, path: &str, hash: &str) -> bool {
+        if self.is_trusted(path, hash) {
+            return false;
+        }
+        self.trusted.insert(path.to_string(), hash.to_string());
+        true
+    }
+
+    pub fn revoke(&mut self, path: &str) -> bool {
+        self.trusted.remove(path).is_some()
+    }
+
+    pub fn encode(&self) -> String {
+        let file = TrustFile {
+            version: TRUST_VERSION,
+            trusted: self.trusted.clone(),
+        };
+        serde_json::to_string_pretty(&file).unwrap_or_default()
+    }
+
+    /// Parse a trust file. Unknown versions and malformed content yield
+    /// `None`, which callers treat as "nothing is trusted".
+    pub fn decode(raw: &str) -> Option<Self> {
+        let file: TrustFile = serde_json::from_str(raw).ok()?;
+        if file.version != TRUST_VERSION {
+            return None;
+        }
+        let trusted = file
+            .trusted
+            .into_iter()
+            .filter(|(path, hash)| !path.is_empty() && !hash.is_empty())
+            .collect();
+        Some(Self { trusted })
+    }
+}
+
+pub fn load(path: &Path) -> StoreResult<TrustDb> {
+    let Some(raw) = store::read_optional(path)? else {
+        return Ok(TrustDb::default());
+    };
+    TrustDb::decode(&raw).ok_or_else(|| StoreError::Incompatible {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Read the trust database, apply `update` and write it back if it changed.
+pub fn update<T, F>(path: &Path, update: F) -> StoreResult<T>
+where
+    F: FnOnce(&mut TrustDb) -> (T, bool),
+{
+    store::locked_update(path, || {
+        let mut db = load(path)?;
+        let (value, changed) = update(&mut db);
+        Ok((value, changed.then(|| db.encode())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trust_is_pinned_to_the_content_hash() {
+        let mut db = TrustDb::default();
+        assert!(!db.is_trusted("/repo/.nvimrs.json", "aaa"));
+        assert!(db.trust("/repo/.nvimrs.json", "aaa"));
+        assert!(!db.trust("/repo/.nvimrs.json", "aaa"));
+        assert!(db.is_trusted("/repo/.nvimrs.json", "aaa"));
+        assert!(!db.is_trusted("/repo/.nvimrs.json", "bbb"));
+        assert!(!db.is_trusted("/other/.nvimrs.json", "aaa"));
+        assert!(db.revoke("/repo/.nvimrs.json"));
+        assert!(!db.revoke("/repo/.nvimrs.json"));
+        assert!(!db.is_trusted("/repo/.nvimrs.json", "aaa"));
+    }
+
+    #[test]
+    fn encode_decode_roundtrip_and_version_check() {
+        let mut db = TrustDb::default();
+        db.trust("/a/.nvimrs.json", "aaa");
+        db.trust("/b/.nvimrs.json", "bbb");
+        assert_eq!(TrustDb::decode(&db.encode()), Some(db));
+        assert_eq!(TrustDb::decode(r#"{"version":2,"trusted":{}}"#), None);
+        assert_eq!(TrustDb::decode("[]"), None);
+    }
+
+    #[test]
+    fn update_persists_changes() -> Result<(), StoreError> {
+        let dir = std::env::temp_dir().join(format!(
+            "nvimrs-project-trust-{}-{}",
+            std::process::id(),
+            crate::registry::now_secs()
+        ));
+        let path = dir.join("trust.json");
+        assert_eq!(load(&path)?, TrustDb::default());
+        let changed = update(&path, |db| {
+            let changed = db.trust("/a/.nvimrs.json", "aaa");
+            (changed, changed)
+        })?;
+        assert!(changed);
+        assert!(load(&path)?.is_trusted("/a/.nvimrs.json", "aaa"));
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+}
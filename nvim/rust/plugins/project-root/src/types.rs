@@ -1,16 +1,39 @@
-use crate::core::RootIndicators;
-use crate::core::default_root_indicators;
+use std::collections::HashMap;
+
+use crate::core::RootRules;
+use crate::registry::PendingVisits;
+use crate::settings::DEFAULT_SETTINGS_FILE;
+use crate::settings::SettingsStatus;
 
 #[derive(Debug)]
 pub struct State {
-    pub root_indicators: RootIndicators,
+    pub root_rules: RootRules,
+    pub registry_enabled: bool,
+    /// Root and time of the last registry write from this instance, used to
+    /// avoid rewriting the registry on every `BufEnter` in the same project.
+    pub last_recorded: Option<(String, u64)>,
+    /// Visits waiting for the scheduled registry write.
+    pub pending_visits: PendingVisits,
+    pub flush_scheduled: bool,
+    pub settings_enabled: bool,
+    pub settings_file: String,
+    /// Settings status per project root, refreshed whenever a buffer moves
+    /// into that root.
+    pub settings: HashMap<String, SettingsStatus>,
     pub did_setup: bool,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
-            root_indicators: default_root_indicators(),
+            root_rules: RootRules::default(),
+            registry_enabled: true,
+            last_recorded: None,
+            pending_visits: PendingVisits::default(),
+            flush_scheduled: false,
+            settings_enabled: true,
+            settings_file: DEFAULT_SETTINGS_FILE.to_string(),
+            settings: HashMap::new(),
             did_setup: false,
         }
     }
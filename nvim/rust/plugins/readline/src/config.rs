@@ -0,0 +1,50 @@
+use crate::core::DEFAULT_KILL_RING_SIZE;
+use crate::core::WordChars;
+use nvim_oxi::Dictionary;
+use nvimrs_nvim_oxi_utils::dict;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadlineConfig {
+    /// Register every kill is mirrored to. `None` leaves Vim registers alone.
+    pub register: Option<char>,
+    pub ring_size: usize,
+    /// Characters counted as part of a word besides letters and digits.
+    pub word_chars: WordChars,
+}
+
+impl Default for ReadlineConfig {
+    fn default() -> Self {
+        Self {
+            register: None,
+            ring_size: DEFAULT_KILL_RING_SIZE,
+            word_chars: WordChars::default(),
+        }
+    }
+}
+
+impl ReadlineConfig {
+    fn parse_register(config: &Dictionary) -> Option<char> {
+        let value = dict::get_string_nonempty(config, "register")?;
+        let mut chars = value.chars();
+        let register = chars.next()?;
+        chars.next().is_none().then_some(register)
+    }
+
+    fn parse_ring_size(config: &Dictionary) -> Option<usize> {
+        let value = dict::get_i64(config, "ring_size")?;
+        usize::try_from(value).ok().filter(|size| *size > 0)
+    }
+
+    pub fn from_dict(config: Option<&Dictionary>) -> Self {
+        let defaults = Self::default();
+        let Some(config) = config else {
+            return defaults;
+        };
+        Self {
+            register: Self::parse_register(config),
+            ring_size: Self::parse_ring_size(config).unwrap_or(defaults.ring_size),
+            word_chars: dict::get_string(config, "word_chars")
+                .map_or(defaults.word_chars, |extra| WordChars::new(&extra)),
+        }
+    }
+}
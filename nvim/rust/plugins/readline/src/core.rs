@@ -1,31 +1,13 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum InsertAction {
-    BeginningOfLine,
-    EndOfLine,
-    ForwardWord,
-    BackwardWord,
-    KillWord,
-}
-
-impl InsertAction {
-    pub(crate) const fn key_sequence(self) -> &'static str {
-        match self {
-            Self::BeginningOfLine => "<C-o>0",
-            Self::EndOfLine => "<C-o>$",
-            Self::ForwardWord => "<C-o>w",
-            Self::BackwardWord => "<C-o>b",
-            Self::KillWord => "<C-o>dw",
-        }
-    }
-}
+use std::collections::VecDeque;
 
+/// A rewritten line and the byte column the cursor should land on.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct TransposeResult {
+pub(crate) struct LineEdit {
     pub(crate) new_line: String,
     pub(crate) new_col: usize,
 }
 
-pub(crate) fn transpose_chars(line: &str, cursor_col: usize) -> Option<TransposeResult> {
+pub(crate) fn transpose_chars(line: &str, cursor_col: usize) -> Option<LineEdit> {
     let mut chars: Vec<char> = line.chars().collect();
     let char_count = chars.len();
 
@@ -37,7 +19,7 @@ pub(crate) fn transpose_chars(line: &str, cursor_col: usize) -> Option<Transpose
     if cursor_col >= byte_len {
         chars.swap(char_count - 2, char_count - 1);
         let new_line: String = chars.into_iter().collect();
-        return Some(TransposeResult {
+        return Some(LineEdit {
             new_line,
             new_col: byte_len,
         });
@@ -63,7 +45,370 @@ pub(crate) fn transpose_chars(line: &str, cursor_col: usize) -> Option<Transpose
         .map(|ch| ch.len_utf8())
         .sum();
     let new_line: String = chars.into_iter().collect();
-    Some(TransposeResult { new_line, new_col })
+    Some(LineEdit { new_line, new_col })
+}
+
+/// Clamp a byte column to the line and move it back onto a char boundary.
+fn clamp_col(line: &str, col: usize) -> usize {
+    let mut col = col.min(line.len());
+    while !line.is_char_boundary(col) {
+        col -= 1;
+    }
+    col
+}
+
+/// Characters that make up a word for motions and word kills. Letters and
+/// digits always count; `extra` adds more (for example `_` to move over
+/// identifiers in one step).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct WordChars {
+    extra: Vec<char>,
+}
+
+impl WordChars {
+    pub(crate) fn new(extra: &str) -> Self {
+        Self {
+            extra: extra.chars().filter(|ch| !ch.is_whitespace()).collect(),
+        }
+    }
+
+    fn contains(&self, ch: char) -> bool {
+        ch.is_alphanumeric() || self.extra.contains(&ch)
+    }
+}
+
+/// Byte column of the end of the next word: skip non-word chars, then word
+/// chars, like Emacs `forward-word`.
+pub(crate) fn forward_word(line: &str, cursor_col: usize, words: &WordChars) -> usize {
+    let col = clamp_col(line, cursor_col);
+    let rest = &line[col..];
+    let word_start = rest
+        .char_indices()
+        .find(|(_, ch)| words.contains(*ch))
+        .map_or(rest.len(), |(idx, _)| idx);
+    let after_start = &rest[word_start..];
+    let word_len = after_start
+        .char_indices()
+        .find(|(_, ch)| !words.contains(*ch))
+        .map_or(after_start.len(), |(idx, _)| idx);
+    col + word_start + word_len
+}
+
+/// Byte column of the start of the previous word, like Emacs `backward-word`.
+pub(crate) fn backward_word(line: &str, cursor_col: usize, words: &WordChars) -> usize {
+    let col = clamp_col(line, cursor_col);
+    let before = &line[..col];
+    let word_end = before
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| words.contains(*ch))
+        .map_or(0, |(idx, ch)| idx + ch.len_utf8());
+    before[..word_end]
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| !words.contains(*ch))
+        .map_or(0, |(idx, ch)| idx + ch.len_utf8())
+}
+
+fn inside_word(line: &str, col: usize, words: &WordChars) -> bool {
+    let before = line[..col].chars().next_back();
+    let after = line[col..].chars().next();
+    matches!((before, after), (Some(before), Some(after)) if words.contains(before) && words.contains(after))
+}
+
+/// `M-t`: swap the words around the cursor and leave the cursor after the
+/// second one. Inside a word, that word is swapped with the next; at the end
+/// of the line, the last two words are swapped.
+pub(crate) fn transpose_words(
+    line: &str,
+    cursor_col: usize,
+    words: &WordChars,
+) -> Option<LineEdit> {
+    let mut col = clamp_col(line, cursor_col);
+    if inside_word(line, col, words) {
+        col = forward_word(line, col, words);
+    }
+    let mut end2 = forward_word(line, col, words);
+    let has_next = line[col..end2].chars().any(|ch| words.contains(ch));
+    if !has_next {
+        end2 = backward_word(line, col, words);
+        end2 = forward_word(line, end2, words);
+        col = backward_word(line, end2, words);
+    }
+    let start2 = backward_word(line, end2, words);
+    let start1 = backward_word(line, col, words);
+    let end1 = forward_word(line, start1, words);
+    if !(start1 < end1 && end1 <= start2 && start2 < end2) {
+        return None;
+    }
+    let mut new_line = String::with_capacity(line.len());
+    new_line.push_str(&line[..start1]);
+    new_line.push_str(&line[start2..end2]);
+    new_line.push_str(&line[end1..start2]);
+    new_line.push_str(&line[start1..end1]);
+    new_line.push_str(&line[end2..]);
+    Some(LineEdit {
+        new_line,
+        new_col: end2,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WordCase {
+    Upcase,
+    Downcase,
+    Capitalize,
+}
+
+/// `M-u`, `M-l`, `M-c`: change the case from the cursor to the end of the
+/// next word and move past it.
+pub(crate) fn case_word(
+    line: &str,
+    cursor_col: usize,
+    words: &WordChars,
+    case: WordCase,
+) -> Option<LineEdit> {
+    let col = clamp_col(line, cursor_col);
+    let end = forward_word(line, col, words);
+    let target = &line[col..end];
+    if !target.chars().any(|ch| words.contains(ch)) {
+        return None;
+    }
+    let changed: String = match case {
+        WordCase::Upcase => target.to_uppercase(),
+        WordCase::Downcase => target.to_lowercase(),
+        WordCase::Capitalize => {
+            let mut seen_word = false;
+            target
+                .chars()
+                .flat_map(|ch| {
+                    let first = !seen_word && words.contains(ch);
+                    seen_word |= words.contains(ch);
+                    let mapped: Vec<char> = if first {
+                        ch.to_uppercase().collect()
+                    } else {
+                        ch.to_lowercase().collect()
+                    };
+                    mapped
+                })
+                .collect()
+        }
+    };
+    let mut new_line = String::with_capacity(line.len() + changed.len() - target.len());
+    new_line.push_str(&line[..col]);
+    new_line.push_str(&changed);
+    let new_col = new_line.len();
+    new_line.push_str(&line[end..]);
+    Some(LineEdit { new_line, new_col })
+}
+
+/// Which side of the cursor a kill removed text from. Consecutive forward
+/// kills append to the newest ring entry; backward kills prepend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KillDirection {
+    Forward,
+    Backward,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KillEdit {
+    pub(crate) new_line: String,
+    pub(crate) new_col: usize,
+    pub(crate) killed: String,
+    pub(crate) direction: KillDirection,
+}
+
+fn kill_range(line: &str, start: usize, end: usize, direction: KillDirection) -> Option<KillEdit> {
+    if start >= end {
+        return None;
+    }
+    let mut new_line = String::with_capacity(line.len() - (end - start));
+    new_line.push_str(&line[..start]);
+    new_line.push_str(&line[end..]);
+    Some(KillEdit {
+        new_line,
+        new_col: start,
+        killed: line[start..end].to_string(),
+        direction,
+    })
+}
+
+/// `C-k`: kill from the cursor to the end of the line.
+pub(crate) fn kill_line(line: &str, cursor_col: usize) -> Option<KillEdit> {
+    let col = clamp_col(line, cursor_col);
+    kill_range(line, col, line.len(), KillDirection::Forward)
+}
+
+/// `C-u`: kill from the start of the line to the cursor.
+pub(crate) fn unix_line_discard(line: &str, cursor_col: usize) -> Option<KillEdit> {
+    let col = clamp_col(line, cursor_col);
+    kill_range(line, 0, col, KillDirection::Backward)
+}
+
+/// `M-d`: kill to the end of the next word.
+pub(crate) fn kill_word(line: &str, cursor_col: usize, words: &WordChars) -> Option<KillEdit> {
+    let col = clamp_col(line, cursor_col);
+    kill_range(
+        line,
+        col,
+        forward_word(line, col, words),
+        KillDirection::Forward,
+    )
+}
+
+/// `M-DEL`: kill back to the start of the previous word.
+pub(crate) fn backward_kill_word(
+    line: &str,
+    cursor_col: usize,
+    words: &WordChars,
+) -> Option<KillEdit> {
+    let col = clamp_col(line, cursor_col);
+    kill_range(
+        line,
+        backward_word(line, col, words),
+        col,
+        KillDirection::Backward,
+    )
+}
+
+/// Where an edit happened. Kills only chain, and `yank_pop` only replaces
+/// the previous yank, while the site and line text are exactly as the
+/// previous command left them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum EditSite {
+    /// `prompt` is the read-only prefix of a `buftype=prompt` line; edits
+    /// only see and rewrite the text after it.
+    Buffer {
+        buf: i64,
+        row: usize,
+        prompt: String,
+    },
+    Cmdline,
+}
+
+/// Byte length of `prompt` when `line` starts with it, otherwise 0.
+pub(crate) fn prompt_prefix_len(line: &str, prompt: &str) -> usize {
+    if line.starts_with(prompt) {
+        prompt.len()
+    } else {
+        0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EditPoint {
+    pub(crate) site: EditSite,
+    pub(crate) line: String,
+    pub(crate) col: usize,
+}
+
+#[derive(Debug, Clone)]
+struct LastYank {
+    after: EditPoint,
+    start: usize,
+    index: usize,
+}
+
+pub(crate) const DEFAULT_KILL_RING_SIZE: usize = 60;
+
+/// Emacs-style kill ring: newest entry first, bounded by `capacity`.
+#[derive(Debug, Clone)]
+pub(crate) struct KillRing {
+    entries: VecDeque<String>,
+    capacity: usize,
+    last_kill: Option<EditPoint>,
+    last_yank: Option<LastYank>,
+}
+
+impl Default for KillRing {
+    fn default() -> Self {
+        Self::new(DEFAULT_KILL_RING_SIZE)
+    }
+}
+
+impl KillRing {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            last_kill: None,
+            last_yank: None,
+        }
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.entries.truncate(self.capacity);
+    }
+
+    pub(crate) fn newest(&self) -> Option<&str> {
+        self.entries.front().map(String::as_str)
+    }
+
+    /// Record `edit`, made at `before`. Returns the newest entry after the
+    /// kill, which includes any text appended from consecutive kills.
+    pub(crate) fn kill(&mut self, before: &EditPoint, edit: &KillEdit) -> Option<&str> {
+        let chained = self.last_kill.as_ref() == Some(before);
+        if let (true, Some(newest)) = (chained, self.entries.front_mut()) {
+            match edit.direction {
+                KillDirection::Forward => newest.push_str(&edit.killed),
+                KillDirection::Backward => newest.insert_str(0, &edit.killed),
+            }
+        } else {
+            self.entries.push_front(edit.killed.clone());
+            self.entries.truncate(self.capacity);
+        }
+        self.last_kill = Some(EditPoint {
+            site: before.site.clone(),
+            line: edit.new_line.clone(),
+            col: edit.new_col,
+        });
+        self.last_yank = None;
+        self.newest()
+    }
+
+    fn insert_entry(
+        &mut self,
+        at: &EditPoint,
+        start: usize,
+        end: usize,
+        index: usize,
+    ) -> Option<LineEdit> {
+        let text = self.entries.get(index)?;
+        let mut new_line = String::with_capacity(at.line.len() + text.len());
+        new_line.push_str(&at.line[..start]);
+        new_line.push_str(text);
+        new_line.push_str(&at.line[end..]);
+        let new_col = start + text.len();
+        self.last_yank = Some(LastYank {
+            after: EditPoint {
+                site: at.site.clone(),
+                line: new_line.clone(),
+                col: new_col,
+            },
+            start,
+            index,
+        });
+        self.last_kill = None;
+        Some(LineEdit { new_line, new_col })
+    }
+
+    /// `C-y`: insert the newest entry at the cursor.
+    pub(crate) fn yank(&mut self, at: &EditPoint) -> Option<LineEdit> {
+        let col = clamp_col(&at.line, at.col);
+        self.insert_entry(at, col, col, 0)
+    }
+
+    /// `M-y`: replace the text inserted by the previous yank with the next
+    /// older entry. Does nothing unless the previous command was a yank.
+    pub(crate) fn yank_pop(&mut self, at: &EditPoint) -> Option<LineEdit> {
+        let last = self.last_yank.take()?;
+        if last.after != *at || self.entries.is_empty() {
+            return None;
+        }
+        let index = (last.index + 1) % self.entries.len();
+        self.insert_entry(at, last.start, at.col, index)
+    }
 }
 
 #[cfg(test)]
@@ -102,4 +447,231 @@ mod tests {
         assert_eq!(result.new_line, "ab💡");
         assert_eq!(result.new_col, "ab💡".len());
     }
+
+    fn point(line: &str, col: usize) -> EditPoint {
+        EditPoint {
+            site: EditSite::Cmdline,
+            line: line.to_string(),
+            col,
+        }
+    }
+
+    fn kill_at(ring: &mut KillRing, line: &str, col: usize, edit: Option<KillEdit>) -> KillEdit {
+        let Some(edit) = edit else {
+            panic!("expected kill");
+        };
+        ring.kill(&point(line, col), &edit);
+        edit
+    }
+
+    #[test]
+    fn kill_line_and_unix_line_discard_split_at_cursor() {
+        let Some(forward) = kill_line("let x = 1;", 4) else {
+            panic!("expected kill");
+        };
+        assert_eq!(forward.new_line, "let ");
+        assert_eq!(forward.killed, "x = 1;");
+        let Some(backward) = unix_line_discard("let x = 1;", 4) else {
+            panic!("expected kill");
+        };
+        assert_eq!(backward.new_line, "x = 1;");
+        assert_eq!(backward.new_col, 0);
+        assert_eq!(backward.killed, "let ");
+        assert_eq!(kill_line("abc", 3), None);
+    }
+
+    #[test]
+    fn word_kills_skip_punctuation_like_emacs() {
+        let Some(forward) = kill_word("foo  bar.baz", 3, &WordChars::default()) else {
+            panic!("expected kill");
+        };
+        assert_eq!(forward.killed, "  bar");
+        let Some(backward) = backward_kill_word("foo bar.  ", 10, &WordChars::default()) else {
+            panic!("expected kill");
+        };
+        assert_eq!(backward.killed, "bar.  ");
+        assert_eq!(backward.new_line, "foo ");
+    }
+
+    #[test]
+    fn word_kills_respect_unicode_boundaries() {
+        let Some(edit) =
+            backward_kill_word("héllo wörld", "héllo wörld".len(), &WordChars::default())
+        else {
+            panic!("expected kill");
+        };
+        assert_eq!(edit.killed, "wörld");
+        let Some(edit) = kill_word("a💡b c", 2, &WordChars::default()) else {
+            panic!("expected kill");
+        };
+        assert_eq!(edit.new_line, "a c");
+    }
+
+    fn edit(result: Option<LineEdit>) -> (String, usize) {
+        let Some(result) = result else {
+            panic!("expected edit");
+        };
+        (result.new_line, result.new_col)
+    }
+
+    #[test]
+    fn word_motions_stop_at_word_edges() {
+        let words = WordChars::default();
+        let line = "  foo.bar  baz";
+        assert_eq!(forward_word(line, 0, &words), 5);
+        assert_eq!(forward_word(line, 5, &words), 9);
+        assert_eq!(forward_word(line, 9, &words), line.len());
+        assert_eq!(forward_word(line, line.len(), &words), line.len());
+        assert_eq!(backward_word(line, line.len(), &words), 11);
+        assert_eq!(backward_word(line, 11, &words), 6);
+        assert_eq!(backward_word(line, 6, &words), 2);
+        assert_eq!(backward_word(line, 2, &words), 0);
+    }
+
+    #[test]
+    fn extra_word_chars_join_identifiers() {
+        let line = "foo_bar-baz qux";
+        assert_eq!(forward_word(line, 0, &WordChars::default()), 3);
+        assert_eq!(forward_word(line, 0, &WordChars::new("_")), 7);
+        assert_eq!(forward_word(line, 0, &WordChars::new("_-")), 11);
+        assert_eq!(backward_word(line, 11, &WordChars::new("_-")), 0);
+    }
+
+    #[test]
+    fn word_motions_clamp_to_char_boundaries() {
+        let words = WordChars::default();
+        let line = "日本 語";
+        assert_eq!(forward_word(line, 1, &words), "日本".len());
+        assert_eq!(forward_word(line, "日本 ".len() + 1, &words), line.len());
+        assert_eq!(backward_word(line, line.len() - 1, &words), 0);
+    }
+
+    #[test]
+    fn transpose_words_between_inside_and_at_end() {
+        let words = WordChars::default();
+        assert_eq!(
+            edit(transpose_words("foo bar", 4, &words)),
+            ("bar foo".to_string(), 7)
+        );
+        assert_eq!(
+            edit(transpose_words("fo|o, bar baz", 1, &WordChars::new("|"))),
+            ("bar, fo|o baz".to_string(), 9)
+        );
+        assert_eq!(
+            edit(transpose_words("one two.", 8, &words)),
+            ("two one.".to_string(), 7)
+        );
+        assert_eq!(transpose_words("single", 3, &words), None);
+    }
+
+    #[test]
+    fn case_word_changes_from_cursor_to_word_end() {
+        let words = WordChars::default();
+        assert_eq!(
+            edit(case_word("hello world", 0, &words, WordCase::Upcase)),
+            ("HELLO world".to_string(), 5)
+        );
+        assert_eq!(
+            edit(case_word("HELLO WORLD", 5, &words, WordCase::Downcase)),
+            ("HELLO world".to_string(), 11)
+        );
+        assert_eq!(
+            edit(case_word("  hELLO x", 0, &words, WordCase::Capitalize)),
+            ("  Hello x".to_string(), 7)
+        );
+        assert_eq!(
+            edit(case_word("straße", 0, &words, WordCase::Upcase)),
+            ("STRASSE".to_string(), 7)
+        );
+        assert_eq!(case_word("abc  ", 3, &words, WordCase::Upcase), None);
+    }
+
+    #[test]
+    fn consecutive_kills_append_and_prepend() {
+        let mut ring = KillRing::default();
+        let first = kill_at(
+            &mut ring,
+            "one two three",
+            3,
+            kill_word("one two three", 3, &WordChars::default()),
+        );
+        let second = kill_word(&first.new_line, first.new_col, &WordChars::default());
+        kill_at(&mut ring, &first.new_line, first.new_col, second);
+        assert_eq!(ring.newest(), Some(" two three"));
+
+        let line = "alpha beta";
+        let first = kill_at(
+            &mut ring,
+            line,
+            10,
+            backward_kill_word(line, 10, &WordChars::default()),
+        );
+        let second = backward_kill_word(&first.new_line, first.new_col, &WordChars::default());
+        kill_at(&mut ring, &first.new_line, first.new_col, second);
+        assert_eq!(ring.newest(), Some("alpha beta"));
+        assert_eq!(ring.entries.len(), 2);
+    }
+
+    #[test]
+    fn kills_at_other_points_start_new_entries() {
+        let mut ring = KillRing::default();
+        kill_at(&mut ring, "abc def", 3, kill_line("abc def", 3));
+        kill_at(&mut ring, "xyz", 1, kill_line("xyz", 1));
+        assert_eq!(ring.newest(), Some("yz"));
+        assert_eq!(ring.entries.len(), 2);
+    }
+
+    #[test]
+    fn yank_pop_cycles_older_entries() {
+        let mut ring = KillRing::default();
+        kill_at(&mut ring, "first", 0, kill_line("first", 0));
+        kill_at(&mut ring, "second", 0, kill_line("second", 0));
+
+        let Some(yanked) = ring.yank(&point("<>", 1)) else {
+            panic!("expected yank");
+        };
+        assert_eq!(yanked.new_line, "<second>");
+        assert_eq!(yanked.new_col, 7);
+
+        let Some(popped) = ring.yank_pop(&point(&yanked.new_line, yanked.new_col)) else {
+            panic!("expected yank_pop");
+        };
+        assert_eq!(popped.new_line, "<first>");
+        let Some(wrapped) = ring.yank_pop(&point(&popped.new_line, popped.new_col)) else {
+            panic!("expected yank_pop");
+        };
+        assert_eq!(wrapped.new_line, "<second>");
+    }
+
+    #[test]
+    fn yank_pop_requires_preceding_yank() {
+        let mut ring = KillRing::default();
+        kill_at(&mut ring, "text", 0, kill_line("text", 0));
+        assert_eq!(ring.yank_pop(&point("", 0)), None);
+        let Some(yanked) = ring.yank(&point("", 0)) else {
+            panic!("expected yank");
+        };
+        assert_eq!(ring.yank_pop(&point(&yanked.new_line, 0)), None);
+    }
+
+    #[test]
+    fn ring_is_bounded_by_capacity() {
+        let mut ring = KillRing::new(2);
+        for text in ["a", "b", "c"] {
+            kill_at(&mut ring, text, 0, kill_line(text, 0));
+        }
+        assert_eq!(
+            ring.entries,
+            VecDeque::from(["c".to_string(), "b".to_string()])
+        );
+        ring.set_capacity(1);
+        assert_eq!(ring.entries.len(), 1);
+    }
+
+    #[test]
+    fn prompt_prefix_len_only_strips_a_leading_prompt() {
+        assert_eq!(prompt_prefix_len("% echo hi", "% "), 2);
+        assert_eq!(prompt_prefix_len("echo hi", "% "), 0);
+        assert_eq!(prompt_prefix_len("anything", ""), 0);
+    }
 }
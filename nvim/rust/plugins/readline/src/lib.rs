@@ -1,92 +1,307 @@
+mod config;
 mod core;
 
-use crate::core::InsertAction;
-use crate::core::transpose_chars;
+use std::sync::LazyLock;
+
+use crate::config::ReadlineConfig;
+use crate::core::EditPoint;
+use crate::core::EditSite;
+use crate::core::KillEdit;
+use crate::core::KillRing;
+use crate::core::LineEdit;
+use crate::core::WordCase;
+use crate::core::WordChars;
+use crate::core::prompt_prefix_len;
+use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Function;
+use nvim_oxi::Object;
 use nvim_oxi::Result;
 use nvim_oxi::String as NvimString;
 use nvim_oxi::api;
+use nvim_oxi::api::Buffer;
+use nvim_oxi::api::opts::OptionOpts;
+use nvimrs_nvim_oxi_utils::notify;
+use nvimrs_nvim_oxi_utils::state::StateCell;
+use nvimrs_nvim_oxi_utils::state::StateGuard;
+use nvimrs_nvim_utils::mode::is_cmdline_mode;
 use nvimrs_nvim_utils::mode::is_insert_like_mode;
 
-fn is_insert_mode() -> bool {
-    let mode = api::get_mode();
-    is_insert_like_mode(&mode.mode.to_string_lossy())
+const LOG_CONTEXT: &str = "readline";
+
+#[derive(Debug, Default)]
+struct State {
+    config: ReadlineConfig,
+    ring: KillRing,
 }
 
-fn feedkeys(keys: &str) {
-    let keys = api::replace_termcodes(keys, true, false, true);
-    let mode = NvimString::from("n");
-    api::feedkeys(keys.as_nvim_str(), &mode, false);
+#[derive(Debug)]
+struct ReadlineContext {
+    state: StateCell<State>,
 }
 
-fn beginning_of_line() {
-    if is_insert_mode() {
-        feedkeys(InsertAction::BeginningOfLine.key_sequence());
+impl ReadlineContext {
+    fn new() -> Self {
+        Self {
+            state: StateCell::new(State::default()),
+        }
+    }
+
+    fn state_lock(&self) -> StateGuard<'_, State> {
+        self.state.lock_recover(|state| {
+            notify::warn(LOG_CONTEXT, "state mutex poisoned; resetting kill ring");
+            *state = State::default();
+        })
     }
 }
 
-fn end_of_line() {
-    if is_insert_mode() {
-        feedkeys(InsertAction::EndOfLine.key_sequence());
+static CONTEXT: LazyLock<ReadlineContext> = LazyLock::new(ReadlineContext::new);
+
+fn context() -> &'static ReadlineContext {
+    &CONTEXT
+}
+
+fn current_mode() -> String {
+    api::get_mode().mode.to_string_lossy().into_owned()
+}
+
+fn buf_option_string(buf: &Buffer, name: &str) -> String {
+    let opts = OptionOpts::builder().buf(buf.clone()).build();
+    api::get_option_value::<NvimString>(name, &opts)
+        .map(|value| value.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The read-only prompt of a `buftype=prompt` buffer, or an empty string for
+/// any other buffer.
+fn buffer_prompt(buf: &Buffer) -> Result<String> {
+    if buf_option_string(buf, "buftype") != "prompt" {
+        return Ok(String::new());
     }
+    let args = Array::from_iter([Object::from(i64::from(buf.handle()))]);
+    let prompt: NvimString = api::call_function("prompt_getprompt", args)?;
+    Ok(prompt.to_string_lossy().into_owned())
 }
 
-fn forward_word() {
-    if is_insert_mode() {
-        feedkeys(InsertAction::ForwardWord.key_sequence());
+/// The line being edited and the cursor byte column, from the current buffer
+/// in insert mode or from `getcmdline()` in the command line. In a prompt
+/// buffer both exclude the prompt text.
+fn current_point() -> Result<Option<EditPoint>> {
+    let mode = current_mode();
+    if is_cmdline_mode(&mode) {
+        let line: NvimString = api::call_function("getcmdline", Array::new())?;
+        let pos: i64 = api::call_function("getcmdpos", Array::new())?;
+        return Ok(Some(EditPoint {
+            site: EditSite::Cmdline,
+            line: line.to_string_lossy().into_owned(),
+            col: usize::try_from(pos.saturating_sub(1)).unwrap_or_default(),
+        }));
+    }
+    if !is_insert_like_mode(&mode) {
+        return Ok(None);
     }
+    let (row, col) = api::get_current_win().get_cursor()?;
+    let buf = api::get_current_buf();
+    let mut line = api::get_current_line()?;
+    let mut prompt = buffer_prompt(&buf)?;
+    let prefix = prompt_prefix_len(&line, &prompt);
+    prompt.truncate(prefix);
+    line.replace_range(..prefix, "");
+    Ok(Some(EditPoint {
+        site: EditSite::Buffer {
+            buf: i64::from(buf.handle()),
+            row,
+            prompt,
+        },
+        line,
+        col: col.saturating_sub(prefix),
+    }))
 }
 
-fn backward_word() {
-    if is_insert_mode() {
-        feedkeys(InsertAction::BackwardWord.key_sequence());
+fn apply_line(site: &EditSite, line: &str, col: usize) -> Result<()> {
+    match site {
+        EditSite::Cmdline => {
+            let pos = i64::try_from(col + 1).unwrap_or(i64::MAX);
+            let args = Array::from_iter([Object::from(line), Object::from(pos)]);
+            let _: i64 = api::call_function("setcmdline", args)?;
+        }
+        EditSite::Buffer { row, prompt, .. } => {
+            api::set_current_line(format!("{prompt}{line}"))?;
+            api::get_current_win().set_cursor(*row, prompt.len() + col)?;
+        }
     }
+    Ok(())
 }
 
-fn kill_word() {
-    if is_insert_mode() {
-        feedkeys(InsertAction::KillWord.key_sequence());
+/// `setcmdpos()` only takes effect inside `c_CTRL-\_e` or `c_CTRL-R_=`, so a
+/// mapping moves the command-line cursor by re-setting the unchanged line.
+fn apply_cursor(point: &EditPoint, col: usize) -> Result<()> {
+    match &point.site {
+        EditSite::Cmdline => apply_line(&point.site, &point.line, col)?,
+        EditSite::Buffer { row, prompt, .. } => {
+            api::get_current_win().set_cursor(*row, prompt.len() + col)?;
+        }
     }
+    Ok(())
+}
+
+fn word_chars() -> WordChars {
+    context().state_lock().config.word_chars.clone()
+}
+
+fn move_with<F>(motion: F) -> Result<()>
+where
+    F: FnOnce(&str, usize, &WordChars) -> usize,
+{
+    let Some(point) = current_point()? else {
+        return Ok(());
+    };
+    let col = motion(&point.line, point.col, &word_chars());
+    if col != point.col {
+        apply_cursor(&point, col)?;
+    }
+    Ok(())
+}
+
+fn edit_with<F>(edit: F) -> Result<()>
+where
+    F: FnOnce(&str, usize, &WordChars) -> Option<LineEdit>,
+{
+    let Some(point) = current_point()? else {
+        return Ok(());
+    };
+    if let Some(result) = edit(&point.line, point.col, &word_chars()) {
+        apply_line(&point.site, &result.new_line, result.new_col)?;
+    }
+    Ok(())
+}
+
+fn set_register(register: char, text: &str) -> Result<()> {
+    let args = Array::from_iter([Object::from(register.to_string()), Object::from(text)]);
+    let _: i64 = api::call_function("setreg", args)?;
+    Ok(())
 }
 
-fn transpose_chars_action() -> Result<()> {
-    if !is_insert_mode() {
+fn kill_with<F>(kill: F) -> Result<()>
+where
+    F: FnOnce(&str, usize, &WordChars) -> Option<KillEdit>,
+{
+    let Some(point) = current_point()? else {
         return Ok(());
+    };
+    let Some(edit) = kill(&point.line, point.col, &word_chars()) else {
+        return Ok(());
+    };
+    apply_line(&point.site, &edit.new_line, edit.new_col)?;
+    let mirrored = {
+        let mut state = context().state_lock();
+        let register = state.config.register;
+        let newest = state.ring.kill(&point, &edit).map(str::to_owned);
+        register.zip(newest)
+    };
+    if let Some((register, text)) = mirrored {
+        set_register(register, &text)?;
     }
-    let mut win = api::get_current_win();
-    let (row, col) = win.get_cursor()?;
-    let line = api::get_current_line()?;
-    if let Some(result) = transpose_chars(&line, col) {
-        api::set_current_line(result.new_line)?;
-        win.set_cursor(row, result.new_col)?;
+    Ok(())
+}
+
+fn yank_with(yank: fn(&mut KillRing, &EditPoint) -> Option<LineEdit>) -> Result<()> {
+    let Some(point) = current_point()? else {
+        return Ok(());
+    };
+    let edit = {
+        let mut state = context().state_lock();
+        yank(&mut state.ring, &point)
+    };
+    if let Some(edit) = edit {
+        apply_line(&point.site, &edit.new_line, edit.new_col)?;
     }
     Ok(())
 }
 
+fn setup(opts: Option<&Dictionary>) {
+    let config = ReadlineConfig::from_dict(opts);
+    let mut state = context().state_lock();
+    state.ring.set_capacity(config.ring_size);
+    state.config = config;
+}
+
 #[nvim_oxi::plugin]
 fn nvimrs_readline() -> Dictionary {
     let mut api = Dictionary::new();
+    api.insert(
+        "setup",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| setup(opts.as_ref())),
+    );
     api.insert(
         "beginning_of_line",
-        Function::<(), ()>::from_fn(|()| beginning_of_line()),
+        Function::<(), ()>::from_fn(|()| move_with(|_, _, _| 0)),
     );
     api.insert(
         "end_of_line",
-        Function::<(), ()>::from_fn(|()| end_of_line()),
+        Function::<(), ()>::from_fn(|()| move_with(|line, _, _| line.len())),
     );
     api.insert(
         "forward_word",
-        Function::<(), ()>::from_fn(|()| forward_word()),
+        Function::<(), ()>::from_fn(|()| move_with(core::forward_word)),
     );
     api.insert(
         "backward_word",
-        Function::<(), ()>::from_fn(|()| backward_word()),
+        Function::<(), ()>::from_fn(|()| move_with(core::backward_word)),
+    );
+    api.insert(
+        "kill_word",
+        Function::<(), ()>::from_fn(|()| kill_with(core::kill_word)),
+    );
+    api.insert(
+        "backward_kill_word",
+        Function::<(), ()>::from_fn(|()| kill_with(core::backward_kill_word)),
+    );
+    api.insert(
+        "kill_line",
+        Function::<(), ()>::from_fn(|()| kill_with(|line, col, _| core::kill_line(line, col))),
+    );
+    api.insert(
+        "unix_line_discard",
+        Function::<(), ()>::from_fn(|()| {
+            kill_with(|line, col, _| core::unix_line_discard(line, col))
+        }),
+    );
+    api.insert(
+        "yank",
+        Function::<(), ()>::from_fn(|()| yank_with(KillRing::yank)),
+    );
+    api.insert(
+        "yank_pop",
+        Function::<(), ()>::from_fn(|()| yank_with(KillRing::yank_pop)),
     );
-    api.insert("kill_word", Function::<(), ()>::from_fn(|()| kill_word()));
     api.insert(
         "transpose_chars",
-        Function::<(), ()>::from_fn(|()| transpose_chars_action()),
+        Function::<(), ()>::from_fn(|()| {
+            edit_with(|line, col, _| core::transpose_chars(line, col))
+        }),
+    );
+    api.insert(
+        "transpose_words",
+        Function::<(), ()>::from_fn(|()| edit_with(core::transpose_words)),
+    );
+    api.insert(
+        "upcase_word",
+        Function::<(), ()>::from_fn(|()| {
+            edit_with(|line, col, words| core::case_word(line, col, words, WordCase::Upcase))
+        }),
+    );
+    api.insert(
+        "downcase_word",
+        Function::<(), ()>::from_fn(|()| {
+            edit_with(|line, col, words| core::case_word(line, col, words, WordCase::Downcase))
+        }),
+    );
+    api.insert(
+        "capitalize_word",
+        Function::<(), ()>::from_fn(|()| {
+            edit_with(|line, col, words| core::case_word(line, col, words, WordCase::Capitalize))
+        }),
     );
     api
 }
@@ -87,10 +87,17 @@ fn parse_nonempty_string(value: String, key: &'static str) -> ParseResult<NonEmp
     })
 }
 
-fn first_img_src(imgs: Option<Object>) -> Option<NonEmptyString> {
-    let imgs = Vec::<RawImage>::deserialize(Deserializer::new(imgs?)).ok()?;
-    let src = imgs.into_iter().next()?.src?;
-    NonEmptyString::try_new(src).ok()
+fn img_srcs(imgs: Option<Object>) -> Vec<NonEmptyString> {
+    let Some(imgs) = imgs else {
+        return Vec::new();
+    };
+    let Ok(imgs) = Vec::<RawImage>::deserialize(Deserializer::new(imgs)) else {
+        return Vec::new();
+    };
+    imgs.into_iter()
+        .filter_map(|img| img.src)
+        .filter_map(|src| NonEmptyString::try_new(src).ok())
+        .collect()
 }
 
 #[derive(Debug, Deserialize)]
@@ -135,12 +142,12 @@ fn decode_doc_find_args(args: &Dictionary) -> ParseResult<DocFindArgs> {
         decode::require_i64(raw.win, "win").map_err(ArgsError::from)?,
         "win",
     )?;
-    let img_src = first_img_src(raw.imgs);
+    let img_srcs = img_srcs(raw.imgs);
     Ok(DocFindArgs {
         buf_handle,
         token,
         win_handle,
-        img_src,
+        img_srcs,
     })
 }
 
@@ -170,7 +177,7 @@ pub struct DocFindArgs {
     pub buf_handle: BufHandle,
     pub token: PreviewToken,
     pub win_handle: WinHandle,
-    pub img_src: Option<NonEmptyString>,
+    pub img_srcs: Vec<NonEmptyString>,
 }
 
 impl DocFindArgs {
@@ -202,17 +209,23 @@ mod tests {
     }
 
     #[test]
-    fn parse_doc_find_with_img_src() {
+    fn parse_doc_find_with_img_srcs() {
         let args = dict([
             ("buf", Object::from(10_i64)),
             ("token", Object::from(1_i64)),
             ("win", Object::from(20_i64)),
             (
                 "imgs",
-                Object::from(Array::from_iter([Object::from(Dictionary::from_iter([(
-                    "src",
-                    Object::from("https://example.test/image.png"),
-                )]))])),
+                Object::from(Array::from_iter([
+                    Object::from(Dictionary::from_iter([(
+                        "src",
+                        Object::from("https://example.test/image.png"),
+                    )])),
+                    Object::from(Dictionary::from_iter([(
+                        "src",
+                        Object::from("assets/second.png"),
+                    )])),
+                ])),
             ),
         ]);
 
@@ -220,8 +233,11 @@ mod tests {
         assert_eq!(parsed.buf_handle.raw(), 10);
         assert_eq!(parsed.win_handle.raw(), 20);
         assert_eq!(parsed.token.raw(), 1);
-        let img = parsed.img_src.expect("expected image source");
-        assert_eq!(img.as_str(), "https://example.test/image.png");
+        let srcs: Vec<&str> = parsed.img_srcs.iter().map(NonEmptyString::as_str).collect();
+        assert_eq!(
+            srcs,
+            vec!["https://example.test/image.png", "assets/second.png"]
+        );
     }
 
     #[test]
@@ -240,7 +256,7 @@ mod tests {
         ]);
 
         let parsed = DocFindArgs::parse(&args).expect("expected valid args");
-        assert!(parsed.img_src.is_none());
+        assert!(parsed.img_srcs.is_empty());
     }
 
     #[test]
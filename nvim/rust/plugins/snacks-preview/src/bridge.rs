@@ -35,13 +35,6 @@ pub fn filetype_for_path(path: &str) -> Result<String> {
     Ok(ft.unwrap_or_default())
 }
 
-pub fn is_doc_preview_filetype(ft: &str) -> bool {
-    matches!(
-        ft,
-        "markdown" | "markdown.mdx" | "mdx" | "typst" | "tex" | "plaintex" | "latex"
-    )
-}
-
 pub fn snacks_has_doc_preview() -> bool {
     let lua = lua::state();
     let Some(snacks) = lua::try_require_table(&lua, "snacks") else {
@@ -94,7 +87,12 @@ pub fn snacks_doc_find(
     let Ok(doc) = image.get::<mlua::Table>("doc") else {
         return Ok(());
     };
-    let Ok(find_visible) = doc.get::<mlua::Function>("find_visible") else {
+    // `find` walks the whole document so the gallery sees every image;
+    // older snacks releases only ship `find_visible`.
+    let Ok(find) = doc
+        .get::<mlua::Function>("find")
+        .or_else(|_| doc.get::<mlua::Function>("find_visible"))
+    else {
         return Ok(());
     };
 
@@ -154,16 +152,83 @@ pub fn snacks_doc_find(
         })
         .map_err(nvim_oxi::Error::from)?;
 
-    find_visible
-        .call::<()>((buf_handle.raw(), callback))
+    find.call::<()>((buf_handle.raw(), callback))
         .map_err(Into::into)
 }
 
-pub fn snacks_open_preview(win_handle: WinHandle, src: &str) -> Result<Option<i64>> {
+/// Runs `callback` on the main loop once `delay_ms` has passed.
+pub fn defer<F>(delay_ms: u64, callback: F) -> Result<()>
+where
+    F: Fn() + 'static,
+{
+    let lua = lua::state();
+    let vim: mlua::Table = lua.globals().get("vim")?;
+    let defer_fn: mlua::Function = vim.get("defer_fn")?;
+    let callback = lua
+        .create_function(move |_, ()| {
+            callback();
+            Ok(())
+        })
+        .map_err(nvim_oxi::Error::from)?;
+    defer_fn
+        .call::<()>((callback, delay_ms))
+        .map_err(Into::into)
+}
+
+/// Starts `argv` asynchronously; `on_done` runs on the main loop with whether
+/// the renderer exited successfully and its stderr. Returns the bridge's
+/// reason when the renderer could not be started.
+pub fn render_diagram<F>(argv: Vec<String>, on_done: F) -> Result<Option<String>>
+where
+    F: Fn(bool, Option<String>) + 'static,
+{
+    let lua = lua::state();
+    let callback = lua
+        .create_function(move |_, (ok, stderr): (bool, Option<String>)| {
+            on_done(ok, stderr);
+            Ok(())
+        })
+        .map_err(nvim_oxi::Error::from)?;
+    let args = lua.create_table()?;
+    args.set("cmd", argv)?;
+    args.set("on_done", callback)?;
+    let (started, start_error) =
+        call_bridge::<_, (bool, Option<String>)>(&lua, "render_diagram", args)?;
+    Ok(if started {
+        None
+    } else {
+        Some(start_error.unwrap_or_else(|| "renderer failed to start".to_string()))
+    })
+}
+
+/// Where the preview float is placed relative to its target window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewAnchor {
+    /// Top-left corner of the window.
+    Window,
+    /// Just below (or above) the cursor line.
+    Cursor,
+}
+
+impl PreviewAnchor {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Window => "win",
+            Self::Cursor => "cursor",
+        }
+    }
+}
+
+pub fn snacks_open_preview(
+    win_handle: WinHandle,
+    src: &str,
+    anchor: PreviewAnchor,
+) -> Result<Option<i64>> {
     let lua = lua::state();
     let args = lua.create_table()?;
     args.set("win", win_handle.raw())?;
     args.set("src", src)?;
+    args.set("anchor", anchor.as_str())?;
     let (cleanup, open_error) = call_bridge::<_, (Option<mlua::Function>, Option<String>)>(
         &lua,
         "snacks_open_preview",
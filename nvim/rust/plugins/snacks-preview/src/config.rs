@@ -0,0 +1,136 @@
+use nvim_oxi::Dictionary;
+use nvim_oxi::conversion::FromObject;
+use nvimrs_nvim_oxi_utils::decode;
+use nvimrs_nvim_oxi_utils::dict;
+
+const DEFAULT_FILETYPES: [&str; 7] = [
+    "markdown",
+    "markdown.mdx",
+    "mdx",
+    "typst",
+    "tex",
+    "plaintex",
+    "latex",
+];
+const DEFAULT_MERMAID_CMD: &str = "mmdc";
+const DEFAULT_GRAPHVIZ_CMD: &str = "dot";
+const DEFAULT_FOLLOW_DEBOUNCE_MS: u64 = 150;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewConfig {
+    pub filetypes: Vec<String>,
+    pub diagrams: bool,
+    pub mermaid_cmd: String,
+    pub graphviz_cmd: String,
+    pub follow_cursor: bool,
+    pub follow_debounce_ms: u64,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            filetypes: DEFAULT_FILETYPES.iter().map(ToString::to_string).collect(),
+            diagrams: true,
+            mermaid_cmd: DEFAULT_MERMAID_CMD.to_string(),
+            graphviz_cmd: DEFAULT_GRAPHVIZ_CMD.to_string(),
+            follow_cursor: false,
+            follow_debounce_ms: DEFAULT_FOLLOW_DEBOUNCE_MS,
+        }
+    }
+}
+
+impl PreviewConfig {
+    /// Reads `setup()` options; absent or malformed keys keep their defaults.
+    pub fn from_opts(opts: Option<&Dictionary>) -> Self {
+        let defaults = Self::default();
+        let Some(opts) = opts else {
+            return defaults;
+        };
+        let filetypes = dict::get_object(opts, "filetypes")
+            .and_then(|value| {
+                decode::parse_from_object::<Vec<String>>(value, "filetypes", "array[string]").ok()
+            })
+            .map(|filetypes| {
+                filetypes
+                    .into_iter()
+                    .filter(|filetype| !filetype.is_empty())
+                    .collect()
+            })
+            .unwrap_or(defaults.filetypes);
+        let diagrams = dict::get_object(opts, "diagrams")
+            .and_then(|value| bool::from_object(value).ok())
+            .unwrap_or(defaults.diagrams);
+        let follow_cursor = dict::get_object(opts, "follow_cursor")
+            .and_then(|value| bool::from_object(value).ok())
+            .unwrap_or(defaults.follow_cursor);
+        let follow_debounce_ms = dict::get_i64(opts, "follow_debounce_ms")
+            .and_then(|value| u64::try_from(value).ok())
+            .unwrap_or(defaults.follow_debounce_ms);
+        Self {
+            filetypes,
+            diagrams,
+            mermaid_cmd: dict::get_string_nonempty(opts, "mermaid_cmd")
+                .unwrap_or(defaults.mermaid_cmd),
+            graphviz_cmd: dict::get_string_nonempty(opts, "graphviz_cmd")
+                .unwrap_or(defaults.graphviz_cmd),
+            follow_cursor,
+            follow_debounce_ms,
+        }
+    }
+
+    pub fn allows_filetype(&self, ft: &str) -> bool {
+        self.filetypes.iter().any(|allowed| allowed == ft)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nvim_oxi::Array;
+    use nvim_oxi::Object;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn missing_opts_use_defaults() {
+        let config = PreviewConfig::from_opts(None);
+
+        assert_eq!(config, PreviewConfig::default());
+        assert!(config.allows_filetype("markdown"));
+        assert!(!config.allows_filetype("org"));
+    }
+
+    #[test]
+    fn opts_override_filetypes_and_renderers() {
+        let opts = Dictionary::from_iter([
+            (
+                "filetypes",
+                Object::from(Array::from_iter([
+                    Object::from("org"),
+                    Object::from(""),
+                    Object::from("markdown"),
+                ])),
+            ),
+            ("diagrams", Object::from(false)),
+            ("mermaid_cmd", Object::from("/opt/bin/mmdc")),
+            ("graphviz_cmd", Object::from("")),
+            ("follow_cursor", Object::from(true)),
+            ("follow_debounce_ms", Object::from(-5_i64)),
+        ]);
+
+        let config = PreviewConfig::from_opts(Some(&opts));
+
+        assert_eq!(
+            config,
+            PreviewConfig {
+                filetypes: vec!["org".to_string(), "markdown".to_string()],
+                diagrams: false,
+                mermaid_cmd: "/opt/bin/mmdc".to_string(),
+                graphviz_cmd: "dot".to_string(),
+                follow_cursor: true,
+                follow_debounce_ms: 150,
+            }
+        );
+        assert!(!config.allows_filetype("typst"));
+    }
+}
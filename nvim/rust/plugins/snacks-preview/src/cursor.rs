@@ -0,0 +1,250 @@
+//! Follow mode: an inline preview of the image reference under the cursor in
+//! Markdown and Org buffers.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use nvim_oxi::Result;
+use nvim_oxi::String as NvimString;
+use nvim_oxi::api;
+use nvim_oxi::api::Window;
+use nvim_oxi::api::opts::CreateAugroupOpts;
+use nvim_oxi::api::opts::CreateAutocmdOpts;
+use nvim_oxi::api::types::AutocmdCallbackArgs;
+use nvimrs_nvim_oxi_utils::guard;
+use nvimrs_nvim_oxi_utils::handles::WinHandle;
+use nvimrs_nvim_oxi_utils::notify;
+
+use crate::LOG_CONTEXT;
+use crate::bridge::PreviewAnchor;
+use crate::bridge::defer;
+use crate::image_ref::DocSyntax;
+use crate::image_ref::ResolveContext;
+use crate::image_ref::ResolvedImage;
+use crate::image_ref::image_ref_at;
+use crate::image_ref::resolve_image;
+use crate::preview::create_preview_cleanup;
+use crate::preview::execute_transition;
+use crate::preview::get_buf_filetype;
+use crate::preview::log_unexpected_command;
+use crate::preview::report_panic;
+use crate::preview::run_preview_cleanup;
+use crate::reducer::CursorTarget;
+use crate::reducer::PreviewCommand;
+use crate::reducer::PreviewEvent;
+use crate::reducer::PreviewToken;
+use crate::reducer::WinKey;
+use crate::state::context;
+use crate::state::win_key;
+
+const FOLLOW_GROUP: &str = "snacks.cursor_preview";
+const PROJECT_ROOT_VAR: &str = "project_root";
+
+fn buf_project_root(buf: &api::Buffer) -> Option<PathBuf> {
+    buf.get_var::<NvimString>(PROJECT_ROOT_VAR)
+        .ok()
+        .map(|root| PathBuf::from(root.to_string_lossy().into_owned()))
+        .filter(|root| !root.as_os_str().is_empty())
+}
+
+fn missing_detail(tried: &[PathBuf]) -> String {
+    if tried.is_empty() {
+        return "no buffer directory or project root to resolve it against".to_string();
+    }
+    let tried = tried
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("looked in {tried}")
+}
+
+fn cursor_target(window: &Window) -> Result<Option<CursorTarget>> {
+    let buf = window.get_buf()?;
+    let Some(syntax) = DocSyntax::for_filetype(&get_buf_filetype(&buf)) else {
+        return Ok(None);
+    };
+    let (row, col) = window.get_cursor()?;
+    let Some(line) = buf
+        .get_lines(row.saturating_sub(1)..row, false)?
+        .next()
+        .map(|line| line.to_string_lossy().into_owned())
+    else {
+        return Ok(None);
+    };
+    let Some(src) = image_ref_at(syntax, &line, col) else {
+        return Ok(None);
+    };
+    let name = buf.get_name()?.to_string_lossy().into_owned();
+    let buf_dir = Path::new(&name).parent().map(Path::to_path_buf);
+    let project_root = buf_project_root(&buf);
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let resolve_context = ResolveContext {
+        buf_dir: buf_dir.as_deref().filter(|dir| !dir.as_os_str().is_empty()),
+        project_root: project_root.as_deref(),
+        home: home.as_deref(),
+    };
+    let target = match resolve_image(src, &resolve_context, Path::is_file) {
+        ResolvedImage::Remote(url) => CursorTarget::Image(url),
+        ResolvedImage::Local(path) => CursorTarget::Image(path.to_string_lossy().into_owned()),
+        ResolvedImage::Missing { tried } => CursorTarget::Missing {
+            src: src.to_string(),
+            detail: missing_detail(&tried),
+        },
+    };
+    Ok(Some(target))
+}
+
+fn show_cursor_image(win: WinKey, token: PreviewToken, src: &str) {
+    let Some(win_handle) = WinHandle::try_from_i64(win.raw()) else {
+        return;
+    };
+    if win_handle.valid_window().is_none() {
+        return;
+    }
+    let Some(cleanup_id) = create_preview_cleanup(win_handle, src, PreviewAnchor::Cursor) else {
+        return;
+    };
+    if !context().is_cursor_token_current(win, token) {
+        run_preview_cleanup(cleanup_id);
+        return;
+    }
+    let transition = context().apply_event(PreviewEvent::CursorCleanupOpened {
+        win,
+        token,
+        cleanup_id,
+    });
+    let command = execute_transition(transition);
+    log_unexpected_command("cursor_cleanup_opened", command.as_ref());
+}
+
+fn on_debounce_elapsed(win: WinKey, token: PreviewToken) {
+    let transition = context().apply_event(PreviewEvent::CursorDebounceElapsed { win, token });
+    match execute_transition(transition) {
+        Some(PreviewCommand::ShowCursorImage { win, token, src }) => {
+            show_cursor_image(win, token, &src);
+        }
+        Some(PreviewCommand::ReportMissingImage { src, detail }) => {
+            notify::warn(LOG_CONTEXT, &format!("image not found: {src} ({detail})"));
+        }
+        other => log_unexpected_command("cursor_debounce_elapsed", other.as_ref()),
+    }
+}
+
+fn apply_cursor_event(win: WinKey, target: Option<CursorTarget>) {
+    let transition = context().apply_event(PreviewEvent::CursorMoved { win, target });
+    match execute_transition(transition) {
+        Some(PreviewCommand::DebounceCursor { win, token }) => {
+            let delay_ms = context().config().follow_debounce_ms;
+            let armed = defer(delay_ms, move || {
+                guard::with_panic(
+                    (),
+                    || on_debounce_elapsed(win, token),
+                    |info| report_panic("cursor_debounce", &info),
+                );
+            });
+            if let Err(err) = armed {
+                notify::warn(LOG_CONTEXT, &format!("arm cursor preview failed: {err}"));
+            }
+        }
+        other => log_unexpected_command("cursor_moved", other.as_ref()),
+    }
+}
+
+fn refresh_current_window() {
+    let window = api::get_current_win();
+    let Some(win) = win_key(WinHandle::from_window(&window)) else {
+        return;
+    };
+    let target = match cursor_target(&window) {
+        Ok(target) => target,
+        Err(err) => {
+            notify::warn(
+                LOG_CONTEXT,
+                &format!("read image under cursor failed: {err}"),
+            );
+            None
+        }
+    };
+    apply_cursor_event(win, target);
+}
+
+fn hide_window(win: Option<WinKey>) {
+    if let Some(win) = win {
+        apply_cursor_event(win, None);
+    }
+}
+
+fn run_follow_autocmd(label: &'static str, callback: impl FnOnce()) -> bool {
+    guard::with_panic((), callback, |info| report_panic(label, &info));
+    false
+}
+
+fn enable_follow() -> Result<()> {
+    let group = api::create_augroup(
+        FOLLOW_GROUP,
+        &CreateAugroupOpts::builder().clear(true).build(),
+    )?;
+    let refresh_opts = CreateAutocmdOpts::builder()
+        .group(group)
+        .callback(|_args: AutocmdCallbackArgs| {
+            run_follow_autocmd("cursor_follow_refresh", refresh_current_window)
+        })
+        .build();
+    api::create_autocmd(
+        ["CursorMoved", "BufEnter", "WinEnter", "InsertLeave"],
+        &refresh_opts,
+    )?;
+
+    let hide_opts = CreateAutocmdOpts::builder()
+        .group(group)
+        .callback(|_args: AutocmdCallbackArgs| {
+            run_follow_autocmd("cursor_follow_hide", || {
+                hide_window(win_key(WinHandle::from_window(&api::get_current_win())));
+            })
+        })
+        .build();
+    api::create_autocmd(["InsertEnter", "WinLeave"], &hide_opts)?;
+
+    let closed_opts = CreateAutocmdOpts::builder()
+        .group(group)
+        .callback(|args: AutocmdCallbackArgs| {
+            run_follow_autocmd("cursor_follow_closed", || {
+                let win = args.r#match.parse::<i64>().ok().and_then(WinKey::try_new);
+                hide_window(win);
+            })
+        })
+        .build();
+    api::create_autocmd(["WinClosed"], &closed_opts)?;
+
+    refresh_current_window();
+    Ok(())
+}
+
+fn disable_follow() {
+    if let Err(err) = api::del_augroup_by_name(FOLLOW_GROUP) {
+        notify::warn(LOG_CONTEXT, &format!("delete follow augroup failed: {err}"));
+    }
+    let transition = context().apply_event(PreviewEvent::CursorStopAll);
+    let command = execute_transition(transition);
+    log_unexpected_command("cursor_stop_all", command.as_ref());
+}
+
+/// Turns follow mode on or off; a no-op when it is already in that state.
+pub fn set_follow_cursor(enabled: bool) {
+    if context().set_follow_cursor(enabled) == enabled {
+        return;
+    }
+    if enabled {
+        if let Err(err) = enable_follow() {
+            notify::warn(LOG_CONTEXT, &format!("enable follow mode failed: {err}"));
+            let _ = context().set_follow_cursor(false);
+        }
+    } else {
+        disable_follow();
+    }
+}
+
+pub fn toggle_follow_cursor() {
+    set_follow_cursor(!context().follow_cursor());
+}
@@ -0,0 +1,316 @@
+//! Diagram code blocks (Mermaid, Graphviz) that can be rendered to images by
+//! a local CLI. Everything here is pure; the host decides where files live and
+//! how the renderer is spawned.
+
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramKind {
+    Mermaid,
+    Graphviz,
+}
+
+impl DiagramKind {
+    /// Classifies a fence info string such as `mermaid` or `dot {#fig}`.
+    pub fn from_info(info: &str) -> Option<Self> {
+        let lang = info
+            .trim()
+            .trim_start_matches('{')
+            .trim_start_matches('.')
+            .split(|ch: char| ch.is_whitespace() || matches!(ch, '{' | '}' | ','))
+            .next()?;
+        match lang.to_ascii_lowercase().as_str() {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Graphviz),
+            _ => None,
+        }
+    }
+
+    pub const fn source_extension(self) -> &'static str {
+        match self {
+            Self::Mermaid => "mmd",
+            Self::Graphviz => "dot",
+        }
+    }
+
+    /// Command line that renders `input` to a PNG at `output`.
+    pub fn render_command(self, program: &str, input: &Path, output: &Path) -> Vec<String> {
+        let input = input.to_string_lossy().into_owned();
+        let output = output.to_string_lossy().into_owned();
+        match self {
+            Self::Mermaid => vec![
+                program.to_string(),
+                "-i".to_string(),
+                input,
+                "-o".to_string(),
+                output,
+            ],
+            Self::Graphviz => vec![
+                program.to_string(),
+                "-Tpng".to_string(),
+                input,
+                "-o".to_string(),
+                output,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramBlock {
+    pub kind: DiagramKind,
+    pub source: String,
+}
+
+impl DiagramBlock {
+    /// Cache file stem; identical sources share one rendered image.
+    pub fn cache_stem(&self) -> String {
+        let mut hash = FNV_OFFSET;
+        hash = fnv1a(hash, self.kind.source_extension().as_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, self.source.as_bytes());
+        format!("{hash:016x}")
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+struct OpenFence {
+    marker: char,
+    len: usize,
+    kind: Option<DiagramKind>,
+    body: Vec<String>,
+}
+
+fn fence_marker(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let marker = trimmed
+        .chars()
+        .next()
+        .filter(|ch| matches!(ch, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|ch| *ch == marker).count();
+    (len >= 3).then_some((marker, len, &trimmed[len..]))
+}
+
+fn org_src_lang(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    let prefix = trimmed.get(..12)?;
+    if !prefix.eq_ignore_ascii_case("#+begin_src ") {
+        return None;
+    }
+    Some(&trimmed[12..])
+}
+
+fn is_org_src_end(line: &str) -> bool {
+    line.trim().eq_ignore_ascii_case("#+end_src")
+}
+
+/// Diagram blocks in document order. Recognises Markdown backtick and tilde
+/// fences plus Org `#+begin_src` blocks; unterminated blocks are ignored.
+pub fn extract_diagram_blocks<S: AsRef<str>>(lines: &[S]) -> Vec<DiagramBlock> {
+    let mut blocks = Vec::new();
+    let mut fence: Option<OpenFence> = None;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(open) = fence.as_mut() {
+            let closes = if open.marker == '#' {
+                is_org_src_end(line)
+            } else {
+                fence_marker(line).is_some_and(|(marker, len, rest)| {
+                    marker == open.marker && len >= open.len && rest.trim().is_empty()
+                })
+            };
+            if !closes {
+                open.body.push(line.to_string());
+                continue;
+            }
+            if let Some(open) = fence.take()
+                && let Some(kind) = open.kind
+            {
+                let source = open.body.join("\n");
+                if !source.trim().is_empty() {
+                    blocks.push(DiagramBlock { kind, source });
+                }
+            }
+            continue;
+        }
+        if let Some((marker, len, info)) = fence_marker(line) {
+            if marker == '`' && info.contains('`') {
+                continue;
+            }
+            fence = Some(OpenFence {
+                marker,
+                len,
+                kind: DiagramKind::from_info(info),
+                body: Vec::new(),
+            });
+        } else if let Some(lang) = org_src_lang(line) {
+            fence = Some(OpenFence {
+                marker: '#',
+                len: 0,
+                kind: DiagramKind::from_info(lang),
+                body: Vec::new(),
+            });
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn extracts_mermaid_and_graphviz_blocks_in_order() {
+        let lines = [
+            "# Title",
+            "```mermaid",
+            "graph TD",
+            "  A --> B",
+            "```",
+            "text",
+            "~~~ dot",
+            "digraph { a -> b }",
+            "~~~",
+        ];
+
+        let blocks = extract_diagram_blocks(&lines);
+
+        assert_eq!(
+            blocks,
+            vec![
+                DiagramBlock {
+                    kind: DiagramKind::Mermaid,
+                    source: "graph TD\n  A --> B".to_string(),
+                },
+                DiagramBlock {
+                    kind: DiagramKind::Graphviz,
+                    source: "digraph { a -> b }".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_other_languages_and_nested_fences() {
+        let lines = [
+            "````markdown",
+            "```mermaid",
+            "graph TD",
+            "```",
+            "````",
+            "```rust",
+            "fn main() {}",
+            "```",
+        ];
+
+        assert!(extract_diagram_blocks(&lines).is_empty());
+    }
+
+    #[test]
+    fn closing_fence_must_match_marker_and_length() {
+        let lines = ["````mermaid", "graph TD", "```", "~~~~", "````"];
+
+        let blocks = extract_diagram_blocks(&lines);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].source, "graph TD\n```\n~~~~");
+    }
+
+    #[test]
+    fn ignores_unterminated_and_empty_blocks() {
+        let empty = ["```mermaid", "   ", "```"];
+        let unterminated = ["```dot", "digraph {}"];
+
+        assert!(extract_diagram_blocks(&empty).is_empty());
+        assert!(extract_diagram_blocks(&unterminated).is_empty());
+    }
+
+    #[test]
+    fn extracts_org_src_blocks() {
+        let lines = [
+            "#+BEGIN_SRC graphviz :file out.png",
+            "digraph {}",
+            "#+END_SRC",
+        ];
+
+        let blocks = extract_diagram_blocks(&lines);
+
+        assert_eq!(
+            blocks,
+            vec![DiagramBlock {
+                kind: DiagramKind::Graphviz,
+                source: "digraph {}".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn info_string_accepts_attribute_forms() {
+        assert_eq!(
+            DiagramKind::from_info("{.mermaid}"),
+            Some(DiagramKind::Mermaid)
+        );
+        assert_eq!(
+            DiagramKind::from_info(" Dot {#fig}"),
+            Some(DiagramKind::Graphviz)
+        );
+        assert_eq!(DiagramKind::from_info(""), None);
+        assert_eq!(DiagramKind::from_info("python"), None);
+    }
+
+    #[test]
+    fn cache_stem_depends_on_kind_and_source() {
+        let mermaid = DiagramBlock {
+            kind: DiagramKind::Mermaid,
+            source: "graph TD".to_string(),
+        };
+        let graphviz = DiagramBlock {
+            kind: DiagramKind::Graphviz,
+            ..mermaid.clone()
+        };
+        let edited = DiagramBlock {
+            kind: DiagramKind::Mermaid,
+            source: "graph LR".to_string(),
+        };
+
+        let same = DiagramBlock {
+            kind: DiagramKind::Mermaid,
+            source: "graph TD".to_string(),
+        };
+
+        assert_eq!(mermaid.cache_stem(), same.cache_stem());
+        assert_eq!(mermaid.cache_stem().len(), 16);
+        assert_ne!(mermaid.cache_stem(), graphviz.cache_stem());
+        assert_ne!(mermaid.cache_stem(), edited.cache_stem());
+    }
+
+    #[test]
+    fn render_commands_write_png_output() {
+        let input = Path::new("/cache/a.mmd");
+        let output = Path::new("/cache/a.png");
+
+        assert_eq!(
+            DiagramKind::Mermaid.render_command("mmdc", input, output),
+            vec!["mmdc", "-i", "/cache/a.mmd", "-o", "/cache/a.png"]
+        );
+        assert_eq!(
+            DiagramKind::Graphviz.render_command("dot", input, output),
+            vec!["dot", "-Tpng", "/cache/a.mmd", "-o", "/cache/a.png"]
+        );
+    }
+}
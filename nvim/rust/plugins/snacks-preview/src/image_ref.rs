@@ -0,0 +1,327 @@
+//! Image references under the cursor in Markdown and Org buffers, and how
+//! they resolve to files.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocSyntax {
+    Markdown,
+    Org,
+}
+
+impl DocSyntax {
+    pub fn for_filetype(ft: &str) -> Option<Self> {
+        match ft {
+            "markdown" | "markdown.mdx" | "mdx" => Some(Self::Markdown),
+            "org" => Some(Self::Org),
+            _ => None,
+        }
+    }
+}
+
+/// Byte span `[start, end)` of a reference within its line plus its target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImageRef<'a> {
+    start: usize,
+    end: usize,
+    src: &'a str,
+}
+
+fn markdown_destination(inner: &str) -> &str {
+    let inner = inner.trim();
+    if let Some(rest) = inner.strip_prefix('<') {
+        return rest.split('>').next().unwrap_or_default();
+    }
+    inner.split_whitespace().next().unwrap_or_default()
+}
+
+fn markdown_refs(line: &str) -> Vec<ImageRef<'_>> {
+    let mut refs = Vec::new();
+    let mut search = 0;
+    while let Some(offset) = line[search..].find("![") {
+        let start = search + offset;
+        search = start + 2;
+        let Some(label_len) = line[search..].find("](") else {
+            break;
+        };
+        let open = search + label_len + 2;
+        let mut depth = 0_usize;
+        let mut close = None;
+        for (idx, ch) in line[open..].char_indices() {
+            match ch {
+                '(' => depth += 1,
+                ')' if depth == 0 => {
+                    close = Some(open + idx);
+                    break;
+                }
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        let Some(close) = close else {
+            break;
+        };
+        let src = markdown_destination(&line[open..close]);
+        if !src.is_empty() {
+            refs.push(ImageRef {
+                start,
+                end: close + 1,
+                src,
+            });
+        }
+        search = close + 1;
+    }
+    refs
+}
+
+fn has_image_extension(src: &str) -> bool {
+    Path::new(src)
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|ext| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+}
+
+fn org_refs(line: &str) -> Vec<ImageRef<'_>> {
+    let mut refs = Vec::new();
+    let mut search = 0;
+    while let Some(offset) = line[search..].find("[[") {
+        let start = search + offset;
+        let link_start = start + 2;
+        let Some(len) = line[link_start..].find("]]") else {
+            break;
+        };
+        let end = link_start + len + 2;
+        let link = line[link_start..link_start + len]
+            .split("][")
+            .next()
+            .unwrap_or_default();
+        let src = link
+            .strip_prefix("file:")
+            .or_else(|| link.strip_prefix("attachment:"))
+            .unwrap_or(link);
+        if has_image_extension(src) {
+            refs.push(ImageRef { start, end, src });
+        }
+        search = end;
+    }
+    refs
+}
+
+/// Target of the image reference covering byte column `col` of `line`.
+pub fn image_ref_at(syntax: DocSyntax, line: &str, col: usize) -> Option<&str> {
+    let refs = match syntax {
+        DocSyntax::Markdown => markdown_refs(line),
+        DocSyntax::Org => org_refs(line),
+    };
+    refs.into_iter()
+        .find(|image| (image.start..image.end).contains(&col))
+        .map(|image| image.src)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedImage {
+    Remote(String),
+    Local(PathBuf),
+    Missing { tried: Vec<PathBuf> },
+}
+
+pub struct ResolveContext<'a> {
+    pub buf_dir: Option<&'a Path>,
+    pub project_root: Option<&'a Path>,
+    pub home: Option<&'a Path>,
+}
+
+fn percent_decode(src: &str) -> String {
+    let bytes = src.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%'
+            && let Some(hex) = src.get(idx + 1..idx + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(byte);
+            idx += 3;
+            continue;
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| src.to_string())
+}
+
+fn push_candidate(candidates: &mut Vec<PathBuf>, path: PathBuf) {
+    if !candidates.contains(&path) {
+        candidates.push(path);
+    }
+}
+
+/// Resolves `src` to a remote URL or an existing local file. Relative paths
+/// are tried against the buffer directory first, then the project root; a
+/// leading `/` that does not exist on disk is read as project-root relative.
+pub fn resolve_image(
+    src: &str,
+    context: &ResolveContext<'_>,
+    exists: impl Fn(&Path) -> bool,
+) -> ResolvedImage {
+    if src.starts_with("http://") || src.starts_with("https://") || src.starts_with("data:") {
+        return ResolvedImage::Remote(src.to_string());
+    }
+    let src = src.strip_prefix("file://").unwrap_or(src);
+    let decoded = percent_decode(src);
+    let mut candidates = Vec::new();
+    if let Some(rest) = decoded.strip_prefix("~/") {
+        if let Some(home) = context.home {
+            push_candidate(&mut candidates, home.join(rest));
+        }
+    } else if Path::new(&decoded).is_absolute() {
+        push_candidate(&mut candidates, PathBuf::from(&decoded));
+        if let Some(root) = context.project_root {
+            push_candidate(&mut candidates, root.join(decoded.trim_start_matches('/')));
+        }
+    } else {
+        for base in [context.buf_dir, context.project_root]
+            .into_iter()
+            .flatten()
+        {
+            push_candidate(&mut candidates, base.join(&decoded));
+        }
+    }
+    match candidates.iter().find(|path| exists(path)) {
+        Some(path) => ResolvedImage::Local(path.clone()),
+        None => ResolvedImage::Missing { tried: candidates },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn markdown_ref_covers_whole_link() {
+        let line = "See ![diagram](img/a.png \"Title\") and ![](b.png).";
+
+        assert_eq!(
+            image_ref_at(DocSyntax::Markdown, line, 4),
+            Some("img/a.png")
+        );
+        assert_eq!(
+            image_ref_at(DocSyntax::Markdown, line, 32),
+            Some("img/a.png")
+        );
+        assert_eq!(image_ref_at(DocSyntax::Markdown, line, 33), None);
+        assert_eq!(image_ref_at(DocSyntax::Markdown, line, 40), Some("b.png"));
+    }
+
+    #[test]
+    fn markdown_ref_handles_angle_brackets_and_parens() {
+        let angle = "![x](<my image.png>)";
+        let parens = "![x](shot_(1).png)";
+
+        assert_eq!(
+            image_ref_at(DocSyntax::Markdown, angle, 0),
+            Some("my image.png")
+        );
+        assert_eq!(
+            image_ref_at(DocSyntax::Markdown, parens, 8),
+            Some("shot_(1).png")
+        );
+    }
+
+    #[test]
+    fn markdown_ignores_plain_links_and_unterminated_refs() {
+        assert_eq!(image_ref_at(DocSyntax::Markdown, "[a](b.png)", 1), None);
+        assert_eq!(image_ref_at(DocSyntax::Markdown, "![a](b.png", 1), None);
+        assert_eq!(image_ref_at(DocSyntax::Markdown, "![a]()", 1), None);
+    }
+
+    #[test]
+    fn org_ref_accepts_image_file_links_only() {
+        let line = "[[file:img/a.png]] [[https://example.test][site]] [[./b.JPG][shot]]";
+
+        assert_eq!(image_ref_at(DocSyntax::Org, line, 3), Some("img/a.png"));
+        assert_eq!(image_ref_at(DocSyntax::Org, line, 25), None);
+        assert_eq!(image_ref_at(DocSyntax::Org, line, 55), Some("./b.JPG"));
+    }
+
+    #[test]
+    fn resolve_prefers_buffer_dir_then_project_root() {
+        let context = ResolveContext {
+            buf_dir: Some(Path::new("/proj/docs")),
+            project_root: Some(Path::new("/proj")),
+            home: None,
+        };
+
+        let in_root = resolve_image("assets/a.png", &context, |path| {
+            path == Path::new("/proj/assets/a.png")
+        });
+        let in_both = resolve_image("a.png", &context, |_| true);
+
+        assert_eq!(
+            in_root,
+            ResolvedImage::Local(PathBuf::from("/proj/assets/a.png"))
+        );
+        assert_eq!(
+            in_both,
+            ResolvedImage::Local(PathBuf::from("/proj/docs/a.png"))
+        );
+    }
+
+    #[test]
+    fn resolve_reports_every_location_tried() {
+        let context = ResolveContext {
+            buf_dir: Some(Path::new("/proj/docs")),
+            project_root: Some(Path::new("/proj")),
+            home: None,
+        };
+
+        let missing = resolve_image("my%20shot.png", &context, |_| false);
+
+        assert_eq!(
+            missing,
+            ResolvedImage::Missing {
+                tried: vec![
+                    PathBuf::from("/proj/docs/my shot.png"),
+                    PathBuf::from("/proj/my shot.png"),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_root_relative_and_home_paths() {
+        let context = ResolveContext {
+            buf_dir: Some(Path::new("/proj/docs")),
+            project_root: Some(Path::new("/proj")),
+            home: Some(Path::new("/home/me")),
+        };
+
+        let rooted = resolve_image("/static/a.png", &context, |path| {
+            path == Path::new("/proj/static/a.png")
+        });
+        let home = resolve_image("~/pics/a.png", &context, |_| true);
+        let remote = resolve_image("https://example.test/a.png", &context, |_| false);
+
+        assert_eq!(
+            rooted,
+            ResolvedImage::Local(PathBuf::from("/proj/static/a.png"))
+        );
+        assert_eq!(
+            home,
+            ResolvedImage::Local(PathBuf::from("/home/me/pics/a.png"))
+        );
+        assert_eq!(
+            remote,
+            ResolvedImage::Remote("https://example.test/a.png".to_string())
+        );
+    }
+}
@@ -1,17 +1,26 @@
 mod args;
 mod bridge;
+mod config;
+mod cursor;
+mod diagram;
+mod image_ref;
 mod preview;
 mod reducer;
 mod state;
 
 use nvim_oxi::Dictionary;
 use nvim_oxi::Function;
+use reducer::GalleryStep;
 
 const LOG_CONTEXT: &str = "nvimrs_snacks_preview";
 
 #[nvim_oxi::plugin]
 fn nvimrs_snacks_preview() -> Dictionary {
     let mut api = Dictionary::new();
+    api.insert(
+        "setup",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| preview::setup_lua(opts.as_ref())),
+    );
     api.insert(
         "on_doc_find",
         Function::<Dictionary, ()>::from_fn(|args| preview::on_doc_find(&args)),
@@ -28,6 +37,26 @@ fn nvimrs_snacks_preview() -> Dictionary {
         "close_doc_preview_for_window",
         Function::<i64, ()>::from_fn(preview::close_doc_preview_for_window_lua),
     );
+    api.insert(
+        "gallery_next",
+        Function::<Option<i64>, ()>::from_fn(|win| {
+            preview::gallery_step_lua(win, GalleryStep::Next);
+        }),
+    );
+    api.insert(
+        "gallery_prev",
+        Function::<Option<i64>, ()>::from_fn(|win| {
+            preview::gallery_step_lua(win, GalleryStep::Prev);
+        }),
+    );
+    api.insert(
+        "follow_cursor",
+        Function::<bool, ()>::from_fn(cursor::set_follow_cursor),
+    );
+    api.insert(
+        "stats",
+        Function::<(), Dictionary>::from_fn(|()| preview::stats_lua()),
+    );
     api.insert(
         "reset_state",
         Function::<(), ()>::from_fn(|()| preview::reset_state_lua()),
@@ -1,5 +1,8 @@
+use std::fs;
 use std::path::Path;
+use std::path::PathBuf;
 
+use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Result;
 use nvim_oxi::String as NvimString;
@@ -7,39 +10,54 @@ use nvim_oxi::api;
 use nvim_oxi::api::Buffer;
 use nvim_oxi::api::opts::CreateAugroupOpts;
 use nvim_oxi::api::opts::CreateAutocmdOpts;
+use nvim_oxi::api::opts::CreateCommandOpts;
 use nvim_oxi::api::opts::OptionOpts;
 use nvim_oxi::api::types::AutocmdCallbackArgs;
+use nvim_oxi::api::types::CommandArgs;
 use nvim_oxi::schedule;
 use nvimrs_nvim_oxi_utils::guard;
 use nvimrs_nvim_oxi_utils::handles::BufHandle;
 use nvimrs_nvim_oxi_utils::handles::WinHandle;
 use nvimrs_nvim_oxi_utils::notify;
+use nvimrs_support::NonEmptyString;
 
 use crate::LOG_CONTEXT;
 use crate::args::AttachDocPreviewArgs;
 use crate::args::DocFindArgs;
+use crate::bridge::PreviewAnchor;
 use crate::bridge::filetype_for_path;
-use crate::bridge::is_doc_preview_filetype;
+use crate::bridge::render_diagram;
 use crate::bridge::reset_preview_state;
 use crate::bridge::snacks_close_preview;
 use crate::bridge::snacks_doc_find;
 use crate::bridge::snacks_has_doc_preview;
 use crate::bridge::snacks_open_preview;
+use crate::config::PreviewConfig;
+use crate::cursor::set_follow_cursor;
+use crate::cursor::toggle_follow_cursor;
+use crate::diagram::DiagramBlock;
+use crate::diagram::DiagramKind;
+use crate::diagram::extract_diagram_blocks;
+use crate::reducer::BufKey;
+use crate::reducer::GalleryStep;
 use crate::reducer::PreviewCommand;
 use crate::reducer::PreviewEffect;
 use crate::reducer::PreviewEvent;
 use crate::reducer::PreviewToken;
 use crate::reducer::PreviewTransition;
 use crate::reducer::RestoreNamePlan;
+use crate::reducer::WinKey;
 use crate::state::buf_key;
 use crate::state::context;
 use crate::state::win_key;
 
-fn report_panic(label: &str, info: &guard::PanicInfo) {
+const DIAGRAM_CACHE_DIR: &str = "nvimrs/diagrams";
+
+pub fn report_panic(label: &str, info: &guard::PanicInfo) {
     notify::error(LOG_CONTEXT, &format!("{label} panic: {}", info.render()));
 }
 
-fn get_buf_filetype(buf: &Buffer) -> String {
+pub fn get_buf_filetype(buf: &Buffer) -> String {
     let opt_opts = OptionOpts::builder().buf(buf.clone()).build();
     match api::get_option_value::<NvimString>("filetype", &opt_opts) {
         Ok(value) => value.to_string_lossy().into_owned(),
@@ -75,7 +93,7 @@ fn restore_doc_preview_name(buf_handle: BufHandle, plan: &RestoreNamePlan) {
     }
 }
 
-fn run_preview_cleanup(cleanup_id: i64) {
+pub fn run_preview_cleanup(cleanup_id: i64) {
     if let Err(err) = snacks_close_preview(cleanup_id) {
         notify::warn(LOG_CONTEXT, &format!("preview cleanup failed: {err}"));
     }
@@ -104,12 +122,12 @@ fn execute_effects(effects: Vec<PreviewEffect>) {
     }
 }
 
-fn execute_transition(transition: PreviewTransition) -> Option<PreviewCommand> {
+pub fn execute_transition(transition: PreviewTransition) -> Option<PreviewCommand> {
     execute_effects(transition.effects);
     transition.command
 }
 
-fn log_unexpected_command(context: &str, command: Option<&PreviewCommand>) {
+pub fn log_unexpected_command(context: &str, command: Option<&PreviewCommand>) {
     if command.is_some() {
         notify::warn(
             LOG_CONTEXT,
@@ -118,6 +136,18 @@ fn log_unexpected_command(context: &str, command: Option<&PreviewCommand>) {
     }
 }
 
+fn execute_show_command(context: &str, command: Option<PreviewCommand>) {
+    match command {
+        Some(PreviewCommand::ShowImage {
+            key,
+            win,
+            token,
+            src,
+        }) => schedule_show_image(key, win, token, src),
+        other => log_unexpected_command(context, other.as_ref()),
+    }
+}
+
 fn close_doc_preview(buf_handle: BufHandle) -> bool {
     let Some(key) = buf_key(buf_handle) else {
         return false;
@@ -186,7 +216,7 @@ fn attach_doc_preview(buf_handle: BufHandle, path: &str, win_handle: WinHandle)
     };
 
     let ft = filetype_for_path(path)?;
-    if !is_doc_preview_filetype(&ft) {
+    if !context().allows_filetype(&ft) {
         return Ok(());
     }
 
@@ -284,8 +314,12 @@ fn attach_doc_preview(buf_handle: BufHandle, path: &str, win_handle: WinHandle)
     Ok(())
 }
 
-fn create_preview_cleanup(win_handle: WinHandle, src: &str) -> Option<i64> {
-    match snacks_open_preview(win_handle, src) {
+pub fn create_preview_cleanup(
+    win_handle: WinHandle,
+    src: &str,
+    anchor: PreviewAnchor,
+) -> Option<i64> {
+    match snacks_open_preview(win_handle, src, anchor) {
         Ok(value) => value,
         Err(err) => {
             notify::warn(LOG_CONTEXT, &format!("snacks open preview failed: {err}"));
@@ -294,57 +328,195 @@ fn create_preview_cleanup(win_handle: WinHandle, src: &str) -> Option<i64> {
     }
 }
 
-fn on_doc_find_inner(args: DocFindArgs) {
-    let DocFindArgs {
-        buf_handle,
-        token,
-        win_handle,
-        img_src,
-    } = args;
-    let Some(key) = buf_key(buf_handle) else {
+fn show_image(key: BufKey, win: WinKey, token: PreviewToken, src: &str) {
+    if !context().is_current_preview_token(key, token) {
+        return;
+    }
+    let (Some(buf_handle), Some(win_handle)) = (
+        BufHandle::try_from_i64(key.raw()),
+        WinHandle::try_from_i64(win.raw()),
+    ) else {
         return;
     };
-    if !context().is_current_preview_token(key, token) {
+    if !preview_target_is_current(buf_handle, win_handle) {
+        let _ = close_doc_preview_by_token(token);
         return;
     }
-    let arrived_transition = context().apply_event(PreviewEvent::DocFindArrived { key, token });
-    let command = execute_transition(arrived_transition);
-    log_unexpected_command("doc_find_arrived", command.as_ref());
-
-    let Some(src) = img_src else {
+    let Some(cleanup_id) = create_preview_cleanup(win_handle, src, PreviewAnchor::Window) else {
         return;
     };
-    let src = src.into_string();
+    if !context().is_current_preview_token(key, token) {
+        run_preview_cleanup(cleanup_id);
+        return;
+    }
+    let cleanup_effects = context().apply_event(PreviewEvent::CleanupOpened {
+        key,
+        token,
+        cleanup_id,
+    });
+    let command = execute_transition(cleanup_effects);
+    log_unexpected_command("cleanup_opened", command.as_ref());
+}
 
+fn schedule_show_image(key: BufKey, win: WinKey, token: PreviewToken, src: String) {
     schedule(move |()| {
+        guard::with_panic(
+            (),
+            || show_image(key, win, token, &src),
+            |info| report_panic("doc_preview_schedule", &info),
+        );
+    });
+}
+
+fn add_diagram_image(key: BufKey, token: PreviewToken, output: &Path) {
+    let transition = context().apply_event(PreviewEvent::ImageAdded {
+        key,
+        token,
+        src: output.to_string_lossy().into_owned(),
+    });
+    let command = execute_transition(transition);
+    execute_show_command("image_added", command);
+}
+
+fn diagram_cache_dir() -> Result<PathBuf> {
+    let dir: NvimString = api::call_function("stdpath", Array::from_iter(["cache"]))?;
+    Ok(PathBuf::from(dir.to_string_lossy().into_owned()).join(DIAGRAM_CACHE_DIR))
+}
+
+fn buffer_lines(buf: &Buffer) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for line in buf.get_lines(.., false)? {
+        lines.push(line.to_string_lossy().into_owned());
+    }
+    Ok(lines)
+}
+
+fn start_diagram_render(
+    key: BufKey,
+    token: PreviewToken,
+    program: &str,
+    block: &DiagramBlock,
+    dir: &Path,
+) {
+    let stem = block.cache_stem();
+    let output = dir.join(format!("{stem}.png"));
+    if output.is_file() {
+        add_diagram_image(key, token, &output);
+        return;
+    }
+    let input = dir.join(format!("{stem}.{}", block.kind.source_extension()));
+    if let Err(err) = fs::write(&input, &block.source) {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("write diagram source '{}' failed: {err}", input.display()),
+        );
+        return;
+    }
+    let argv = block.kind.render_command(program, &input, &output);
+    let rendered = output.clone();
+    let started = render_diagram(argv, move |ok, stderr| {
         guard::with_panic(
             (),
             || {
-                if !context().is_current_preview_token(key, token) {
-                    return;
-                }
-                if !preview_target_is_current(buf_handle, win_handle) {
-                    let _ = close_doc_preview_by_token(token);
+                if ok && rendered.is_file() {
+                    add_diagram_image(key, token, &rendered);
                     return;
                 }
-                let Some(cleanup_id) = create_preview_cleanup(win_handle, &src) else {
-                    return;
-                };
-                if !context().is_current_preview_token(key, token) {
-                    run_preview_cleanup(cleanup_id);
-                    return;
-                }
-                let cleanup_effects = context().apply_event(PreviewEvent::CleanupOpened {
-                    key,
-                    token,
-                    cleanup_id,
-                });
-                let command = execute_transition(cleanup_effects);
-                log_unexpected_command("cleanup_opened", command.as_ref());
+                let detail = stderr.filter(|stderr| !stderr.is_empty());
+                notify::warn(
+                    LOG_CONTEXT,
+                    &format!(
+                        "diagram render failed: {}",
+                        detail.as_deref().unwrap_or("no output produced")
+                    ),
+                );
             },
-            |info| report_panic("doc_preview_schedule", &info),
+            |info| report_panic("diagram_render", &info),
         );
     });
+    match started {
+        Ok(None) => {}
+        Ok(Some(reason)) => {
+            if context().note_missing_renderer(program) {
+                notify::warn(
+                    LOG_CONTEXT,
+                    &format!("diagram previews disabled for `{program}`: {reason}"),
+                );
+            }
+        }
+        Err(err) => notify::warn(LOG_CONTEXT, &format!("diagram render failed: {err}")),
+    }
+}
+
+/// Renders the diagram blocks of `buf_handle` into the cache and adds each
+/// image to the preview's gallery once it exists.
+fn start_diagram_previews(buf_handle: BufHandle, key: BufKey, token: PreviewToken) {
+    let config = context().config();
+    if !config.diagrams {
+        return;
+    }
+    let Some(buf) = buf_handle.valid_buffer() else {
+        return;
+    };
+    let blocks = match buffer_lines(&buf) {
+        Ok(lines) => extract_diagram_blocks(&lines),
+        Err(err) => {
+            notify::warn(LOG_CONTEXT, &format!("read preview lines failed: {err}"));
+            return;
+        }
+    };
+    if blocks.is_empty() {
+        return;
+    }
+    let dir = match diagram_cache_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            notify::warn(LOG_CONTEXT, &format!("resolve diagram cache failed: {err}"));
+            return;
+        }
+    };
+    if let Err(err) = fs::create_dir_all(&dir) {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("create diagram cache '{}' failed: {err}", dir.display()),
+        );
+        return;
+    }
+    for block in &blocks {
+        let program = match block.kind {
+            DiagramKind::Mermaid => config.mermaid_cmd.as_str(),
+            DiagramKind::Graphviz => config.graphviz_cmd.as_str(),
+        };
+        start_diagram_render(key, token, program, block, &dir);
+    }
+}
+
+fn on_doc_find_inner(args: DocFindArgs) {
+    let DocFindArgs {
+        buf_handle,
+        token,
+        win_handle,
+        img_srcs,
+    } = args;
+    let Some(key) = buf_key(buf_handle) else {
+        return;
+    };
+    if !context().is_current_preview_token(key, token) {
+        return;
+    }
+    if !preview_target_is_current(buf_handle, win_handle) {
+        let _ = close_doc_preview_by_token(token);
+        return;
+    }
+    let images = img_srcs
+        .into_iter()
+        .map(NonEmptyString::into_string)
+        .collect();
+    let arrived_transition =
+        context().apply_event(PreviewEvent::DocFindArrived { key, token, images });
+    let command = execute_transition(arrived_transition);
+    execute_show_command("doc_find_arrived", command);
+    start_diagram_previews(buf_handle, key, token);
 }
 
 pub fn on_doc_find(args: &Dictionary) {
@@ -394,6 +566,105 @@ pub fn close_doc_preview_for_window_lua(win_handle: i64) {
     let _ = close_doc_preview_for_window(win_handle);
 }
 
+fn step_gallery(win: Option<WinKey>, step: GalleryStep) {
+    let transition = context().apply_event(PreviewEvent::GalleryStep { win, step });
+    let command = execute_transition(transition);
+    execute_show_command("gallery_step", command);
+}
+
+/// Cycles the gallery in `win`, or in the only open preview when `win` is
+/// omitted.
+pub fn gallery_step_lua(win: Option<i64>, step: GalleryStep) {
+    let win = match win {
+        Some(raw) => match WinKey::try_new(raw) {
+            Some(win) => Some(win),
+            None => return,
+        },
+        None => None,
+    };
+    guard::with_panic(
+        (),
+        || step_gallery(win, step),
+        |info| report_panic("gallery_step", &info),
+    );
+}
+
+/// Command entry point: the current window when it shows a preview, else the
+/// only open preview.
+pub fn gallery_step_current(step: GalleryStep) {
+    let current = WinHandle::from_window(&api::get_current_win());
+    let win = win_key(current).filter(|win| context().token_for_win(*win).is_some());
+    guard::with_panic(
+        (),
+        || step_gallery(win, step),
+        |info| report_panic("gallery_step", &info),
+    );
+}
+
+fn show_diagnostics() {
+    notify::info(LOG_CONTEXT, &context().diagnostics().render());
+}
+
+pub fn stats_lua() -> Dictionary {
+    context().diagnostics().to_dictionary()
+}
+
+fn setup_commands() -> Result<()> {
+    let next_opts = CreateCommandOpts::builder()
+        .force(true)
+        .desc("Show the next image of the document preview")
+        .build();
+    api::create_user_command(
+        "SnacksPreviewNext",
+        |_args: CommandArgs| gallery_step_current(GalleryStep::Next),
+        &next_opts,
+    )?;
+
+    let prev_opts = CreateCommandOpts::builder()
+        .force(true)
+        .desc("Show the previous image of the document preview")
+        .build();
+    api::create_user_command(
+        "SnacksPreviewPrev",
+        |_args: CommandArgs| gallery_step_current(GalleryStep::Prev),
+        &prev_opts,
+    )?;
+
+    let diagnostics_opts = CreateCommandOpts::builder()
+        .force(true)
+        .desc("Show document preview lifecycle counters")
+        .build();
+    api::create_user_command(
+        "SnacksPreviewDiagnostics",
+        |_args: CommandArgs| show_diagnostics(),
+        &diagnostics_opts,
+    )?;
+
+    let follow_opts = CreateCommandOpts::builder()
+        .force(true)
+        .desc("Toggle the inline preview of the image under the cursor")
+        .build();
+    api::create_user_command(
+        "SnacksPreviewFollow",
+        |_args: CommandArgs| toggle_follow_cursor(),
+        &follow_opts,
+    )?;
+    Ok(())
+}
+
+pub fn setup_lua(opts: Option<&Dictionary>) {
+    let config = PreviewConfig::from_opts(opts);
+    let follow_cursor = config.follow_cursor;
+    context().set_config(config);
+    if let Err(err) = setup_commands() {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("create preview commands failed: {err}"),
+        );
+    }
+    set_follow_cursor(follow_cursor);
+}
+
 pub fn reset_state_lua() {
     let transition = context().apply_event(PreviewEvent::Reset);
     let command = execute_transition(transition);
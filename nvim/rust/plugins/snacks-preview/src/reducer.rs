@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use nvimrs_nvim_oxi_utils::indexed_registry::EvictionReason;
 use nvimrs_nvim_oxi_utils::indexed_registry::IndexedRegistry;
 use nvimrs_nvim_oxi_utils::indexed_registry::IndexedValue;
@@ -47,6 +49,62 @@ impl WinKey {
             None => None,
         }
     }
+
+    pub const fn raw(self) -> i64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GalleryStep {
+    Next,
+    Prev,
+}
+
+/// Images a preview can cycle through, in document order followed by
+/// rendered diagrams as they complete.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Gallery {
+    images: Vec<String>,
+    index: usize,
+}
+
+impl Gallery {
+    pub fn from_images(images: Vec<String>) -> Self {
+        let mut gallery = Self::default();
+        for src in images {
+            let _ = gallery.push(src);
+        }
+        gallery
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.images.get(self.index).map(String::as_str)
+    }
+
+    /// Appends `src` unless already present. Returns whether it is now the
+    /// current image, i.e. the gallery was empty.
+    fn push(&mut self, src: String) -> bool {
+        if src.is_empty() || self.images.contains(&src) {
+            return false;
+        }
+        self.images.push(src);
+        self.images.len() == 1
+    }
+
+    /// Moves to the neighbouring image, wrapping at either end. `None` when
+    /// there is nothing to cycle to.
+    fn step(&mut self, step: GalleryStep) -> Option<&str> {
+        let len = self.images.len();
+        if len < 2 {
+            return None;
+        }
+        self.index = match step {
+            GalleryStep::Next => (self.index + 1) % len,
+            GalleryStep::Prev => (self.index + len - 1) % len,
+        };
+        self.current()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +114,7 @@ pub struct DocPreviewState {
     pub group: Option<u32>,
     pub cleanup: Option<i64>,
     pub restore_name_plan: Option<RestoreNamePlan>,
+    pub gallery: Gallery,
 }
 
 impl IndexedValue<WinKey, PreviewToken> for DocPreviewState {
@@ -68,6 +127,26 @@ impl IndexedValue<WinKey, PreviewToken> for DocPreviewState {
     }
 }
 
+/// What the cursor rests on in a follow-mode window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorTarget {
+    /// A resolved local path or remote URL.
+    Image(String),
+    /// A local reference that resolved to no file; `detail` says where it was
+    /// looked for.
+    Missing { src: String, detail: String },
+}
+
+/// Inline preview that follows the image reference under the cursor. Each new
+/// target takes a fresh token, so debounce timers armed for an earlier target
+/// are rejected when they fire.
+#[derive(Debug, Clone)]
+pub struct CursorPreviewState {
+    pub token: PreviewToken,
+    pub target: CursorTarget,
+    pub cleanup: Option<i64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RestoreNamePlan {
     pub name: String,
@@ -84,6 +163,25 @@ pub enum PreviewEffect {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PreviewCommand {
     RequestDocFind(PreviewToken),
+    ShowImage {
+        key: BufKey,
+        win: WinKey,
+        token: PreviewToken,
+        src: String,
+    },
+    DebounceCursor {
+        win: WinKey,
+        token: PreviewToken,
+    },
+    ShowCursorImage {
+        win: WinKey,
+        token: PreviewToken,
+        src: String,
+    },
+    ReportMissingImage {
+        src: String,
+        detail: String,
+    },
 }
 
 pub type PreviewTransition = Transition<PreviewEffect, PreviewCommand>;
@@ -106,18 +204,73 @@ pub enum PreviewEvent {
     DocFindArrived {
         key: BufKey,
         token: PreviewToken,
+        images: Vec<String>,
+    },
+    /// A rendered diagram joins the gallery of the preview `token` owns.
+    ImageAdded {
+        key: BufKey,
+        token: PreviewToken,
+        src: String,
+    },
+    /// Cycle the gallery shown in `win`; without a window, the only open
+    /// preview is used.
+    GalleryStep {
+        win: Option<WinKey>,
+        step: GalleryStep,
     },
     CleanupOpened {
         key: BufKey,
         token: PreviewToken,
         cleanup_id: i64,
     },
+    /// The reference under the cursor in `win` changed; `None` hides the
+    /// inline preview.
+    CursorMoved {
+        win: WinKey,
+        target: Option<CursorTarget>,
+    },
+    CursorDebounceElapsed {
+        win: WinKey,
+        token: PreviewToken,
+    },
+    CursorCleanupOpened {
+        win: WinKey,
+        token: PreviewToken,
+        cleanup_id: i64,
+    },
+    /// Follow mode was turned off.
+    CursorStopAll,
+}
+
+/// Why a registered preview went away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CloseCounts {
+    pub by_buffer: u64,
+    pub by_token: u64,
+    pub by_reset: u64,
+}
+
+/// Lifecycle counters kept by the reducer. They only grow: `Reset` closes
+/// previews but leaves the counts in place.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreviewStats {
+    pub registrations: u64,
+    /// Previews dropped because a new registration took their buffer or
+    /// window.
+    pub replacements: u64,
+    pub stale_doc_find: u64,
+    /// Late `CleanupOpened`/`CursorCleanupOpened` events whose cleanup was
+    /// closed straight away.
+    pub stale_cleanup_opened: u64,
+    pub closes: CloseCounts,
 }
 
 #[derive(Debug, Default)]
 pub struct PreviewRegistry {
     next_token: i64,
     previews: IndexedRegistry<BufKey, DocPreviewState, WinKey, PreviewToken>,
+    cursor_previews: HashMap<WinKey, CursorPreviewState>,
+    stats: PreviewStats,
 }
 
 impl PreviewRegistry {
@@ -139,7 +292,12 @@ impl PreviewRegistry {
                 self.next_token + 1
             };
             let candidate = PreviewToken(self.next_token);
-            if !self.previews.contains_index_two(candidate) {
+            if !self.previews.contains_index_two(candidate)
+                && !self
+                    .cursor_previews
+                    .values()
+                    .any(|entry| entry.token == candidate)
+            {
                 return candidate;
             }
         }
@@ -151,6 +309,45 @@ impl PreviewRegistry {
             .is_some_and(|entry| entry.token == token)
     }
 
+    pub fn is_cursor_token_current(&self, win: WinKey, token: PreviewToken) -> bool {
+        self.cursor_previews
+            .get(&win)
+            .is_some_and(|entry| entry.token == token)
+    }
+
+    pub const fn stats(&self) -> PreviewStats {
+        self.stats
+    }
+
+    pub fn open_previews(&self) -> usize {
+        self.previews.len()
+    }
+
+    pub fn follow_previews(&self) -> usize {
+        self.cursor_previews.len()
+    }
+
+    /// Cleanup ids currently owned by a document or follow-mode preview.
+    pub fn owned_cleanups(&self) -> impl Iterator<Item = i64> + '_ {
+        self.previews
+            .iter()
+            .filter_map(|(_, entry)| entry.cleanup)
+            .chain(
+                self.cursor_previews
+                    .values()
+                    .filter_map(|entry| entry.cleanup),
+            )
+    }
+
+    fn close_stale_cleanup(&mut self, cleanup_id: i64) -> PreviewTransition {
+        self.stats.stale_cleanup_opened = self.stats.stale_cleanup_opened.saturating_add(1);
+        Self::close_cleanup_only(cleanup_id)
+    }
+
+    fn note_replacement(&mut self) {
+        self.stats.replacements = self.stats.replacements.saturating_add(1);
+    }
+
     pub fn token_for_win(&self, win: WinKey) -> Option<PreviewToken> {
         self.previews
             .get_by_index_one(win)
@@ -187,6 +384,26 @@ impl PreviewRegistry {
         self.previews.take_by_index_two(token)
     }
 
+    fn show_image_command(key: BufKey, entry: &DocPreviewState) -> Option<PreviewCommand> {
+        entry
+            .gallery
+            .current()
+            .map(|src| PreviewCommand::ShowImage {
+                key,
+                win: entry.win,
+                token: entry.token,
+                src: src.to_string(),
+            })
+    }
+
+    fn gallery_target(&self, win: Option<WinKey>) -> Option<BufKey> {
+        match win {
+            Some(win) => self.previews.key_by_index_one(win),
+            None if self.previews.len() == 1 => self.previews.iter().next().map(|(key, _)| *key),
+            None => None,
+        }
+    }
+
     fn restore_name_effect(key: BufKey, state: &mut DocPreviewState) -> Option<PreviewEffect> {
         state
             .restore_name_plan
@@ -208,6 +425,16 @@ impl PreviewRegistry {
         effects
     }
 
+    fn stop_cursor_previews(&mut self) -> Vec<PreviewEffect> {
+        let mut entries = self.cursor_previews.drain().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(win, _)| win.raw());
+        entries
+            .into_iter()
+            .filter_map(|(_, entry)| entry.cleanup)
+            .map(PreviewEffect::CloseCleanup)
+            .collect()
+    }
+
     fn replace_effects(state: &DocPreviewState, new_group: u32) -> Vec<PreviewEffect> {
         let mut effects = Vec::new();
         if let Some(group) = state.group
@@ -233,19 +460,25 @@ impl PreviewRegistry {
                 let mut effects = Vec::new();
                 for key in keys {
                     if let Some(state) = self.remove_preview_by_key(key) {
+                        self.stats.closes.by_reset = self.stats.closes.by_reset.saturating_add(1);
                         effects.extend(Self::close_effects(key, state));
                     }
                 }
+                effects.extend(self.stop_cursor_previews());
                 PreviewTransition::with_effects(effects)
             }
             PreviewEvent::Close { key } => {
                 let removed = self.remove_preview_by_key(key);
+                if removed.is_some() {
+                    self.stats.closes.by_buffer = self.stats.closes.by_buffer.saturating_add(1);
+                }
                 Self::transition_from_closed(key, removed)
             }
             PreviewEvent::CloseByToken { token } => {
                 let Some((key, removed)) = self.remove_preview_by_token(token) else {
                     return PreviewTransition::default();
                 };
+                self.stats.closes.by_token = self.stats.closes.by_token.saturating_add(1);
                 Self::transition_from_closed(key, Some(removed))
             }
             PreviewEvent::Register {
@@ -254,13 +487,16 @@ impl PreviewRegistry {
                 group,
                 restore_name_plan,
             } => {
+                self.stats.registrations = self.stats.registrations.saturating_add(1);
                 let mut effects = Vec::new();
                 if let Some(old) = self.remove_preview_by_key(key) {
+                    self.note_replacement();
                     effects.extend(Self::replace_effects(&old, group));
                 }
                 if let Some((old_key, old)) = self.previews.take_by_index_one(win)
                     && old_key != key
                 {
+                    self.note_replacement();
                     effects.extend(Self::close_effects(old_key, old));
                 }
                 let token = self.next_token();
@@ -272,9 +508,11 @@ impl PreviewRegistry {
                         group: Some(group),
                         cleanup: None,
                         restore_name_plan,
+                        gallery: Gallery::default(),
                     },
                 );
                 for evicted in unexpected_evicted.into_evicted() {
+                    self.note_replacement();
                     match evicted.reason {
                         EvictionReason::Key
                         | EvictionReason::KeyAndIndexOne
@@ -293,33 +531,132 @@ impl PreviewRegistry {
                 transition.set_command(PreviewCommand::RequestDocFind(token));
                 transition
             }
-            PreviewEvent::DocFindArrived { key, token } => {
+            PreviewEvent::DocFindArrived { key, token, images } => {
+                if !self.is_token_current(key, token) {
+                    self.stats.stale_doc_find = self.stats.stale_doc_find.saturating_add(1);
+                    return PreviewTransition::default();
+                }
                 let Some(entry) = self.get_preview_mut(key) else {
                     return PreviewTransition::default();
                 };
-                if entry.token != token {
+                let mut transition = Self::restore_name_effect(key, entry)
+                    .map(|effect| vec![effect])
+                    .map_or_else(PreviewTransition::default, PreviewTransition::with_effects);
+                entry.gallery = Gallery::from_images(images);
+                if let Some(command) = Self::show_image_command(key, entry) {
+                    transition.set_command(command);
+                }
+                transition
+            }
+            PreviewEvent::ImageAdded { key, token, src } => {
+                let Some(entry) = self.get_preview_mut(key) else {
+                    return PreviewTransition::default();
+                };
+                if entry.token != token || !entry.gallery.push(src) {
                     return PreviewTransition::default();
                 }
-                Self::restore_name_effect(key, entry)
-                    .map(|effect| vec![effect])
-                    .map_or_else(PreviewTransition::default, PreviewTransition::with_effects)
+                Self::show_image_command(key, entry)
+                    .map_or_else(PreviewTransition::default, PreviewTransition::with_command)
+            }
+            PreviewEvent::GalleryStep { win, step } => {
+                let Some(key) = self.gallery_target(win) else {
+                    return PreviewTransition::default();
+                };
+                let Some(entry) = self.get_preview_mut(key) else {
+                    return PreviewTransition::default();
+                };
+                if entry.gallery.step(step).is_none() {
+                    return PreviewTransition::default();
+                }
+                let mut transition = entry
+                    .cleanup
+                    .take()
+                    .map(|cleanup_id| vec![PreviewEffect::CloseCleanup(cleanup_id)])
+                    .map_or_else(PreviewTransition::default, PreviewTransition::with_effects);
+                if let Some(command) = Self::show_image_command(key, entry) {
+                    transition.set_command(command);
+                }
+                transition
             }
             PreviewEvent::CleanupOpened {
                 key,
                 token,
                 cleanup_id,
             } => {
+                if !self.is_token_current(key, token) {
+                    return self.close_stale_cleanup(cleanup_id);
+                }
                 let Some(entry) = self.get_preview_mut(key) else {
                     return Self::close_cleanup_only(cleanup_id);
                 };
+                let replaced = entry.cleanup.replace(cleanup_id);
+                let effects =
+                    replaced.map_or_else(Vec::new, |old| vec![PreviewEffect::CloseCleanup(old)]);
+                PreviewTransition::with_effects(effects)
+            }
+            PreviewEvent::CursorMoved { win, target } => {
+                if self.cursor_previews.get(&win).map(|entry| &entry.target) == target.as_ref() {
+                    return PreviewTransition::default();
+                }
+                let mut transition = self
+                    .cursor_previews
+                    .remove(&win)
+                    .and_then(|entry| entry.cleanup)
+                    .map_or_else(PreviewTransition::default, Self::close_cleanup_only);
+                let Some(target) = target else {
+                    return transition;
+                };
+                let token = self.next_token();
+                let _ = self.cursor_previews.insert(
+                    win,
+                    CursorPreviewState {
+                        token,
+                        target,
+                        cleanup: None,
+                    },
+                );
+                transition.set_command(PreviewCommand::DebounceCursor { win, token });
+                transition
+            }
+            PreviewEvent::CursorDebounceElapsed { win, token } => {
+                let Some(entry) = self.cursor_previews.get(&win) else {
+                    return PreviewTransition::default();
+                };
                 if entry.token != token {
-                    return Self::close_cleanup_only(cleanup_id);
+                    return PreviewTransition::default();
                 }
+                let command = match &entry.target {
+                    CursorTarget::Image(src) => PreviewCommand::ShowCursorImage {
+                        win,
+                        token,
+                        src: src.clone(),
+                    },
+                    CursorTarget::Missing { src, detail } => PreviewCommand::ReportMissingImage {
+                        src: src.clone(),
+                        detail: detail.clone(),
+                    },
+                };
+                PreviewTransition::with_command(command)
+            }
+            PreviewEvent::CursorCleanupOpened {
+                win,
+                token,
+                cleanup_id,
+            } => {
+                if !self.is_cursor_token_current(win, token) {
+                    return self.close_stale_cleanup(cleanup_id);
+                }
+                let Some(entry) = self.cursor_previews.get_mut(&win) else {
+                    return Self::close_cleanup_only(cleanup_id);
+                };
                 let replaced = entry.cleanup.replace(cleanup_id);
                 let effects =
                     replaced.map_or_else(Vec::new, |old| vec![PreviewEffect::CloseCleanup(old)]);
                 PreviewTransition::with_effects(effects)
             }
+            PreviewEvent::CursorStopAll => {
+                PreviewTransition::with_effects(self.stop_cursor_previews())
+            }
         }
     }
 }
@@ -357,6 +694,15 @@ mod tests {
 
     fn assert_registry_invariants(registry: &PreviewRegistry) {
         assert!(registry.next_token >= 0);
+        let stats = registry.stats();
+        assert_eq!(
+            stats.registrations,
+            registry.open_previews() as u64
+                + stats.replacements
+                + stats.closes.by_buffer
+                + stats.closes.by_token
+                + stats.closes.by_reset
+        );
         assert_eq!(
             registry.previews.len(),
             registry.previews.iter_index_one().count()
@@ -377,6 +723,27 @@ mod tests {
                 assert!(!plan.preview_name.is_empty());
                 assert_ne!(plan.name, plan.preview_name);
             }
+            if state.gallery.images.is_empty() {
+                assert_eq!(state.gallery.index, 0);
+            } else {
+                assert!(state.gallery.index < state.gallery.images.len());
+            }
+        }
+
+        for entry in registry.cursor_previews.values() {
+            assert!(entry.token.raw() > 0);
+            assert!(!registry.previews.contains_index_two(entry.token));
+            assert_eq!(
+                registry
+                    .cursor_previews
+                    .values()
+                    .filter(|other| other.token == entry.token)
+                    .count(),
+                1
+            );
+            if let Some(cleanup_id) = entry.cleanup {
+                assert!(cleanup_id > 0);
+            }
         }
 
         for (win, key) in registry.previews.iter_index_one() {
@@ -416,6 +783,7 @@ mod tests {
                 group: None,
                 cleanup: None,
                 restore_name_plan: None,
+                gallery: Gallery::default(),
             },
         );
         let _ = registry.take_preview(key);
@@ -438,6 +806,7 @@ mod tests {
                     name: "a".to_string(),
                     preview_name: "b".to_string(),
                 }),
+                gallery: Gallery::default(),
             },
         );
 
@@ -457,6 +826,13 @@ mod tests {
             ]
         );
         assert_eq!(transition.command, None);
+        assert_eq!(
+            registry.stats().closes,
+            CloseCounts {
+                by_buffer: 1,
+                ..CloseCounts::default()
+            }
+        );
         Ok(())
     }
 
@@ -481,6 +857,13 @@ mod tests {
             Some(PreviewCommand::RequestDocFind(token(1)?))
         );
         assert!(registry.is_token_current(key, token(1)?));
+        assert_eq!(
+            registry.stats(),
+            PreviewStats {
+                registrations: 1,
+                ..PreviewStats::default()
+            }
+        );
         Ok(())
     }
 
@@ -537,6 +920,7 @@ mod tests {
         });
         assert_eq!(transition.effects, vec![PreviewEffect::CloseCleanup(12)]);
         assert_eq!(transition.command, None);
+        assert_eq!(registry.stats().stale_cleanup_opened, 1);
         Ok(())
     }
 
@@ -549,6 +933,7 @@ mod tests {
 
         assert!(transition.effects.is_empty());
         assert_eq!(transition.command, None);
+        assert_eq!(registry.stats(), PreviewStats::default());
         Ok(())
     }
 
@@ -567,6 +952,7 @@ mod tests {
                     name: "doc".to_string(),
                     preview_name: "doc.preview".to_string(),
                 }),
+                gallery: Gallery::default(),
             },
         );
 
@@ -588,6 +974,7 @@ mod tests {
         );
         assert_eq!(transition.command, None);
         assert!(registry.get_preview(key).is_none());
+        assert_eq!(registry.stats().closes.by_token, 1);
         Ok(())
     }
 
@@ -638,6 +1025,7 @@ mod tests {
                     name: "old".to_string(),
                     preview_name: "old.preview".to_string(),
                 }),
+                gallery: Gallery::default(),
             },
         );
 
@@ -671,6 +1059,14 @@ mod tests {
         );
         assert!(registry.get_preview(key_a).is_none());
         assert_eq!(registry.token_for_win(shared_win), Some(token(1)?));
+        assert_eq!(
+            registry.stats(),
+            PreviewStats {
+                registrations: 1,
+                replacements: 1,
+                ..PreviewStats::default()
+            }
+        );
         Ok(())
     }
 
@@ -689,6 +1085,7 @@ mod tests {
                     name: "orig".to_string(),
                     preview_name: "orig.preview".to_string(),
                 }),
+                gallery: Gallery::default(),
             },
         );
 
@@ -721,6 +1118,7 @@ mod tests {
             registry.get_preview(key).and_then(|entry| entry.cleanup),
             None
         );
+        assert_eq!(registry.stats().replacements, 1);
         Ok(())
     }
 
@@ -736,6 +1134,7 @@ mod tests {
                 group: Some(70),
                 cleanup: Some(80),
                 restore_name_plan: None,
+                gallery: Gallery::default(),
             },
         );
 
@@ -766,12 +1165,14 @@ mod tests {
                 group: Some(1),
                 cleanup: None,
                 restore_name_plan: None,
+                gallery: Gallery::default(),
             },
         );
 
         let transition = registry.reduce(PreviewEvent::DocFindArrived {
             key,
             token: token(33)?,
+            images: Vec::new(),
         });
 
         assert!(transition.effects.is_empty());
@@ -794,12 +1195,14 @@ mod tests {
                     name: String::new(),
                     preview_name: "doc.md.snacks-preview".to_string(),
                 }),
+                gallery: Gallery::default(),
             },
         );
 
         let arrived = registry.reduce(PreviewEvent::DocFindArrived {
             key,
             token: token(34)?,
+            images: Vec::new(),
         });
         let closed = registry.reduce(PreviewEvent::Close { key });
 
@@ -855,6 +1258,7 @@ mod tests {
         let stale = registry.reduce(PreviewEvent::DocFindArrived {
             key,
             token: token(1)?,
+            images: Vec::new(),
         });
         assert!(stale.effects.is_empty());
         assert_eq!(stale.command, None);
@@ -862,6 +1266,7 @@ mod tests {
         let current = registry.reduce(PreviewEvent::DocFindArrived {
             key,
             token: token(2)?,
+            images: Vec::new(),
         });
         assert_eq!(
             current.effects,
@@ -874,6 +1279,15 @@ mod tests {
             }]
         );
         assert_eq!(current.command, None);
+        assert_eq!(
+            registry.stats(),
+            PreviewStats {
+                registrations: 2,
+                replacements: 1,
+                stale_doc_find: 1,
+                ..PreviewStats::default()
+            }
+        );
         Ok(())
     }
 
@@ -890,6 +1304,330 @@ mod tests {
 
         assert_eq!(transition.effects, vec![PreviewEffect::CloseCleanup(99)]);
         assert_eq!(transition.command, None);
+        assert_eq!(registry.stats().stale_cleanup_opened, 1);
+        Ok(())
+    }
+
+    fn gallery_registry(images: &[&str]) -> Result<(PreviewRegistry, BufKey), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        let key = key(41)?;
+        let registered = registry.reduce(PreviewEvent::Register {
+            key,
+            win: win(51)?,
+            group: 1,
+            restore_name_plan: None,
+        });
+        let Some(PreviewCommand::RequestDocFind(current)) = registered.command else {
+            return Err("expected doc find request");
+        };
+        let _ = registry.reduce(PreviewEvent::DocFindArrived {
+            key,
+            token: current,
+            images: images.iter().map(|src| (*src).to_string()).collect(),
+        });
+        let _ = registry.reduce(PreviewEvent::CleanupOpened {
+            key,
+            token: current,
+            cleanup_id: 10,
+        });
+        Ok((registry, key))
+    }
+
+    fn shown_src(transition: &PreviewTransition) -> Option<&str> {
+        match &transition.command {
+            Some(PreviewCommand::ShowImage { src, .. }) => Some(src.as_str()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn doc_find_arrived_shows_first_image() -> Result<(), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        let key = key(41)?;
+        let registered = registry.reduce(PreviewEvent::Register {
+            key,
+            win: win(51)?,
+            group: 1,
+            restore_name_plan: None,
+        });
+        let Some(PreviewCommand::RequestDocFind(current)) = registered.command else {
+            return Err("expected doc find request");
+        };
+
+        let arrived = registry.reduce(PreviewEvent::DocFindArrived {
+            key,
+            token: current,
+            images: vec![
+                "a.png".to_string(),
+                "b.png".to_string(),
+                "a.png".to_string(),
+            ],
+        });
+
+        assert_eq!(
+            arrived.command,
+            Some(PreviewCommand::ShowImage {
+                key,
+                win: win(51)?,
+                token: current,
+                src: "a.png".to_string(),
+            })
+        );
+        let entry = registry.get_preview(key).ok_or("expected preview")?;
+        assert_eq!(entry.gallery.images, vec!["a.png", "b.png"]);
+        Ok(())
+    }
+
+    #[test]
+    fn gallery_step_wraps_and_closes_current_image() -> Result<(), &'static str> {
+        let (mut registry, _) = gallery_registry(&["a.png", "b.png", "c.png"])?;
+
+        let prev = registry.reduce(PreviewEvent::GalleryStep {
+            win: Some(win(51)?),
+            step: GalleryStep::Prev,
+        });
+        assert_eq!(prev.effects, vec![PreviewEffect::CloseCleanup(10)]);
+        assert_eq!(shown_src(&prev), Some("c.png"));
+
+        let next = registry.reduce(PreviewEvent::GalleryStep {
+            win: Some(win(51)?),
+            step: GalleryStep::Next,
+        });
+        assert!(next.effects.is_empty());
+        assert_eq!(shown_src(&next), Some("a.png"));
+        Ok(())
+    }
+
+    #[test]
+    fn gallery_step_with_single_image_is_noop() -> Result<(), &'static str> {
+        let (mut registry, key) = gallery_registry(&["a.png"])?;
+
+        let step = registry.reduce(PreviewEvent::GalleryStep {
+            win: None,
+            step: GalleryStep::Next,
+        });
+
+        assert!(step.is_empty());
+        let entry = registry.get_preview(key).ok_or("expected preview")?;
+        assert_eq!(entry.cleanup, Some(10));
+        Ok(())
+    }
+
+    #[test]
+    fn gallery_step_for_unknown_window_is_noop() -> Result<(), &'static str> {
+        let (mut registry, _) = gallery_registry(&["a.png", "b.png"])?;
+
+        let step = registry.reduce(PreviewEvent::GalleryStep {
+            win: Some(win(99)?),
+            step: GalleryStep::Next,
+        });
+
+        assert!(step.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn image_added_shows_only_when_gallery_was_empty() -> Result<(), &'static str> {
+        let (mut registry, key) = gallery_registry(&[])?;
+        let current = current_token(&registry, key).ok_or("expected token")?;
+
+        let first = registry.reduce(PreviewEvent::ImageAdded {
+            key,
+            token: current,
+            src: "diagram-1.png".to_string(),
+        });
+        let second = registry.reduce(PreviewEvent::ImageAdded {
+            key,
+            token: current,
+            src: "diagram-2.png".to_string(),
+        });
+        let duplicate = registry.reduce(PreviewEvent::ImageAdded {
+            key,
+            token: current,
+            src: "diagram-2.png".to_string(),
+        });
+
+        assert_eq!(shown_src(&first), Some("diagram-1.png"));
+        assert!(second.is_empty());
+        assert!(duplicate.is_empty());
+        let entry = registry.get_preview(key).ok_or("expected preview")?;
+        assert_eq!(entry.gallery.images, vec!["diagram-1.png", "diagram-2.png"]);
+        Ok(())
+    }
+
+    #[test]
+    fn image_added_for_stale_token_is_noop() -> Result<(), &'static str> {
+        let (mut registry, key) = gallery_registry(&[])?;
+
+        let stale = registry.reduce(PreviewEvent::ImageAdded {
+            key,
+            token: token(99_999)?,
+            src: "diagram.png".to_string(),
+        });
+
+        assert!(stale.is_empty());
+        let entry = registry.get_preview(key).ok_or("expected preview")?;
+        assert!(entry.gallery.images.is_empty());
+        assert_eq!(registry.stats().stale_doc_find, 0);
+        Ok(())
+    }
+
+    fn cursor_image(src: &str) -> Option<CursorTarget> {
+        Some(CursorTarget::Image(src.to_string()))
+    }
+
+    #[test]
+    fn cursor_moved_arms_debounce_with_fresh_token() -> Result<(), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        let first = registry.next_token();
+
+        let moved = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: cursor_image("/doc/a.png"),
+        });
+        let same = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: cursor_image("/doc/a.png"),
+        });
+
+        let Some(PreviewCommand::DebounceCursor { token, .. }) = moved.command else {
+            return Err("expected debounce command");
+        };
+        assert!(token.raw() > first.raw());
+        assert!(same.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn cursor_debounce_rejects_superseded_generation() -> Result<(), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        let first = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: cursor_image("/doc/a.png"),
+        });
+        let second = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: cursor_image("/doc/b.png"),
+        });
+        let (
+            Some(PreviewCommand::DebounceCursor { token: stale, .. }),
+            Some(PreviewCommand::DebounceCursor { token: current, .. }),
+        ) = (first.command, second.command)
+        else {
+            return Err("expected debounce commands");
+        };
+
+        let stale_elapsed = registry.reduce(PreviewEvent::CursorDebounceElapsed {
+            win: win(5)?,
+            token: stale,
+        });
+        let current_elapsed = registry.reduce(PreviewEvent::CursorDebounceElapsed {
+            win: win(5)?,
+            token: current,
+        });
+
+        assert!(stale_elapsed.is_empty());
+        assert_eq!(
+            current_elapsed.command,
+            Some(PreviewCommand::ShowCursorImage {
+                win: win(5)?,
+                token: current,
+                src: "/doc/b.png".to_string(),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cursor_missing_target_reports_after_debounce() -> Result<(), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        let _ = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: Some(CursorTarget::Missing {
+                src: "img/gone.png".to_string(),
+                detail: "/doc/img/gone.png".to_string(),
+            }),
+        });
+        let token = cursor_token(&registry, win(5)?).ok_or("expected cursor token")?;
+
+        let elapsed = registry.reduce(PreviewEvent::CursorDebounceElapsed {
+            win: win(5)?,
+            token,
+        });
+
+        assert_eq!(
+            elapsed.command,
+            Some(PreviewCommand::ReportMissingImage {
+                src: "img/gone.png".to_string(),
+                detail: "/doc/img/gone.png".to_string(),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cursor_leaving_image_closes_open_preview() -> Result<(), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        let _ = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: cursor_image("/doc/a.png"),
+        });
+        let token = cursor_token(&registry, win(5)?).ok_or("expected cursor token")?;
+        let _ = registry.reduce(PreviewEvent::CursorCleanupOpened {
+            win: win(5)?,
+            token,
+            cleanup_id: 8,
+        });
+
+        let left = registry.reduce(PreviewEvent::CursorMoved {
+            win: win(5)?,
+            target: None,
+        });
+        let late = registry.reduce(PreviewEvent::CursorCleanupOpened {
+            win: win(5)?,
+            token,
+            cleanup_id: 9,
+        });
+
+        assert_eq!(
+            left,
+            PreviewTransition::with_effects(vec![PreviewEffect::CloseCleanup(8)])
+        );
+        assert_eq!(
+            late,
+            PreviewTransition::with_effects(vec![PreviewEffect::CloseCleanup(9)])
+        );
+        assert!(cursor_token(&registry, win(5)?).is_none());
+        assert_eq!(registry.stats().stale_cleanup_opened, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn cursor_stop_all_closes_every_follow_preview() -> Result<(), &'static str> {
+        let mut registry = PreviewRegistry::default();
+        for (raw_win, cleanup_id) in [(7, 70), (6, 60)] {
+            let _ = registry.reduce(PreviewEvent::CursorMoved {
+                win: win(raw_win)?,
+                target: cursor_image("/doc/a.png"),
+            });
+            let token = cursor_token(&registry, win(raw_win)?).ok_or("expected cursor token")?;
+            let _ = registry.reduce(PreviewEvent::CursorCleanupOpened {
+                win: win(raw_win)?,
+                token,
+                cleanup_id,
+            });
+        }
+
+        let stopped = registry.reduce(PreviewEvent::CursorStopAll);
+
+        assert_eq!(
+            stopped,
+            PreviewTransition::with_effects(vec![
+                PreviewEffect::CloseCleanup(60),
+                PreviewEffect::CloseCleanup(70),
+            ])
+        );
+        assert!(registry.cursor_previews.is_empty());
         Ok(())
     }
 
@@ -901,16 +1639,32 @@ mod tests {
         DocFindStale,
         CleanupCurrent,
         CleanupStale,
+        ImageAdded,
+        GalleryNext,
+        GalleryPrev,
+        CursorOnImage,
+        CursorOnMissing,
+        CursorOff,
+        CursorElapsed,
+        CursorCleanup,
     }
 
     impl Step {
-        const ALL: [Self; 6] = [
+        const ALL: [Self; 14] = [
             Self::Register,
             Self::Close,
             Self::DocFindCurrent,
             Self::DocFindStale,
             Self::CleanupCurrent,
             Self::CleanupStale,
+            Self::ImageAdded,
+            Self::GalleryNext,
+            Self::GalleryPrev,
+            Self::CursorOnImage,
+            Self::CursorOnMissing,
+            Self::CursorOff,
+            Self::CursorElapsed,
+            Self::CursorCleanup,
         ];
     }
 
@@ -918,6 +1672,10 @@ mod tests {
         registry.get_preview(key).map(|entry| entry.token)
     }
 
+    fn cursor_token(registry: &PreviewRegistry, win: WinKey) -> Option<PreviewToken> {
+        registry.cursor_previews.get(&win).map(|entry| entry.token)
+    }
+
     fn apply_step(
         registry: &mut PreviewRegistry,
         key: BufKey,
@@ -925,6 +1683,7 @@ mod tests {
         next_cleanup_id: &mut i64,
     ) -> Result<(), &'static str> {
         let stale_token = token(99_999)?;
+        let before = registry.stats();
         let transition = match step {
             Step::Register => registry.reduce(PreviewEvent::Register {
                 key,
@@ -943,12 +1702,68 @@ mod tests {
                 registry.reduce(PreviewEvent::DocFindArrived {
                     key,
                     token: current,
+                    images: vec!["a.png".to_string(), "b.png".to_string()],
                 })
             }
             Step::DocFindStale => registry.reduce(PreviewEvent::DocFindArrived {
                 key,
                 token: stale_token,
+                images: vec!["stale.png".to_string()],
+            }),
+            Step::ImageAdded => {
+                let Some(current) = current_token(registry, key) else {
+                    return Ok(());
+                };
+                registry.reduce(PreviewEvent::ImageAdded {
+                    key,
+                    token: current,
+                    src: "diagram.png".to_string(),
+                })
+            }
+            Step::GalleryNext => registry.reduce(PreviewEvent::GalleryStep {
+                win: Some(win(1)?),
+                step: GalleryStep::Next,
+            }),
+            Step::GalleryPrev => registry.reduce(PreviewEvent::GalleryStep {
+                win: None,
+                step: GalleryStep::Prev,
             }),
+            Step::CursorOnImage => registry.reduce(PreviewEvent::CursorMoved {
+                win: win(1)?,
+                target: Some(CursorTarget::Image("/doc/a.png".to_string())),
+            }),
+            Step::CursorOnMissing => registry.reduce(PreviewEvent::CursorMoved {
+                win: win(1)?,
+                target: Some(CursorTarget::Missing {
+                    src: "b.png".to_string(),
+                    detail: "/doc/b.png".to_string(),
+                }),
+            }),
+            Step::CursorOff => registry.reduce(PreviewEvent::CursorMoved {
+                win: win(1)?,
+                target: None,
+            }),
+            Step::CursorElapsed => {
+                let Some(current) = cursor_token(registry, win(1)?) else {
+                    return Ok(());
+                };
+                registry.reduce(PreviewEvent::CursorDebounceElapsed {
+                    win: win(1)?,
+                    token: current,
+                })
+            }
+            Step::CursorCleanup => {
+                let Some(current) = cursor_token(registry, win(1)?) else {
+                    return Ok(());
+                };
+                let cleanup_id = *next_cleanup_id;
+                *next_cleanup_id += 1;
+                registry.reduce(PreviewEvent::CursorCleanupOpened {
+                    win: win(1)?,
+                    token: current,
+                    cleanup_id,
+                })
+            }
             Step::CleanupCurrent => {
                 let Some(current) = current_token(registry, key) else {
                     return Ok(());
@@ -976,8 +1791,30 @@ mod tests {
                 assert!(cleanup_id > 0);
             }
         }
-        if let Some(PreviewCommand::RequestDocFind(token)) = transition.command {
-            assert!(token.raw() > 0);
+        match transition.command {
+            Some(PreviewCommand::RequestDocFind(token)) => assert!(token.raw() > 0),
+            Some(PreviewCommand::ShowImage {
+                key, token, src, ..
+            }) => {
+                assert_eq!(current_token(registry, key), Some(token));
+                assert!(!src.is_empty());
+            }
+            Some(
+                PreviewCommand::DebounceCursor { win, token }
+                | PreviewCommand::ShowCursorImage { win, token, .. },
+            ) => assert_eq!(cursor_token(registry, win), Some(token)),
+            Some(PreviewCommand::ReportMissingImage { src, .. }) => assert!(!src.is_empty()),
+            None => {}
+        }
+        let after = registry.stats();
+        assert!(after.registrations >= before.registrations);
+        assert!(after.stale_doc_find >= before.stale_doc_find);
+        assert!(after.stale_cleanup_opened >= before.stale_cleanup_opened);
+        if matches!(step, Step::DocFindStale) {
+            assert_eq!(after.stale_doc_find, before.stale_doc_find + 1);
+        }
+        if matches!(step, Step::CleanupStale) {
+            assert_eq!(after.stale_cleanup_opened, before.stale_cleanup_opened + 1);
         }
         assert_registry_invariants(registry);
         Ok(())
@@ -1073,6 +1910,7 @@ mod tests {
                 registry.reduce(PreviewEvent::DocFindArrived {
                     key: key_a,
                     token: current,
+                    images: vec!["key_a.png".to_string(), "shared.png".to_string()],
                 })
             }
             MultiStep::DocFindCurrentB => {
@@ -1082,6 +1920,7 @@ mod tests {
                 registry.reduce(PreviewEvent::DocFindArrived {
                     key: key_b,
                     token: current,
+                    images: vec!["key_b.png".to_string(), "shared.png".to_string()],
                 })
             }
             MultiStep::CleanupStaleA => {
@@ -1109,8 +1948,20 @@ mod tests {
                 assert!(cleanup_id > 0);
             }
         }
-        if let Some(PreviewCommand::RequestDocFind(token)) = transition.command {
-            assert!(token.raw() > 0);
+        match transition.command {
+            Some(PreviewCommand::RequestDocFind(token)) => assert!(token.raw() > 0),
+            Some(PreviewCommand::ShowImage {
+                key, token, src, ..
+            }) => {
+                assert_eq!(current_token(registry, key), Some(token));
+                assert!(!src.is_empty());
+            }
+            Some(
+                PreviewCommand::DebounceCursor { win, token }
+                | PreviewCommand::ShowCursorImage { win, token, .. },
+            ) => assert_eq!(cursor_token(registry, win), Some(token)),
+            Some(PreviewCommand::ReportMissingImage { src, .. }) => assert!(!src.is_empty()),
+            None => {}
         }
         assert_registry_invariants(registry);
         Ok(())
@@ -66,6 +66,13 @@ fn reset_closes_every_owned_resource_on_its_buffer() -> Result<(), &'static str>
             0,
         )
     );
+    assert_eq!(
+        registry.stats().closes,
+        CloseCounts {
+            by_reset: 2,
+            ..CloseCounts::default()
+        }
+    );
     Ok(())
 }
 
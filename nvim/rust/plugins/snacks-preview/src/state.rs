@@ -1,12 +1,17 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::LazyLock;
 
+use crate::config::PreviewConfig;
 use crate::reducer::BufKey;
 use crate::reducer::PreviewEvent;
 use crate::reducer::PreviewRegistry;
+use crate::reducer::PreviewStats;
 use crate::reducer::PreviewToken;
 use crate::reducer::PreviewTransition;
 use crate::reducer::WinKey;
+use nvim_oxi::Dictionary;
+use nvim_oxi::Object;
 use nvim_oxi::mlua;
 use nvimrs_nvim_oxi_utils::handles::BufHandle;
 use nvimrs_nvim_oxi_utils::handles::WinHandle;
@@ -19,6 +24,9 @@ use crate::LOG_CONTEXT;
 #[derive(Debug, Default)]
 pub struct State {
     pub registry: PreviewRegistry,
+    config: PreviewConfig,
+    follow_cursor: bool,
+    missing_renderers: HashSet<String>,
     next_cleanup_id: i64,
     cleanups: HashMap<i64, mlua::RegistryKey>,
 }
@@ -49,6 +57,70 @@ impl State {
     }
 }
 
+/// Reducer counters plus what is live right now. `leaked_cleanups` counts
+/// registered cleanups that no preview owns any more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewDiagnostics {
+    pub stats: PreviewStats,
+    pub open_previews: usize,
+    pub follow_previews: usize,
+    pub pending_cleanups: usize,
+    pub leaked_cleanups: usize,
+}
+
+fn count_object(count: u64) -> Object {
+    Object::from(i64::try_from(count).unwrap_or(i64::MAX))
+}
+
+fn len_object(len: usize) -> Object {
+    Object::from(i64::try_from(len).unwrap_or(i64::MAX))
+}
+
+impl PreviewDiagnostics {
+    pub fn render(self) -> String {
+        let closes = self.stats.closes;
+        format!(
+            "open={} follow={} registrations={} replacements={} stale_doc_find={} stale_cleanup_opened={} closes(buffer={} token={} reset={}) pending_cleanups={} leaked_cleanups={}",
+            self.open_previews,
+            self.follow_previews,
+            self.stats.registrations,
+            self.stats.replacements,
+            self.stats.stale_doc_find,
+            self.stats.stale_cleanup_opened,
+            closes.by_buffer,
+            closes.by_token,
+            closes.by_reset,
+            self.pending_cleanups,
+            self.leaked_cleanups,
+        )
+    }
+
+    pub fn to_dictionary(self) -> Dictionary {
+        let closes = self.stats.closes;
+        Dictionary::from_iter([
+            ("open_previews", len_object(self.open_previews)),
+            ("follow_previews", len_object(self.follow_previews)),
+            ("registrations", count_object(self.stats.registrations)),
+            ("replacements", count_object(self.stats.replacements)),
+            ("stale_doc_find", count_object(self.stats.stale_doc_find)),
+            (
+                "stale_cleanup_opened",
+                count_object(self.stats.stale_cleanup_opened),
+            ),
+            (
+                "closes",
+                Object::from(Dictionary::from_iter([
+                    ("buffer", count_object(closes.by_buffer)),
+                    ("token", count_object(closes.by_token)),
+                    ("reset", count_object(closes.by_reset)),
+                ])),
+            ),
+            ("pending_cleanups", len_object(self.pending_cleanups)),
+            ("leaked_cleanups", len_object(self.leaked_cleanups)),
+        ])
+    }
+}
+
 #[derive(Debug)]
 pub struct PreviewContext {
     state: StateCell<State>,
@@ -70,10 +142,50 @@ impl PreviewContext {
                     "state mutex poisoned; resetting preview registry (dropping {dropped_cleanups} pending cleanups)"
                 ),
             );
-            *state = State::default();
+            *state = State {
+                config: std::mem::take(&mut state.config),
+                follow_cursor: state.follow_cursor,
+                ..State::default()
+            };
         })
     }
 
+    pub fn config(&self) -> PreviewConfig {
+        self.state_lock().config.clone()
+    }
+
+    pub fn set_config(&self, config: PreviewConfig) {
+        let mut state = self.state_lock();
+        state.config = config;
+        state.missing_renderers.clear();
+    }
+
+    pub fn follow_cursor(&self) -> bool {
+        self.state_lock().follow_cursor
+    }
+
+    /// Records whether follow mode is on and returns the previous setting.
+    pub fn set_follow_cursor(&self, enabled: bool) -> bool {
+        std::mem::replace(&mut self.state_lock().follow_cursor, enabled)
+    }
+
+    pub fn is_cursor_token_current(&self, win: WinKey, token: PreviewToken) -> bool {
+        let state = self.state_lock();
+        state.registry.is_cursor_token_current(win, token)
+    }
+
+    pub fn allows_filetype(&self, ft: &str) -> bool {
+        self.state_lock().config.allows_filetype(ft)
+    }
+
+    /// Returns `true` the first time `program` is reported missing, so the
+    /// user is warned once per session rather than on every preview.
+    pub fn note_missing_renderer(&self, program: &str) -> bool {
+        self.state_lock()
+            .missing_renderers
+            .insert(program.to_string())
+    }
+
     pub fn is_current_preview_token(&self, key: BufKey, token: PreviewToken) -> bool {
         let state = self.state_lock();
         state.registry.is_token_current(key, token)
@@ -89,6 +201,24 @@ impl PreviewContext {
         state.registry.reduce(event)
     }
 
+    pub fn diagnostics(&self) -> PreviewDiagnostics {
+        let state = self.state_lock();
+        let registry = &state.registry;
+        let owned = registry.owned_cleanups().collect::<HashSet<_>>();
+        let leaked_cleanups = state
+            .cleanups
+            .keys()
+            .filter(|cleanup_id| !owned.contains(cleanup_id))
+            .count();
+        PreviewDiagnostics {
+            stats: registry.stats(),
+            open_previews: registry.open_previews(),
+            follow_previews: registry.follow_previews(),
+            pending_cleanups: state.cleanups.len(),
+            leaked_cleanups,
+        }
+    }
+
     pub fn register_cleanup_key(&self, cleanup_key: mlua::RegistryKey) -> i64 {
         let mut state = self.state_lock();
         state.insert_cleanup(cleanup_key)
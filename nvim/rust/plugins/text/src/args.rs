@@ -0,0 +1,297 @@
+use crate::core::AlignOptions;
+use crate::core::CaseStyle;
+use crate::core::Column;
+use crate::core::Justify;
+use crate::core::SortDirection;
+use crate::core::SortKey;
+use crate::core::SortOptions;
+use crate::core::SortOrder;
+use nvim_oxi::Dictionary;
+use nvimrs_nvim_oxi_utils::Error as DecodeError;
+use nvimrs_nvim_oxi_utils::decode;
+use serde::Deserialize;
+
+#[derive(Debug)]
+pub enum ArgsError {
+    MissingKey {
+        key: String,
+    },
+    InvalidValue {
+        key: String,
+        expected: &'static str,
+    },
+    Conflict {
+        left: &'static str,
+        right: &'static str,
+    },
+    Unexpected {
+        message: String,
+    },
+}
+
+pub type ParseResult<T> = std::result::Result<T, ArgsError>;
+
+impl std::fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingKey { key } => write!(f, "missing key '{key}'"),
+            Self::InvalidValue { key, expected } => {
+                write!(f, "invalid value for '{key}', expected {expected}")
+            }
+            Self::Conflict { left, right } => {
+                write!(f, "'{left}' and '{right}' cannot be combined")
+            }
+            Self::Unexpected { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<DecodeError> for ArgsError {
+    fn from(value: DecodeError) -> Self {
+        match value {
+            DecodeError::MissingKey { key } => Self::MissingKey { key },
+            DecodeError::InvalidValue { key, expected } => Self::InvalidValue { key, expected },
+            DecodeError::EmptyValue { key } => Self::InvalidValue {
+                key,
+                expected: "non-empty value",
+            },
+            DecodeError::Unexpected { message } => Self::Unexpected { message },
+            DecodeError::Nvim(err) => Self::Unexpected {
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+/// Explicit 1-based line range, as passed by a ranged user command
+/// (`<line1>`/`<line2>`). Either bound alone targets a single line.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct RangeArgs {
+    pub start_line: Option<i64>,
+    pub end_line: Option<i64>,
+}
+
+impl RangeArgs {
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        decode::deserialize(dict).map_err(ArgsError::from)
+    }
+
+    pub const fn is_explicit(self) -> bool {
+        self.start_line.is_some() || self.end_line.is_some()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawSortArgs {
+    order: Option<String>,
+    ignore_case: bool,
+    reverse: bool,
+    unique: bool,
+    separator: Option<String>,
+    field: Option<i64>,
+    column: Option<i64>,
+    pattern: Option<String>,
+}
+
+/// Options accepted by `sort_lines_with_options`.
+///
+/// `pattern` is a Vim regex evaluated by Neovim; the first non-empty capture
+/// group (or the whole match) becomes the sort key, like `:sort /pat/ r`.
+#[derive(Debug, Default)]
+pub struct SortArgs {
+    pub options: SortOptions,
+    pub pattern: Option<String>,
+}
+
+impl SortArgs {
+    fn parse_order(value: Option<String>) -> ParseResult<SortOrder> {
+        let Some(value) = value else {
+            return Ok(SortOrder::default());
+        };
+        SortOrder::parse(&value).ok_or_else(|| ArgsError::InvalidValue {
+            key: "order".to_string(),
+            expected: "\"lexical\" | \"numeric\" | \"natural\"",
+        })
+    }
+
+    fn parse_key(
+        separator: Option<String>,
+        field: Option<i64>,
+        column: Option<i64>,
+    ) -> ParseResult<SortKey> {
+        let separator = separator.filter(|value| !value.is_empty());
+        if let Some(column) = column {
+            if field.is_some() || separator.is_some() {
+                return Err(ArgsError::Conflict {
+                    left: "column",
+                    right: "field/separator",
+                });
+            }
+            let column = usize::try_from(column).map_err(|_| ArgsError::InvalidValue {
+                key: "column".to_string(),
+                expected: "integer >= 0",
+            })?;
+            return Ok(SortKey::FromColumn(Column(column)));
+        }
+        match field {
+            None if separator.is_some() => Ok(SortKey::Field {
+                separator,
+                index: 1,
+            }),
+            None => Ok(SortKey::WholeLine),
+            Some(field) => {
+                let index = usize::try_from(field)
+                    .ok()
+                    .filter(|index| *index >= 1)
+                    .ok_or_else(|| ArgsError::InvalidValue {
+                        key: "field".to_string(),
+                        expected: "integer >= 1",
+                    })?;
+                Ok(SortKey::Field { separator, index })
+            }
+        }
+    }
+
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let raw: RawSortArgs = decode::deserialize(dict).map_err(ArgsError::from)?;
+        let pattern = raw.pattern.filter(|value| !value.is_empty());
+        if pattern.is_some()
+            && (raw.field.is_some() || raw.separator.is_some() || raw.column.is_some())
+        {
+            return Err(ArgsError::Conflict {
+                left: "pattern",
+                right: "field/separator/column",
+            });
+        }
+        let direction = if raw.reverse {
+            SortDirection::Desc
+        } else {
+            SortDirection::Asc
+        };
+        let options = SortOptions {
+            order: Self::parse_order(raw.order)?,
+            key: Self::parse_key(raw.separator, raw.field, raw.column)?,
+            direction,
+            ignore_case: raw.ignore_case,
+            unique: raw.unique,
+        };
+        Ok(Self { options, pattern })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawLayoutArgs {
+    pattern: Option<String>,
+    literal: bool,
+    justify: Option<String>,
+    /// Named `every_column` rather than `repeat`, which is a Lua keyword.
+    every_column: bool,
+    spacing: Option<i64>,
+}
+
+fn parse_justify(value: Option<String>) -> ParseResult<Justify> {
+    let Some(value) = value else {
+        return Ok(Justify::default());
+    };
+    Justify::parse(&value).ok_or_else(|| ArgsError::InvalidValue {
+        key: "justify".to_string(),
+        expected: "\"left\" | \"right\" | \"center\"",
+    })
+}
+
+fn parse_spacing(value: Option<i64>) -> ParseResult<Option<usize>> {
+    value
+        .map(|value| {
+            usize::try_from(value).map_err(|_| ArgsError::InvalidValue {
+                key: "spacing".to_string(),
+                expected: "integer >= 0",
+            })
+        })
+        .transpose()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlignSeparator {
+    /// Vim regex evaluated by Neovim.
+    Pattern(String),
+    Literal(String),
+}
+
+/// Options accepted by `align`. `pattern` is required.
+#[derive(Debug)]
+pub struct AlignArgs {
+    pub separator: AlignSeparator,
+    pub options: AlignOptions,
+}
+
+impl AlignArgs {
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let raw: RawLayoutArgs = decode::deserialize(dict).map_err(ArgsError::from)?;
+        let pattern = raw
+            .pattern
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| ArgsError::MissingKey {
+                key: "pattern".to_string(),
+            })?;
+        let separator = if raw.literal {
+            AlignSeparator::Literal(pattern)
+        } else {
+            AlignSeparator::Pattern(pattern)
+        };
+        let defaults = AlignOptions::default();
+        let options = AlignOptions {
+            justify: parse_justify(raw.justify)?,
+            repeat: raw.every_column,
+            spacing: parse_spacing(raw.spacing)?.unwrap_or(defaults.spacing),
+        };
+        Ok(Self { separator, options })
+    }
+}
+
+/// Options accepted by `columnize_lines`.
+#[derive(Debug, Default)]
+pub struct ColumnizeArgs {
+    pub options: AlignOptions,
+}
+
+impl ColumnizeArgs {
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let raw: RawLayoutArgs = decode::deserialize(dict).map_err(ArgsError::from)?;
+        let defaults = AlignOptions::default();
+        let options = AlignOptions {
+            justify: parse_justify(raw.justify)?,
+            spacing: parse_spacing(raw.spacing)?.unwrap_or(defaults.spacing),
+            ..defaults
+        };
+        Ok(Self { options })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawCaseArgs {
+    style: Option<String>,
+}
+
+/// Options accepted by `convert_case`. `style` is required.
+#[derive(Debug)]
+pub struct CaseArgs {
+    pub style: CaseStyle,
+}
+
+impl CaseArgs {
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let raw: RawCaseArgs = decode::deserialize(dict).map_err(ArgsError::from)?;
+        let value = raw.style.ok_or_else(|| ArgsError::MissingKey {
+            key: "style".to_string(),
+        })?;
+        let style = CaseStyle::parse(&value).ok_or_else(|| ArgsError::InvalidValue {
+            key: "style".to_string(),
+            expected: "\"snake\" | \"camel\" | \"pascal\" | \"kebab\" | \"screaming_snake\" | \"title\"",
+        })?;
+        Ok(Self { style })
+    }
+}
@@ -0,0 +1,324 @@
+use std::ops::Range;
+
+use super::width::CellWidths;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Justify {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+impl Justify {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "center" => Some(Self::Center),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AlignOptions {
+    pub(crate) justify: Justify,
+    /// Align on every separator in the line instead of only the first.
+    pub(crate) repeat: bool,
+    /// Spaces placed on each side of a separator.
+    pub(crate) spacing: usize,
+}
+
+impl Default for AlignOptions {
+    fn default() -> Self {
+        Self {
+            justify: Justify::default(),
+            repeat: false,
+            spacing: 1,
+        }
+    }
+}
+
+/// Byte ranges of every non-empty occurrence of `separator` in `line`.
+pub(crate) fn find_literal_separators(line: &str, separator: &str) -> Vec<Range<usize>> {
+    if separator.is_empty() {
+        return Vec::new();
+    }
+    line.match_indices(separator)
+        .map(|(start, matched)| start..start + matched.len())
+        .collect()
+}
+
+#[derive(Debug)]
+struct SplitLine<'a> {
+    cells: Vec<&'a str>,
+    separators: Vec<&'a str>,
+}
+
+fn split_line<'a>(line: &'a str, matches: &[Range<usize>], repeat: bool) -> Option<SplitLine<'a>> {
+    let limit = if repeat { matches.len() } else { 1 };
+    let mut cells = Vec::new();
+    let mut separators = Vec::new();
+    let mut cursor = 0;
+    for range in matches.iter().take(limit) {
+        if range.start < cursor || range.end > line.len() || range.start >= range.end {
+            continue;
+        }
+        let (Some(cell), Some(separator)) =
+            (line.get(cursor..range.start), line.get(range.clone()))
+        else {
+            continue;
+        };
+        let cell = if cells.is_empty() {
+            cell.trim_end()
+        } else {
+            cell.trim()
+        };
+        cells.push(cell);
+        separators.push(separator);
+        cursor = range.end;
+    }
+    if separators.is_empty() {
+        return None;
+    }
+    cells.push(line.get(cursor..).map_or("", str::trim));
+    Some(SplitLine { cells, separators })
+}
+
+fn push_spaces(out: &mut String, count: usize) {
+    out.extend(std::iter::repeat_n(' ', count));
+}
+
+fn push_justified(
+    out: &mut String,
+    text: &str,
+    width: usize,
+    justify: Justify,
+    widths: &CellWidths,
+) {
+    let padding = width.saturating_sub(widths.display_width(text));
+    let (before, after) = match justify {
+        Justify::Left => (0, padding),
+        Justify::Right => (padding, 0),
+        Justify::Center => (padding / 2, padding - padding / 2),
+    };
+    push_spaces(out, before);
+    out.push_str(text);
+    push_spaces(out, after);
+}
+
+fn widen(widths: &mut Vec<usize>, column: usize, width: usize) {
+    if widths.len() <= column {
+        widths.resize(column + 1, 0);
+    }
+    if let Some(current) = widths.get_mut(column) {
+        *current = (*current).max(width);
+    }
+}
+
+/// Pad lines so that separators line up in columns, like Emacs
+/// `align-regexp`. `matches[i]` holds the separator byte ranges found in
+/// `lines[i]`; lines without a separator are returned unchanged. Widths are
+/// measured in display cells, so wide characters line up too.
+pub(crate) fn align_lines(
+    lines: &[String],
+    matches: &[Vec<Range<usize>>],
+    options: &AlignOptions,
+    widths: &CellWidths,
+) -> Vec<String> {
+    let split: Vec<Option<SplitLine<'_>>> = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let ranges = matches.get(idx).map_or(&[][..], Vec::as_slice);
+            split_line(line, ranges, options.repeat)
+        })
+        .collect();
+
+    let mut cell_widths = Vec::new();
+    let mut separator_widths = Vec::new();
+    for parts in split.iter().flatten() {
+        for (column, separator) in parts.separators.iter().enumerate() {
+            let cell = parts.cells.get(column).copied().unwrap_or_default();
+            widen(&mut cell_widths, column, widths.display_width(cell));
+            widen(
+                &mut separator_widths,
+                column,
+                widths.display_width(separator),
+            );
+        }
+    }
+
+    lines
+        .iter()
+        .zip(split)
+        .map(|(line, parts)| {
+            let Some(parts) = parts else {
+                return line.clone();
+            };
+            let mut out = String::with_capacity(line.len());
+            for (column, separator) in parts.separators.iter().enumerate() {
+                let cell = parts.cells.get(column).copied().unwrap_or_default();
+                let width = cell_widths.get(column).copied().unwrap_or_default();
+                push_justified(&mut out, cell, width, options.justify, widths);
+                if width > 0 {
+                    push_spaces(&mut out, options.spacing);
+                }
+                let separator_width = separator_widths.get(column).copied().unwrap_or_default();
+                push_justified(&mut out, separator, separator_width, Justify::Left, widths);
+                push_spaces(&mut out, options.spacing);
+            }
+            if let Some(last) = parts.cells.last() {
+                out.push_str(last);
+            }
+            out.truncate(out.trim_end().len());
+            out
+        })
+        .collect()
+}
+
+/// Split every line on runs of whitespace and lay the fields out in
+/// columns, like `column -t`. Leading indentation of each line is kept.
+pub(crate) fn columnize_lines(
+    lines: &[String],
+    options: &AlignOptions,
+    widths: &CellWidths,
+) -> Vec<String> {
+    let fields: Vec<Vec<&str>> = lines
+        .iter()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    let mut column_widths = Vec::new();
+    for row in &fields {
+        for (column, field) in row.iter().enumerate() {
+            widen(&mut column_widths, column, widths.display_width(field));
+        }
+    }
+    lines
+        .iter()
+        .zip(&fields)
+        .map(|(line, row)| {
+            if row.is_empty() {
+                return line.clone();
+            }
+            let indent_len = line.len() - line.trim_start().len();
+            let mut out = String::with_capacity(line.len());
+            out.push_str(&line[..indent_len]);
+            for (column, field) in row.iter().enumerate() {
+                if column > 0 {
+                    push_spaces(&mut out, options.spacing.max(1));
+                }
+                let width = column_widths.get(column).copied().unwrap_or_default();
+                push_justified(&mut out, field, width, options.justify, widths);
+            }
+            out.truncate(out.trim_end().len());
+            out
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::width::test_widths;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_string()).collect()
+    }
+
+    fn align_literal(input: &[String], separator: &str, options: &AlignOptions) -> Vec<String> {
+        let matches: Vec<_> = input
+            .iter()
+            .map(|line| find_literal_separators(line, separator))
+            .collect();
+        align_lines(input, &matches, options, &test_widths(8))
+    }
+
+    #[test]
+    fn justify_parse_accepts_known_names() {
+        assert_eq!(Justify::parse("left"), Some(Justify::Left));
+        assert_eq!(Justify::parse("right"), Some(Justify::Right));
+        assert_eq!(Justify::parse("center"), Some(Justify::Center));
+        assert_eq!(Justify::parse("middle"), None);
+    }
+
+    #[test]
+    fn aligns_first_separator_and_keeps_indentation() {
+        let input = lines(&["  a = 1", "  long_name=2", "  no separator", "  bb = x = y"]);
+        let aligned = align_literal(&input, "=", &AlignOptions::default());
+        assert_eq!(
+            aligned,
+            lines(&[
+                "  a         = 1",
+                "  long_name = 2",
+                "  no separator",
+                "  bb        = x = y",
+            ])
+        );
+    }
+
+    #[test]
+    fn repeat_aligns_markdown_table_columns() {
+        let input = lines(&["| name | qty |", "|---|---|", "| apple | 3 |"]);
+        let options = AlignOptions {
+            repeat: true,
+            ..AlignOptions::default()
+        };
+        let aligned = align_literal(&input, "|", &options);
+        assert_eq!(
+            aligned,
+            lines(&["| name  | qty |", "| ---   | --- |", "| apple | 3   |"])
+        );
+    }
+
+    #[test]
+    fn right_and_center_justification() {
+        let input = lines(&["a: 1", "bbb: 2"]);
+        let right = AlignOptions {
+            justify: Justify::Right,
+            ..AlignOptions::default()
+        };
+        assert_eq!(
+            align_literal(&input, ":", &right),
+            lines(&["  a : 1", "bbb : 2"])
+        );
+        let input = lines(&["a: 1", "bbbb: 2"]);
+        let center = AlignOptions {
+            justify: Justify::Center,
+            ..AlignOptions::default()
+        };
+        assert_eq!(
+            align_literal(&input, ":", &center),
+            lines(&[" a   : 1", "bbbb : 2"])
+        );
+    }
+
+    #[test]
+    fn aligns_wide_characters_by_display_width() {
+        let input = lines(&["漢字 = 1", "ab = 2", "😀 = 3"]);
+        let aligned = align_literal(&input, "=", &AlignOptions::default());
+        assert_eq!(aligned, lines(&["漢字 = 1", "ab   = 2", "😀   = 3"]));
+    }
+
+    #[test]
+    fn separators_of_different_width_are_padded() {
+        let input = lines(&["a := 1", "bb = 2"]);
+        let matches = vec![vec![2..4], vec![3..4]];
+        let aligned = align_lines(&input, &matches, &AlignOptions::default(), &test_widths(8));
+        assert_eq!(aligned, lines(&["a  := 1", "bb =  2"]));
+    }
+
+    #[test]
+    fn columnize_lays_out_whitespace_fields() {
+        let input = lines(&["  web 30 up", "  cache   4 down", "", "  db 120"]);
+        let options = AlignOptions {
+            justify: Justify::Right,
+            ..AlignOptions::default()
+        };
+        assert_eq!(
+            columnize_lines(&input, &options, &test_widths(8)),
+            lines(&["    web  30   up", "  cache   4 down", "", "     db 120",])
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use super::width::CellWidths;
+
+/// Display-column span of a blockwise (`<C-v>`) selection. `start` is
+/// inclusive and `end` exclusive, both zero-based screen columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlockSpan {
+    start: usize,
+    end: usize,
+}
+
+/// One corner of a blockwise selection: the line text and the byte column of
+/// the mark on that line.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockCorner<'a> {
+    pub(crate) line: &'a str,
+    pub(crate) byte_col: usize,
+}
+
+impl BlockSpan {
+    /// Build the span covered by two corners, the way Vim does: from the
+    /// leftmost starting cell to the rightmost ending cell, inclusive of wide
+    /// characters under either corner.
+    pub(crate) fn from_corners(
+        first: BlockCorner<'_>,
+        second: BlockCorner<'_>,
+        widths: &CellWidths,
+    ) -> Self {
+        let (first_col, first_width) = widths.display_span_at(first.line, first.byte_col);
+        let (second_col, second_width) = widths.display_span_at(second.line, second.byte_col);
+        let start = first_col.min(second_col);
+        let end = (first_col + first_width).max(second_col + second_width);
+        Self { start, end }
+    }
+
+    /// Extend the span to the end of every line, as a `$` blockwise
+    /// selection does.
+    pub(crate) const fn to_line_end(self) -> Self {
+        Self {
+            start: self.start,
+            end: usize::MAX,
+        }
+    }
+
+    const fn width(self) -> usize {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BlockCut<'a> {
+    prefix: &'a str,
+    prefix_width: usize,
+    cell: &'a str,
+    suffix: &'a str,
+}
+
+fn cut_block<'a>(line: &'a str, span: BlockSpan, widths: &CellWidths) -> BlockCut<'a> {
+    let mut col = 0;
+    let mut cell_start = line.len();
+    let mut prefix_width = None;
+    let mut cell_end = line.len();
+    for (idx, ch) in line.char_indices() {
+        if col >= span.end {
+            cell_end = idx;
+            break;
+        }
+        if prefix_width.is_none() && col >= span.start {
+            cell_start = idx;
+            prefix_width = Some(col);
+        }
+        col += widths.advance(ch, col);
+    }
+    if prefix_width.is_none() {
+        cell_start = cell_start.min(cell_end);
+    }
+    BlockCut {
+        prefix: &line[..cell_start],
+        prefix_width: prefix_width.unwrap_or(col),
+        cell: &line[cell_start..cell_end],
+        suffix: &line[cell_end..],
+    }
+}
+
+/// The text inside `span` for every line. Short lines yield empty cells.
+pub(crate) fn block_cells(lines: &[String], span: BlockSpan, widths: &CellWidths) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| cut_block(line, span, widths).cell.to_string())
+        .collect()
+}
+
+fn push_spaces(out: &mut String, count: usize) {
+    out.extend(std::iter::repeat_n(' ', count));
+}
+
+/// Put `cells` back into the block of each line, leaving the text before and
+/// after the block untouched. Cells are padded to the block width when text
+/// follows them; missing cells (for example after uniquifying) become empty.
+pub(crate) fn replace_block_cells(
+    lines: &[String],
+    span: BlockSpan,
+    widths: &CellWidths,
+    cells: &[String],
+) -> Vec<String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let cut = cut_block(line, span, widths);
+            let cell = cells.get(idx).map_or("", String::as_str);
+            let mut out = String::with_capacity(line.len() + cell.len());
+            out.push_str(cut.prefix);
+            if cell.is_empty() && cut.suffix.is_empty() {
+                return out;
+            }
+            push_spaces(&mut out, span.start.saturating_sub(cut.prefix_width));
+            out.push_str(cell);
+            if !cut.suffix.is_empty() {
+                push_spaces(
+                    &mut out,
+                    span.width().saturating_sub(widths.display_width(cell)),
+                );
+                out.push_str(cut.suffix);
+            }
+            out
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::width::test_widths;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_string()).collect()
+    }
+
+    fn span(start: usize, end: usize) -> BlockSpan {
+        BlockSpan { start, end }
+    }
+
+    #[test]
+    fn from_corners_orders_columns_and_covers_wide_chars() {
+        let first = BlockCorner {
+            line: "ab漢d",
+            byte_col: 2,
+        };
+        let second = BlockCorner {
+            line: "x",
+            byte_col: 0,
+        };
+        assert_eq!(
+            BlockSpan::from_corners(first, second, &test_widths(8)),
+            span(0, 4)
+        );
+    }
+
+    #[test]
+    fn block_cells_extracts_columns_and_short_lines_are_empty() {
+        let input = lines(&["a1 x", "b22y", "c"]);
+        assert_eq!(
+            block_cells(&input, span(1, 3), &test_widths(8)),
+            lines(&["1 ", "22", ""])
+        );
+    }
+
+    #[test]
+    fn replace_block_cells_keeps_text_outside_block() {
+        let input = lines(&["id c: x", "id a: y", "id b: z"]);
+        let mut cells = block_cells(&input, span(3, 4), &test_widths(8));
+        cells.sort();
+        assert_eq!(
+            replace_block_cells(&input, span(3, 4), &test_widths(8), &cells),
+            lines(&["id a: x", "id b: y", "id c: z"])
+        );
+    }
+
+    #[test]
+    fn replace_block_cells_pads_shorter_cells_before_suffix() {
+        let input = lines(&["[bb]|1", "[a ]|2", "[  ]|3"]);
+        let cells = lines(&["a", "bb"]);
+        assert_eq!(
+            replace_block_cells(&input, span(1, 3), &test_widths(8), &cells),
+            lines(&["[a ]|1", "[bb]|2", "[  ]|3"])
+        );
+    }
+
+    #[test]
+    fn replace_block_cells_pads_short_lines_up_to_block_start() {
+        let input = lines(&["abcd", "a"]);
+        let cells = lines(&["", "cd"]);
+        assert_eq!(
+            replace_block_cells(&input, span(2, 4), &test_widths(8), &cells),
+            lines(&["ab", "a cd"])
+        );
+    }
+
+    #[test]
+    fn line_end_span_covers_ragged_lines() {
+        let input = lines(&["ab: 2", "abcd: 10", "a"]);
+        let span = span(2, 3).to_line_end();
+        let mut cells = block_cells(&input, span, &test_widths(8));
+        assert_eq!(cells, lines(&[": 2", "cd: 10", ""]));
+        cells.sort();
+        assert_eq!(
+            replace_block_cells(&input, span, &test_widths(8), &cells),
+            lines(&["ab", "ab: 2", "a cd: 10"])
+        );
+    }
+
+    #[test]
+    fn cut_block_uses_display_columns_for_wide_chars() {
+        let input = lines(&["漢字x", "abcd"]);
+        assert_eq!(
+            block_cells(&input, span(2, 4), &test_widths(8)),
+            lines(&["字", "cd"])
+        );
+    }
+}
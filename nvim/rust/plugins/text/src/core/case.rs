@@ -0,0 +1,557 @@
+use std::ops::Range;
+
+use super::clamp_to_boundary;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CaseStyle {
+    Snake,
+    Camel,
+    Pascal,
+    Kebab,
+    ScreamingSnake,
+    Title,
+}
+
+impl CaseStyle {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "snake" => Some(Self::Snake),
+            "camel" => Some(Self::Camel),
+            "pascal" => Some(Self::Pascal),
+            "kebab" => Some(Self::Kebab),
+            "screaming_snake" => Some(Self::ScreamingSnake),
+            "title" => Some(Self::Title),
+            _ => None,
+        }
+    }
+
+    /// Next style in the inflection cycle, like Emacs
+    /// `string-inflection-all-cycle`.
+    pub(crate) const fn next(self) -> Self {
+        match self {
+            Self::Snake => Self::ScreamingSnake,
+            Self::ScreamingSnake => Self::Pascal,
+            Self::Pascal => Self::Camel,
+            Self::Camel => Self::Kebab,
+            Self::Kebab => Self::Title,
+            Self::Title => Self::Snake,
+        }
+    }
+}
+
+/// Region transforms in the spirit of Emacs `upcase-region` and friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CaseTransform {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Convert(CaseStyle),
+    Cycle,
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Byte ranges of identifiers in `line`. A `-` joins two identifier chars so
+/// kebab-case words are picked up whole.
+fn identifier_ranges(line: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut chars = line.char_indices().peekable();
+    let mut prev_word = false;
+    while let Some((idx, ch)) = chars.next() {
+        let next_word = chars.peek().is_some_and(|(_, next)| is_word_char(*next));
+        let in_word = is_word_char(ch) || (ch == '-' && prev_word && next_word);
+        match (in_word, start) {
+            (true, None) => start = Some(idx),
+            (false, Some(from)) => {
+                ranges.push(from..idx);
+                start = None;
+            }
+            _ => {}
+        }
+        prev_word = in_word;
+    }
+    if let Some(from) = start {
+        ranges.push(from..line.len());
+    }
+    ranges
+}
+
+/// Byte range of the identifier under (or directly before) byte column
+/// `col`. Columns inside a multi-byte character are clamped to its start.
+pub(crate) fn identifier_at(line: &str, col: usize) -> Option<Range<usize>> {
+    let col = clamp_to_boundary(line, col);
+    let ranges = identifier_ranges(line);
+    ranges
+        .iter()
+        .find(|range| range.contains(&col))
+        .or_else(|| ranges.iter().find(|range| range.end == col))
+        .cloned()
+}
+
+/// True for a single capitalized word such as `Name` or `V2`.
+fn is_title_word(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+        && word.chars().all(char::is_alphanumeric)
+        && capitalize_word(word) == word
+}
+
+/// True when `text` is Title Case: two or more capitalized words separated
+/// by single spaces.
+fn is_title_phrase(text: &str) -> bool {
+    text.contains(' ') && text.split(' ').all(is_title_word)
+}
+
+/// Byte range of the Title Case phrase around the identifier under `col`,
+/// so a cycle can pick `User Name` up again as one value.
+fn title_phrase_at(line: &str, col: usize) -> Option<Range<usize>> {
+    let word = identifier_at(line, col)?;
+    if !is_title_word(&line[word.clone()]) {
+        return None;
+    }
+    let ranges = identifier_ranges(line);
+    let index = ranges.iter().position(|range| *range == word)?;
+    let joined = |left: &Range<usize>, right: &Range<usize>| {
+        right.start == left.end + 1
+            && line.as_bytes()[left.end] == b' '
+            && is_title_word(&line[left.clone()])
+            && is_title_word(&line[right.clone()])
+    };
+    let mut first = index;
+    while first > 0 && joined(&ranges[first - 1], &ranges[first]) {
+        first -= 1;
+    }
+    let mut last = index;
+    while last + 1 < ranges.len() && joined(&ranges[last], &ranges[last + 1]) {
+        last += 1;
+    }
+    (last > first).then(|| ranges[first].start..ranges[last].end)
+}
+
+/// Split `value` into words, reading it as written in `style`. Camel and
+/// Pascal case start every word after a digit with a capital, so `A0A0`
+/// is two words there; in the delimited styles an all-caps `A0A0` is one.
+fn split_words(value: &str, style: CaseStyle) -> Vec<String> {
+    let humped = matches!(style, CaseStyle::Camel | CaseStyle::Pascal);
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars().peekable();
+    let mut prev: Option<char> = None;
+    while let Some(ch) = chars.next() {
+        if !ch.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev = None;
+            continue;
+        }
+        if let Some(prev) = prev {
+            let next_lower = chars.peek().is_some_and(|next| next.is_lowercase());
+            let lower_to_upper = prev.is_lowercase() && ch.is_uppercase();
+            // `v2Api` splits after the digit, `HTTP2Server` before `Server`,
+            // but a delimited all-caps `A0A` stays one word.
+            let digit_to_upper = prev.is_numeric()
+                && ch.is_uppercase()
+                && (humped || next_lower || current.chars().any(char::is_lowercase));
+            let acronym_end = prev.is_uppercase() && ch.is_uppercase() && next_lower;
+            if (lower_to_upper || digit_to_upper || acronym_end) && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(ch);
+        prev = Some(ch);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    first
+        .to_uppercase()
+        .chain(chars.flat_map(char::to_lowercase))
+        .collect()
+}
+
+fn join_words(words: &[String], style: CaseStyle) -> String {
+    match style {
+        CaseStyle::Snake => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        CaseStyle::ScreamingSnake => words
+            .iter()
+            .map(|word| word.to_uppercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        CaseStyle::Kebab => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-"),
+        CaseStyle::Pascal => words.iter().map(|word| capitalize_word(word)).collect(),
+        CaseStyle::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(idx, word)| {
+                if idx == 0 {
+                    word.to_lowercase()
+                } else {
+                    capitalize_word(word)
+                }
+            })
+            .collect(),
+        CaseStyle::Title => words
+            .iter()
+            .map(|word| capitalize_word(word))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Best guess at the style `value` is written in. Single lowercase words
+/// count as `snake_case` and single uppercase words as `SCREAMING_SNAKE`.
+pub(crate) fn detect_style(value: &str) -> CaseStyle {
+    let has_lower = value.chars().any(char::is_lowercase);
+    let has_upper = value.chars().any(char::is_uppercase);
+    if value.contains(' ') {
+        CaseStyle::Title
+    } else if value.contains('-') {
+        CaseStyle::Kebab
+    } else if !has_lower && has_upper {
+        CaseStyle::ScreamingSnake
+    } else if value.contains('_') || !has_upper {
+        CaseStyle::Snake
+    } else if value.chars().next().is_some_and(char::is_uppercase) {
+        CaseStyle::Pascal
+    } else {
+        CaseStyle::Camel
+    }
+}
+
+/// Rewrite one identifier into `style`, keeping leading and trailing
+/// underscores (`_private`, `__init__`) in place.
+pub(crate) fn convert_identifier(value: &str, style: CaseStyle) -> String {
+    convert_identifier_from(value, detect_style(value), style)
+}
+
+/// [`convert_identifier`] for a value known to be written in `from`.
+pub(crate) fn convert_identifier_from(value: &str, from: CaseStyle, style: CaseStyle) -> String {
+    let body = value.trim_matches('_');
+    if body.is_empty() {
+        return value.to_string();
+    }
+    let leading = value.len() - value.trim_start_matches('_').len();
+    let trailing = value.len() - value.trim_end_matches('_').len();
+    let words = split_words(body, from);
+    if words.is_empty() {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len() + words.len());
+    out.push_str(&value[..leading]);
+    out.push_str(&join_words(&words, style));
+    out.push_str(&value[value.len() - trailing..]);
+    out
+}
+
+pub(crate) fn cycle_identifier(value: &str) -> String {
+    convert_identifier(value, detect_style(value).next())
+}
+
+fn capitalize_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_word = false;
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            if in_word {
+                out.extend(ch.to_lowercase());
+            } else {
+                out.extend(ch.to_uppercase());
+            }
+            in_word = true;
+        } else {
+            out.push(ch);
+            in_word = false;
+        }
+    }
+    out
+}
+
+fn map_identifiers<F>(text: &str, mut convert: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for range in identifier_ranges(text) {
+        out.push_str(&text[cursor..range.start]);
+        out.push_str(&convert(&text[range.clone()]));
+        cursor = range.end;
+    }
+    out.push_str(&text[cursor..]);
+    out
+}
+
+/// Apply `transform` to a whole region of text. Style conversions rewrite
+/// every identifier in the region and leave everything else alone.
+pub(crate) fn transform_text(text: &str, transform: CaseTransform) -> String {
+    match transform {
+        CaseTransform::Upcase => text.to_uppercase(),
+        CaseTransform::Downcase => text.to_lowercase(),
+        CaseTransform::Capitalize => capitalize_text(text),
+        CaseTransform::Convert(style) => {
+            map_identifiers(text, |ident| convert_identifier(ident, style))
+        }
+        CaseTransform::Cycle => {
+            let phrase = text.trim();
+            if is_title_phrase(phrase) {
+                let start = text.len() - text.trim_start().len();
+                let end = start + phrase.len();
+                format!(
+                    "{}{}{}",
+                    &text[..start],
+                    cycle_identifier(phrase),
+                    &text[end..]
+                )
+            } else {
+                map_identifiers(text, cycle_identifier)
+            }
+        }
+    }
+}
+
+/// Apply `transform` to the identifier under byte column `col`. Returns
+/// `None` when the cursor is not on an identifier.
+pub(crate) fn transform_word_at(
+    line: &str,
+    col: usize,
+    transform: CaseTransform,
+) -> Option<String> {
+    let range = match transform {
+        CaseTransform::Cycle => title_phrase_at(line, col).or_else(|| identifier_at(line, col)),
+        _ => identifier_at(line, col),
+    }?;
+    let word = transform_text(&line[range.clone()], transform);
+    let mut out = String::with_capacity(line.len() + word.len());
+    out.push_str(&line[..range.start]);
+    out.push_str(&word);
+    out.push_str(&line[range.end..]);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn case_style_parse_accepts_known_names() {
+        assert_eq!(CaseStyle::parse("snake"), Some(CaseStyle::Snake));
+        assert_eq!(
+            CaseStyle::parse("screaming_snake"),
+            Some(CaseStyle::ScreamingSnake)
+        );
+        assert_eq!(CaseStyle::parse("title"), Some(CaseStyle::Title));
+        assert_eq!(CaseStyle::parse("SNAKE"), None);
+    }
+
+    #[test]
+    fn converts_between_styles() {
+        let cases = [
+            ("user_id", CaseStyle::Camel, "userId"),
+            ("user_id", CaseStyle::Pascal, "UserId"),
+            ("userId", CaseStyle::Snake, "user_id"),
+            ("HTTPServerError", CaseStyle::Snake, "http_server_error"),
+            ("parse-json-v2", CaseStyle::ScreamingSnake, "PARSE_JSON_V2"),
+            ("MAX_LEN", CaseStyle::Kebab, "max-len"),
+            ("getV2Api", CaseStyle::Snake, "get_v2_api"),
+            ("HTTP2Server", CaseStyle::Snake, "http2_server"),
+            ("v2Api", CaseStyle::Snake, "v2_api"),
+            ("http2_server", CaseStyle::Pascal, "Http2Server"),
+            ("Http2Server", CaseStyle::Snake, "http2_server"),
+            ("some_value", CaseStyle::Title, "Some Value"),
+            ("__init__", CaseStyle::Pascal, "__Init__"),
+            ("_private_name", CaseStyle::Camel, "_privateName"),
+        ];
+        for (input, style, expected) in cases {
+            assert_eq!(
+                convert_identifier(input, style),
+                expected,
+                "{input} -> {style:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn converts_non_ascii_identifiers() {
+        assert_eq!(
+            convert_identifier("straße_größe", CaseStyle::Pascal),
+            "StraßeGröße"
+        );
+        assert_eq!(
+            convert_identifier("ÉtéChaud", CaseStyle::Snake),
+            "été_chaud"
+        );
+    }
+
+    #[test]
+    fn detect_style_cases() {
+        assert_eq!(detect_style("foo_bar"), CaseStyle::Snake);
+        assert_eq!(detect_style("foo"), CaseStyle::Snake);
+        assert_eq!(detect_style("FOO_BAR"), CaseStyle::ScreamingSnake);
+        assert_eq!(detect_style("FooBar"), CaseStyle::Pascal);
+        assert_eq!(detect_style("fooBar"), CaseStyle::Camel);
+        assert_eq!(detect_style("foo-bar"), CaseStyle::Kebab);
+        assert_eq!(detect_style("Foo Bar"), CaseStyle::Title);
+    }
+
+    #[test]
+    fn cycle_walks_all_identifier_styles() {
+        let mut value = "user_name".to_string();
+        let mut seen = Vec::new();
+        for _ in 0..6 {
+            value = cycle_identifier(&value);
+            seen.push(value.clone());
+        }
+        assert_eq!(
+            seen,
+            vec![
+                "USER_NAME",
+                "UserName",
+                "userName",
+                "user-name",
+                "User Name",
+                "user_name"
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle_picks_up_title_phrases_under_the_cursor() {
+        assert_eq!(
+            transform_word_at("x = User Name;", 9, CaseTransform::Cycle),
+            Some("x = user_name;".to_string())
+        );
+        assert_eq!(
+            transform_word_at("x = user-name;", 6, CaseTransform::Cycle),
+            Some("x = User Name;".to_string())
+        );
+        assert_eq!(
+            transform_text(" Http2 Server ", CaseTransform::Cycle),
+            " http2_server "
+        );
+        assert_eq!(
+            transform_word_at("use Foo as bar", 5, CaseTransform::Cycle),
+            Some("use foo as bar".to_string())
+        );
+    }
+
+    #[test]
+    fn humped_styles_split_after_digits() {
+        assert_eq!(
+            convert_identifier_from("A0A0", CaseStyle::Pascal, CaseStyle::Snake),
+            "a0_a0"
+        );
+        assert_eq!(
+            convert_identifier_from("A0A0", CaseStyle::ScreamingSnake, CaseStyle::Snake),
+            "a0a0"
+        );
+    }
+
+    #[test]
+    fn transform_word_at_uses_identifier_under_cursor() {
+        let line = "let my_value = other-name;";
+        assert_eq!(
+            transform_word_at(line, 6, CaseTransform::Convert(CaseStyle::Camel)),
+            Some("let myValue = other-name;".to_string())
+        );
+        assert_eq!(
+            transform_word_at(line, 16, CaseTransform::Cycle),
+            Some("let my_value = Other Name;".to_string())
+        );
+        assert_eq!(
+            transform_word_at(line, 3, CaseTransform::Upcase),
+            Some("LET my_value = other-name;".to_string())
+        );
+        assert_eq!(transform_word_at("a = b", 2, CaseTransform::Upcase), None);
+    }
+
+    #[test]
+    fn transform_text_handles_regions() {
+        assert_eq!(
+            transform_text("hello wORLD-wide", CaseTransform::Capitalize),
+            "Hello World-Wide"
+        );
+        assert_eq!(
+            transform_text(
+                "fooBar(baz_qux, 1)",
+                CaseTransform::Convert(CaseStyle::Snake)
+            ),
+            "foo_bar(baz_qux, 1)"
+        );
+        assert_eq!(transform_text("Straße", CaseTransform::Upcase), "STRASSE");
+    }
+
+    // Words need a second character: once capitalized, `a` + `b` reads as
+    // the acronym `AB`, which no splitter can undo.
+    fn word_strategy() -> impl Strategy<Value = String> {
+        "[a-z][a-z0-9]{1,4}"
+    }
+
+    proptest! {
+        #[test]
+        fn snake_round_trips_through_every_style(
+            words in prop::collection::vec(word_strategy(), 1..5),
+            style_index in 0_usize..6,
+        ) {
+            let styles = [
+                CaseStyle::Snake,
+                CaseStyle::Camel,
+                CaseStyle::Pascal,
+                CaseStyle::Kebab,
+                CaseStyle::ScreamingSnake,
+                CaseStyle::Title,
+            ];
+            let snake = words.join("_");
+            let style = styles[style_index];
+            let converted = convert_identifier(&snake, style);
+            prop_assert_eq!(
+                convert_identifier_from(&converted, style, CaseStyle::Snake),
+                snake
+            );
+        }
+
+        #[test]
+        fn transform_word_at_never_panics_on_unicode(
+            line in "\\PC{0,24}",
+            col in 0_usize..64,
+            style_index in 0_usize..5,
+        ) {
+            let transforms = [
+                CaseTransform::Upcase,
+                CaseTransform::Downcase,
+                CaseTransform::Capitalize,
+                CaseTransform::Convert(CaseStyle::Camel),
+                CaseTransform::Cycle,
+            ];
+            let _ = transform_word_at(&line, col, transforms[style_index]);
+        }
+
+        #[test]
+        fn downcase_after_upcase_matches_downcase_for_ascii(text in "[ -~]{0,32}") {
+            let upcased = transform_text(&text, CaseTransform::Upcase);
+            prop_assert_eq!(
+                transform_text(&upcased, CaseTransform::Downcase),
+                transform_text(&text, CaseTransform::Downcase)
+            );
+        }
+    }
+}
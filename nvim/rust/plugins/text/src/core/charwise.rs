@@ -0,0 +1,145 @@
+use super::clamp_to_boundary;
+
+/// Byte span of a charwise (`v`) selection. `start` is inclusive on the
+/// first line and `end` exclusive on the last line; lines in between are
+/// covered completely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CharSpan {
+    start: usize,
+    end: usize,
+}
+
+impl CharSpan {
+    /// Build the span from the byte columns of the selection start and end.
+    /// The character under `end_col` is included, and `to_line_end` (a `$`
+    /// selection) extends the span to the end of the last line.
+    pub(crate) fn from_marks(
+        first_line: &str,
+        start_col: usize,
+        last_line: &str,
+        end_col: usize,
+        to_line_end: bool,
+    ) -> Self {
+        let start = clamp_to_boundary(first_line, start_col);
+        let end = if to_line_end || end_col >= last_line.len() {
+            last_line.len()
+        } else {
+            let end_col = clamp_to_boundary(last_line, end_col);
+            last_line[end_col..]
+                .chars()
+                .next()
+                .map_or(last_line.len(), |ch| end_col + ch.len_utf8())
+        };
+        Self { start, end }
+    }
+
+    /// Byte bounds of the selected text on line `idx` of `count` lines.
+    fn bounds(self, line: &str, idx: usize, count: usize) -> (usize, usize) {
+        let start = if idx == 0 {
+            self.start.min(line.len())
+        } else {
+            0
+        };
+        let end = if idx + 1 == count {
+            self.end.min(line.len())
+        } else {
+            line.len()
+        };
+        (start, end.max(start))
+    }
+}
+
+/// The selected text of every line: the tail of the first line, the middle
+/// lines whole and the head of the last line.
+pub(crate) fn charwise_cells(lines: &[String], span: CharSpan) -> Vec<String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let (start, end) = span.bounds(line, idx, lines.len());
+            line[start..end].to_string()
+        })
+        .collect()
+}
+
+/// Put `cells` back between the text before the selection on the first line
+/// and the text after it on the last line. `cells` may hold fewer lines than
+/// were selected (for example after uniquifying).
+pub(crate) fn replace_charwise_cells(
+    lines: &[String],
+    span: CharSpan,
+    cells: &[String],
+) -> Vec<String> {
+    let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+        return cells.to_vec();
+    };
+    let (prefix_end, _) = span.bounds(first, 0, lines.len());
+    let (_, suffix_start) = span.bounds(last, lines.len() - 1, lines.len());
+    let prefix = &first[..prefix_end];
+    let suffix = &last[suffix_start..];
+    let mut out = if cells.is_empty() {
+        vec![String::new()]
+    } else {
+        cells.to_vec()
+    };
+    out[0].insert_str(0, prefix);
+    if let Some(tail) = out.last_mut() {
+        tail.push_str(suffix);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_string()).collect()
+    }
+
+    #[test]
+    fn from_marks_includes_the_character_under_the_end_mark() {
+        let span = CharSpan::from_marks("ab漢d", 1, "ab漢d", 2, false);
+        assert_eq!(span, CharSpan { start: 1, end: 5 });
+        let span = CharSpan::from_marks("abc", 1, "xyz", 0, true);
+        assert_eq!(span, CharSpan { start: 1, end: 3 });
+    }
+
+    #[test]
+    fn charwise_cells_cut_first_and_last_lines() {
+        let input = lines(&["let b = 2;", "let a = 1;", "let c = 3;"]);
+        let span = CharSpan::from_marks(&input[0], 4, &input[2], 4, false);
+        assert_eq!(
+            charwise_cells(&input, span),
+            lines(&["b = 2;", "let a = 1;", "let c"])
+        );
+        let single = lines(&["foo bar baz"]);
+        let span = CharSpan::from_marks(&single[0], 4, &single[0], 6, false);
+        assert_eq!(charwise_cells(&single, span), lines(&["bar"]));
+    }
+
+    #[test]
+    fn replace_charwise_cells_keeps_text_outside_the_selection() {
+        let input = lines(&["x = [b,", "a,", "c] + y"]);
+        let span = CharSpan::from_marks(&input[0], 5, &input[2], 1, false);
+        let mut cells = charwise_cells(&input, span);
+        assert_eq!(cells, lines(&["b,", "a,", "c]"]));
+        cells.sort();
+        assert_eq!(
+            replace_charwise_cells(&input, span, &cells),
+            lines(&["x = [a,", "b,", "c] + y"])
+        );
+    }
+
+    #[test]
+    fn replace_charwise_cells_joins_prefix_and_suffix_when_cells_shrink() {
+        let input = lines(&["<a", "a>"]);
+        let span = CharSpan::from_marks(&input[0], 1, &input[1], 0, false);
+        let cells = lines(&["a"]);
+        assert_eq!(
+            replace_charwise_cells(&input, span, &cells),
+            lines(&["<a>"])
+        );
+        assert_eq!(replace_charwise_cells(&input, span, &[]), lines(&["<>"]));
+    }
+}
@@ -1,10 +1,38 @@
 use std::cmp::Ordering;
 use std::collections::HashSet;
 
+mod align;
+mod block;
+mod case;
+mod charwise;
+mod sort;
 mod types;
-
+mod width;
+
+pub(crate) use align::AlignOptions;
+pub(crate) use align::Justify;
+pub(crate) use align::align_lines;
+pub(crate) use align::columnize_lines;
+pub(crate) use align::find_literal_separators;
+pub(crate) use block::BlockCorner;
+pub(crate) use block::BlockSpan;
+pub(crate) use block::block_cells;
+pub(crate) use block::replace_block_cells;
+pub(crate) use case::CaseStyle;
+pub(crate) use case::CaseTransform;
+pub(crate) use case::transform_text;
+pub(crate) use case::transform_word_at;
+pub(crate) use charwise::CharSpan;
+pub(crate) use charwise::charwise_cells;
+pub(crate) use charwise::replace_charwise_cells;
+pub(crate) use sort::SortKey;
+pub(crate) use sort::SortOptions;
+pub(crate) use sort::SortOrder;
+pub(crate) use sort::sort_lines_by_keys;
+pub(crate) use sort::sort_lines_with_options;
 pub(crate) use types::LineRange;
 pub(crate) use types::TextRangeError;
+pub(crate) use width::CellWidths;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct Column(pub(crate) usize);
@@ -70,7 +98,7 @@ fn stable_cmp(
     }
 }
 
-fn sort_with<F>(lines: &[String], mut cmp: F) -> Vec<String>
+fn sort_indexed_with<F>(lines: &[String], mut cmp: F) -> Vec<IndexedLine>
 where
     F: FnMut(&IndexedLine, &IndexedLine) -> Ordering,
 {
@@ -81,7 +109,17 @@ where
         .map(|(idx, line)| IndexedLine { idx, line })
         .collect();
     indexed.sort_by(|left, right| cmp(left, right));
-    indexed.into_iter().map(|entry| entry.line).collect()
+    indexed
+}
+
+fn sort_with<F>(lines: &[String], cmp: F) -> Vec<String>
+where
+    F: FnMut(&IndexedLine, &IndexedLine) -> Ordering,
+{
+    sort_indexed_with(lines, cmp)
+        .into_iter()
+        .map(|entry| entry.line)
+        .collect()
 }
 
 pub(crate) fn sort_lines(lines: &[String], direction: SortDirection) -> Vec<String> {
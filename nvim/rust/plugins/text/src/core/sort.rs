@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+
+use super::Column;
+use super::SortDirection;
+use super::slice_from_column;
+use super::sort_indexed_with;
+use super::stable_cmp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum SortOrder {
+    /// Raw `str` ordering, like plain `:sort`.
+    #[default]
+    Lexical,
+    /// Compare the first decimal integer in the key, like `:sort n`.
+    /// Keys without a number sort before keys with one.
+    Numeric,
+    /// Compare digit runs by value and everything else lexically, so
+    /// `v2` sorts before `v10`.
+    Natural,
+}
+
+impl SortOrder {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "lexical" => Some(Self::Lexical),
+            "numeric" => Some(Self::Numeric),
+            "natural" => Some(Self::Natural),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) enum SortKey {
+    #[default]
+    WholeLine,
+    /// Everything from the byte column to the end of the line.
+    FromColumn(Column),
+    /// A single 1-based field. `None` splits on runs of whitespace like
+    /// `sort -k`; `Some(sep)` splits on the literal separator like `sort -t`.
+    Field {
+        separator: Option<String>,
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SortOptions {
+    pub(crate) order: SortOrder,
+    pub(crate) key: SortKey,
+    pub(crate) direction: SortDirection,
+    pub(crate) ignore_case: bool,
+    pub(crate) unique: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            order: SortOrder::default(),
+            key: SortKey::default(),
+            direction: SortDirection::Asc,
+            ignore_case: false,
+            unique: false,
+        }
+    }
+}
+
+fn field_at<'a>(line: &'a str, separator: Option<&str>, index: usize) -> Option<&'a str> {
+    let position = index.checked_sub(1)?;
+    match separator {
+        Some(separator) if !separator.is_empty() => line.split(separator).nth(position),
+        _ => line.split_whitespace().nth(position),
+    }
+}
+
+fn extract_sort_key<'a>(line: &'a str, key: &SortKey) -> Option<&'a str> {
+    match key {
+        SortKey::WholeLine => Some(line),
+        SortKey::FromColumn(column) => Some(slice_from_column(line, *column)),
+        SortKey::Field { separator, index } => field_at(line, separator.as_deref(), *index),
+    }
+}
+
+fn cmp_text(left: &str, right: &str, ignore_case: bool) -> Ordering {
+    if ignore_case {
+        left.chars()
+            .flat_map(char::to_lowercase)
+            .cmp(right.chars().flat_map(char::to_lowercase))
+    } else {
+        left.cmp(right)
+    }
+}
+
+/// Compare two runs of ASCII digits by value without parsing, so arbitrarily
+/// long numbers never overflow.
+fn cmp_digit_runs(left: &str, right: &str) -> Ordering {
+    let left = left.trim_start_matches('0');
+    let right = right.trim_start_matches('0');
+    left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NumericKey<'a> {
+    negative: bool,
+    digits: &'a str,
+}
+
+fn first_number(key: &str) -> Option<NumericKey<'_>> {
+    let start = key.find(|ch: char| ch.is_ascii_digit())?;
+    let rest = &key[start..];
+    let len = rest
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let digits = &rest[..len];
+    let is_zero = digits.bytes().all(|byte| byte == b'0');
+    let negative = !is_zero && key[..start].ends_with('-');
+    Some(NumericKey { negative, digits })
+}
+
+fn cmp_numeric(left: &str, right: &str) -> Ordering {
+    match (first_number(left), first_number(right)) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(left), Some(right)) => match (left.negative, right.negative) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => cmp_digit_runs(left.digits, right.digits),
+            (true, true) => cmp_digit_runs(right.digits, left.digits),
+        },
+    }
+}
+
+fn split_chunk(value: &str) -> Option<(&str, &str, bool)> {
+    let first = value.chars().next()?;
+    let is_digit = first.is_ascii_digit();
+    let len = value
+        .find(|ch: char| ch.is_ascii_digit() != is_digit)
+        .unwrap_or(value.len());
+    Some((&value[..len], &value[len..], is_digit))
+}
+
+fn cmp_natural(mut left: &str, mut right: &str, ignore_case: bool) -> Ordering {
+    loop {
+        let Some((left_chunk, left_rest, left_digit)) = split_chunk(left) else {
+            return if right.is_empty() {
+                Ordering::Equal
+            } else {
+                Ordering::Less
+            };
+        };
+        let Some((right_chunk, right_rest, right_digit)) = split_chunk(right) else {
+            return Ordering::Greater;
+        };
+        let order = match (left_digit, right_digit) {
+            (true, true) => cmp_digit_runs(left_chunk, right_chunk),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => cmp_text(left_chunk, right_chunk, ignore_case),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+        left = left_rest;
+        right = right_rest;
+    }
+}
+
+fn cmp_keys(left: Option<&str>, right: Option<&str>, options: &SortOptions) -> Ordering {
+    let (left, right) = match (left, right) {
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) => return Ordering::Less,
+        (Some(_), None) => return Ordering::Greater,
+        (Some(left), Some(right)) => (left, right),
+    };
+    match options.order {
+        SortOrder::Lexical => cmp_text(left, right, options.ignore_case),
+        SortOrder::Numeric => cmp_numeric(left, right),
+        SortOrder::Natural => cmp_natural(left, right, options.ignore_case),
+    }
+}
+
+fn sort_keyed<'k, K>(lines: &[String], key_for: K, options: &SortOptions) -> Vec<String>
+where
+    K: Fn(usize) -> Option<&'k str>,
+{
+    let sorted = sort_indexed_with(lines, |left, right| {
+        let order = cmp_keys(key_for(left.idx), key_for(right.idx), options);
+        stable_cmp(order, options.direction, left.idx, right.idx)
+    });
+    if !options.unique {
+        return sorted.into_iter().map(|entry| entry.line).collect();
+    }
+    let mut out: Vec<String> = Vec::with_capacity(sorted.len());
+    let mut last_idx: Option<usize> = None;
+    for entry in sorted {
+        let duplicate = last_idx.is_some_and(|last| {
+            cmp_keys(key_for(last), key_for(entry.idx), options) == Ordering::Equal
+        });
+        if !duplicate {
+            last_idx = Some(entry.idx);
+            out.push(entry.line);
+        }
+    }
+    out
+}
+
+/// Sort lines using keys extracted according to `options.key`.
+pub(crate) fn sort_lines_with_options(lines: &[String], options: &SortOptions) -> Vec<String> {
+    sort_keyed(
+        lines,
+        |idx| {
+            lines
+                .get(idx)
+                .and_then(|line| extract_sort_key(line, &options.key))
+        },
+        options,
+    )
+}
+
+/// Sort lines using precomputed keys, one per line. Lines whose key is `None`
+/// (for example a pattern that did not match) keep their relative order and
+/// sort before every keyed line, like `:sort /pat/`.
+pub(crate) fn sort_lines_by_keys(
+    lines: &[String],
+    keys: &[Option<String>],
+    options: &SortOptions,
+) -> Vec<String> {
+    sort_keyed(
+        lines,
+        |idx| keys.get(idx).and_then(Option::as_deref),
+        options,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_string()).collect()
+    }
+
+    #[test]
+    fn sort_order_parse_accepts_known_names() {
+        assert_eq!(SortOrder::parse("lexical"), Some(SortOrder::Lexical));
+        assert_eq!(SortOrder::parse("numeric"), Some(SortOrder::Numeric));
+        assert_eq!(SortOrder::parse("natural"), Some(SortOrder::Natural));
+        assert_eq!(SortOrder::parse("Natural"), None);
+    }
+
+    #[test]
+    fn natural_order_compares_digit_runs_by_value() {
+        let options = SortOptions {
+            order: SortOrder::Natural,
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(&lines(&["x:2", "x:10", "x:1"]), &options);
+        assert_eq!(sorted, lines(&["x:1", "x:2", "x:10"]));
+    }
+
+    #[test]
+    fn natural_order_handles_version_lists() {
+        let options = SortOptions {
+            order: SortOrder::Natural,
+            ..SortOptions::default()
+        };
+        let sorted =
+            sort_lines_with_options(&lines(&["v1.10.0", "v1.2.10", "v1.2.9", "v0.9"]), &options);
+        assert_eq!(sorted, lines(&["v0.9", "v1.2.9", "v1.2.10", "v1.10.0"]));
+    }
+
+    #[test]
+    fn numeric_order_uses_first_number_and_sign() {
+        let options = SortOptions {
+            order: SortOrder::Numeric,
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(
+            &lines(&["b 10", "none", "a -3", "c 2", "d 00002"]),
+            &options,
+        );
+        assert_eq!(sorted, lines(&["none", "a -3", "c 2", "d 00002", "b 10"]));
+    }
+
+    #[test]
+    fn numeric_order_does_not_overflow_on_long_numbers() {
+        let options = SortOptions {
+            order: SortOrder::Numeric,
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(
+            &lines(&["99999999999999999999999", "100000000000000000000000", "7"]),
+            &options,
+        );
+        assert_eq!(
+            sorted,
+            lines(&["7", "99999999999999999999999", "100000000000000000000000"])
+        );
+    }
+
+    #[test]
+    fn ignore_case_folds_lexical_comparison() {
+        let options = SortOptions {
+            ignore_case: true,
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(&lines(&["beta", "Alpha", "alpha", "Beta"]), &options);
+        assert_eq!(sorted, lines(&["Alpha", "alpha", "beta", "Beta"]));
+    }
+
+    #[test]
+    fn unique_drops_lines_with_equal_keys() {
+        let options = SortOptions {
+            ignore_case: true,
+            unique: true,
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(&lines(&["b", "A", "a", "B", "c"]), &options);
+        assert_eq!(sorted, lines(&["A", "b", "c"]));
+    }
+
+    #[test]
+    fn reverse_keeps_equal_keys_stable() {
+        let options = SortOptions {
+            order: SortOrder::Numeric,
+            direction: SortDirection::Desc,
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(&lines(&["a 1", "b 2", "c 1"]), &options);
+        assert_eq!(sorted, lines(&["b 2", "a 1", "c 1"]));
+    }
+
+    #[test]
+    fn field_key_splits_on_whitespace_by_default() {
+        let options = SortOptions {
+            order: SortOrder::Numeric,
+            key: SortKey::Field {
+                separator: None,
+                index: 2,
+            },
+            ..SortOptions::default()
+        };
+        let sorted =
+            sort_lines_with_options(&lines(&["web   30  x", "db 4 y", "cache 12 z"]), &options);
+        assert_eq!(sorted, lines(&["db 4 y", "cache 12 z", "web   30  x"]));
+    }
+
+    #[test]
+    fn field_key_uses_literal_separator() {
+        let options = SortOptions {
+            key: SortKey::Field {
+                separator: Some("|".to_string()),
+                index: 3,
+            },
+            ..SortOptions::default()
+        };
+        let sorted = sort_lines_with_options(&lines(&["a|b|z", "c|d|m", "short"]), &options);
+        assert_eq!(sorted, lines(&["short", "c|d|m", "a|b|z"]));
+    }
+
+    #[test]
+    fn sort_lines_by_keys_puts_unmatched_lines_first() {
+        let options = SortOptions {
+            order: SortOrder::Natural,
+            ..SortOptions::default()
+        };
+        let input = lines(&["id=10", "header", "id=9", "footer"]);
+        let keys = vec![Some("10".to_string()), None, Some("9".to_string()), None];
+        let sorted = sort_lines_by_keys(&input, &keys, &options);
+        assert_eq!(sorted, lines(&["header", "footer", "id=9", "id=10"]));
+    }
+}
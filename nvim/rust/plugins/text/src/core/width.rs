@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+/// Screen cells per character as Neovim draws them. ASCII is fixed; every
+/// other character is measured at the plugin boundary (with
+/// `strdisplaywidth()`) so wide CJK, emoji and combining marks follow the
+/// editor instead of a private table. Unmeasured characters count as one
+/// cell.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CellWidths {
+    tabstop: usize,
+    measured: HashMap<char, usize>,
+}
+
+impl CellWidths {
+    pub(crate) fn new(tabstop: usize, measured: HashMap<char, usize>) -> Self {
+        Self { tabstop, measured }
+    }
+
+    /// The non-ASCII characters of `lines`, which need measuring.
+    pub(crate) fn chars_to_measure<'a, I>(lines: I) -> BTreeSet<char>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        lines
+            .into_iter()
+            .flat_map(str::chars)
+            .filter(|ch| !ch.is_ascii())
+            .collect()
+    }
+
+    /// Number of screen cells `ch` occupies, ignoring tabs.
+    pub(crate) fn char_width(&self, ch: char) -> usize {
+        if ch.is_ascii() {
+            return usize::from(!ch.is_ascii_control());
+        }
+        self.measured.get(&ch).copied().unwrap_or(1)
+    }
+
+    /// Width of `ch` when drawn starting at screen column `col`.
+    pub(crate) fn advance(&self, ch: char, col: usize) -> usize {
+        if ch == '\t' && self.tabstop > 0 {
+            self.tabstop - (col % self.tabstop)
+        } else {
+            self.char_width(ch)
+        }
+    }
+
+    /// Display width of `text` drawn from column zero.
+    pub(crate) fn display_width(&self, text: &str) -> usize {
+        text.chars().fold(0, |col, ch| col + self.advance(ch, col))
+    }
+
+    /// Display column at which the character starting at byte `byte`
+    /// begins, and the number of cells it spans. Bytes past the end report
+    /// the end column and a width of one so a cursor there still selects a
+    /// cell.
+    pub(crate) fn display_span_at(&self, line: &str, byte: usize) -> (usize, usize) {
+        let mut col = 0;
+        for (idx, ch) in line.char_indices() {
+            let width = self.advance(ch, col);
+            if idx + ch.len_utf8() > byte {
+                return (col, width.max(1));
+            }
+            col += width;
+        }
+        (col, 1)
+    }
+}
+
+/// Widths for tests: the CJK and emoji characters they use are two cells and
+/// combining marks are zero.
+#[cfg(test)]
+pub(crate) fn test_widths(tabstop: usize) -> CellWidths {
+    let measured = ['漢', '字', '가', '😀']
+        .into_iter()
+        .map(|ch| (ch, 2))
+        .chain([('\u{0301}', 0), ('\u{200D}', 0)])
+        .collect();
+    CellWidths::new(tabstop, measured)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_width_uses_measured_widths_beyond_ascii() {
+        let widths = test_widths(8);
+        assert_eq!(widths.char_width('a'), 1);
+        assert_eq!(widths.char_width('\u{7}'), 0);
+        assert_eq!(widths.char_width('漢'), 2);
+        assert_eq!(widths.char_width('\u{0301}'), 0);
+        assert_eq!(widths.char_width('é'), 1);
+    }
+
+    #[test]
+    fn chars_to_measure_collects_distinct_non_ascii() {
+        let chars = CellWidths::chars_to_measure(["a漢b", "漢字\t"]);
+        assert_eq!(chars.into_iter().collect::<Vec<_>>(), vec!['字', '漢']);
+    }
+
+    #[test]
+    fn display_width_expands_tabs_to_tabstop() {
+        assert_eq!(test_widths(4).display_width("a\tb"), 5);
+        assert_eq!(test_widths(4).display_width("abcd\tb"), 9);
+        assert_eq!(test_widths(8).display_width("漢字"), 4);
+    }
+
+    #[test]
+    fn display_span_at_reports_start_and_width() {
+        let widths = test_widths(8);
+        let line = "a漢b";
+        assert_eq!(widths.display_span_at(line, 0), (0, 1));
+        assert_eq!(widths.display_span_at(line, 1), (1, 2));
+        assert_eq!(widths.display_span_at(line, 2), (1, 2));
+        assert_eq!(widths.display_span_at(line, 4), (3, 1));
+        assert_eq!(widths.display_span_at(line, 9), (4, 1));
+    }
+}
@@ -1,23 +1,49 @@
+mod args;
 mod core;
 
+use std::collections::HashMap;
+use std::ops::Range;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
+use crate::args::AlignArgs;
+use crate::args::AlignSeparator;
+use crate::args::ArgsError;
+use crate::args::CaseArgs;
+use crate::args::ColumnizeArgs;
+use crate::args::RangeArgs;
+use crate::args::SortArgs;
+use crate::core::BlockCorner;
+use crate::core::BlockSpan;
+use crate::core::CaseTransform;
+use crate::core::CellWidths;
+use crate::core::CharSpan;
 use crate::core::Column;
 use crate::core::LineRange;
 use crate::core::SortDirection;
 use crate::core::TextRangeError;
+use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Function;
+use nvim_oxi::Object;
 use nvim_oxi::Result;
+use nvim_oxi::String as NvimString;
 use nvim_oxi::api;
 use nvim_oxi::api::Buffer;
+use nvim_oxi::api::opts::OptionOpts;
+use nvim_oxi::conversion::FromObject;
+use nvimrs_nvim_utils::mode::is_blockwise_visual_mode;
+use nvimrs_nvim_utils::mode::is_charwise_visual_mode;
 use nvimrs_nvim_utils::mode::is_visual_like_mode;
 
 fn text_error_to_nvim(err: TextRangeError) -> nvim_oxi::Error {
     nvim_oxi::api::Error::Other(err.to_string()).into()
 }
 
+fn args_error_to_nvim(err: ArgsError) -> nvim_oxi::Error {
+    nvim_oxi::api::Error::Other(err.to_string()).into()
+}
+
 fn resolve_line_range(
     buf: &Buffer,
     start_line: Option<i64>,
@@ -39,32 +65,194 @@ fn line_index_to_i64(line: usize) -> Result<i64> {
         .map_err(|_| nvim_oxi::api::Error::Other("line index overflow".into()).into())
 }
 
-fn is_visual_mode() -> bool {
-    let mode = api::get_mode();
-    is_visual_like_mode(&mode.mode.to_string_lossy())
+#[derive(Debug, Clone, Copy)]
+struct BlockTarget {
+    span: BlockSpan,
+    tabstop: usize,
+}
+
+/// The part of each target line an operation sees.
+#[derive(Debug, Clone, Copy)]
+enum TargetShape {
+    Lines,
+    Chars(CharSpan),
+    Block(BlockTarget),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TextTarget {
+    range: LineRange,
+    shape: TargetShape,
+    /// True when neither a range nor a selection was given and the target
+    /// fell back to the cursor line.
+    from_cursor: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisualKind {
+    Charwise,
+    Linewise,
+    Blockwise,
+}
+
+/// A live visual selection: `start` and `end` are `(line, byte col)` in
+/// buffer order, and `to_line_end` is set after `$`.
+#[derive(Debug, Clone, Copy)]
+struct VisualSelection {
+    start: (usize, usize),
+    end: (usize, usize),
+    kind: VisualKind,
+    to_line_end: bool,
 }
 
-fn visual_line_range(buf: &Buffer) -> Result<Option<(i64, i64)>> {
-    if !is_visual_mode() {
+/// `v:maxcol`, the `curswant` Vim reports after `$`.
+const MAXCOL: i64 = 2_147_483_647;
+
+fn current_mode() -> String {
+    api::get_mode().mode.to_string_lossy().into_owned()
+}
+
+/// `(line, zero-based byte col)` of `getpos(expr)`.
+fn get_pos(expr: &str) -> Result<(usize, usize)> {
+    let pos: Vec<i64> = api::call_function("getpos", Array::from_iter([expr]))?;
+    let line = pos.get(1).copied().unwrap_or_default();
+    let col = pos.get(2).copied().unwrap_or_default();
+    Ok((
+        usize::try_from(line).unwrap_or_default(),
+        usize::try_from(col.saturating_sub(1)).unwrap_or_default(),
+    ))
+}
+
+fn cursor_wants_line_end() -> Result<bool> {
+    let pos: Vec<i64> = api::call_function("getcurpos", Array::new())?;
+    Ok(pos.get(4).is_some_and(|curswant| *curswant >= MAXCOL))
+}
+
+/// The selection of the visual mode in progress. `'<`/`'>` are only set on
+/// leaving visual mode, so the corners come from `getpos("v")` and the
+/// cursor.
+fn visual_selection() -> Result<Option<VisualSelection>> {
+    let mode = current_mode();
+    if !is_visual_like_mode(&mode) {
         return Ok(None);
     }
-    let (start_line, _) = buf.get_mark('<')?;
-    let (end_line, _) = buf.get_mark('>')?;
-    if start_line == 0 || end_line == 0 {
+    let anchor = get_pos("v")?;
+    let cursor = get_pos(".")?;
+    if anchor.0 == 0 || cursor.0 == 0 {
         return Ok(None);
     }
-    Ok(Some((
-        line_index_to_i64(start_line)?,
-        line_index_to_i64(end_line)?,
-    )))
+    let kind = if is_blockwise_visual_mode(&mode) {
+        VisualKind::Blockwise
+    } else if is_charwise_visual_mode(&mode) {
+        VisualKind::Charwise
+    } else {
+        VisualKind::Linewise
+    };
+    Ok(Some(VisualSelection {
+        start: anchor.min(cursor),
+        end: anchor.max(cursor),
+        kind,
+        to_line_end: cursor_wants_line_end()?,
+    }))
+}
+
+fn buffer_tabstop(buf: &Buffer) -> Result<usize> {
+    let opts = OptionOpts::builder().buf(buf.clone()).build();
+    let tabstop: i64 = api::get_option_value("tabstop", &opts)?;
+    Ok(usize::try_from(tabstop).unwrap_or(8))
 }
 
-fn resolve_target_range(buf: &Buffer) -> Result<LineRange> {
-    let (start_line, end_line) = match visual_line_range(buf)? {
-        Some((start_line, end_line)) => (Some(start_line), Some(end_line)),
-        None => (None, None),
+/// Cell widths for the characters of `lines`, measured by Neovim itself.
+/// Each character is measured after an ASCII base so combining marks report
+/// the zero cells they take when composed.
+fn measure_cell_widths<'a, I>(tabstop: usize, lines: I) -> Result<CellWidths>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut measured = HashMap::new();
+    for ch in CellWidths::chars_to_measure(lines) {
+        let probe = format!("x{ch}");
+        let width: i64 = api::call_function("strdisplaywidth", Array::from_iter([probe.as_str()]))?;
+        measured.insert(ch, usize::try_from(width - 1).unwrap_or_default());
+    }
+    Ok(CellWidths::new(tabstop, measured))
+}
+
+fn fetch_line(buf: &Buffer, line: usize) -> Result<String> {
+    let range = LineRange::new(line, line).map_err(text_error_to_nvim)?;
+    Ok(fetch_lines(buf, range)?.pop().unwrap_or_default())
+}
+
+fn block_target(buf: &Buffer, selection: VisualSelection) -> Result<BlockTarget> {
+    let tabstop = buffer_tabstop(buf)?;
+    let start_line = fetch_line(buf, selection.start.0)?;
+    let end_line = fetch_line(buf, selection.end.0)?;
+    let widths = measure_cell_widths(tabstop, [start_line.as_str(), end_line.as_str()])?;
+    let span = BlockSpan::from_corners(
+        BlockCorner {
+            line: &start_line,
+            byte_col: selection.start.1,
+        },
+        BlockCorner {
+            line: &end_line,
+            byte_col: selection.end.1,
+        },
+        &widths,
+    );
+    let span = if selection.to_line_end {
+        span.to_line_end()
+    } else {
+        span
     };
-    resolve_line_range(buf, start_line, end_line)
+    Ok(BlockTarget { span, tabstop })
+}
+
+fn char_span(buf: &Buffer, selection: VisualSelection) -> Result<CharSpan> {
+    let start_line = fetch_line(buf, selection.start.0)?;
+    let end_line = fetch_line(buf, selection.end.0)?;
+    Ok(CharSpan::from_marks(
+        &start_line,
+        selection.start.1,
+        &end_line,
+        selection.end.1,
+        selection.to_line_end,
+    ))
+}
+
+/// Resolve the lines an operation applies to: an explicit `{ start_line,
+/// end_line }` range wins, then the visual selection, then the cursor line.
+fn resolve_target(buf: &Buffer, args: RangeArgs) -> Result<TextTarget> {
+    if args.is_explicit() {
+        let range = resolve_line_range(buf, args.start_line, args.end_line)?;
+        return Ok(TextTarget {
+            range,
+            shape: TargetShape::Lines,
+            from_cursor: false,
+        });
+    }
+    let Some(selection) = visual_selection()? else {
+        let range = resolve_line_range(buf, None, None)?;
+        return Ok(TextTarget {
+            range,
+            shape: TargetShape::Lines,
+            from_cursor: true,
+        });
+    };
+    let range = resolve_line_range(
+        buf,
+        Some(line_index_to_i64(selection.start.0)?),
+        Some(line_index_to_i64(selection.end.0)?),
+    )?;
+    let shape = match selection.kind {
+        VisualKind::Linewise => TargetShape::Lines,
+        VisualKind::Charwise => TargetShape::Chars(char_span(buf, selection)?),
+        VisualKind::Blockwise => TargetShape::Block(block_target(buf, selection)?),
+    };
+    Ok(TextTarget {
+        range,
+        shape,
+        from_cursor: false,
+    })
 }
 
 fn current_cursor_col() -> Result<usize> {
@@ -104,58 +292,237 @@ fn seed_from_time() -> u64 {
         .wrapping_add(u64::from(duration.subsec_nanos()))
 }
 
-fn load_target_lines() -> Result<(Buffer, LineRange, Vec<String>)> {
+fn parse_range_args(opts: Option<&Dictionary>) -> Result<RangeArgs> {
+    opts.map(RangeArgs::parse)
+        .transpose()
+        .map_err(args_error_to_nvim)
+        .map(Option::unwrap_or_default)
+}
+
+fn load_target(args: RangeArgs) -> Result<(Buffer, TextTarget, Vec<String>)> {
     let buf = api::get_current_buf();
-    let range = resolve_target_range(&buf)?;
-    let lines = fetch_lines(&buf, range)?;
-    Ok((buf, range, lines))
+    let target = resolve_target(&buf, args)?;
+    let lines = fetch_lines(&buf, target.range)?;
+    Ok((buf, target, lines))
 }
 
-fn sort_lines() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
-    let sorted = core::sort_lines(&lines, SortDirection::Asc);
-    replace_lines(&mut buf, range, sorted)
+fn load_target_lines(args: RangeArgs) -> Result<(Buffer, LineRange, Vec<String>)> {
+    let (buf, target, lines) = load_target(args)?;
+    Ok((buf, target.range, lines))
 }
 
-fn sort_lines_reverse() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
-    let sorted = core::sort_lines(&lines, SortDirection::Desc);
-    replace_lines(&mut buf, range, sorted)
+/// Rewrite the target lines with `rewrite`. For charwise and blockwise
+/// selections only the selected text is handed to `rewrite`; everything
+/// outside the selection stays where it was.
+fn rewrite_target<F>(args: RangeArgs, rewrite: F) -> Result<()>
+where
+    F: FnOnce(&[String]) -> Result<Vec<String>>,
+{
+    let (buf, target, lines) = load_target(args)?;
+    rewrite_loaded_target(buf, target, &lines, rewrite)
+}
+
+fn rewrite_loaded_target<F>(
+    mut buf: Buffer,
+    target: TextTarget,
+    lines: &[String],
+    rewrite: F,
+) -> Result<()>
+where
+    F: FnOnce(&[String]) -> Result<Vec<String>>,
+{
+    let rewritten = match target.shape {
+        TargetShape::Block(BlockTarget { span, tabstop }) => {
+            let widths = measure_cell_widths(tabstop, lines.iter().map(String::as_str))?;
+            let cells = core::block_cells(lines, span, &widths);
+            let cells = rewrite(&cells)?;
+            core::replace_block_cells(lines, span, &widths, &cells)
+        }
+        TargetShape::Chars(span) => {
+            let cells = core::charwise_cells(lines, span);
+            let cells = rewrite(&cells)?;
+            core::replace_charwise_cells(lines, span, &cells)
+        }
+        TargetShape::Lines => rewrite(lines)?,
+    };
+    replace_lines(&mut buf, target.range, rewritten)
 }
 
-fn sort_lines_by_column() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
+fn sort_lines(opts: Option<&Dictionary>) -> Result<()> {
+    rewrite_target(parse_range_args(opts)?, |lines| {
+        Ok(core::sort_lines(lines, SortDirection::Asc))
+    })
+}
+
+fn sort_lines_reverse(opts: Option<&Dictionary>) -> Result<()> {
+    rewrite_target(parse_range_args(opts)?, |lines| {
+        Ok(core::sort_lines(lines, SortDirection::Desc))
+    })
+}
+
+fn sort_lines_by_column(opts: Option<&Dictionary>) -> Result<()> {
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
     let column = Column(current_cursor_col()?);
     let sorted = core::sort_lines_by_column(&lines, column, SortDirection::Asc);
     replace_lines(&mut buf, range, sorted)
 }
 
-fn sort_lines_by_column_reverse() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
+fn sort_lines_by_column_reverse(opts: Option<&Dictionary>) -> Result<()> {
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
     let column = Column(current_cursor_col()?);
     let sorted = core::sort_lines_by_column(&lines, column, SortDirection::Desc);
     replace_lines(&mut buf, range, sorted)
 }
 
-fn randomize_lines() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
+fn pattern_sort_key(line: &str, pattern: &str) -> Result<Option<String>> {
+    let args = Array::from_iter([Object::from(line), Object::from(pattern)]);
+    let matches: Vec<NvimString> = api::call_function("matchlist", args)?;
+    let mut matches = matches
+        .into_iter()
+        .map(|value| value.to_string_lossy().into_owned());
+    let Some(whole) = matches.next() else {
+        return Ok(None);
+    };
+    Ok(Some(
+        matches.find(|group| !group.is_empty()).unwrap_or(whole),
+    ))
+}
+
+fn sort_lines_with_options(opts: Option<&Dictionary>) -> Result<()> {
+    let args = opts
+        .map(SortArgs::parse)
+        .transpose()
+        .map_err(args_error_to_nvim)?
+        .unwrap_or_default();
+    let range = parse_range_args(opts)?;
+    rewrite_target(range, |lines| match args.pattern.as_deref() {
+        Some(pattern) => {
+            let keys = lines
+                .iter()
+                .map(|line| pattern_sort_key(line, pattern))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(core::sort_lines_by_keys(lines, &keys, &args.options))
+        }
+        None => Ok(core::sort_lines_with_options(lines, &args.options)),
+    })
+}
+
+/// Byte ranges of every non-empty match of the Vim regex `pattern` in
+/// `line`, found with `matchstrpos()`.
+fn pattern_separators(line: &str, pattern: &str) -> Result<Vec<Range<usize>>> {
+    let line_len = i64::try_from(line.len()).unwrap_or(i64::MAX);
+    let mut ranges = Vec::new();
+    let mut start = 0_i64;
+    while start <= line_len {
+        let args = Array::from_iter([
+            Object::from(line),
+            Object::from(pattern),
+            Object::from(start),
+        ]);
+        let result: Array = api::call_function("matchstrpos", args)?;
+        let mut bounds = result.into_iter().skip(1).map(i64::from_object);
+        let (Some(Ok(match_start)), Some(Ok(match_end))) = (bounds.next(), bounds.next()) else {
+            break;
+        };
+        let (Ok(from), Ok(to)) = (usize::try_from(match_start), usize::try_from(match_end)) else {
+            break;
+        };
+        if to > from {
+            ranges.push(from..to);
+            start = match_end;
+        } else {
+            start = match_end + 1;
+        }
+    }
+    Ok(ranges)
+}
+
+fn align(opts: Option<&Dictionary>) -> Result<()> {
+    let Some(dict) = opts else {
+        return Err(args_error_to_nvim(ArgsError::MissingKey {
+            key: "pattern".to_string(),
+        }));
+    };
+    let AlignArgs { separator, options } = AlignArgs::parse(dict).map_err(args_error_to_nvim)?;
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
+    let widths = measure_cell_widths(buffer_tabstop(&buf)?, lines.iter().map(String::as_str))?;
+    let matches = match &separator {
+        AlignSeparator::Pattern(pattern) => lines
+            .iter()
+            .map(|line| pattern_separators(line, pattern))
+            .collect::<Result<Vec<_>>>()?,
+        AlignSeparator::Literal(literal) => lines
+            .iter()
+            .map(|line| core::find_literal_separators(line, literal))
+            .collect(),
+    };
+    let aligned = core::align_lines(&lines, &matches, &options, &widths);
+    replace_lines(&mut buf, range, aligned)
+}
+
+fn columnize_lines(opts: Option<&Dictionary>) -> Result<()> {
+    let options = opts
+        .map(ColumnizeArgs::parse)
+        .transpose()
+        .map_err(args_error_to_nvim)?
+        .unwrap_or_default()
+        .options;
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
+    let widths = measure_cell_widths(buffer_tabstop(&buf)?, lines.iter().map(String::as_str))?;
+    let columns = core::columnize_lines(&lines, &options, &widths);
+    replace_lines(&mut buf, range, columns)
+}
+
+/// Apply a case transform to the identifier under the cursor, or to every
+/// line (or block cell) of an explicit range or visual selection.
+fn transform_case(opts: Option<&Dictionary>, transform: CaseTransform) -> Result<()> {
+    let (mut buf, target, lines) = load_target(parse_range_args(opts)?)?;
+    if target.from_cursor {
+        let Some(line) = lines.first() else {
+            return Ok(());
+        };
+        let Some(updated) = core::transform_word_at(line, current_cursor_col()?, transform) else {
+            return Ok(());
+        };
+        return replace_lines(&mut buf, target.range, vec![updated]);
+    }
+    rewrite_loaded_target(buf, target, &lines, |lines| {
+        Ok(lines
+            .iter()
+            .map(|line| core::transform_text(line, transform))
+            .collect())
+    })
+}
+
+fn convert_case(opts: Option<&Dictionary>) -> Result<()> {
+    let Some(dict) = opts else {
+        return Err(args_error_to_nvim(ArgsError::MissingKey {
+            key: "style".to_string(),
+        }));
+    };
+    let CaseArgs { style } = CaseArgs::parse(dict).map_err(args_error_to_nvim)?;
+    transform_case(opts, CaseTransform::Convert(style))
+}
+
+fn randomize_lines(opts: Option<&Dictionary>) -> Result<()> {
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
     let shuffled = core::randomize_lines(&lines, seed_from_time());
     replace_lines(&mut buf, range, shuffled)
 }
 
-fn uniquify_lines() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
-    let uniq = core::uniquify_lines(&lines);
-    replace_lines(&mut buf, range, uniq)
+fn uniquify_lines(opts: Option<&Dictionary>) -> Result<()> {
+    rewrite_target(parse_range_args(opts)?, |lines| {
+        Ok(core::uniquify_lines(lines))
+    })
 }
 
-fn duplicate_line_or_region() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
+fn duplicate_line_or_region(opts: Option<&Dictionary>) -> Result<()> {
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
     insert_lines_after(&mut buf, range, lines)
 }
 
-fn kill_back_to_indentation() -> Result<()> {
-    let (mut buf, range, lines) = load_target_lines()?;
+fn kill_back_to_indentation(opts: Option<&Dictionary>) -> Result<()> {
+    let (mut buf, range, lines) = load_target_lines(parse_range_args(opts)?)?;
     let column = Column(current_cursor_col()?);
     let killed = core::kill_back_to_indentation(&lines, column);
     replace_lines(&mut buf, range, killed)
@@ -164,34 +531,79 @@ fn kill_back_to_indentation() -> Result<()> {
 #[nvim_oxi::plugin]
 fn nvimrs_text() -> Dictionary {
     let mut api = Dictionary::new();
-    api.insert("sort_lines", Function::<(), ()>::from_fn(|()| sort_lines()));
+    api.insert(
+        "sort_lines",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| sort_lines(opts.as_ref())),
+    );
     api.insert(
         "sort_lines_reverse",
-        Function::<(), ()>::from_fn(|()| sort_lines_reverse()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| sort_lines_reverse(opts.as_ref())),
     );
     api.insert(
         "sort_lines_by_column",
-        Function::<(), ()>::from_fn(|()| sort_lines_by_column()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| sort_lines_by_column(opts.as_ref())),
     );
     api.insert(
         "sort_lines_by_column_reverse",
-        Function::<(), ()>::from_fn(|()| sort_lines_by_column_reverse()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| {
+            sort_lines_by_column_reverse(opts.as_ref())
+        }),
+    );
+    api.insert(
+        "sort_lines_with_options",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| sort_lines_with_options(opts.as_ref())),
+    );
+    api.insert(
+        "align",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| align(opts.as_ref())),
+    );
+    api.insert(
+        "columnize_lines",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| columnize_lines(opts.as_ref())),
+    );
+    api.insert(
+        "cycle_case",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| {
+            transform_case(opts.as_ref(), CaseTransform::Cycle)
+        }),
+    );
+    api.insert(
+        "convert_case",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| convert_case(opts.as_ref())),
+    );
+    api.insert(
+        "upcase",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| {
+            transform_case(opts.as_ref(), CaseTransform::Upcase)
+        }),
+    );
+    api.insert(
+        "downcase",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| {
+            transform_case(opts.as_ref(), CaseTransform::Downcase)
+        }),
+    );
+    api.insert(
+        "capitalize",
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| {
+            transform_case(opts.as_ref(), CaseTransform::Capitalize)
+        }),
     );
     api.insert(
         "randomize_lines",
-        Function::<(), ()>::from_fn(|()| randomize_lines()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| randomize_lines(opts.as_ref())),
     );
     api.insert(
         "uniquify_lines",
-        Function::<(), ()>::from_fn(|()| uniquify_lines()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| uniquify_lines(opts.as_ref())),
     );
     api.insert(
         "duplicate_line_or_region",
-        Function::<(), ()>::from_fn(|()| duplicate_line_or_region()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| duplicate_line_or_region(opts.as_ref())),
     );
     api.insert(
         "kill_back_to_indentation",
-        Function::<(), ()>::from_fn(|()| kill_back_to_indentation()),
+        Function::<Option<Dictionary>, ()>::from_fn(|opts| kill_back_to_indentation(opts.as_ref())),
     );
     api
 }
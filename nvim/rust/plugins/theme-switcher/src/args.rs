@@ -1,7 +1,11 @@
 use std::path::PathBuf;
 
+use crate::core::BackgroundFilter;
+use crate::core::PickerOptions;
+use crate::core::ThemeBackground;
 use crate::core::ThemeSpec;
 use crate::core::ThemeSpecError;
+use crate::core::TimeOfDay;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Object;
 use nvim_oxi::String as NvimString;
@@ -20,6 +24,7 @@ pub enum ArgsError {
         expected: &'static str,
     },
     EmptyThemes,
+    NoAutoSource,
     InvalidTheme {
         index: usize,
         reason: ThemeSpecError,
@@ -39,6 +44,7 @@ impl std::fmt::Display for ArgsError {
                 write!(f, "invalid value for '{key}', expected {expected}")
             }
             Self::EmptyThemes => write!(f, "theme list must be non-empty"),
+            Self::NoAutoSource => write!(f, "expected 'schedule' and/or 'appearance'"),
             Self::InvalidTheme { index, reason } => {
                 write!(f, "theme[{index}] is invalid: {reason}")
             }
@@ -68,13 +74,28 @@ impl From<DecodeError> for ArgsError {
 struct RawThemeSpec {
     name: String,
     colorscheme: String,
+    #[serde(default)]
+    background: Option<String>,
+}
+
+impl RawThemeSpec {
+    fn into_spec(self) -> Result<ThemeSpec, ThemeSpecError> {
+        let background = match self.background.as_deref() {
+            None | Some("") => None,
+            Some(value) => {
+                Some(ThemeBackground::parse(value).ok_or(ThemeSpecError::InvalidBackground)?)
+            }
+        };
+        ThemeSpec::try_new(self.name, self.colorscheme)
+            .map(|theme| theme.with_background(background))
+    }
 }
 
 fn parse_theme_specs(value: Object) -> ParseResult<Vec<ThemeSpec>> {
     let raw = Vec::<RawThemeSpec>::deserialize(Deserializer::new(value)).map_err(|_| {
         ArgsError::InvalidValue {
             key: "themes".to_string(),
-            expected: "array[{ name: string, colorscheme: string }]",
+            expected: "array[{ name: string, colorscheme: string, background?: \"light\"|\"dark\" }]",
         }
     })?;
 
@@ -85,11 +106,9 @@ fn parse_theme_specs(value: Object) -> ParseResult<Vec<ThemeSpec>> {
     raw.into_iter()
         .enumerate()
         .map(|(index, theme)| {
-            ThemeSpec::try_new(theme.name, theme.colorscheme).map_err(|reason| {
-                ArgsError::InvalidTheme {
-                    index: index + 1,
-                    reason,
-                }
+            theme.into_spec().map_err(|reason| ArgsError::InvalidTheme {
+                index: index + 1,
+                reason,
             })
         })
         .collect()
@@ -104,12 +123,37 @@ fn parse_optional_string(dict: &Dictionary, key: &'static str) -> ParseResult<Op
         .filter(|value| !value.is_empty()))
 }
 
+fn parse_picker_options(dict: &Dictionary) -> ParseResult<PickerOptions> {
+    let defaults = PickerOptions::default();
+    let live_preview = decode::optional_from_object::<bool>(
+        decode::get_object(dict, "live_preview"),
+        "live_preview",
+        "boolean",
+    )
+    .map_err(ArgsError::from)?
+    .unwrap_or(defaults.live_preview);
+    let background = match parse_optional_string(dict, "background")? {
+        None => defaults.background,
+        Some(value) => BackgroundFilter::parse(&value).ok_or_else(|| ArgsError::InvalidValue {
+            key: "background".to_string(),
+            expected: "\"all\" | \"dark\" | \"light\"",
+        })?,
+    };
+    Ok(PickerOptions {
+        live_preview,
+        background,
+    })
+}
+
 #[derive(Debug)]
 pub struct OpenArgs {
     pub themes: Vec<ThemeSpec>,
     pub title: String,
     pub current_colorscheme: Option<String>,
     pub state_path: Option<PathBuf>,
+    /// Project whose pinned theme (if any) is read and updated.
+    pub project_root: Option<String>,
+    pub options: PickerOptions,
 }
 
 impl OpenArgs {
@@ -120,34 +164,206 @@ impl OpenArgs {
             parse_optional_string(dict, "title")?.unwrap_or_else(|| "Theme Switcher".to_string());
         let current_colorscheme = parse_optional_string(dict, "current_colorscheme")?;
         let state_path = parse_optional_string(dict, "state_path")?.map(PathBuf::from);
+        let project_root = parse_optional_string(dict, "project_root")?;
+        let options = parse_picker_options(dict)?;
         Ok(Self {
             themes,
             title,
             current_colorscheme,
             state_path,
+            project_root,
+            options,
         })
     }
 }
 
+/// Order `cycle_next`/`cycle_prev` step through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CycleOrder {
+    #[default]
+    Catalog,
+    /// Recently used themes, newest first; falls back to catalog order when
+    /// the history has fewer than two usable entries.
+    History,
+}
+
+impl CycleOrder {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "catalog" => Some(Self::Catalog),
+            "history" => Some(Self::History),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CycleArgs {
     pub themes: Vec<ThemeSpec>,
     pub current_colorscheme: Option<String>,
     pub state_path: Option<PathBuf>,
+    pub project_root: Option<String>,
+    pub order: CycleOrder,
 }
 
 impl CycleArgs {
     pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let order = match parse_optional_string(dict, "order")? {
+            None => CycleOrder::default(),
+            Some(value) => CycleOrder::parse(&value).ok_or_else(|| ArgsError::InvalidValue {
+                key: "order".to_string(),
+                expected: "\"catalog\" | \"history\"",
+            })?,
+        };
         let OpenArgs {
             themes,
             current_colorscheme,
             state_path,
+            project_root,
             ..
         } = OpenArgs::parse(dict)?;
         Ok(Self {
             themes,
             current_colorscheme,
             state_path,
+            project_root,
+            order,
+        })
+    }
+}
+
+/// Arguments for reading the state file or editing project pins.
+#[derive(Debug)]
+pub struct StateArgs {
+    pub state_path: PathBuf,
+    pub project_root: Option<String>,
+    pub colorscheme: Option<String>,
+}
+
+impl StateArgs {
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let state_path = parse_optional_string(dict, "state_path")?
+            .map(PathBuf::from)
+            .ok_or_else(|| ArgsError::MissingKey {
+                key: "state_path".to_string(),
+            })?;
+        Ok(Self {
+            state_path,
+            project_root: parse_optional_string(dict, "project_root")?,
+            colorscheme: parse_optional_string(dict, "colorscheme")?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawScheduleEntry {
+    at: String,
+    colorscheme: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAppearance {
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    env: Option<String>,
+    dark: String,
+    light: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleArg {
+    pub start: TimeOfDay,
+    pub colorscheme: String,
+}
+
+/// Where the dark/light flag comes from. The environment variable is checked
+/// before the file when both are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppearanceProbe {
+    pub env: Option<String>,
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppearanceArg {
+    pub probe: AppearanceProbe,
+    pub dark: String,
+    pub light: String,
+}
+
+fn parse_schedule(dict: &Dictionary) -> ParseResult<Vec<ScheduleArg>> {
+    const EXPECTED: &str = "array[{ at: \"HH:MM\", colorscheme: string }]";
+    let invalid = || ArgsError::InvalidValue {
+        key: "schedule".to_string(),
+        expected: EXPECTED,
+    };
+    let Some(value) = decode::get_object(dict, "schedule") else {
+        return Ok(Vec::new());
+    };
+    let raw =
+        Vec::<RawScheduleEntry>::deserialize(Deserializer::new(value)).map_err(|_| invalid())?;
+    raw.into_iter()
+        .map(|entry| {
+            let start = TimeOfDay::parse(&entry.at).ok_or_else(invalid)?;
+            if entry.colorscheme.is_empty() {
+                return Err(invalid());
+            }
+            Ok(ScheduleArg {
+                start,
+                colorscheme: entry.colorscheme,
+            })
+        })
+        .collect()
+}
+
+fn parse_appearance(dict: &Dictionary) -> ParseResult<Option<AppearanceArg>> {
+    let invalid = || ArgsError::InvalidValue {
+        key: "appearance".to_string(),
+        expected: "{ env?: string, file?: string, dark: string, light: string } with env or file",
+    };
+    let Some(value) = decode::get_object(dict, "appearance") else {
+        return Ok(None);
+    };
+    let raw = RawAppearance::deserialize(Deserializer::new(value)).map_err(|_| invalid())?;
+    let probe = AppearanceProbe {
+        env: raw.env.filter(|name| !name.is_empty()),
+        file: raw.file.filter(|path| !path.is_empty()).map(PathBuf::from),
+    };
+    if (probe.env.is_none() && probe.file.is_none()) || raw.dark.is_empty() || raw.light.is_empty()
+    {
+        return Err(invalid());
+    }
+    Ok(Some(AppearanceArg {
+        probe,
+        dark: raw.dark,
+        light: raw.light,
+    }))
+}
+
+#[derive(Debug)]
+pub struct AutoArgs {
+    pub themes: Vec<ThemeSpec>,
+    pub state_path: Option<PathBuf>,
+    pub schedule: Vec<ScheduleArg>,
+    pub appearance: Option<AppearanceArg>,
+}
+
+impl AutoArgs {
+    pub fn parse(dict: &Dictionary) -> ParseResult<Self> {
+        let themes_value = decode::require_object(dict, "themes").map_err(ArgsError::from)?;
+        let themes = parse_theme_specs(themes_value)?;
+        let state_path = parse_optional_string(dict, "state_path")?.map(PathBuf::from);
+        let schedule = parse_schedule(dict)?;
+        let appearance = parse_appearance(dict)?;
+        if schedule.is_empty() && appearance.is_none() {
+            return Err(ArgsError::NoAutoSource);
+        }
+        Ok(Self {
+            themes,
+            state_path,
+            schedule,
+            appearance,
         })
     }
 }
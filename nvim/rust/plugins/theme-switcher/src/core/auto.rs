@@ -0,0 +1,400 @@
+use nonempty::NonEmpty;
+use nvimrs_nvim_oxi_utils::state_machine::Machine;
+use nvimrs_nvim_oxi_utils::state_machine::NoCommand;
+use nvimrs_nvim_oxi_utils::state_machine::Transition;
+
+use super::reducer::ThemeBackground;
+use super::reducer::ThemeIndex;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Local wall-clock time with minute precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    pub const fn from_minutes(minutes: u16) -> Option<Self> {
+        if minutes < MINUTES_PER_DAY {
+            Some(Self(minutes))
+        } else {
+            None
+        }
+    }
+
+    /// Parses `HH:MM` (24-hour clock).
+    pub fn parse(value: &str) -> Option<Self> {
+        let (hours, minutes) = value.trim().split_once(':')?;
+        let hours: u16 = hours.parse().ok()?;
+        let minutes: u16 = minutes.parse().ok()?;
+        if hours >= 24 || minutes >= 60 {
+            return None;
+        }
+        Self::from_minutes(hours * 60 + minutes)
+    }
+
+    pub const fn minutes(self) -> u16 {
+        self.0
+    }
+}
+
+/// Parses the dark/light flag published by an appearance source.
+///
+/// Accepts `dark`/`light`, the freedesktop `prefer-dark`/`prefer-light`/
+/// `default` values and macOS's `Dark`, case-insensitively; the first
+/// non-empty line wins.
+pub fn parse_appearance(value: &str) -> Option<ThemeBackground> {
+    let line = value.lines().map(str::trim).find(|line| !line.is_empty())?;
+    match line.trim_matches(['\'', '"']).to_ascii_lowercase().as_str() {
+        "dark" | "prefer-dark" => Some(ThemeBackground::Dark),
+        "light" | "prefer-light" | "default" => Some(ThemeBackground::Light),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleEntry {
+    pub start: TimeOfDay,
+    pub theme: ThemeIndex,
+}
+
+/// Daily theme schedule. Each entry holds from its start until the next
+/// entry's start; the last entry wraps past midnight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeSchedule {
+    entries: NonEmpty<ScheduleEntry>,
+}
+
+impl ThemeSchedule {
+    /// Sorts entries by start time; for duplicate starts the later entry wins.
+    pub fn new(mut entries: Vec<ScheduleEntry>) -> Option<Self> {
+        entries.sort_by_key(|entry| entry.start);
+        let mut deduped: Vec<ScheduleEntry> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match deduped.last_mut() {
+                Some(last) if last.start == entry.start => *last = entry,
+                _ => deduped.push(entry),
+            }
+        }
+        NonEmpty::from_vec(deduped).map(|entries| Self { entries })
+    }
+
+    pub fn theme_at(&self, now: TimeOfDay) -> ThemeIndex {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.start <= now)
+            .unwrap_or_else(|| self.entries.last())
+            .theme
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppearanceThemes {
+    pub dark: ThemeIndex,
+    pub light: ThemeIndex,
+}
+
+impl AppearanceThemes {
+    pub const fn theme_for(self, appearance: ThemeBackground) -> ThemeIndex {
+        match appearance {
+            ThemeBackground::Dark => self.dark,
+            ThemeBackground::Light => self.light,
+        }
+    }
+}
+
+/// What drives automatic switching. A reported appearance wins over the
+/// schedule; the schedule covers sources that are missing or unreadable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoThemeConfig {
+    pub schedule: Option<ThemeSchedule>,
+    pub appearance: Option<AppearanceThemes>,
+}
+
+impl AutoThemeConfig {
+    pub fn target(&self, inputs: AutoThemeInputs) -> Option<ThemeIndex> {
+        let from_appearance = self
+            .appearance
+            .zip(inputs.appearance)
+            .map(|(themes, appearance)| themes.theme_for(appearance));
+        from_appearance.or_else(|| {
+            self.schedule
+                .as_ref()
+                .zip(inputs.now)
+                .map(|(schedule, now)| schedule.theme_at(now))
+        })
+    }
+}
+
+/// Observations gathered by the runtime for one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoThemeInputs {
+    pub now: Option<TimeOfDay>,
+    pub appearance: Option<ThemeBackground>,
+    /// Catalog index of the active colorscheme, if it is in the catalog.
+    pub current: Option<ThemeIndex>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoThemeEvent {
+    Tick(AutoThemeInputs),
+    /// Forget the last target so the next tick re-applies it.
+    Reset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoThemeEffect {
+    ApplyTheme(ThemeIndex),
+}
+
+pub type AutoThemeTransition = Transition<AutoThemeEffect, NoCommand>;
+
+/// Decides when automatic switching changes the theme.
+///
+/// Only a change of target (a schedule boundary or an appearance flip)
+/// applies a theme, so a theme picked by hand stays until the next change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoThemeMachine {
+    config: AutoThemeConfig,
+    last_target: Option<ThemeIndex>,
+}
+
+impl AutoThemeMachine {
+    pub const fn new(config: AutoThemeConfig) -> Self {
+        Self {
+            config,
+            last_target: None,
+        }
+    }
+
+    pub const fn last_target(&self) -> Option<ThemeIndex> {
+        self.last_target
+    }
+
+    fn reduce_tick(&mut self, inputs: AutoThemeInputs) -> AutoThemeTransition {
+        let Some(target) = self.config.target(inputs) else {
+            return AutoThemeTransition::default();
+        };
+        if self.last_target == Some(target) {
+            return AutoThemeTransition::default();
+        }
+        self.last_target = Some(target);
+        if inputs.current == Some(target) {
+            return AutoThemeTransition::default();
+        }
+        AutoThemeTransition::with_effect(AutoThemeEffect::ApplyTheme(target))
+    }
+}
+
+impl Machine for AutoThemeMachine {
+    type Event = AutoThemeEvent;
+    type Effect = AutoThemeEffect;
+    type Command = NoCommand;
+
+    fn reduce(&mut self, event: Self::Event) -> AutoThemeTransition {
+        match event {
+            AutoThemeEvent::Tick(inputs) => self.reduce_tick(inputs),
+            AutoThemeEvent::Reset => {
+                self.last_target = None;
+                AutoThemeTransition::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ThemeCatalog;
+    use crate::core::ThemeSpec;
+
+    const LIGHT: usize = 0;
+    const DARK: usize = 1;
+    const DUSK: usize = 2;
+
+    fn index(raw: usize) -> Result<ThemeIndex, &'static str> {
+        let themes: Result<Vec<_>, _> = ["light", "dark", "dusk"]
+            .iter()
+            .map(|value| ThemeSpec::try_new(value.to_string(), value.to_string()))
+            .collect();
+        let catalog = ThemeCatalog::try_from_vec(themes.map_err(|_| "invalid theme")?)
+            .map_err(|_| "empty catalog")?;
+        catalog.index(raw).ok_or("index out of range")
+    }
+
+    fn at(value: &str) -> Result<TimeOfDay, &'static str> {
+        TimeOfDay::parse(value).ok_or("invalid time")
+    }
+
+    fn entry(start: &str, raw: usize) -> Result<ScheduleEntry, &'static str> {
+        Ok(ScheduleEntry {
+            start: at(start)?,
+            theme: index(raw)?,
+        })
+    }
+
+    fn day_night() -> Result<ThemeSchedule, &'static str> {
+        ThemeSchedule::new(vec![entry("19:00", DARK)?, entry("07:00", LIGHT)?])
+            .ok_or("empty schedule")
+    }
+
+    fn inputs(
+        now: &str,
+        appearance: Option<ThemeBackground>,
+        current: Option<usize>,
+    ) -> Result<AutoThemeInputs, &'static str> {
+        Ok(AutoThemeInputs {
+            now: Some(at(now)?),
+            appearance,
+            current: current.map(index).transpose()?,
+        })
+    }
+
+    #[test]
+    fn time_of_day_parses_24_hour_clock() {
+        assert_eq!(TimeOfDay::parse("07:30").map(TimeOfDay::minutes), Some(450));
+        assert_eq!(TimeOfDay::parse(" 0:00 ").map(TimeOfDay::minutes), Some(0));
+        assert_eq!(TimeOfDay::parse("24:00"), None);
+        assert_eq!(TimeOfDay::parse("12:60"), None);
+        assert_eq!(TimeOfDay::parse("noon"), None);
+    }
+
+    #[test]
+    fn parse_appearance_accepts_common_flags() {
+        assert_eq!(parse_appearance("dark\n"), Some(ThemeBackground::Dark));
+        assert_eq!(parse_appearance("Dark"), Some(ThemeBackground::Dark));
+        assert_eq!(
+            parse_appearance("'prefer-light'"),
+            Some(ThemeBackground::Light)
+        );
+        assert_eq!(parse_appearance("\n default"), Some(ThemeBackground::Light));
+        assert_eq!(parse_appearance("dim"), None);
+        assert_eq!(parse_appearance(""), None);
+    }
+
+    #[test]
+    fn schedule_picks_latest_start_and_wraps_past_midnight() -> Result<(), &'static str> {
+        let schedule = day_night()?;
+        assert_eq!(schedule.theme_at(at("07:00")?), index(LIGHT)?);
+        assert_eq!(schedule.theme_at(at("18:59")?), index(LIGHT)?);
+        assert_eq!(schedule.theme_at(at("19:00")?), index(DARK)?);
+        assert_eq!(schedule.theme_at(at("03:00")?), index(DARK)?);
+        Ok(())
+    }
+
+    #[test]
+    fn schedule_duplicate_starts_keep_the_later_entry() -> Result<(), &'static str> {
+        let schedule = ThemeSchedule::new(vec![entry("07:00", LIGHT)?, entry("07:00", DUSK)?])
+            .ok_or("empty schedule")?;
+        assert_eq!(schedule.theme_at(at("12:00")?), index(DUSK)?);
+        assert_eq!(ThemeSchedule::new(Vec::new()), None);
+        Ok(())
+    }
+
+    #[test]
+    fn tick_applies_only_when_the_target_changes() -> Result<(), &'static str> {
+        let mut machine = AutoThemeMachine::new(AutoThemeConfig {
+            schedule: Some(day_night()?),
+            appearance: None,
+        });
+        let transition = machine.reduce(AutoThemeEvent::Tick(inputs("08:00", None, Some(DARK))?));
+        assert_eq!(
+            transition.effects,
+            vec![AutoThemeEffect::ApplyTheme(index(LIGHT)?)]
+        );
+
+        // The user picked another theme by hand; same target, no override.
+        assert!(
+            machine
+                .reduce(AutoThemeEvent::Tick(inputs("12:00", None, Some(DUSK))?))
+                .is_empty()
+        );
+
+        let transition = machine.reduce(AutoThemeEvent::Tick(inputs("19:01", None, Some(DUSK))?));
+        assert_eq!(
+            transition.effects,
+            vec![AutoThemeEffect::ApplyTheme(index(DARK)?)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tick_records_target_without_reapplying_active_theme() -> Result<(), &'static str> {
+        let mut machine = AutoThemeMachine::new(AutoThemeConfig {
+            schedule: Some(day_night()?),
+            appearance: None,
+        });
+        assert!(
+            machine
+                .reduce(AutoThemeEvent::Tick(inputs("08:00", None, Some(LIGHT))?))
+                .is_empty()
+        );
+        assert_eq!(machine.last_target(), Some(index(LIGHT)?));
+        Ok(())
+    }
+
+    #[test]
+    fn appearance_wins_over_schedule_and_falls_back_when_unknown() -> Result<(), &'static str> {
+        let mut machine = AutoThemeMachine::new(AutoThemeConfig {
+            schedule: Some(day_night()?),
+            appearance: Some(AppearanceThemes {
+                dark: index(DUSK)?,
+                light: index(LIGHT)?,
+            }),
+        });
+        let transition = machine.reduce(AutoThemeEvent::Tick(inputs(
+            "08:00",
+            Some(ThemeBackground::Dark),
+            Some(LIGHT),
+        )?));
+        assert_eq!(
+            transition.effects,
+            vec![AutoThemeEffect::ApplyTheme(index(DUSK)?)]
+        );
+
+        let transition = machine.reduce(AutoThemeEvent::Tick(inputs("20:00", None, Some(DUSK))?));
+        assert_eq!(
+            transition.effects,
+            vec![AutoThemeEffect::ApplyTheme(index(DARK)?)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reset_reapplies_the_current_target() -> Result<(), &'static str> {
+        let mut machine = AutoThemeMachine::new(AutoThemeConfig {
+            schedule: Some(day_night()?),
+            appearance: None,
+        });
+        let _ = machine.reduce(AutoThemeEvent::Tick(inputs("08:00", None, None)?));
+        assert!(
+            machine
+                .reduce(AutoThemeEvent::Tick(inputs("09:00", None, Some(DUSK))?))
+                .is_empty()
+        );
+        let _ = machine.reduce(AutoThemeEvent::Reset);
+        let transition = machine.reduce(AutoThemeEvent::Tick(inputs("09:00", None, Some(DUSK))?));
+        assert_eq!(
+            transition.effects,
+            vec![AutoThemeEffect::ApplyTheme(index(LIGHT)?)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_inputs_means_no_target() -> Result<(), &'static str> {
+        let mut machine = AutoThemeMachine::new(AutoThemeConfig {
+            schedule: None,
+            appearance: Some(AppearanceThemes {
+                dark: index(DARK)?,
+                light: index(LIGHT)?,
+            }),
+        });
+        assert!(
+            machine
+                .reduce(AutoThemeEvent::Tick(inputs("08:00", None, None)?))
+                .is_empty()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 32;
+const WORD_START_BONUS: i64 = 32;
+const LEADING_GAP_PENALTY_LIMIT: i64 = 16;
+
+fn is_word_start(previous: Option<char>, current: char) -> bool {
+    match previous {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+        }
+    }
+}
+
+/// Scores `candidate` against `query` as a fuzzy subsequence.
+///
+/// Semantics:
+/// - every query character must appear in order; otherwise `None`.
+/// - matching is case-insensitive unless the query contains an uppercase
+///   character (smart case).
+/// - whitespace in the query is ignored.
+/// - consecutive matches and matches at word starts score higher; leading
+///   unmatched characters cost a little. The best-scoring alignment wins.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let smart_case = query.chars().any(char::is_uppercase);
+    let fold = |value: char| {
+        if smart_case {
+            value
+        } else {
+            value.to_lowercase().next().unwrap_or(value)
+        }
+    };
+    let needle: Vec<char> = query
+        .chars()
+        .filter(|value| !value.is_whitespace())
+        .map(fold)
+        .collect();
+    if needle.is_empty() {
+        return Some(0);
+    }
+    let haystack: Vec<char> = candidate.chars().collect();
+    let bonuses: Vec<i64> = haystack
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let previous = index.checked_sub(1).map(|previous| haystack[previous]);
+            MATCH_SCORE
+                + if is_word_start(previous, *value) {
+                    WORD_START_BONUS
+                } else {
+                    0
+                }
+        })
+        .collect();
+
+    // `row[j]`: best score with the current needle char matched at `j`.
+    let mut row: Vec<Option<i64>> = (0_i64..)
+        .zip(&haystack)
+        .zip(&bonuses)
+        .map(|((position, value), bonus)| {
+            (fold(*value) == needle[0]).then(|| bonus - position.min(LEADING_GAP_PENALTY_LIMIT))
+        })
+        .collect();
+    for wanted in &needle[1..] {
+        let mut next = vec![None; haystack.len()];
+        let mut best_before: Option<i64> = None;
+        for index in 1..haystack.len() {
+            let adjacent = row[index - 1];
+            if fold(haystack[index]) == *wanted {
+                let via_gap = best_before;
+                let via_run = adjacent.map(|score| score + CONSECUTIVE_BONUS);
+                next[index] = via_gap.max(via_run).map(|score| score + bonuses[index]);
+            }
+            best_before = best_before.max(adjacent);
+        }
+        row = next;
+    }
+    row.into_iter().flatten().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(fuzzy_score("", "Kanagawa Wave"), Some(0));
+        assert_eq!(fuzzy_score("  ", "Kanagawa Wave"), Some(0));
+    }
+
+    #[test]
+    fn missing_characters_do_not_match() {
+        assert_eq!(fuzzy_score("kx", "Kanagawa Wave"), None);
+        assert_eq!(fuzzy_score("waka", "Kanagawa Wave"), None);
+    }
+
+    #[test]
+    fn lowercase_query_is_case_insensitive_and_uppercase_is_strict() {
+        assert!(fuzzy_score("kw", "Kanagawa Wave").is_some());
+        assert!(fuzzy_score("KW", "Kanagawa Wave").is_some());
+        assert_eq!(fuzzy_score("KW", "kanagawa wave"), None);
+    }
+
+    #[test]
+    fn word_starts_and_runs_outrank_scattered_matches() {
+        let word_starts = fuzzy_score("mv", "Modus Vivendi");
+        let scattered = fuzzy_score("mv", "Kanagawa Monokai vim");
+        assert!(word_starts > scattered);
+
+        let run = fuzzy_score("wave", "Kanagawa Wave");
+        let spread = fuzzy_score("wave", "Wild Cave");
+        assert!(run > spread);
+    }
+
+    #[test]
+    fn camel_case_boundaries_count_as_word_starts() {
+        assert!(fuzzy_score("mp", "MonokaiPro") > fuzzy_score("mp", "Mountain top"));
+    }
+}
@@ -1,8 +1,26 @@
+mod auto;
+mod filter;
 mod reducer;
 mod selection;
+mod state;
 
+pub use auto::AppearanceThemes;
+pub use auto::AutoThemeConfig;
+pub use auto::AutoThemeEffect;
+pub use auto::AutoThemeEvent;
+pub use auto::AutoThemeInputs;
+pub use auto::AutoThemeMachine;
+pub use auto::ScheduleEntry;
+pub use auto::ThemeSchedule;
+pub use auto::TimeOfDay;
+pub use auto::parse_appearance;
+pub use filter::fuzzy_score;
+pub use reducer::BackgroundFilter;
 pub use reducer::EmptyThemeCatalogError;
+pub use reducer::PickerOptions;
+pub use reducer::PickerRow;
 pub use reducer::PickerStatus;
+pub use reducer::ThemeBackground;
 pub use reducer::ThemeCatalog;
 pub use reducer::ThemeCycleDirection;
 pub use reducer::ThemeIndex;
@@ -15,3 +33,9 @@ pub use reducer::ThemeSwitcherTransition;
 pub use reducer::cycle_theme_index;
 pub use reducer::cycle_theme_index_from_index;
 pub use selection::resolve_effective_theme_index;
+pub use state::HistoryUpdate;
+pub use state::THEME_HISTORY_LIMIT;
+pub use state::THEME_STATE_VERSION;
+pub use state::ThemeState;
+pub use state::ThemeStateDecodeError;
+pub use state::cycle_history_index;
@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::collections::BTreeMap;
 
 use nonempty::NonEmpty;
@@ -6,11 +7,14 @@ use nvimrs_nvim_oxi_utils::state_machine::NoCommand;
 use nvimrs_nvim_oxi_utils::state_machine::Transition;
 use nvimrs_support::NonEmptyString;
 
+use super::filter::fuzzy_score;
+
 /// Why constructing a theme entry failed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThemeSpecError {
     EmptyName,
     EmptyColorscheme,
+    InvalidBackground,
 }
 
 impl std::fmt::Display for ThemeSpecError {
@@ -18,17 +22,43 @@ impl std::fmt::Display for ThemeSpecError {
         match self {
             Self::EmptyName => write!(f, "theme name must be non-empty"),
             Self::EmptyColorscheme => write!(f, "theme colorscheme must be non-empty"),
+            Self::InvalidBackground => write!(f, "theme background must be \"light\" or \"dark\""),
         }
     }
 }
 
 impl std::error::Error for ThemeSpecError {}
 
+/// Optional `background` metadata used to group and filter themes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum ThemeBackground {
+    Dark,
+    Light,
+}
+
+impl ThemeBackground {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dark" => Some(Self::Dark),
+            "light" => Some(Self::Light),
+            _ => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+        }
+    }
+}
+
 /// Immutable theme data used by the switcher.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ThemeSpec {
     name: NonEmptyString,
     colorscheme: NonEmptyString,
+    background: Option<ThemeBackground>,
 }
 
 impl ThemeSpec {
@@ -36,7 +66,21 @@ impl ThemeSpec {
         let name = NonEmptyString::try_new(name).map_err(|_| ThemeSpecError::EmptyName)?;
         let colorscheme =
             NonEmptyString::try_new(colorscheme).map_err(|_| ThemeSpecError::EmptyColorscheme)?;
-        Ok(Self { name, colorscheme })
+        Ok(Self {
+            name,
+            colorscheme,
+            background: None,
+        })
+    }
+
+    #[must_use]
+    pub const fn with_background(mut self, background: Option<ThemeBackground>) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub const fn background(&self) -> Option<ThemeBackground> {
+        self.background
     }
 
     pub const fn name(&self) -> &NonEmptyString {
@@ -138,6 +182,10 @@ impl ThemeCatalog {
         self.colorscheme_to_index.get(colorscheme).copied()
     }
 
+    pub fn has_background_metadata(&self) -> bool {
+        self.iter().any(|theme| theme.background().is_some())
+    }
+
     fn next_wrapped(&self, current: ThemeIndex) -> Option<ThemeIndex> {
         self.get(current)?;
         let len = self.len();
@@ -168,13 +216,72 @@ pub enum PickerStatus {
     Closed,
 }
 
+/// Which themes the picker lists, by `background` metadata.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackgroundFilter {
+    #[default]
+    All,
+    Dark,
+    Light,
+}
+
+impl BackgroundFilter {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "all" => Some(Self::All),
+            _ => ThemeBackground::parse(value).map(Self::from),
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Dark => "dark",
+            Self::Light => "light",
+        }
+    }
+
+    /// `All -> Dark -> Light -> All`.
+    #[must_use]
+    pub const fn cycled(self) -> Self {
+        match self {
+            Self::All => Self::Dark,
+            Self::Dark => Self::Light,
+            Self::Light => Self::All,
+        }
+    }
+
+    /// Themes without metadata are only listed under `All`.
+    pub fn accepts(self, background: Option<ThemeBackground>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Dark => background == Some(ThemeBackground::Dark),
+            Self::Light => background == Some(ThemeBackground::Light),
+        }
+    }
+}
+
+impl From<ThemeBackground> for BackgroundFilter {
+    fn from(value: ThemeBackground) -> Self {
+        match value {
+            ThemeBackground::Dark => Self::Dark,
+            ThemeBackground::Light => Self::Light,
+        }
+    }
+}
+
 /// Input events for the theme picker state machine.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ThemeSwitcherEvent {
     MoveNext,
     MovePrev,
     Confirm,
     Cancel,
+    /// Replace the fuzzy filter with the prompt line contents.
+    SetQuery(String),
+    SetBackground(BackgroundFilter),
+    CycleBackground,
+    ToggleLivePreview,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -222,12 +329,49 @@ pub fn cycle_theme_index(
 /// Side-effects requested by one state transition.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThemeSwitcherEffect {
+    /// Cursor moved and the theme should be applied (live preview).
     PreviewTheme(ThemeIndex),
+    /// Cursor moved without applying the theme.
+    MoveCursor(ThemeIndex),
     PersistTheme(ThemeIndex),
     RestoreTheme(ThemeIndex),
+    /// Visible rows or the status line changed; redraw the whole list.
+    RefreshList,
     ClosePicker,
 }
 
+/// One rendered picker row. Group headers are only produced while the query
+/// is empty and at least one theme carries `background` metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerRow {
+    Group(Option<ThemeBackground>),
+    Theme(ThemeIndex),
+}
+
+/// Session options fixed when the picker opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickerOptions {
+    pub live_preview: bool,
+    pub background: BackgroundFilter,
+}
+
+impl Default for PickerOptions {
+    fn default() -> Self {
+        Self {
+            live_preview: true,
+            background: BackgroundFilter::All,
+        }
+    }
+}
+
+const fn group_rank(background: Option<ThemeBackground>) -> u8 {
+    match background {
+        Some(ThemeBackground::Dark) => 0,
+        Some(ThemeBackground::Light) => 1,
+        None => 2,
+    }
+}
+
 pub type ThemeSwitcherTransition = Transition<ThemeSwitcherEffect, NoCommand>;
 
 /// Pure reducer for one picker session.
@@ -235,25 +379,52 @@ pub type ThemeSwitcherTransition = Transition<ThemeSwitcherEffect, NoCommand>;
 /// Invariants:
 /// - `catalog` is non-empty.
 /// - `persisted` and `cursor` are always catalog indexes.
+/// - `visible` holds the themes passing the query and background filter, in
+///   display order; `cursor` is one of them whenever it is non-empty.
+/// - `preview_applied` is set while a live preview differs from the
+///   colorscheme active when the picker opened.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ThemeSwitcherMachine {
     catalog: ThemeCatalog,
     persisted: ThemeIndex,
     cursor: ThemeIndex,
     status: PickerStatus,
+    live_preview: bool,
+    preview_applied: bool,
+    query: String,
+    background: BackgroundFilter,
+    visible: Vec<ThemeIndex>,
 }
 
 impl ThemeSwitcherMachine {
     pub fn new(catalog: ThemeCatalog, persisted_index_raw: usize) -> Self {
+        Self::with_options(catalog, persisted_index_raw, PickerOptions::default())
+    }
+
+    pub fn with_options(
+        catalog: ThemeCatalog,
+        persisted_index_raw: usize,
+        options: PickerOptions,
+    ) -> Self {
         let persisted = catalog
             .index(persisted_index_raw)
             .unwrap_or_else(|| catalog.first_index());
-        Self {
+        let mut machine = Self {
             catalog,
             persisted,
             cursor: persisted,
             status: PickerStatus::Active,
+            live_preview: options.live_preview,
+            preview_applied: false,
+            query: String::new(),
+            background: options.background,
+            visible: Vec::new(),
+        };
+        machine.visible = machine.compute_visible();
+        if let Some(first) = machine.first_visible_unless_cursor() {
+            machine.cursor = first;
         }
+        machine
     }
 
     pub const fn status(&self) -> PickerStatus {
@@ -284,22 +455,170 @@ impl ThemeSwitcherMachine {
         &self.catalog
     }
 
-    fn reduce_move(
-        &mut self,
-        pick_next: impl FnOnce(&ThemeCatalog, ThemeIndex) -> Option<ThemeIndex>,
-    ) -> ThemeSwitcherTransition {
-        let Some(next) = pick_next(&self.catalog, self.cursor) else {
+    pub const fn live_preview(&self) -> bool {
+        self.live_preview
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub const fn background_filter(&self) -> BackgroundFilter {
+        self.background
+    }
+
+    pub fn visible(&self) -> &[ThemeIndex] {
+        &self.visible
+    }
+
+    fn grouped(&self) -> bool {
+        self.query.trim().is_empty() && self.catalog.has_background_metadata()
+    }
+
+    /// Display rows: themes in `visible` order, with a header before each
+    /// background group when grouping applies.
+    pub fn rows(&self) -> Vec<PickerRow> {
+        let grouped = self.grouped();
+        let mut rows = Vec::with_capacity(self.visible.len() + 3);
+        let mut current_group = None;
+        for index in &self.visible {
+            let background = self.catalog.get(*index).and_then(ThemeSpec::background);
+            if grouped && current_group != Some(background) {
+                rows.push(PickerRow::Group(background));
+                current_group = Some(background);
+            }
+            rows.push(PickerRow::Theme(*index));
+        }
+        rows
+    }
+
+    /// Zero-based row of `index` in [`Self::rows`].
+    pub fn row_of(&self, index: ThemeIndex) -> Option<usize> {
+        self.rows()
+            .iter()
+            .position(|row| *row == PickerRow::Theme(index))
+    }
+
+    fn compute_visible(&self) -> Vec<ThemeIndex> {
+        let mut scored: Vec<(i64, Option<ThemeBackground>, ThemeIndex)> = self
+            .catalog
+            .iter()
+            .enumerate()
+            .filter(|(_, theme)| self.background.accepts(theme.background()))
+            .filter_map(|(raw, theme)| {
+                let by_name = fuzzy_score(&self.query, theme.name().as_str());
+                let by_colorscheme = fuzzy_score(&self.query, theme.colorscheme().as_str());
+                by_name
+                    .max(by_colorscheme)
+                    .map(|score| (score, theme.background(), ThemeIndex(raw)))
+            })
+            .collect();
+        if self.grouped() {
+            scored.sort_by_key(|(_, background, index)| (group_rank(*background), *index));
+        } else {
+            scored.sort_by_key(|(score, _, index)| (Reverse(*score), *index));
+        }
+        scored.into_iter().map(|(_, _, index)| index).collect()
+    }
+
+    fn first_visible_unless_cursor(&self) -> Option<ThemeIndex> {
+        if self.visible.contains(&self.cursor) {
+            return None;
+        }
+        self.visible.first().copied()
+    }
+
+    /// Effect announcing a cursor move, applying the theme when live preview
+    /// is on.
+    fn cursor_effect(&mut self, index: ThemeIndex) -> ThemeSwitcherEffect {
+        if self.live_preview {
+            self.preview_applied = true;
+            ThemeSwitcherEffect::PreviewTheme(index)
+        } else {
+            ThemeSwitcherEffect::MoveCursor(index)
+        }
+    }
+
+    fn reduce_move(&mut self, forward: bool) -> ThemeSwitcherTransition {
+        let len = self.visible.len();
+        if len == 0 {
             return ThemeSwitcherTransition::default();
+        }
+        let position = self.visible.iter().position(|index| *index == self.cursor);
+        let next_position = match (position, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(position), true) => (position + 1) % len,
+            (Some(position), false) => (position + len - 1) % len,
         };
+        let next = self.visible[next_position];
         if next == self.cursor {
             return ThemeSwitcherTransition::default();
         }
         self.cursor = next;
-        ThemeSwitcherTransition::with_effect(ThemeSwitcherEffect::PreviewTheme(next))
+        let effect = self.cursor_effect(next);
+        ThemeSwitcherTransition::with_effect(effect)
+    }
+
+    /// Recompute `visible` after a filter change. A new query jumps to the
+    /// best match; a background change keeps the cursor when still listed.
+    fn refilter(&mut self, jump_to_best: bool) -> ThemeSwitcherTransition {
+        self.visible = self.compute_visible();
+        let target = if jump_to_best {
+            self.visible.first().copied()
+        } else {
+            self.first_visible_unless_cursor()
+        };
+        let mut effects = Vec::new();
+        if let Some(target) = target
+            && target != self.cursor
+        {
+            self.cursor = target;
+            let effect = self.cursor_effect(target);
+            effects.push(effect);
+        }
+        effects.push(ThemeSwitcherEffect::RefreshList);
+        ThemeSwitcherTransition::with_effects(effects)
+    }
+
+    fn reduce_set_query(&mut self, query: String) -> ThemeSwitcherTransition {
+        if query == self.query {
+            return ThemeSwitcherTransition::default();
+        }
+        self.query = query;
+        self.refilter(true)
+    }
+
+    fn reduce_set_background(&mut self, background: BackgroundFilter) -> ThemeSwitcherTransition {
+        if background == self.background {
+            return ThemeSwitcherTransition::default();
+        }
+        self.background = background;
+        self.refilter(false)
+    }
+
+    fn reduce_toggle_live_preview(&mut self) -> ThemeSwitcherTransition {
+        self.live_preview = !self.live_preview;
+        let mut effects = Vec::new();
+        if self.live_preview {
+            if self.cursor != self.persisted && self.visible.contains(&self.cursor) {
+                self.preview_applied = true;
+                effects.push(ThemeSwitcherEffect::PreviewTheme(self.cursor));
+            }
+        } else if self.preview_applied {
+            self.preview_applied = false;
+            effects.push(ThemeSwitcherEffect::RestoreTheme(self.persisted));
+        }
+        effects.push(ThemeSwitcherEffect::RefreshList);
+        ThemeSwitcherTransition::with_effects(effects)
     }
 
     fn reduce_confirm(&mut self) -> ThemeSwitcherTransition {
+        if !self.visible.contains(&self.cursor) {
+            return ThemeSwitcherTransition::default();
+        }
         self.persisted = self.cursor;
+        self.preview_applied = false;
         self.status = PickerStatus::Closed;
         ThemeSwitcherTransition::with_effects(vec![
             ThemeSwitcherEffect::PersistTheme(self.persisted),
@@ -310,9 +629,10 @@ impl ThemeSwitcherMachine {
     fn reduce_cancel(&mut self) -> ThemeSwitcherTransition {
         self.status = PickerStatus::Closed;
         let mut effects = Vec::new();
-        if self.cursor != self.persisted {
+        if self.preview_applied {
             effects.push(ThemeSwitcherEffect::RestoreTheme(self.persisted));
         }
+        self.preview_applied = false;
         self.cursor = self.persisted;
         effects.push(ThemeSwitcherEffect::ClosePicker);
         ThemeSwitcherTransition::with_effects(effects)
@@ -329,10 +649,16 @@ impl Machine for ThemeSwitcherMachine {
             return ThemeSwitcherTransition::default();
         }
         match event {
-            ThemeSwitcherEvent::MoveNext => self.reduce_move(ThemeCatalog::next_wrapped),
-            ThemeSwitcherEvent::MovePrev => self.reduce_move(ThemeCatalog::prev_wrapped),
+            ThemeSwitcherEvent::MoveNext => self.reduce_move(true),
+            ThemeSwitcherEvent::MovePrev => self.reduce_move(false),
             ThemeSwitcherEvent::Confirm => self.reduce_confirm(),
             ThemeSwitcherEvent::Cancel => self.reduce_cancel(),
+            ThemeSwitcherEvent::SetQuery(query) => self.reduce_set_query(query),
+            ThemeSwitcherEvent::SetBackground(background) => self.reduce_set_background(background),
+            ThemeSwitcherEvent::CycleBackground => {
+                self.reduce_set_background(self.background.cycled())
+            }
+            ThemeSwitcherEvent::ToggleLivePreview => self.reduce_toggle_live_preview(),
         }
     }
 }
@@ -483,4 +809,219 @@ mod tests {
         );
         Ok(())
     }
+
+    fn themed(name: &str, background: Option<ThemeBackground>) -> Result<ThemeSpec, &'static str> {
+        ThemeSpec::try_new(name.to_string(), name.to_lowercase().replace(' ', "-"))
+            .map(|theme| theme.with_background(background))
+            .map_err(|_| "expected valid theme")
+    }
+
+    /// `[Dawn (light), Night (dark), Plain, Dusk (dark)]`.
+    fn grouped_catalog() -> Result<ThemeCatalog, &'static str> {
+        ThemeCatalog::try_from_vec(vec![
+            themed("Dawn", Some(ThemeBackground::Light))?,
+            themed("Night", Some(ThemeBackground::Dark))?,
+            themed("Plain", None)?,
+            themed("Dusk", Some(ThemeBackground::Dark))?,
+        ])
+        .map_err(|_| "expected non-empty catalog")
+    }
+
+    fn raw_visible(machine: &ThemeSwitcherMachine) -> Vec<usize> {
+        machine.visible().iter().map(|index| index.raw()).collect()
+    }
+
+    #[test]
+    fn rows_group_by_background_when_query_is_empty() -> Result<(), &'static str> {
+        let machine = ThemeSwitcherMachine::new(grouped_catalog()?, 0);
+        assert_eq!(
+            machine.rows(),
+            vec![
+                PickerRow::Group(Some(ThemeBackground::Dark)),
+                PickerRow::Theme(ThemeIndex(1)),
+                PickerRow::Theme(ThemeIndex(3)),
+                PickerRow::Group(Some(ThemeBackground::Light)),
+                PickerRow::Theme(ThemeIndex(0)),
+                PickerRow::Group(None),
+                PickerRow::Theme(ThemeIndex(2)),
+            ]
+        );
+        assert_eq!(machine.row_of(ThemeIndex(0)), Some(4));
+        Ok(())
+    }
+
+    #[test]
+    fn rows_without_metadata_have_no_headers() -> Result<(), &'static str> {
+        let machine = ThemeSwitcherMachine::new(catalog(&["a", "b"])?, 0);
+        assert_eq!(
+            machine.rows(),
+            vec![
+                PickerRow::Theme(ThemeIndex(0)),
+                PickerRow::Theme(ThemeIndex(1)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn moves_follow_display_order() -> Result<(), &'static str> {
+        let mut machine = ThemeSwitcherMachine::new(grouped_catalog()?, 1);
+        let transition = machine.reduce(ThemeSwitcherEvent::MoveNext);
+        assert_eq!(
+            transition.effects,
+            vec![ThemeSwitcherEffect::PreviewTheme(ThemeIndex(3))]
+        );
+        let _ = machine.reduce(ThemeSwitcherEvent::MoveNext);
+        assert_eq!(machine.cursor_index().raw(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn set_query_filters_ranks_and_jumps_to_best_match() -> Result<(), &'static str> {
+        let mut machine = ThemeSwitcherMachine::new(grouped_catalog()?, 0);
+        let transition = machine.reduce(ThemeSwitcherEvent::SetQuery("du".to_string()));
+        assert_eq!(
+            transition.effects,
+            vec![
+                ThemeSwitcherEffect::PreviewTheme(ThemeIndex(3)),
+                ThemeSwitcherEffect::RefreshList,
+            ]
+        );
+        assert_eq!(raw_visible(&machine), vec![3]);
+        assert_eq!(machine.rows(), vec![PickerRow::Theme(ThemeIndex(3))]);
+        assert!(
+            machine
+                .reduce(ThemeSwitcherEvent::SetQuery("du".to_string()))
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn query_without_matches_keeps_cursor_and_blocks_confirm() -> Result<(), &'static str> {
+        let mut machine = ThemeSwitcherMachine::new(grouped_catalog()?, 0);
+        let transition = machine.reduce(ThemeSwitcherEvent::SetQuery("zzz".to_string()));
+        assert_eq!(transition.effects, vec![ThemeSwitcherEffect::RefreshList]);
+        assert!(machine.visible().is_empty());
+        assert!(machine.reduce(ThemeSwitcherEvent::MoveNext).is_empty());
+        assert!(machine.reduce(ThemeSwitcherEvent::Confirm).is_empty());
+        assert!(machine.is_active());
+        Ok(())
+    }
+
+    #[test]
+    fn background_filter_keeps_listed_cursor_and_moves_hidden_one() -> Result<(), &'static str> {
+        let mut machine = ThemeSwitcherMachine::new(grouped_catalog()?, 3);
+        let transition = machine.reduce(ThemeSwitcherEvent::CycleBackground);
+        assert_eq!(machine.background_filter(), BackgroundFilter::Dark);
+        assert_eq!(transition.effects, vec![ThemeSwitcherEffect::RefreshList]);
+        assert_eq!(raw_visible(&machine), vec![1, 3]);
+
+        let transition = machine.reduce(ThemeSwitcherEvent::CycleBackground);
+        assert_eq!(machine.background_filter(), BackgroundFilter::Light);
+        assert_eq!(
+            transition.effects,
+            vec![
+                ThemeSwitcherEffect::PreviewTheme(ThemeIndex(0)),
+                ThemeSwitcherEffect::RefreshList,
+            ]
+        );
+
+        let _ = machine.reduce(ThemeSwitcherEvent::CycleBackground);
+        assert_eq!(machine.background_filter(), BackgroundFilter::All);
+        assert_eq!(raw_visible(&machine), vec![1, 3, 0, 2]);
+        assert!(
+            machine
+                .reduce(ThemeSwitcherEvent::SetBackground(BackgroundFilter::All))
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn initial_background_filter_moves_cursor_without_preview() -> Result<(), &'static str> {
+        let options = PickerOptions {
+            live_preview: true,
+            background: BackgroundFilter::Light,
+        };
+        let mut machine = ThemeSwitcherMachine::with_options(grouped_catalog()?, 1, options);
+        assert_eq!(machine.cursor_index().raw(), 0);
+        assert_eq!(machine.persisted_index().raw(), 1);
+        let transition = machine.reduce(ThemeSwitcherEvent::Cancel);
+        assert_eq!(transition.effects, vec![ThemeSwitcherEffect::ClosePicker]);
+        Ok(())
+    }
+
+    #[test]
+    fn moves_without_live_preview_do_not_apply_or_restore() -> Result<(), &'static str> {
+        let options = PickerOptions {
+            live_preview: false,
+            ..PickerOptions::default()
+        };
+        let mut machine =
+            ThemeSwitcherMachine::with_options(catalog(&["a", "b", "c"])?, 0, options);
+        let transition = machine.reduce(ThemeSwitcherEvent::MoveNext);
+        assert_eq!(
+            transition.effects,
+            vec![ThemeSwitcherEffect::MoveCursor(ThemeIndex(1))]
+        );
+        let transition = machine.reduce(ThemeSwitcherEvent::Cancel);
+        assert_eq!(transition.effects, vec![ThemeSwitcherEffect::ClosePicker]);
+        Ok(())
+    }
+
+    #[test]
+    fn toggling_live_preview_applies_and_restores() -> Result<(), &'static str> {
+        let mut machine = ThemeSwitcherMachine::new(catalog(&["a", "b", "c"])?, 0);
+        let _ = machine.reduce(ThemeSwitcherEvent::MoveNext);
+
+        let transition = machine.reduce(ThemeSwitcherEvent::ToggleLivePreview);
+        assert!(!machine.live_preview());
+        assert_eq!(
+            transition.effects,
+            vec![
+                ThemeSwitcherEffect::RestoreTheme(ThemeIndex(0)),
+                ThemeSwitcherEffect::RefreshList,
+            ]
+        );
+
+        let transition = machine.reduce(ThemeSwitcherEvent::ToggleLivePreview);
+        assert_eq!(
+            transition.effects,
+            vec![
+                ThemeSwitcherEffect::PreviewTheme(ThemeIndex(1)),
+                ThemeSwitcherEffect::RefreshList,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cancel_restores_after_previewing_back_to_start() -> Result<(), &'static str> {
+        let mut machine = ThemeSwitcherMachine::new(catalog(&["a", "b"])?, 0);
+        let _ = machine.reduce(ThemeSwitcherEvent::MoveNext);
+        let _ = machine.reduce(ThemeSwitcherEvent::MovePrev);
+        let transition = machine.reduce(ThemeSwitcherEvent::Cancel);
+        assert_eq!(
+            transition.effects,
+            vec![
+                ThemeSwitcherEffect::RestoreTheme(ThemeIndex(0)),
+                ThemeSwitcherEffect::ClosePicker,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn background_filter_parse_and_cycle() {
+        assert_eq!(
+            BackgroundFilter::parse("dark"),
+            Some(BackgroundFilter::Dark)
+        );
+        assert_eq!(BackgroundFilter::parse("all"), Some(BackgroundFilter::All));
+        assert_eq!(BackgroundFilter::parse("dim"), None);
+        assert_eq!(BackgroundFilter::Light.cycled(), BackgroundFilter::All);
+        assert!(!BackgroundFilter::Dark.accepts(None));
+        assert!(BackgroundFilter::All.accepts(None));
+    }
 }
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::reducer::ThemeCatalog;
+use super::reducer::ThemeCycleDirection;
+use super::reducer::ThemeIndex;
+
+pub const THEME_STATE_VERSION: u32 = 1;
+/// Most-recently-used colorschemes kept in the state file.
+pub const THEME_HISTORY_LIMIT: usize = 16;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ThemeStateFile {
+    version: u32,
+    #[serde(flatten)]
+    state: ThemeState,
+}
+
+/// Persisted theme switcher state.
+///
+/// `current` is the global theme, `projects` maps a project root to a pinned
+/// theme and `history` lists recently applied themes, newest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeState {
+    pub current: Option<String>,
+    pub projects: BTreeMap<String, String>,
+    pub history: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeStateDecodeError {
+    /// Malformed JSON or a version this build does not understand. Callers
+    /// must not overwrite such files.
+    Incompatible,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryUpdate {
+    /// Move the theme to the front of the history.
+    Push,
+    /// Leave the history order alone (used while walking it).
+    Keep,
+}
+
+impl ThemeState {
+    /// Decodes a state file. Files that do not start with `{` are the legacy
+    /// one-line format and migrate to `current` plus a one-entry history.
+    pub fn decode(raw: &str) -> Result<Self, ThemeStateDecodeError> {
+        let trimmed = raw.trim_start();
+        if !trimmed.starts_with('{') {
+            return Ok(Self::from_legacy(trimmed));
+        }
+        let file: ThemeStateFile =
+            serde_json::from_str(trimmed).map_err(|_| ThemeStateDecodeError::Incompatible)?;
+        if file.version != THEME_STATE_VERSION {
+            return Err(ThemeStateDecodeError::Incompatible);
+        }
+        let mut state = file.state;
+        state.current = state.current.filter(|name| !name.is_empty());
+        state
+            .projects
+            .retain(|root, colorscheme| !root.is_empty() && !colorscheme.is_empty());
+        let history = std::mem::take(&mut state.history);
+        for colorscheme in history.into_iter().rev() {
+            state.push_history(&colorscheme);
+        }
+        Ok(state)
+    }
+
+    fn from_legacy(raw: &str) -> Self {
+        let current = raw
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(ToOwned::to_owned);
+        Self {
+            history: current.iter().cloned().collect(),
+            current,
+            projects: BTreeMap::new(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let file = ThemeStateFile {
+            version: THEME_STATE_VERSION,
+            state: self.clone(),
+        };
+        serde_json::to_string_pretty(&file).unwrap_or_default()
+    }
+
+    /// The project pin when `project_root` has one, otherwise the global theme.
+    pub fn effective(&self, project_root: Option<&str>) -> Option<&str> {
+        project_root
+            .and_then(|root| self.projects.get(root))
+            .or(self.current.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Records an applied theme. A pinned project keeps its pin in sync with
+    /// what was chosen there; everywhere else the global theme changes.
+    pub fn record(
+        &mut self,
+        colorscheme: &str,
+        project_root: Option<&str>,
+        history: HistoryUpdate,
+    ) {
+        match project_root.and_then(|root| self.projects.get_mut(root)) {
+            Some(pinned) => colorscheme.clone_into(pinned),
+            None => self.current = Some(colorscheme.to_string()),
+        }
+        if history == HistoryUpdate::Push {
+            self.push_history(colorscheme);
+        }
+    }
+
+    pub fn pin_project(&mut self, root: &str, colorscheme: &str) {
+        self.projects
+            .insert(root.to_string(), colorscheme.to_string());
+    }
+
+    pub fn unpin_project(&mut self, root: &str) -> bool {
+        self.projects.remove(root).is_some()
+    }
+
+    fn push_history(&mut self, colorscheme: &str) {
+        if colorscheme.is_empty() {
+            return;
+        }
+        self.history.retain(|entry| entry != colorscheme);
+        self.history.insert(0, colorscheme.to_string());
+        self.history.truncate(THEME_HISTORY_LIMIT);
+    }
+}
+
+/// Steps through the history instead of catalog order: `Next` moves to an
+/// older entry and `Prev` to a newer one, wrapping at both ends.
+///
+/// Entries missing from the catalog are skipped. Returns `None` when fewer
+/// than two history entries are usable, so callers can fall back to catalog
+/// order.
+pub fn cycle_history_index(
+    catalog: &ThemeCatalog,
+    history: &[String],
+    current: Option<ThemeIndex>,
+    direction: ThemeCycleDirection,
+) -> Option<ThemeIndex> {
+    let mut entries: Vec<ThemeIndex> = Vec::with_capacity(history.len());
+    for index in history
+        .iter()
+        .filter_map(|colorscheme| catalog.find_by_colorscheme(colorscheme))
+    {
+        if !entries.contains(&index) {
+            entries.push(index);
+        }
+    }
+    if entries.len() < 2 {
+        return None;
+    }
+    let position = current.and_then(|current| entries.iter().position(|entry| *entry == current));
+    let target = match (direction, position) {
+        (ThemeCycleDirection::Next, Some(position)) => (position + 1) % entries.len(),
+        (ThemeCycleDirection::Next, None) => 0,
+        (ThemeCycleDirection::Prev, Some(position)) => {
+            position.checked_sub(1).unwrap_or(entries.len() - 1)
+        }
+        (ThemeCycleDirection::Prev, None) => entries.len() - 1,
+    };
+    entries.get(target).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ThemeSpec;
+
+    fn catalog(names: &[&str]) -> Result<ThemeCatalog, &'static str> {
+        let themes: Result<Vec<_>, _> = names
+            .iter()
+            .map(|name| ThemeSpec::try_new(name.to_string(), name.to_string()))
+            .collect();
+        ThemeCatalog::try_from_vec(themes.map_err(|_| "invalid theme")?).map_err(|_| "empty")
+    }
+
+    fn history(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn decode_migrates_legacy_one_line_files() -> Result<(), ThemeStateDecodeError> {
+        let state = ThemeState::decode("\n  kanagawa-wave \nignored\n")?;
+        assert_eq!(state.current.as_deref(), Some("kanagawa-wave"));
+        assert_eq!(state.history, history(&["kanagawa-wave"]));
+        assert!(state.projects.is_empty());
+        assert_eq!(ThemeState::decode("")?, ThemeState::default());
+        Ok(())
+    }
+
+    #[test]
+    fn encode_decode_roundtrip_and_version_check() -> Result<(), ThemeStateDecodeError> {
+        let mut state = ThemeState::default();
+        state.record("a", None, HistoryUpdate::Push);
+        state.pin_project("/repo", "b");
+        assert_eq!(ThemeState::decode(&state.encode())?, state);
+        assert_eq!(
+            ThemeState::decode(r#"{"version":2,"current":"a"}"#),
+            Err(ThemeStateDecodeError::Incompatible)
+        );
+        assert_eq!(
+            ThemeState::decode("{ broken"),
+            Err(ThemeStateDecodeError::Incompatible)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decode_cleans_empty_values_and_duplicate_history() -> Result<(), ThemeStateDecodeError> {
+        let state = ThemeState::decode(
+            r#"{"version":1,"current":"","projects":{"":"a","/r":""},"history":["a","b","a",""]}"#,
+        )?;
+        assert_eq!(state.current, None);
+        assert!(state.projects.is_empty());
+        assert_eq!(state.history, history(&["a", "b"]));
+        Ok(())
+    }
+
+    #[test]
+    fn record_updates_pins_or_global_theme_and_history() {
+        let mut state = ThemeState::default();
+        state.record("a", Some("/repo"), HistoryUpdate::Push);
+        assert_eq!(state.effective(Some("/repo")), Some("a"));
+
+        state.pin_project("/repo", "b");
+        state.record("c", Some("/repo"), HistoryUpdate::Push);
+        assert_eq!(state.effective(Some("/repo")), Some("c"));
+        assert_eq!(state.effective(Some("/other")), Some("a"));
+        assert_eq!(state.effective(None), Some("a"));
+        assert_eq!(state.history, history(&["c", "a"]));
+
+        state.record("a", None, HistoryUpdate::Keep);
+        assert_eq!(state.history, history(&["c", "a"]));
+        assert!(state.unpin_project("/repo"));
+        assert_eq!(state.effective(Some("/repo")), Some("a"));
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut state = ThemeState::default();
+        for index in 0..THEME_HISTORY_LIMIT + 4 {
+            state.record(&format!("theme-{index}"), None, HistoryUpdate::Push);
+        }
+        assert_eq!(state.history.len(), THEME_HISTORY_LIMIT);
+        assert_eq!(state.history.first().map(String::as_str), Some("theme-19"));
+    }
+
+    #[test]
+    fn cycle_history_walks_older_and_newer_entries() -> Result<(), &'static str> {
+        let catalog = catalog(&["a", "b", "c", "d"])?;
+        let recent = history(&["c", "missing", "a", "d"]);
+        let at = |raw| catalog.index(raw);
+        let step = |current, direction| {
+            cycle_history_index(&catalog, &recent, current, direction).map(ThemeIndex::raw)
+        };
+        assert_eq!(step(at(2), ThemeCycleDirection::Next), Some(0));
+        assert_eq!(step(at(0), ThemeCycleDirection::Next), Some(3));
+        assert_eq!(step(at(3), ThemeCycleDirection::Next), Some(2));
+        assert_eq!(step(at(2), ThemeCycleDirection::Prev), Some(3));
+        assert_eq!(step(at(1), ThemeCycleDirection::Next), Some(2));
+        assert_eq!(step(None, ThemeCycleDirection::Prev), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn cycle_history_needs_two_usable_entries() -> Result<(), &'static str> {
+        let catalog = catalog(&["a", "b"])?;
+        assert_eq!(
+            cycle_history_index(
+                &catalog,
+                &history(&["a", "missing"]),
+                None,
+                ThemeCycleDirection::Next
+            ),
+            None
+        );
+        Ok(())
+    }
+}
@@ -33,6 +33,44 @@ fn nvimrs_theme_switcher() -> Dictionary {
         Function::<(), ()>::from_fn(|()| picker::confirm()),
     );
     api.insert("cancel", Function::<(), ()>::from_fn(|()| picker::cancel()));
+    api.insert(
+        "toggle_live_preview",
+        Function::<(), ()>::from_fn(|()| picker::toggle_live_preview()),
+    );
+    api.insert(
+        "cycle_background",
+        Function::<(), ()>::from_fn(|()| picker::cycle_background()),
+    );
+    api.insert(
+        "set_background",
+        Function::<String, ()>::from_fn(|value: String| picker::set_background(&value)),
+    );
     api.insert("close", Function::<(), ()>::from_fn(|()| picker::close()));
+    api.insert(
+        "persisted_colorscheme",
+        Function::<Dictionary, Option<String>>::from_fn(|args| {
+            picker::persisted_colorscheme(&args)
+        }),
+    );
+    api.insert(
+        "pin_project",
+        Function::<Dictionary, ()>::from_fn(|args| picker::pin_project(&args)),
+    );
+    api.insert(
+        "unpin_project",
+        Function::<Dictionary, ()>::from_fn(|args| picker::unpin_project(&args)),
+    );
+    api.insert(
+        "start_auto",
+        Function::<Dictionary, ()>::from_fn(|args| picker::start_auto(&args)),
+    );
+    api.insert(
+        "auto_tick",
+        Function::<(), ()>::from_fn(|()| picker::auto_tick()),
+    );
+    api.insert(
+        "stop_auto",
+        Function::<(), ()>::from_fn(|()| picker::stop_auto()),
+    );
     api
 }
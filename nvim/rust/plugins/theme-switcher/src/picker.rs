@@ -1,28 +1,51 @@
 use std::fs;
-use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::LazyLock;
 
+use crate::args::AppearanceProbe;
+use crate::args::AutoArgs;
 use crate::args::CycleArgs;
+use crate::args::CycleOrder;
 use crate::args::OpenArgs;
+use crate::args::StateArgs;
+use crate::core::AppearanceThemes;
+use crate::core::AutoThemeConfig;
+use crate::core::AutoThemeEffect;
+use crate::core::AutoThemeEvent;
+use crate::core::AutoThemeInputs;
+use crate::core::AutoThemeMachine;
+use crate::core::BackgroundFilter;
+use crate::core::HistoryUpdate;
+use crate::core::PickerRow;
+use crate::core::ScheduleEntry;
+use crate::core::ThemeBackground;
 use crate::core::ThemeCatalog;
 use crate::core::ThemeCycleDirection;
 use crate::core::ThemeIndex;
+use crate::core::ThemeSchedule;
+use crate::core::ThemeState;
 use crate::core::ThemeSwitcherEffect;
 use crate::core::ThemeSwitcherEvent;
 use crate::core::ThemeSwitcherMachine;
+use crate::core::TimeOfDay;
+use crate::core::cycle_history_index;
 use crate::core::cycle_theme_index_from_index;
+use crate::core::parse_appearance;
 use crate::core::resolve_effective_theme_index;
+use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Result;
+use nvim_oxi::String as NvimString;
 use nvim_oxi::api;
 use nvim_oxi::api::Buffer;
 use nvim_oxi::api::Window;
 use nvim_oxi::api::opts::CmdOpts;
+use nvim_oxi::api::opts::CreateAutocmdOpts;
 use nvim_oxi::api::opts::OptionOpts;
 use nvim_oxi::api::opts::OptionScope;
 use nvim_oxi::api::opts::SetKeymapOpts;
+use nvim_oxi::api::types::AutocmdCallbackArgs;
 use nvim_oxi::api::types::CmdInfos;
 use nvim_oxi::api::types::Mode;
 use nvim_oxi::api::types::WindowBorder;
@@ -35,28 +58,38 @@ use nvimrs_nvim_oxi_utils::notify;
 use nvimrs_nvim_oxi_utils::state::StateCell;
 use nvimrs_nvim_oxi_utils::state::StateGuard;
 use nvimrs_nvim_oxi_utils::state_machine::Machine;
+use nvimrs_nvim_utils::store;
+use nvimrs_nvim_utils::store::StoreError;
+use nvimrs_nvim_utils::store::StoreResult;
 
 const LOG_CONTEXT: &str = "nvimrs_theme_switcher";
-const HELP_LINE: &str = "<C-n> next  <C-p> prev  <CR> confirm  <Esc>/q cancel";
+const HELP_LINE: &str =
+    "<C-n>/<C-p> move  <CR> confirm  <C-t> preview  <C-g> background  <Esc> cancel";
 const THEME_LINE_START: usize = 4;
+const NO_MATCHES_LINE: &str = "  (no matching themes)";
+/// Rows a prompt window takes above the list, including its border.
+const PROMPT_ROWS: usize = 3;
 
 #[derive(Debug, Clone)]
 struct PickerSession {
     machine: ThemeSwitcherMachine,
     title: String,
-    state_path: Option<PathBuf>,
+    state: Option<StateTarget>,
     restore_colorscheme_on_cancel: Option<String>,
     buf_handle: BufHandle,
     win_handle: WinHandle,
+    prompt_buf_handle: BufHandle,
+    prompt_win_handle: WinHandle,
 }
 
 impl PickerSession {
-    fn theme_line_number_for(index: ThemeIndex) -> usize {
-        THEME_LINE_START + index.raw()
+    fn theme_line_number_for(&self, index: ThemeIndex) -> Option<usize> {
+        self.machine.row_of(index).map(|row| THEME_LINE_START + row)
     }
 
     fn theme_line_number(&self) -> usize {
-        Self::theme_line_number_for(self.machine.cursor_index())
+        self.theme_line_number_for(self.machine.cursor_index())
+            .unwrap_or(THEME_LINE_START)
     }
 
     fn theme_line(&self, index: ThemeIndex, selected: bool) -> Option<String> {
@@ -67,16 +100,40 @@ impl PickerSession {
             .map(|theme| format!("{prefix}{}", theme.name().as_str()))
     }
 
+    fn status_line(&self) -> String {
+        let preview = if self.machine.live_preview() {
+            "on"
+        } else {
+            "off"
+        };
+        format!(
+            "background: {}  preview: {preview}  {}/{}",
+            self.machine.background_filter().as_str(),
+            self.machine.visible().len(),
+            self.machine.catalog().len()
+        )
+    }
+
+    fn row_line(&self, row: PickerRow) -> Option<String> {
+        match row {
+            PickerRow::Group(background) => Some(format!(
+                "── {}",
+                background.map_or("other", |background| background.as_str())
+            )),
+            PickerRow::Theme(index) => self.theme_line(index, index == self.machine.cursor_index()),
+        }
+    }
+
     fn lines(&self) -> Vec<String> {
-        let mut lines = Vec::with_capacity(self.machine.catalog().len() + (THEME_LINE_START - 1));
+        let rows = self.machine.rows();
+        let mut lines = Vec::with_capacity(rows.len() + THEME_LINE_START);
         lines.push(self.title.clone());
         lines.push(HELP_LINE.to_string());
-        lines.push(String::new());
-        for (raw_index, theme) in self.machine.catalog().iter().enumerate() {
-            let selected = raw_index == self.machine.cursor_index().raw();
-            let prefix = if selected { "> " } else { "  " };
-            lines.push(format!("{prefix}{}", theme.name().as_str()));
+        lines.push(self.status_line());
+        if rows.is_empty() {
+            lines.push(NO_MATCHES_LINE.to_string());
         }
+        lines.extend(rows.into_iter().filter_map(|row| self.row_line(row)));
         lines
     }
 
@@ -88,7 +145,10 @@ impl PickerSession {
     }
 
     fn has_valid_handles(&self) -> bool {
-        self.buf_handle.valid_buffer().is_some() && self.win_handle.valid_window().is_some()
+        self.buf_handle.valid_buffer().is_some()
+            && self.win_handle.valid_window().is_some()
+            && self.prompt_buf_handle.valid_buffer().is_some()
+            && self.prompt_win_handle.valid_window().is_some()
     }
 }
 
@@ -118,54 +178,16 @@ impl ThemeSwitcherContext {
     }
 }
 
-#[derive(Debug)]
-enum ThemeStateStoreError {
-    CreateDirectory { path: PathBuf, message: String },
-    ReadFile { path: PathBuf, message: String },
-    WriteFile { path: PathBuf, message: String },
-}
-
-impl std::fmt::Display for ThemeStateStoreError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::CreateDirectory { path, message } => {
-                write!(
-                    f,
-                    "failed to create persistence directory '{}': {message}",
-                    path.display()
-                )
-            }
-            Self::ReadFile { path, message } => {
-                write!(
-                    f,
-                    "failed to read persistence file '{}': {message}",
-                    path.display()
-                )
-            }
-            Self::WriteFile { path, message } => {
-                write!(
-                    f,
-                    "failed to write persistence file '{}': {message}",
-                    path.display()
-                )
-            }
-        }
-    }
-}
-
-impl std::error::Error for ThemeStateStoreError {}
+/// Pre-JSON name of the state file. Read once, when the JSON file does not
+/// exist yet, and removed after the first successful write.
+const LEGACY_STATE_FILE: &str = "colorscheme.txt";
 
 trait ThemeStateStore {
-    fn read_colorscheme(
-        &self,
-        path: &Path,
-    ) -> std::result::Result<Option<String>, ThemeStateStoreError>;
+    fn load(&self, path: &Path) -> StoreResult<ThemeState>;
 
-    fn write_colorscheme(
-        &self,
-        path: &Path,
-        colorscheme: &str,
-    ) -> std::result::Result<(), ThemeStateStoreError>;
+    /// Read-modify-write of the state at `path`, serialised against other
+    /// instances so concurrent pins and history pushes are not lost.
+    fn update(&self, path: &Path, update: &mut dyn FnMut(&mut ThemeState)) -> StoreResult<()>;
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -175,47 +197,57 @@ impl FsThemeStateStore {
     const fn new() -> Self {
         Self
     }
+
+    /// The stored state plus the legacy file it was migrated from, if any.
+    fn read(path: &Path) -> StoreResult<(ThemeState, Option<PathBuf>)> {
+        let legacy = path.with_file_name(LEGACY_STATE_FILE);
+        let (raw, migrated_from) = match store::read_optional(path)? {
+            Some(raw) => (raw, None),
+            None if legacy != path => match store::read_optional(&legacy)? {
+                Some(raw) => (raw, Some(legacy)),
+                None => return Ok((ThemeState::default(), None)),
+            },
+            None => return Ok((ThemeState::default(), None)),
+        };
+        let state = ThemeState::decode(&raw).map_err(|_| StoreError::Incompatible {
+            path: migrated_from.clone().unwrap_or_else(|| path.to_path_buf()),
+        })?;
+        Ok((state, migrated_from))
+    }
 }
 
 impl ThemeStateStore for FsThemeStateStore {
-    fn read_colorscheme(
-        &self,
-        path: &Path,
-    ) -> std::result::Result<Option<String>, ThemeStateStoreError> {
-        let content = match fs::read_to_string(path) {
-            Ok(content) => content,
-            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
-            Err(err) => {
-                return Err(ThemeStateStoreError::ReadFile {
-                    path: path.to_path_buf(),
-                    message: err.to_string(),
-                });
-            }
-        };
-        Ok(parse_first_non_empty_line(&content))
-    }
-
-    fn write_colorscheme(
-        &self,
-        path: &Path,
-        colorscheme: &str,
-    ) -> std::result::Result<(), ThemeStateStoreError> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|err| ThemeStateStoreError::CreateDirectory {
-                path: parent.to_path_buf(),
-                message: err.to_string(),
-            })?;
-        }
-        fs::write(path, format!("{colorscheme}\n")).map_err(|err| {
-            ThemeStateStoreError::WriteFile {
-                path: path.to_path_buf(),
-                message: err.to_string(),
-            }
+    fn load(&self, path: &Path) -> StoreResult<ThemeState> {
+        Self::read(path).map(|(state, _)| state)
+    }
+
+    fn update(&self, path: &Path, update: &mut dyn FnMut(&mut ThemeState)) -> StoreResult<()> {
+        let migrated_from = store::locked_update(path, || {
+            let (mut state, migrated_from) = Self::read(path)?;
+            update(&mut state);
+            Ok((migrated_from, Some(format!("{}\n", state.encode()))))
         })?;
+        if let Some(legacy) = migrated_from {
+            let _ = fs::remove_file(legacy);
+        }
         Ok(())
     }
 }
 
+/// Where a theme choice is persisted: the state file plus the project root
+/// whose pin (if any) should follow the choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StateTarget {
+    path: PathBuf,
+    project_root: Option<String>,
+}
+
+impl StateTarget {
+    fn new(path: Option<PathBuf>, project_root: Option<String>) -> Option<Self> {
+        path.map(|path| Self { path, project_root })
+    }
+}
+
 #[derive(Debug)]
 enum RuntimeAction {
     ApplyColorscheme {
@@ -223,10 +255,10 @@ enum RuntimeAction {
     },
     ApplyAndPersistColorscheme {
         colorscheme: String,
-        path: Option<PathBuf>,
+        state: Option<StateTarget>,
     },
     ClosePicker {
-        win_handle: WinHandle,
+        win_handles: [WinHandle; 2],
     },
 }
 
@@ -248,6 +280,7 @@ struct DispatchPlan {
 
 static CONTEXT: LazyLock<ThemeSwitcherContext> = LazyLock::new(ThemeSwitcherContext::new);
 static STATE_STORE: FsThemeStateStore = FsThemeStateStore::new();
+static AUTO_STATE: StateCell<Option<AutoSession>> = StateCell::new(None);
 
 fn context() -> &'static ThemeSwitcherContext {
     &CONTEXT
@@ -257,6 +290,16 @@ fn state_store() -> &'static dyn ThemeStateStore {
     &STATE_STORE
 }
 
+fn auto_lock() -> StateGuard<'static, Option<AutoSession>> {
+    AUTO_STATE.lock_recover(|state| {
+        notify::warn(
+            LOG_CONTEXT,
+            "auto state mutex poisoned; stopping automatic theme switching",
+        );
+        *state = None;
+    })
+}
+
 fn dimensions() -> (usize, usize) {
     let opts = OptionOpts::builder().build();
     let columns = api::get_option_value::<i64>("columns", &opts)
@@ -272,7 +315,8 @@ fn dimensions() -> (usize, usize) {
     (columns, lines)
 }
 
-fn build_window_config(title: &str, catalog: &ThemeCatalog) -> WindowConfig {
+/// Floating window configs for the prompt line and the theme list below it.
+fn build_window_configs(title: &str, catalog: &ThemeCatalog) -> (WindowConfig, WindowConfig) {
     let (columns, lines) = dimensions();
     let longest_theme = catalog
         .iter()
@@ -293,26 +337,38 @@ fn build_window_config(title: &str, catalog: &ThemeCatalog) -> WindowConfig {
     let min_width = 32_usize.min(max_width);
     let width = desired_width.clamp(min_width, max_width);
 
-    let desired_height = catalog.len() + (THEME_LINE_START - 1);
-    let max_height = lines.saturating_sub(2).max(1);
+    // Worst case: every theme plus a header for each background group.
+    let desired_height = catalog.len() + 3 + (THEME_LINE_START - 1);
+    let max_height = lines.saturating_sub(2 + PROMPT_ROWS).max(1);
     let min_height = 6_usize.min(max_height);
     let height = desired_height.clamp(min_height, max_height);
 
-    let row = (lines.saturating_sub(height)) as f64 / 2.0;
+    let row = (lines.saturating_sub(height + PROMPT_ROWS)) as f64 / 2.0;
     let col = (columns.saturating_sub(width)) as f64 / 2.0;
 
-    let mut builder = WindowConfig::builder();
-    builder
+    let mut prompt = WindowConfig::builder();
+    prompt
         .relative(WindowRelativeTo::Editor)
         .style(WindowStyle::Minimal)
         .border(WindowBorder::Rounded)
         .width(width as u32)
-        .height(height as u32)
+        .height(1)
         .row(row)
         .col(col)
         .focusable(true)
+        .zindex(121);
+
+    let mut list = WindowConfig::builder();
+    list.relative(WindowRelativeTo::Editor)
+        .style(WindowStyle::Minimal)
+        .border(WindowBorder::Rounded)
+        .width(width as u32)
+        .height(height as u32)
+        .row(row + PROMPT_ROWS as f64)
+        .col(col)
+        .focusable(true)
         .zindex(120);
-    builder.build()
+    (prompt.build(), list.build())
 }
 
 fn configure_buffer(buffer: &Buffer) -> Result<()> {
@@ -325,6 +381,15 @@ fn configure_buffer(buffer: &Buffer) -> Result<()> {
     Ok(())
 }
 
+fn configure_prompt_buffer(buffer: &Buffer) -> Result<()> {
+    let opts = OptionOpts::builder().buf(buffer.clone()).build();
+    api::set_option_value("buftype", "nofile", &opts)?;
+    api::set_option_value("bufhidden", "wipe", &opts)?;
+    api::set_option_value("swapfile", false, &opts)?;
+    api::set_option_value("filetype", "nvimrs_theme_switcher_prompt", &opts)?;
+    Ok(())
+}
+
 fn configure_window(window: &Window) -> Result<()> {
     let opts = OptionOpts::builder()
         .scope(OptionScope::Local)
@@ -338,18 +403,118 @@ fn configure_window(window: &Window) -> Result<()> {
     Ok(())
 }
 
-fn set_picker_keymap(buffer: &mut Buffer, lhs: &str, rhs: &str, desc: &str) -> Result<()> {
+fn set_picker_keymap_in(
+    buffer: &mut Buffer,
+    mode: Mode,
+    lhs: &str,
+    rhs: &str,
+    desc: &str,
+) -> Result<()> {
     let opts = SetKeymapOpts::builder()
         .noremap(true)
         .silent(true)
         .nowait(true)
         .desc(desc)
         .build();
-    buffer.set_keymap(Mode::Normal, lhs, rhs, &opts)?;
+    buffer.set_keymap(mode, lhs, rhs, &opts)?;
+    Ok(())
+}
+
+fn set_picker_keymap(buffer: &mut Buffer, lhs: &str, rhs: &str, desc: &str) -> Result<()> {
+    set_picker_keymap_in(buffer, Mode::Normal, lhs, rhs, desc)
+}
+
+/// Picker keys shared by the prompt (insert and normal mode) and the list.
+const PICKER_KEYS: [(&str, &str, &str); 8] = [
+    (
+        "<C-n>",
+        "<cmd>lua require('nvimrs_theme_switcher').move_next()<CR>",
+        "Theme switcher next",
+    ),
+    (
+        "<Down>",
+        "<cmd>lua require('nvimrs_theme_switcher').move_next()<CR>",
+        "Theme switcher next",
+    ),
+    (
+        "<C-p>",
+        "<cmd>lua require('nvimrs_theme_switcher').move_prev()<CR>",
+        "Theme switcher previous",
+    ),
+    (
+        "<Up>",
+        "<cmd>lua require('nvimrs_theme_switcher').move_prev()<CR>",
+        "Theme switcher previous",
+    ),
+    (
+        "<CR>",
+        "<cmd>lua require('nvimrs_theme_switcher').confirm()<CR>",
+        "Theme switcher confirm",
+    ),
+    (
+        "<Esc>",
+        "<cmd>lua require('nvimrs_theme_switcher').cancel()<CR>",
+        "Theme switcher cancel",
+    ),
+    (
+        "<C-t>",
+        "<cmd>lua require('nvimrs_theme_switcher').toggle_live_preview()<CR>",
+        "Theme switcher toggle live preview",
+    ),
+    (
+        "<C-g>",
+        "<cmd>lua require('nvimrs_theme_switcher').cycle_background()<CR>",
+        "Theme switcher cycle background filter",
+    ),
+];
+
+fn setup_prompt_keymaps(buffer: &mut Buffer) -> Result<()> {
+    for (lhs, rhs, desc) in PICKER_KEYS {
+        set_picker_keymap_in(buffer, Mode::Insert, lhs, rhs, desc)?;
+        set_picker_keymap(buffer, lhs, rhs, desc)?;
+    }
+    set_picker_keymap(
+        buffer,
+        "q",
+        "<cmd>lua require('nvimrs_theme_switcher').cancel()<CR>",
+        "Theme switcher cancel",
+    )?;
+    Ok(())
+}
+
+/// Feed prompt edits into the reducer as `SetQuery`.
+fn setup_prompt_autocmd(buffer: &Buffer) -> Result<()> {
+    let opts = CreateAutocmdOpts::builder()
+        .buffer(buffer.clone())
+        .callback(|args: AutocmdCallbackArgs| {
+            let query = args
+                .buffer
+                .get_lines(0..1, false)
+                .ok()
+                .and_then(|mut lines| lines.next())
+                .map(|line| line.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            dispatch_event(ThemeSwitcherEvent::SetQuery(query));
+            false
+        })
+        .build();
+    api::create_autocmd(["TextChanged", "TextChangedI"], &opts)?;
     Ok(())
 }
 
 fn setup_picker_keymaps(buffer: &mut Buffer) -> Result<()> {
+    set_picker_keymap(
+        buffer,
+        "<C-t>",
+        "<cmd>lua require('nvimrs_theme_switcher').toggle_live_preview()<CR>",
+        "Theme switcher toggle live preview",
+    )?;
+    set_picker_keymap(
+        buffer,
+        "<C-g>",
+        "<cmd>lua require('nvimrs_theme_switcher').cycle_background()<CR>",
+        "Theme switcher cycle background filter",
+    )?;
     set_picker_keymap(
         buffer,
         "<C-n>",
@@ -460,14 +625,14 @@ fn render_cursor_delta(previous: ThemeIndex, current: ThemeIndex) -> Result<()>
         let mut updates = Vec::with_capacity(2);
         if previous != current
             && let Some(previous_line) = session.theme_line(previous, false)
+            && let Some(line_number) = session.theme_line_number_for(previous)
         {
-            updates.push((
-                PickerSession::theme_line_number_for(previous),
-                previous_line,
-            ));
+            updates.push((line_number, previous_line));
         }
-        if let Some(current_line) = session.theme_line(current, true) {
-            updates.push((PickerSession::theme_line_number_for(current), current_line));
+        if let Some(current_line) = session.theme_line(current, true)
+            && let Some(line_number) = session.theme_line_number_for(current)
+        {
+            updates.push((line_number, current_line));
         }
         if !updates.is_empty() {
             set_buffer_line_updates(&mut buffer, updates)?;
@@ -496,6 +661,17 @@ fn close_window(win_handle: WinHandle) {
     }
 }
 
+/// Close the prompt and list windows and leave the insert mode the prompt
+/// was typed in.
+fn close_picker_windows(win_handles: [WinHandle; 2]) {
+    for win_handle in win_handles {
+        close_window(win_handle);
+    }
+    if let Err(err) = api::command("stopinsert") {
+        notify::warn(LOG_CONTEXT, &format!("stopinsert failed: {err}"));
+    }
+}
+
 fn apply_colorscheme(colorscheme: &str) -> Result<()> {
     let infos = CmdInfos::builder()
         .cmd("colorscheme")
@@ -506,18 +682,10 @@ fn apply_colorscheme(colorscheme: &str) -> Result<()> {
     Ok(())
 }
 
-fn parse_first_non_empty_line(content: &str) -> Option<String> {
-    content
-        .lines()
-        .map(str::trim)
-        .find(|line| !line.is_empty())
-        .map(ToOwned::to_owned)
-}
-
-fn read_persisted_colorscheme(path: Option<&Path>, store: &dyn ThemeStateStore) -> Option<String> {
-    let path = path?;
-    match store.read_colorscheme(path) {
-        Ok(name) => name,
+fn load_state(target: Option<&StateTarget>, store: &dyn ThemeStateStore) -> Option<ThemeState> {
+    let target = target?;
+    match store.load(&target.path) {
+        Ok(state) => Some(state),
         Err(err) => {
             notify::warn(LOG_CONTEXT, &format!("{err}"));
             None
@@ -528,26 +696,50 @@ fn read_persisted_colorscheme(path: Option<&Path>, store: &dyn ThemeStateStore)
 fn resolve_effective_index(
     catalog: &ThemeCatalog,
     current_colorscheme: Option<&str>,
-    state_path: Option<&Path>,
+    target: Option<&StateTarget>,
     store: &dyn ThemeStateStore,
 ) -> Option<ThemeIndex> {
-    let persisted_colorscheme = read_persisted_colorscheme(state_path, store);
+    let state = load_state(target, store);
+    let project_root = target.and_then(|target| target.project_root.as_deref());
     resolve_effective_theme_index(
         catalog,
-        persisted_colorscheme.as_deref(),
+        state
+            .as_ref()
+            .and_then(|state| state.effective(project_root)),
         current_colorscheme,
     )
 }
 
-fn persist_with_store(path: &Path, colorscheme: &str, store: &dyn ThemeStateStore) {
-    if let Err(err) = store.write_colorscheme(path, colorscheme) {
-        notify::warn(LOG_CONTEXT, &format!("{err}"));
+/// Read-modify-write of the state file. Unreadable or incompatible files are
+/// left alone rather than replaced with a fresh state. Returns whether the
+/// update was written.
+fn update_state<F>(target: &StateTarget, store: &dyn ThemeStateStore, mut update: F) -> bool
+where
+    F: FnMut(&mut ThemeState),
+{
+    match store.update(&target.path, &mut update) {
+        Ok(()) => true,
+        Err(err) => {
+            notify::warn(LOG_CONTEXT, &format!("{err}"));
+            false
+        }
     }
 }
 
+fn persist_with_store(
+    target: &StateTarget,
+    colorscheme: &str,
+    history: HistoryUpdate,
+    store: &dyn ThemeStateStore,
+) {
+    update_state(target, store, |state| {
+        state.record(colorscheme, target.project_root.as_deref(), history);
+    });
+}
+
 fn apply_then_persist<F, E>(
     colorscheme: &str,
-    path: Option<&Path>,
+    target: Option<&StateTarget>,
     store: &dyn ThemeStateStore,
     apply: F,
 ) -> std::result::Result<(), E>
@@ -555,8 +747,8 @@ where
     F: FnOnce(&str) -> std::result::Result<(), E>,
 {
     apply(colorscheme)?;
-    if let Some(path) = path {
-        persist_with_store(path, colorscheme, store);
+    if let Some(target) = target {
+        persist_with_store(target, colorscheme, HistoryUpdate::Push, store);
     }
     Ok(())
 }
@@ -571,9 +763,9 @@ fn execute_action(action: RuntimeAction, store: &dyn ThemeStateStore) {
                 );
             }
         }
-        RuntimeAction::ApplyAndPersistColorscheme { colorscheme, path } => {
+        RuntimeAction::ApplyAndPersistColorscheme { colorscheme, state } => {
             if let Err(err) =
-                apply_then_persist(&colorscheme, path.as_deref(), store, apply_colorscheme)
+                apply_then_persist(&colorscheme, state.as_ref(), store, apply_colorscheme)
             {
                 notify::warn(
                     LOG_CONTEXT,
@@ -581,7 +773,7 @@ fn execute_action(action: RuntimeAction, store: &dyn ThemeStateStore) {
                 );
             }
         }
-        RuntimeAction::ClosePicker { win_handle } => close_window(win_handle),
+        RuntimeAction::ClosePicker { win_handles } => close_picker_windows(win_handles),
     }
 }
 
@@ -594,13 +786,16 @@ fn session_from_args(
         title,
         current_colorscheme,
         state_path,
+        project_root,
+        options,
     } = args;
 
     let catalog = ThemeCatalog::try_from_vec(themes).map_err(|err| err.to_string())?;
+    let state = StateTarget::new(state_path, project_root);
     let matched_index = resolve_effective_index(
         &catalog,
         current_colorscheme.as_deref(),
-        state_path.as_deref(),
+        state.as_ref(),
         store,
     );
     let persisted_index = matched_index.unwrap_or_else(|| catalog.first_index());
@@ -610,22 +805,32 @@ fn session_from_args(
         None
     };
 
-    let window_config = build_window_config(&title, &catalog);
-    let machine = ThemeSwitcherMachine::new(catalog, persisted_index.raw());
+    let (prompt_config, list_config) = build_window_configs(&title, &catalog);
+    let machine = ThemeSwitcherMachine::with_options(catalog, persisted_index.raw(), options);
 
     let mut buffer = api::create_buf(false, true).map_err(|err| err.to_string())?;
     configure_buffer(&buffer).map_err(|err| err.to_string())?;
-    let window = api::open_win(&buffer, true, &window_config).map_err(|err| err.to_string())?;
+    let window = api::open_win(&buffer, false, &list_config).map_err(|err| err.to_string())?;
     configure_window(&window).map_err(|err| err.to_string())?;
     setup_picker_keymaps(&mut buffer).map_err(|err| err.to_string())?;
 
+    let mut prompt_buffer = api::create_buf(false, true).map_err(|err| err.to_string())?;
+    configure_prompt_buffer(&prompt_buffer).map_err(|err| err.to_string())?;
+    setup_prompt_keymaps(&mut prompt_buffer).map_err(|err| err.to_string())?;
+    setup_prompt_autocmd(&prompt_buffer).map_err(|err| err.to_string())?;
+    let prompt_window =
+        api::open_win(&prompt_buffer, true, &prompt_config).map_err(|err| err.to_string())?;
+    api::command("startinsert").map_err(|err| err.to_string())?;
+
     Ok(PickerSession {
         machine,
         title,
-        state_path,
+        state,
         restore_colorscheme_on_cancel,
         buf_handle: BufHandle::from_buffer(&buffer),
         win_handle: WinHandle::from_window(&window),
+        prompt_buf_handle: BufHandle::from_buffer(&prompt_buffer),
+        prompt_win_handle: WinHandle::from_window(&prompt_window),
     })
 }
 
@@ -635,7 +840,7 @@ fn close_active_session() {
         state.take()
     };
     if let Some(session) = old {
-        close_window(session.win_handle);
+        close_picker_windows([session.prompt_win_handle, session.win_handle]);
     }
 }
 
@@ -662,29 +867,33 @@ fn dispatch_event(event: ThemeSwitcherEvent) {
 
         for effect in transition.effects {
             match effect {
-                ThemeSwitcherEffect::PreviewTheme(index) => {
-                    if let Some(colorscheme) = session.colorscheme_for_index(index) {
+                ThemeSwitcherEffect::PreviewTheme(index)
+                | ThemeSwitcherEffect::MoveCursor(index) => {
+                    if matches!(effect, ThemeSwitcherEffect::PreviewTheme(_))
+                        && let Some(colorscheme) = session.colorscheme_for_index(index)
+                    {
                         actions.push(RuntimeAction::ApplyColorscheme {
                             colorscheme: colorscheme.to_string(),
                         });
-                        if previous_cursor != index {
-                            render = match render {
-                                RenderPlan::NoRender => RenderPlan::CursorDelta {
-                                    previous: previous_cursor,
-                                    current: index,
-                                },
-                                RenderPlan::CursorDelta { .. } | RenderPlan::Full => {
-                                    RenderPlan::Full
-                                }
-                            };
-                        }
                     }
+                    if previous_cursor != index {
+                        render = match render {
+                            RenderPlan::NoRender => RenderPlan::CursorDelta {
+                                previous: previous_cursor,
+                                current: index,
+                            },
+                            RenderPlan::CursorDelta { .. } | RenderPlan::Full => RenderPlan::Full,
+                        };
+                    }
+                }
+                ThemeSwitcherEffect::RefreshList => {
+                    render = RenderPlan::Full;
                 }
                 ThemeSwitcherEffect::PersistTheme(index) => {
                     if let Some(colorscheme) = session.colorscheme_for_index(index) {
                         actions.push(RuntimeAction::ApplyAndPersistColorscheme {
                             colorscheme: colorscheme.to_string(),
-                            path: session.state_path.clone(),
+                            state: session.state.clone(),
                         });
                     }
                 }
@@ -705,7 +914,7 @@ fn dispatch_event(event: ThemeSwitcherEvent) {
 
         if should_close {
             actions.push(RuntimeAction::ClosePicker {
-                win_handle: session.win_handle,
+                win_handles: [session.prompt_win_handle, session.win_handle],
             });
             *state = None;
             render = RenderPlan::NoRender;
@@ -770,13 +979,23 @@ fn execute_cycle(args: &Dictionary, direction: ThemeCycleDirection) {
         }
     };
 
-    let effective_index = resolve_effective_index(
+    let target = StateTarget::new(parsed.state_path, parsed.project_root);
+    let state = load_state(target.as_ref(), store).unwrap_or_default();
+    let project_root = target
+        .as_ref()
+        .and_then(|target| target.project_root.as_deref());
+    let effective_index = resolve_effective_theme_index(
         &catalog,
+        state.effective(project_root),
         parsed.current_colorscheme.as_deref(),
-        parsed.state_path.as_deref(),
-        store,
     );
-    let target_index = cycle_theme_index_from_index(&catalog, effective_index, direction);
+    let (target_index, history) = cycle_target(
+        &catalog,
+        effective_index,
+        &state.history,
+        parsed.order,
+        direction,
+    );
     let Some(theme) = catalog.get(target_index) else {
         notify::error(LOG_CONTEXT, "failed to resolve cycle target theme");
         return;
@@ -791,9 +1010,29 @@ fn execute_cycle(args: &Dictionary, direction: ThemeCycleDirection) {
         return;
     }
 
-    if let Some(path) = parsed.state_path.as_deref() {
-        persist_with_store(path, &colorscheme, store);
+    if let Some(target) = target.as_ref() {
+        persist_with_store(target, &colorscheme, history, store);
+    }
+}
+
+/// Walking the history keeps its order intact so repeated steps reach older
+/// entries; catalog steps record the new theme as most recent.
+fn cycle_target(
+    catalog: &ThemeCatalog,
+    effective_index: Option<ThemeIndex>,
+    history: &[String],
+    order: CycleOrder,
+    direction: ThemeCycleDirection,
+) -> (ThemeIndex, HistoryUpdate) {
+    if order == CycleOrder::History
+        && let Some(index) = cycle_history_index(catalog, history, effective_index, direction)
+    {
+        return (index, HistoryUpdate::Keep);
     }
+    (
+        cycle_theme_index_from_index(catalog, effective_index, direction),
+        HistoryUpdate::Push,
+    )
 }
 
 pub fn cycle_next(args: &Dictionary) {
@@ -820,51 +1059,345 @@ pub fn cancel() {
     dispatch_event(ThemeSwitcherEvent::Cancel);
 }
 
+pub fn toggle_live_preview() {
+    dispatch_event(ThemeSwitcherEvent::ToggleLivePreview);
+}
+
+pub fn cycle_background() {
+    dispatch_event(ThemeSwitcherEvent::CycleBackground);
+}
+
+pub fn set_background(value: &str) {
+    let Some(filter) = BackgroundFilter::parse(value) else {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("invalid background filter '{value}', expected all/dark/light"),
+        );
+        return;
+    };
+    dispatch_event(ThemeSwitcherEvent::SetBackground(filter));
+}
+
 pub fn close() {
     close_active_session();
 }
 
+trait ThemeClock {
+    fn now(&self) -> Option<TimeOfDay>;
+}
+
+/// Local time as Neovim sees it, so `TZ` and `:language time` apply.
+#[derive(Debug, Clone, Copy, Default)]
+struct NvimClock;
+
+impl ThemeClock for NvimClock {
+    fn now(&self) -> Option<TimeOfDay> {
+        let value: NvimString = api::call_function("strftime", Array::from_iter(["%H:%M"]))
+            .map_err(|err| notify::warn(LOG_CONTEXT, &format!("strftime failed: {err}")))
+            .ok()?;
+        TimeOfDay::parse(&value.to_string_lossy())
+    }
+}
+
+trait AppearanceSource {
+    fn appearance(&self) -> Option<ThemeBackground>;
+}
+
+impl AppearanceSource for AppearanceProbe {
+    fn appearance(&self) -> Option<ThemeBackground> {
+        let from_env = self
+            .env
+            .as_deref()
+            .and_then(|name| std::env::var(name).ok())
+            .and_then(|value| parse_appearance(&value));
+        from_env.or_else(|| {
+            let path = self.file.as_deref()?;
+            fs::read_to_string(path)
+                .ok()
+                .and_then(|value| parse_appearance(&value))
+        })
+    }
+}
+
+#[derive(Debug)]
+struct AutoSession {
+    machine: AutoThemeMachine,
+    catalog: ThemeCatalog,
+    state: Option<StateTarget>,
+    probe: Option<AppearanceProbe>,
+}
+
+fn auto_session_from_args(args: AutoArgs) -> std::result::Result<AutoSession, String> {
+    let catalog = ThemeCatalog::try_from_vec(args.themes).map_err(|err| err.to_string())?;
+    let resolve = |colorscheme: &str| {
+        catalog
+            .find_by_colorscheme(colorscheme)
+            .ok_or_else(|| format!("colorscheme '{colorscheme}' is not in the theme list"))
+    };
+    let entries = args
+        .schedule
+        .iter()
+        .map(|entry| {
+            resolve(&entry.colorscheme).map(|theme| ScheduleEntry {
+                start: entry.start,
+                theme,
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let appearance = args
+        .appearance
+        .as_ref()
+        .map(|appearance| {
+            Ok::<_, String>(AppearanceThemes {
+                dark: resolve(&appearance.dark)?,
+                light: resolve(&appearance.light)?,
+            })
+        })
+        .transpose()?;
+    let config = AutoThemeConfig {
+        schedule: ThemeSchedule::new(entries),
+        appearance,
+    };
+    Ok(AutoSession {
+        machine: AutoThemeMachine::new(config),
+        catalog,
+        state: StateTarget::new(args.state_path, None),
+        probe: args.appearance.map(|appearance| appearance.probe),
+    })
+}
+
+fn auto_inputs(
+    session: &AutoSession,
+    current_colorscheme: Option<&str>,
+    clock: &dyn ThemeClock,
+    source: Option<&dyn AppearanceSource>,
+) -> AutoThemeInputs {
+    AutoThemeInputs {
+        now: clock.now(),
+        appearance: source.and_then(|source| source.appearance()),
+        current: current_colorscheme.and_then(|name| session.catalog.find_by_colorscheme(name)),
+    }
+}
+
+/// Run one automatic-switching step. A failed apply forgets the target so the
+/// next tick retries instead of waiting for the next schedule boundary.
+fn run_auto_tick<F, E>(
+    session: &mut AutoSession,
+    inputs: AutoThemeInputs,
+    store: &dyn ThemeStateStore,
+    mut apply: F,
+) -> std::result::Result<Option<String>, E>
+where
+    F: FnMut(&str) -> std::result::Result<(), E>,
+{
+    let mut applied = None;
+    for effect in session.machine.reduce(AutoThemeEvent::Tick(inputs)).effects {
+        let AutoThemeEffect::ApplyTheme(index) = effect;
+        let Some(theme) = session.catalog.get(index) else {
+            continue;
+        };
+        let colorscheme = theme.colorscheme().as_str().to_string();
+        if let Err(err) =
+            apply_then_persist(&colorscheme, session.state.as_ref(), store, &mut apply)
+        {
+            let _ = session.machine.reduce(AutoThemeEvent::Reset);
+            return Err(err);
+        }
+        applied = Some(colorscheme);
+    }
+    Ok(applied)
+}
+
+fn current_colorscheme() -> Option<String> {
+    api::get_var::<NvimString>("colors_name")
+        .ok()
+        .map(|value| value.to_string_lossy().into_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// Start (or restart) automatic switching and apply the current target.
+pub fn start_auto(args: &Dictionary) {
+    let parsed = match AutoArgs::parse(args) {
+        Ok(value) => value,
+        Err(err) => {
+            notify::error(LOG_CONTEXT, &format!("invalid auto args: {err}"));
+            return;
+        }
+    };
+    let session = match auto_session_from_args(parsed) {
+        Ok(value) => value,
+        Err(err) => {
+            notify::error(LOG_CONTEXT, &format!("invalid auto args: {err}"));
+            return;
+        }
+    };
+    *auto_lock() = Some(session);
+    auto_tick();
+}
+
+/// Re-read the clock and appearance source; applies a theme only when the
+/// target changed since the previous tick.
+pub fn auto_tick() {
+    let current = current_colorscheme();
+    let mut state = auto_lock();
+    let Some(session) = state.as_mut() else {
+        return;
+    };
+    let source = session
+        .probe
+        .as_ref()
+        .map(|probe| probe as &dyn AppearanceSource);
+    let inputs = auto_inputs(session, current.as_deref(), &NvimClock, source);
+    if let Err(err) = run_auto_tick(session, inputs, state_store(), apply_colorscheme) {
+        notify::warn(
+            LOG_CONTEXT,
+            &format!("automatic theme switch failed: {err}"),
+        );
+    }
+}
+
+pub fn stop_auto() {
+    *auto_lock() = None;
+}
+
+fn state_target_from_args(args: &Dictionary) -> Option<StateTarget> {
+    match StateArgs::parse(args) {
+        Ok(parsed) => Some(StateTarget {
+            path: parsed.state_path,
+            project_root: parsed.project_root,
+        }),
+        Err(err) => {
+            notify::error(LOG_CONTEXT, &format!("invalid state args: {err}"));
+            None
+        }
+    }
+}
+
+/// The persisted theme for `project_root`: its pin, else the global theme.
+/// Legacy one-line files are read transparently.
+pub fn persisted_colorscheme(args: &Dictionary) -> Option<String> {
+    let target = state_target_from_args(args)?;
+    let state = load_state(Some(&target), state_store())?;
+    state
+        .effective(target.project_root.as_deref())
+        .map(ToOwned::to_owned)
+}
+
+/// Pin `colorscheme` (default: the active one) to `project_root`.
+pub fn pin_project(args: &Dictionary) {
+    let parsed = match StateArgs::parse(args) {
+        Ok(value) => value,
+        Err(err) => {
+            notify::error(LOG_CONTEXT, &format!("invalid pin args: {err}"));
+            return;
+        }
+    };
+    let Some(root) = parsed.project_root else {
+        notify::warn(LOG_CONTEXT, "no project root to pin a theme to");
+        return;
+    };
+    let Some(colorscheme) = parsed.colorscheme.or_else(current_colorscheme) else {
+        notify::warn(LOG_CONTEXT, "no active colorscheme to pin");
+        return;
+    };
+    let target = StateTarget {
+        path: parsed.state_path,
+        project_root: Some(root.clone()),
+    };
+    if update_state(&target, state_store(), |state| {
+        state.pin_project(&root, &colorscheme);
+    }) {
+        notify::info(LOG_CONTEXT, &format!("pinned '{colorscheme}' to {root}"));
+    }
+}
+
+pub fn unpin_project(args: &Dictionary) {
+    let Some(target) = state_target_from_args(args) else {
+        return;
+    };
+    let Some(root) = target.project_root.clone() else {
+        notify::warn(LOG_CONTEXT, "no project root to unpin");
+        return;
+    };
+    let mut removed = false;
+    update_state(&target, state_store(), |state| {
+        removed = state.unpin_project(&root);
+    });
+    if removed {
+        notify::info(LOG_CONTEXT, &format!("removed theme pin for {root}"));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::args::AppearanceArg;
+    use crate::args::ScheduleArg;
+    use crate::core::ThemeSpec;
     use std::cell::RefCell;
 
     #[derive(Debug, Default)]
     struct RecordingStateStore {
-        writes: RefCell<Vec<(PathBuf, String)>>,
+        state: RefCell<ThemeState>,
+        writes: RefCell<Vec<(PathBuf, ThemeState)>>,
     }
 
     impl RecordingStateStore {
+        fn with_state(state: ThemeState) -> Self {
+            Self {
+                state: RefCell::new(state),
+                writes: RefCell::default(),
+            }
+        }
+
+        /// Saved paths with the global theme of each saved state.
         fn writes(&self) -> Vec<(PathBuf, String)> {
-            self.writes.borrow().clone()
+            self.writes
+                .borrow()
+                .iter()
+                .map(|(path, state)| (path.clone(), state.current.clone().unwrap_or_default()))
+                .collect()
+        }
+
+        fn state(&self) -> ThemeState {
+            self.state.borrow().clone()
         }
     }
 
     impl ThemeStateStore for RecordingStateStore {
-        fn read_colorscheme(
-            &self,
-            _path: &Path,
-        ) -> std::result::Result<Option<String>, ThemeStateStoreError> {
-            Ok(None)
-        }
-
-        fn write_colorscheme(
-            &self,
-            path: &Path,
-            colorscheme: &str,
-        ) -> std::result::Result<(), ThemeStateStoreError> {
-            self.writes
-                .borrow_mut()
-                .push((path.to_path_buf(), colorscheme.to_string()));
+        fn load(&self, _path: &Path) -> StoreResult<ThemeState> {
+            Ok(self.state())
+        }
+
+        fn update(&self, path: &Path, update: &mut dyn FnMut(&mut ThemeState)) -> StoreResult<()> {
+            let mut state = self.state();
+            update(&mut state);
+            self.state.replace(state.clone());
+            self.writes.borrow_mut().push((path.to_path_buf(), state));
             Ok(())
         }
     }
 
+    fn target(project_root: Option<&str>) -> StateTarget {
+        StateTarget {
+            path: PathBuf::from("/tmp/theme-switcher-state"),
+            project_root: project_root.map(ToOwned::to_owned),
+        }
+    }
+
+    fn temp_state_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!(
+                "nvimrs-theme-switcher-{}-{name}",
+                std::process::id()
+            ))
+            .join("state.json")
+    }
+
     #[test]
     fn apply_then_persist_skips_write_when_apply_fails() {
         let store = RecordingStateStore::default();
-        let path = Path::new("/tmp/theme-switcher-state");
-        let result = apply_then_persist("broken", Some(path), &store, |_| Err("boom"));
+        let result = apply_then_persist("broken", Some(&target(None)), &store, |_| Err("boom"));
         assert!(result.is_err());
         assert!(store.writes().is_empty());
     }
@@ -873,11 +1406,287 @@ mod tests {
     fn apply_then_persist_writes_when_apply_succeeds() {
         let store = RecordingStateStore::default();
         let path = Path::new("/tmp/theme-switcher-state");
-        let result = apply_then_persist("tokyonight", Some(path), &store, |_| Ok::<(), &str>(()));
+        let result = apply_then_persist("tokyonight", Some(&target(None)), &store, |_| {
+            Ok::<(), &str>(())
+        });
         assert!(result.is_ok());
         assert_eq!(
             store.writes(),
             vec![(path.to_path_buf(), "tokyonight".to_string())]
         );
     }
+
+    #[test]
+    fn apply_then_persist_updates_project_pin_and_history() {
+        let mut initial = ThemeState::default();
+        initial.record("kanagawa-wave", None, HistoryUpdate::Push);
+        initial.pin_project("/repo", "kanagawa-lotus");
+        let store = RecordingStateStore::with_state(initial);
+        let result = apply_then_persist(
+            "modus_vivendi",
+            Some(&target(Some("/repo"))),
+            &store,
+            |_| Ok::<(), &str>(()),
+        );
+        assert!(result.is_ok());
+        let state = store.state();
+        assert_eq!(state.effective(Some("/repo")), Some("modus_vivendi"));
+        assert_eq!(state.current.as_deref(), Some("kanagawa-wave"));
+        assert_eq!(
+            state.history,
+            vec!["modus_vivendi".to_string(), "kanagawa-wave".to_string()]
+        );
+    }
+
+    #[test]
+    fn cycle_target_walks_history_without_reordering_it() -> std::result::Result<(), String> {
+        let themes = ["a", "b", "c"]
+            .iter()
+            .map(|name| ThemeSpec::try_new(name.to_string(), name.to_string()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        let catalog = ThemeCatalog::try_from_vec(themes).map_err(|err| err.to_string())?;
+        let history = vec!["c".to_string(), "a".to_string()];
+        let current = catalog.find_by_colorscheme("c");
+
+        let (index, update) = cycle_target(
+            &catalog,
+            current,
+            &history,
+            CycleOrder::History,
+            ThemeCycleDirection::Next,
+        );
+        assert_eq!((index.raw(), update), (0, HistoryUpdate::Keep));
+
+        let (index, update) = cycle_target(
+            &catalog,
+            current,
+            &history,
+            CycleOrder::Catalog,
+            ThemeCycleDirection::Next,
+        );
+        assert_eq!((index.raw(), update), (0, HistoryUpdate::Push));
+
+        let (index, update) = cycle_target(
+            &catalog,
+            current,
+            &history[..1],
+            CycleOrder::History,
+            ThemeCycleDirection::Prev,
+        );
+        assert_eq!((index.raw(), update), (1, HistoryUpdate::Push));
+        Ok(())
+    }
+
+    #[test]
+    fn fs_store_migrates_legacy_files_and_writes_atomically() -> StoreResult<()> {
+        let path = temp_state_path("migrate");
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let legacy = dir.join(LEGACY_STATE_FILE);
+        let store = FsThemeStateStore::new();
+        assert_eq!(store.load(&path)?, ThemeState::default());
+
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(&legacy, "kanagawa-wave\n");
+        let mut state = store.load(&path)?;
+        assert_eq!(state.effective(None), Some("kanagawa-wave"));
+
+        store.update(&path, &mut |stored| {
+            stored.record("modus_operandi", None, HistoryUpdate::Push);
+        })?;
+        state.record("modus_operandi", None, HistoryUpdate::Push);
+        assert_eq!(store.load(&path)?, state);
+        let leftovers = fs::read_dir(&dir).map(Iterator::count).unwrap_or_default();
+        assert_eq!(leftovers, 1);
+        assert!(!legacy.exists());
+        let _ = fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    #[test]
+    fn fs_store_updates_merge_with_other_writers() -> StoreResult<()> {
+        let path = temp_state_path("merge");
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let store = FsThemeStateStore::new();
+        let stale = store.load(&path)?;
+
+        let mut other = stale;
+        other.pin_project("/repo", "kanagawa-wave");
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(&path, format!("{}\n", other.encode()));
+
+        store.update(&path, &mut |state| {
+            state.record("modus_operandi", None, HistoryUpdate::Push);
+        })?;
+
+        let state = store.load(&path)?;
+        assert_eq!(state.effective(Some("/repo")), Some("kanagawa-wave"));
+        assert_eq!(state.effective(None), Some("modus_operandi"));
+        let _ = fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    #[test]
+    fn fs_store_rejects_incompatible_files() {
+        let path = temp_state_path("incompatible");
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let _ = fs::create_dir_all(&dir);
+        let newer = r#"{"version":99,"current":"future"}"#;
+        let _ = fs::write(&path, newer);
+        let store = FsThemeStateStore::new();
+        assert!(matches!(
+            store.load(&path),
+            Err(StoreError::Incompatible { .. })
+        ));
+        assert_eq!(fs::read_to_string(&path).ok().as_deref(), Some(newer));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    struct FixedClock(Option<TimeOfDay>);
+
+    impl ThemeClock for FixedClock {
+        fn now(&self) -> Option<TimeOfDay> {
+            self.0
+        }
+    }
+
+    struct FixedAppearance(Option<ThemeBackground>);
+
+    impl AppearanceSource for FixedAppearance {
+        fn appearance(&self) -> Option<ThemeBackground> {
+            self.0
+        }
+    }
+
+    fn auto_session(appearance: bool) -> std::result::Result<AutoSession, String> {
+        let themes = ["kanagawa-lotus", "kanagawa-wave", "kanagawa-dragon"]
+            .iter()
+            .map(|name| ThemeSpec::try_new(name.to_string(), name.to_string()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        let at = |value: &str| TimeOfDay::parse(value).ok_or("invalid time".to_string());
+        auto_session_from_args(AutoArgs {
+            themes,
+            state_path: Some(PathBuf::from("/tmp/theme-switcher-state")),
+            schedule: vec![
+                ScheduleArg {
+                    start: at("07:00")?,
+                    colorscheme: "kanagawa-lotus".to_string(),
+                },
+                ScheduleArg {
+                    start: at("19:00")?,
+                    colorscheme: "kanagawa-wave".to_string(),
+                },
+            ],
+            appearance: appearance.then(|| AppearanceArg {
+                probe: AppearanceProbe::default(),
+                dark: "kanagawa-dragon".to_string(),
+                light: "kanagawa-lotus".to_string(),
+            }),
+        })
+    }
+
+    fn tick(
+        session: &mut AutoSession,
+        store: &RecordingStateStore,
+        now: &str,
+        appearance: Option<ThemeBackground>,
+        current: Option<&str>,
+    ) -> std::result::Result<Option<String>, &'static str> {
+        let inputs = auto_inputs(
+            session,
+            current,
+            &FixedClock(TimeOfDay::parse(now)),
+            Some(&FixedAppearance(appearance)),
+        );
+        run_auto_tick(session, inputs, store, |_| Ok(()))
+    }
+
+    #[test]
+    fn auto_tick_applies_and_persists_schedule_changes() -> std::result::Result<(), String> {
+        let mut session = auto_session(false)?;
+        let store = RecordingStateStore::default();
+        assert_eq!(
+            tick(&mut session, &store, "08:00", None, Some("kanagawa-wave"))?,
+            Some("kanagawa-lotus".to_string())
+        );
+        assert_eq!(
+            tick(&mut session, &store, "12:00", None, Some("kanagawa-dragon"))?,
+            None
+        );
+        assert_eq!(
+            tick(&mut session, &store, "19:30", None, Some("kanagawa-dragon"))?,
+            Some("kanagawa-wave".to_string())
+        );
+        let path = PathBuf::from("/tmp/theme-switcher-state");
+        assert_eq!(
+            store.writes(),
+            vec![
+                (path.clone(), "kanagawa-lotus".to_string()),
+                (path, "kanagawa-wave".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn auto_tick_follows_appearance_before_schedule() -> std::result::Result<(), String> {
+        let mut session = auto_session(true)?;
+        let store = RecordingStateStore::default();
+        assert_eq!(
+            tick(
+                &mut session,
+                &store,
+                "08:00",
+                Some(ThemeBackground::Dark),
+                None
+            )?,
+            Some("kanagawa-dragon".to_string())
+        );
+        assert_eq!(
+            tick(
+                &mut session,
+                &store,
+                "08:05",
+                Some(ThemeBackground::Light),
+                Some("kanagawa-dragon")
+            )?,
+            Some("kanagawa-lotus".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn auto_tick_retries_after_failed_apply() -> std::result::Result<(), String> {
+        let mut session = auto_session(false)?;
+        let store = RecordingStateStore::default();
+        let inputs = auto_inputs(&session, None, &FixedClock(TimeOfDay::parse("08:00")), None);
+        assert!(run_auto_tick(&mut session, inputs, &store, |_| Err("boom")).is_err());
+        assert!(store.writes().is_empty());
+        assert_eq!(
+            tick(&mut session, &store, "08:01", None, None)?,
+            Some("kanagawa-lotus".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn auto_session_rejects_unknown_colorschemes() -> std::result::Result<(), String> {
+        let theme = ThemeSpec::try_new("Wave".to_string(), "kanagawa-wave".to_string())
+            .map_err(|err| err.to_string())?;
+        let result = auto_session_from_args(AutoArgs {
+            themes: vec![theme],
+            state_path: None,
+            schedule: vec![ScheduleArg {
+                start: TimeOfDay::parse("07:00").ok_or("invalid time")?,
+                colorscheme: "missing".to_string(),
+            }],
+            appearance: None,
+        });
+        assert_eq!(
+            result.map(|_| ()),
+            Err("colorscheme 'missing' is not in the theme list".to_string())
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,382 @@
+//! Which buffers a lifecycle command deletes and what every window showing
+//! one of them switches to, so deleting never closes a window. The host
+//! snapshots buffers and windows and carries the plan out.
+
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferSnapshot {
+    pub buf: i64,
+    pub name: String,
+    pub listed: bool,
+    /// `buftype` is empty, i.e. a regular file buffer.
+    pub normal: bool,
+    pub modified: bool,
+    /// `lastused` from `getbufinfo()`; `None` for buffers never entered.
+    pub last_used: Option<i64>,
+}
+
+impl BufferSnapshot {
+    fn is_replacement_candidate(&self) -> bool {
+        self.listed && self.normal
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSnapshot {
+    pub win: i64,
+    pub buf: i64,
+    /// The window's alternate buffer (`#`), if any.
+    pub alternate: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replacement {
+    Buffer(i64),
+    /// No other buffer is left; the window gets a fresh empty one.
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSwitch {
+    pub win: i64,
+    pub to: Replacement,
+}
+
+/// Most recently used buffer when `lastused` data exists, else the window's
+/// alternate buffer, else the lowest-numbered remaining buffer.
+fn replacement_for(
+    window: &WindowSnapshot,
+    deleted: &[i64],
+    buffers: &[BufferSnapshot],
+) -> Replacement {
+    let candidates = || {
+        buffers
+            .iter()
+            .filter(|buffer| buffer.is_replacement_candidate() && !deleted.contains(&buffer.buf))
+    };
+    if let Some(mru) = candidates()
+        .filter(|buffer| buffer.last_used.is_some())
+        .max_by_key(|buffer| (buffer.last_used, buffer.buf))
+    {
+        return Replacement::Buffer(mru.buf);
+    }
+    if let Some(alternate) = window
+        .alternate
+        .filter(|alternate| candidates().any(|buffer| buffer.buf == *alternate))
+    {
+        return Replacement::Buffer(alternate);
+    }
+    candidates()
+        .map(|buffer| buffer.buf)
+        .min()
+        .map_or(Replacement::Empty, Replacement::Buffer)
+}
+
+fn window_switches(
+    deleted: &[i64],
+    buffers: &[BufferSnapshot],
+    windows: &[WindowSnapshot],
+) -> Vec<WindowSwitch> {
+    windows
+        .iter()
+        .filter(|window| deleted.contains(&window.buf))
+        .map(|window| WindowSwitch {
+            win: window.win,
+            to: replacement_for(window, deleted, buffers),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeleteBufferOutcome {
+    Missing,
+    /// Unsaved changes and no `force`; the host asks before going on.
+    Modified,
+    Delete {
+        switches: Vec<WindowSwitch>,
+    },
+}
+
+pub fn plan_delete_buffer(
+    target: i64,
+    force: bool,
+    buffers: &[BufferSnapshot],
+    windows: &[WindowSnapshot],
+) -> DeleteBufferOutcome {
+    let Some(buffer) = buffers.iter().find(|buffer| buffer.buf == target) else {
+        return DeleteBufferOutcome::Missing;
+    };
+    if buffer.modified && !force {
+        return DeleteBufferOutcome::Modified;
+    }
+    DeleteBufferOutcome::Delete {
+        switches: window_switches(&[target], buffers, windows),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkScope<'a> {
+    /// Every listed buffer except `keep`.
+    AllExcept(i64),
+    /// Listed buffers no window shows.
+    Hidden,
+    /// Named buffers whose path is not under `root`.
+    OutsideProject(&'a str),
+}
+
+impl BulkScope<'_> {
+    fn selects(self, buffer: &BufferSnapshot, windows: &[WindowSnapshot]) -> bool {
+        if !buffer.listed || !buffer.normal {
+            return false;
+        }
+        match self {
+            Self::AllExcept(keep) => buffer.buf != keep,
+            Self::Hidden => !windows.iter().any(|window| window.buf == buffer.buf),
+            Self::OutsideProject(root) => {
+                !buffer.name.is_empty() && !Path::new(&buffer.name).starts_with(root)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkDeletePlan {
+    pub delete: Vec<i64>,
+    /// Selected buffers left alone because they have unsaved changes.
+    pub kept_modified: Vec<i64>,
+    pub switches: Vec<WindowSwitch>,
+}
+
+pub fn plan_bulk_delete(
+    scope: BulkScope<'_>,
+    force: bool,
+    buffers: &[BufferSnapshot],
+    windows: &[WindowSnapshot],
+) -> BulkDeletePlan {
+    let mut plan = BulkDeletePlan::default();
+    for buffer in buffers
+        .iter()
+        .filter(|buffer| scope.selects(buffer, windows))
+    {
+        if buffer.modified && !force {
+            plan.kept_modified.push(buffer.buf);
+        } else {
+            plan.delete.push(buffer.buf);
+        }
+    }
+    plan.switches = window_switches(&plan.delete, buffers, windows);
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferSnapshot;
+    use super::BulkDeletePlan;
+    use super::BulkScope;
+    use super::DeleteBufferOutcome;
+    use super::Replacement;
+    use super::WindowSnapshot;
+    use super::WindowSwitch;
+    use super::plan_bulk_delete;
+    use super::plan_delete_buffer;
+
+    fn buffer(buf: i64, name: &str, last_used: Option<i64>) -> BufferSnapshot {
+        BufferSnapshot {
+            buf,
+            name: name.to_string(),
+            listed: true,
+            normal: true,
+            modified: false,
+            last_used,
+        }
+    }
+
+    fn window(win: i64, buf: i64, alternate: Option<i64>) -> WindowSnapshot {
+        WindowSnapshot {
+            win,
+            buf,
+            alternate,
+        }
+    }
+
+    #[test]
+    fn delete_switches_every_window_to_most_recent_buffer() {
+        let buffers = [
+            buffer(1, "/p/a.rs", Some(10)),
+            buffer(2, "/p/b.rs", Some(30)),
+            buffer(3, "/p/c.rs", Some(20)),
+        ];
+        let windows = [
+            window(1000, 2, Some(1)),
+            window(1001, 3, None),
+            window(1002, 2, None),
+        ];
+
+        assert_eq!(
+            plan_delete_buffer(2, false, &buffers, &windows),
+            DeleteBufferOutcome::Delete {
+                switches: vec![
+                    WindowSwitch {
+                        win: 1000,
+                        to: Replacement::Buffer(3),
+                    },
+                    WindowSwitch {
+                        win: 1002,
+                        to: Replacement::Buffer(3),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn delete_falls_back_to_alternate_without_mru_data() {
+        let buffers = [
+            buffer(1, "/p/a.rs", None),
+            buffer(2, "/p/b.rs", None),
+            buffer(3, "/p/c.rs", None),
+        ];
+        let windows = [window(1000, 1, Some(3)), window(1001, 1, Some(1))];
+
+        assert_eq!(
+            plan_delete_buffer(1, false, &buffers, &windows),
+            DeleteBufferOutcome::Delete {
+                switches: vec![
+                    WindowSwitch {
+                        win: 1000,
+                        to: Replacement::Buffer(3),
+                    },
+                    WindowSwitch {
+                        win: 1001,
+                        to: Replacement::Buffer(2),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn delete_skips_special_and_unlisted_replacements() {
+        let terminal = BufferSnapshot {
+            normal: false,
+            ..buffer(2, "term://sh", Some(50))
+        };
+        let unlisted = BufferSnapshot {
+            listed: false,
+            ..buffer(3, "/p/help.txt", Some(40))
+        };
+        let buffers = [buffer(1, "/p/a.rs", Some(10)), terminal, unlisted];
+
+        assert_eq!(
+            plan_delete_buffer(1, false, &buffers, &[window(1000, 1, Some(2))]),
+            DeleteBufferOutcome::Delete {
+                switches: vec![WindowSwitch {
+                    win: 1000,
+                    to: Replacement::Empty,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn delete_modified_buffer_needs_force() {
+        let modified = BufferSnapshot {
+            modified: true,
+            ..buffer(1, "/p/a.rs", Some(10))
+        };
+        let buffers = [modified, buffer(2, "/p/b.rs", Some(5))];
+        let windows = [window(1000, 1, None)];
+
+        assert_eq!(
+            plan_delete_buffer(1, false, &buffers, &windows),
+            DeleteBufferOutcome::Modified
+        );
+        assert_eq!(
+            plan_delete_buffer(1, true, &buffers, &windows),
+            DeleteBufferOutcome::Delete {
+                switches: vec![WindowSwitch {
+                    win: 1000,
+                    to: Replacement::Buffer(2),
+                }],
+            }
+        );
+        assert_eq!(
+            plan_delete_buffer(9, false, &buffers, &windows),
+            DeleteBufferOutcome::Missing
+        );
+    }
+
+    #[test]
+    fn only_other_buffers_keeps_current_and_modified() {
+        let modified = BufferSnapshot {
+            modified: true,
+            ..buffer(3, "/p/c.rs", Some(30))
+        };
+        let buffers = [
+            buffer(1, "/p/a.rs", Some(10)),
+            buffer(2, "/p/b.rs", Some(20)),
+            modified,
+        ];
+        let windows = [window(1000, 1, None), window(1001, 2, None)];
+
+        assert_eq!(
+            plan_bulk_delete(BulkScope::AllExcept(1), false, &buffers, &windows),
+            BulkDeletePlan {
+                delete: vec![2],
+                kept_modified: vec![3],
+                switches: vec![WindowSwitch {
+                    win: 1001,
+                    to: Replacement::Buffer(3),
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn hidden_buffers_exclude_visible_ones() {
+        let buffers = [
+            buffer(1, "/p/a.rs", Some(10)),
+            buffer(2, "/p/b.rs", Some(20)),
+            buffer(3, "/p/c.rs", Some(30)),
+        ];
+        let windows = [window(1000, 2, None)];
+
+        assert_eq!(
+            plan_bulk_delete(BulkScope::Hidden, false, &buffers, &windows),
+            BulkDeletePlan {
+                delete: vec![1, 3],
+                kept_modified: Vec::new(),
+                switches: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn outside_project_matches_whole_path_components() {
+        let buffers = [
+            buffer(1, "/work/app/src/main.rs", Some(10)),
+            buffer(2, "/work/app-old/main.rs", Some(20)),
+            buffer(3, "", Some(30)),
+            buffer(4, "/etc/hosts", Some(5)),
+        ];
+        let windows = [window(1000, 2, None)];
+
+        assert_eq!(
+            plan_bulk_delete(
+                BulkScope::OutsideProject("/work/app"),
+                false,
+                &buffers,
+                &windows
+            ),
+            BulkDeletePlan {
+                delete: vec![2, 4],
+                kept_modified: Vec::new(),
+                switches: vec![WindowSwitch {
+                    win: 1000,
+                    to: Replacement::Buffer(3),
+                }],
+            }
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use std::fmt;
 
+use nvim_oxi::Dictionary;
 use nvim_oxi::mlua;
+use nvimrs_nvim_oxi_utils::dict;
 use nvimrs_support::NonEmptyString;
 use nvimrs_support::positive_i64;
 
+use crate::window_select::UnknownWindowOption;
+use crate::window_select::WindowSelectOpts;
+use crate::window_select::parse_window_select_opts;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BufferNumber(i64);
 
@@ -52,6 +58,155 @@ impl ColumnNumber {
     }
 }
 
+/// Where the chosen item is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DefinitionTarget {
+    #[default]
+    OtherWindow,
+    Split,
+    Vsplit,
+    Tab,
+    FloatPeek,
+}
+
+impl DefinitionTarget {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "other_window" => Some(Self::OtherWindow),
+            "split" => Some(Self::Split),
+            "vsplit" => Some(Self::Vsplit),
+            "tab" => Some(Self::Tab),
+            "float" | "peek" => Some(Self::FloatPeek),
+            _ => None,
+        }
+    }
+
+    /// Whether focus goes back to the origin window once the item is shown.
+    pub const fn returns_focus(self) -> bool {
+        matches!(self, Self::OtherWindow)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DefinitionMethod {
+    #[default]
+    Definition,
+    References,
+    Implementation,
+    TypeDefinition,
+}
+
+impl DefinitionMethod {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "definition" => Some(Self::Definition),
+            "references" => Some(Self::References),
+            "implementation" => Some(Self::Implementation),
+            "type_definition" => Some(Self::TypeDefinition),
+            _ => None,
+        }
+    }
+
+    /// Name of the `vim.lsp.buf` function that lists the items.
+    pub const fn lsp_buf_function(self) -> &'static str {
+        match self {
+            Self::Definition => "definition",
+            Self::References => "references",
+            Self::Implementation => "implementation",
+            Self::TypeDefinition => "type_definition",
+        }
+    }
+
+    /// `vim.lsp.buf.references` takes a context table before its options.
+    pub const fn takes_context(self) -> bool {
+        matches!(self, Self::References)
+    }
+}
+
+/// What to do with the items when there is more than one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MultipleItems {
+    /// Open the first item and push every item to the quickfix list.
+    #[default]
+    Quickfix,
+    /// Let the user pick the item to open.
+    Picker,
+}
+
+impl MultipleItems {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "quickfix" => Some(Self::Quickfix),
+            "picker" => Some(Self::Picker),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefinitionRequest {
+    pub target: DefinitionTarget,
+    pub method: DefinitionMethod,
+    pub multiple: MultipleItems,
+    /// How the `OtherWindow` target picks or creates its window.
+    pub window: WindowSelectOpts,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownDefinitionOption {
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for UnknownDefinitionOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown definition {} `{}`", self.field, self.value)
+    }
+}
+
+impl From<UnknownWindowOption> for UnknownDefinitionOption {
+    fn from(err: UnknownWindowOption) -> Self {
+        Self {
+            field: err.field,
+            value: err.value,
+        }
+    }
+}
+
+fn parse_request_field<T>(
+    opts: &Dictionary,
+    field: &'static str,
+    parse: fn(&str) -> Option<T>,
+) -> std::result::Result<Option<T>, UnknownDefinitionOption> {
+    let Some(value) = dict::get_string_nonempty(opts, field) else {
+        return Ok(None);
+    };
+    match parse(&value) {
+        Some(parsed) => Ok(Some(parsed)),
+        None => Err(UnknownDefinitionOption { field, value }),
+    }
+}
+
+/// Reads `{ target?, method?, multiple?, strategy?, split? }`; absent keys
+/// keep their defaults and unknown values are rejected.
+pub fn parse_definition_request(
+    opts: Option<&Dictionary>,
+) -> std::result::Result<DefinitionRequest, UnknownDefinitionOption> {
+    let defaults = DefinitionRequest::default();
+    let Some(opts) = opts else {
+        return Ok(defaults);
+    };
+    Ok(DefinitionRequest {
+        target: parse_request_field(opts, "target", DefinitionTarget::parse)?
+            .unwrap_or(defaults.target),
+        method: parse_request_field(opts, "method", DefinitionMethod::parse)?
+            .unwrap_or(defaults.method),
+        multiple: parse_request_field(opts, "multiple", MultipleItems::parse)?
+            .unwrap_or(defaults.multiple),
+        window: parse_window_select_opts(Some(opts))?,
+    })
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DefinitionLocation {
     BufferOnly(BufferNumber),
@@ -103,6 +258,35 @@ impl DefinitionItem {
     pub const fn col(&self) -> i64 {
         self.col.raw()
     }
+
+    /// Same position in the same buffer or file. Buffer numbers are compared
+    /// when both items carry one, file names otherwise.
+    fn same_location(&self, other: &Self) -> bool {
+        if self.lnum != other.lnum || self.col != other.col {
+            return false;
+        }
+        match (self.bufnr(), other.bufnr()) {
+            (Some(lhs), Some(rhs)) => lhs == rhs,
+            _ => self
+                .filename()
+                .zip(other.filename())
+                .is_some_and(|(lhs, rhs)| lhs == rhs),
+        }
+    }
+
+    fn is_in(&self, origin: DefinitionOrigin<'_>) -> bool {
+        self.bufnr() == Some(origin.bufnr)
+            || origin
+                .filename
+                .is_some_and(|filename| self.filename() == Some(filename))
+    }
+}
+
+/// The buffer the request was made from; an item in it is opened first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefinitionOrigin<'a> {
+    pub bufnr: i64,
+    pub filename: Option<&'a str>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -226,32 +410,78 @@ pub enum DefinitionAction {
         title: Option<String>,
         items: Vec<DefinitionItem>,
     },
+    PickItem {
+        title: Option<String>,
+        items: Vec<DefinitionItem>,
+    },
+}
+
+fn dedup_locations(items: Vec<DefinitionItem>) -> Vec<DefinitionItem> {
+    let mut unique: Vec<DefinitionItem> = Vec::with_capacity(items.len());
+    for item in items {
+        if !unique.iter().any(|seen| seen.same_location(&item)) {
+            unique.push(item);
+        }
+    }
+    unique
 }
 
+/// Drops items that point at an already listed location and moves the first
+/// item in the origin buffer to the front; the rest keep the server's order.
 pub fn plan_definition_actions(
     items: Vec<DefinitionItem>,
     title: Option<String>,
+    origin: DefinitionOrigin<'_>,
+    multiple: MultipleItems,
 ) -> Vec<DefinitionAction> {
+    let mut items = dedup_locations(items);
+    if let Some(index) = items.iter().position(|item| item.is_in(origin)) {
+        items[..=index].rotate_right(1);
+    }
     let Some((primary, rest)) = items.split_first() else {
         return vec![DefinitionAction::CloseCreatedTarget];
     };
+    if rest.is_empty() {
+        return vec![DefinitionAction::OpenPrimary(primary.clone())];
+    }
 
-    let mut actions = vec![DefinitionAction::OpenPrimary(primary.clone())];
-    if !rest.is_empty() {
-        actions.push(DefinitionAction::PushQuickfix { title, items });
+    match multiple {
+        MultipleItems::Quickfix => vec![
+            DefinitionAction::OpenPrimary(primary.clone()),
+            DefinitionAction::PushQuickfix { title, items },
+        ],
+        MultipleItems::Picker => vec![DefinitionAction::PickItem { title, items }],
     }
-    actions
 }
 
 #[cfg(test)]
 mod tests {
+    use nvim_oxi::Dictionary;
+    use nvim_oxi::Object;
+    use nvimrs_support::NonEmptyString;
+
     use super::BufferNumber;
     use super::ColumnNumber;
     use super::DefinitionAction;
     use super::DefinitionItem;
     use super::DefinitionLocation;
+    use super::DefinitionMethod;
+    use super::DefinitionOrigin;
+    use super::DefinitionRequest;
+    use super::DefinitionTarget;
     use super::LineNumber;
+    use super::MultipleItems;
+    use super::UnknownDefinitionOption;
+    use super::parse_definition_request;
     use super::plan_definition_actions;
+    use crate::window_select::SplitDirection;
+    use crate::window_select::WindowSelectOpts;
+    use crate::window_select::WindowStrategy;
+
+    const ORIGIN: DefinitionOrigin<'static> = DefinitionOrigin {
+        bufnr: 99,
+        filename: Some("/src/origin.rs"),
+    };
 
     fn definition_item(bufnr: i64, lnum: i64, col: i64) -> Result<DefinitionItem, &'static str> {
         let bufnr = BufferNumber::try_new(bufnr).ok_or("expected valid bufnr")?;
@@ -264,10 +494,22 @@ mod tests {
         })
     }
 
+    fn file_item(filename: &str, lnum: i64, col: i64) -> Result<DefinitionItem, &'static str> {
+        let filename = NonEmptyString::try_new(filename.to_string())
+            .map_err(|_| "expected non-empty filename")?;
+        let lnum = LineNumber::try_new(lnum).ok_or("expected valid line")?;
+        let col = ColumnNumber::try_new(col).ok_or("expected valid column")?;
+        Ok(DefinitionItem {
+            location: DefinitionLocation::FileOnly(filename),
+            lnum,
+            col,
+        })
+    }
+
     #[test]
     fn plan_definition_actions_closes_when_empty() {
         assert_eq!(
-            plan_definition_actions(Vec::new(), None),
+            plan_definition_actions(Vec::new(), None, ORIGIN, MultipleItems::Quickfix),
             vec![DefinitionAction::CloseCreatedTarget]
         );
     }
@@ -275,7 +517,12 @@ mod tests {
     #[test]
     fn plan_definition_actions_opens_primary_for_single_item() -> Result<(), &'static str> {
         assert_eq!(
-            plan_definition_actions(vec![definition_item(1, 2, 3)?], Some("defs".to_string())),
+            plan_definition_actions(
+                vec![definition_item(1, 2, 3)?],
+                Some("defs".to_string()),
+                ORIGIN,
+                MultipleItems::Quickfix
+            ),
             vec![DefinitionAction::OpenPrimary(definition_item(1, 2, 3)?)]
         );
         Ok(())
@@ -286,7 +533,9 @@ mod tests {
         assert_eq!(
             plan_definition_actions(
                 vec![definition_item(1, 2, 3)?, definition_item(4, 5, 6)?],
-                Some("defs".to_string())
+                Some("defs".to_string()),
+                ORIGIN,
+                MultipleItems::Quickfix
             ),
             vec![
                 DefinitionAction::OpenPrimary(definition_item(1, 2, 3)?),
@@ -298,4 +547,152 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn plan_definition_actions_drops_duplicate_locations() -> Result<(), &'static str> {
+        assert_eq!(
+            plan_definition_actions(
+                vec![
+                    definition_item(1, 2, 3)?,
+                    definition_item(1, 2, 3)?,
+                    file_item("/src/a.rs", 4, 1)?,
+                    file_item("/src/a.rs", 4, 1)?,
+                ],
+                None,
+                ORIGIN,
+                MultipleItems::Quickfix
+            ),
+            vec![
+                DefinitionAction::OpenPrimary(definition_item(1, 2, 3)?),
+                DefinitionAction::PushQuickfix {
+                    title: None,
+                    items: vec![definition_item(1, 2, 3)?, file_item("/src/a.rs", 4, 1)?],
+                }
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plan_definition_actions_opens_duplicates_once() -> Result<(), &'static str> {
+        assert_eq!(
+            plan_definition_actions(
+                vec![file_item("/src/a.rs", 4, 1)?, file_item("/src/a.rs", 4, 1)?],
+                None,
+                ORIGIN,
+                MultipleItems::Picker
+            ),
+            vec![DefinitionAction::OpenPrimary(file_item("/src/a.rs", 4, 1)?)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plan_definition_actions_prefers_item_in_origin_file() -> Result<(), &'static str> {
+        assert_eq!(
+            plan_definition_actions(
+                vec![
+                    file_item("/src/a.rs", 1, 1)?,
+                    file_item("/src/b.rs", 2, 1)?,
+                    file_item("/src/origin.rs", 3, 1)?,
+                    definition_item(99, 4, 1)?,
+                ],
+                None,
+                ORIGIN,
+                MultipleItems::Quickfix
+            ),
+            vec![
+                DefinitionAction::OpenPrimary(file_item("/src/origin.rs", 3, 1)?),
+                DefinitionAction::PushQuickfix {
+                    title: None,
+                    items: vec![
+                        file_item("/src/origin.rs", 3, 1)?,
+                        file_item("/src/a.rs", 1, 1)?,
+                        file_item("/src/b.rs", 2, 1)?,
+                        definition_item(99, 4, 1)?,
+                    ],
+                }
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plan_definition_actions_offers_picker_for_multiple_items() -> Result<(), &'static str> {
+        assert_eq!(
+            plan_definition_actions(
+                vec![definition_item(1, 2, 3)?, definition_item(4, 5, 6)?],
+                Some("refs".to_string()),
+                ORIGIN,
+                MultipleItems::Picker
+            ),
+            vec![DefinitionAction::PickItem {
+                title: Some("refs".to_string()),
+                items: vec![definition_item(1, 2, 3)?, definition_item(4, 5, 6)?],
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_definition_request_defaults_to_other_window_definition() {
+        assert_eq!(
+            parse_definition_request(None),
+            Ok(DefinitionRequest::default())
+        );
+        assert_eq!(
+            parse_definition_request(Some(&Dictionary::new())),
+            Ok(DefinitionRequest {
+                target: DefinitionTarget::OtherWindow,
+                method: DefinitionMethod::Definition,
+                multiple: MultipleItems::Quickfix,
+                window: WindowSelectOpts::default(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_definition_request_reads_every_field() {
+        let opts = Dictionary::from_iter([
+            ("target", Object::from("float")),
+            ("method", Object::from("references")),
+            ("multiple", Object::from("picker")),
+            ("strategy", Object::from("ace")),
+            ("split", Object::from("below")),
+        ]);
+
+        assert_eq!(
+            parse_definition_request(Some(&opts)),
+            Ok(DefinitionRequest {
+                target: DefinitionTarget::FloatPeek,
+                method: DefinitionMethod::References,
+                multiple: MultipleItems::Picker,
+                window: WindowSelectOpts {
+                    strategy: WindowStrategy::Ace,
+                    split: SplitDirection::Below,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn parse_definition_request_rejects_unknown_values() {
+        let opts = Dictionary::from_iter([("target", Object::from("window"))]);
+
+        assert_eq!(
+            parse_definition_request(Some(&opts)),
+            Err(UnknownDefinitionOption {
+                field: "target",
+                value: "window".to_string(),
+            })
+        );
+        let opts = Dictionary::from_iter([("split", Object::from("diagonal"))]);
+        assert_eq!(
+            parse_definition_request(Some(&opts)),
+            Err(UnknownDefinitionOption {
+                field: "split",
+                value: "diagonal".to_string(),
+            })
+        );
+    }
 }
@@ -1,13 +1,29 @@
+mod buffer_lifecycle;
 mod definition_flow;
+mod window_select;
 
 use std::collections::HashMap;
 use std::path::Path;
 
+use buffer_lifecycle::BufferSnapshot;
+use buffer_lifecycle::BulkScope;
+use buffer_lifecycle::DeleteBufferOutcome;
+use buffer_lifecycle::Replacement;
+use buffer_lifecycle::WindowSnapshot;
+use buffer_lifecycle::WindowSwitch;
+use buffer_lifecycle::plan_bulk_delete;
+use buffer_lifecycle::plan_delete_buffer;
 use definition_flow::DefinitionAction;
 use definition_flow::DefinitionItem;
+use definition_flow::DefinitionOrigin;
+use definition_flow::DefinitionRequest;
+use definition_flow::DefinitionTarget;
+use definition_flow::MultipleItems;
 use definition_flow::parse_definition_items;
+use definition_flow::parse_definition_request;
 use definition_flow::parse_definition_title;
 use definition_flow::plan_definition_actions;
+use nvim_oxi::Array;
 use nvim_oxi::Dictionary;
 use nvim_oxi::Function;
 use nvim_oxi::Object;
@@ -18,20 +34,36 @@ use nvim_oxi::api::Buffer;
 use nvim_oxi::api::Window;
 use nvim_oxi::api::opts::ClearAutocmdsOpts;
 use nvim_oxi::api::opts::CmdOpts;
+use nvim_oxi::api::opts::CreateAutocmdOpts;
 use nvim_oxi::api::opts::OptionOpts;
 use nvim_oxi::api::opts::OptionScope;
+use nvim_oxi::api::types::AutocmdCallbackArgs;
 use nvim_oxi::api::types::CmdInfos;
+use nvim_oxi::api::types::WindowBorder;
+use nvim_oxi::api::types::WindowConfig;
+use nvim_oxi::api::types::WindowRelativeTo;
+use nvim_oxi::api::types::WindowStyle;
+use nvim_oxi::conversion::FromObject;
 use nvim_oxi::mlua;
+use nvimrs_nvim_oxi_utils::dict;
 use nvimrs_nvim_oxi_utils::handles;
 use nvimrs_nvim_oxi_utils::lua;
 use nvimrs_nvim_oxi_utils::notify;
 use nvimrs_nvim_utils::path::path_is_dir;
 use nvimrs_nvim_utils::path::split_uri_scheme_and_rest;
 use nvimrs_nvim_utils::path::strip_known_prefixes;
-use nvimrs_support::cycle::next_item;
+use window_select::AceLabel;
+use window_select::SplitDirection;
+use window_select::WindowCandidate;
+use window_select::WindowChoice;
+use window_select::WindowSelectOpts;
+use window_select::ace_target;
+use window_select::parse_window_select_opts;
+use window_select::plan_other_window;
 
 type OptMap = HashMap<String, Object>;
 const LOG_CONTEXT: &str = "nvimrs_plugin_util";
+const PROJECT_ROOT_VAR: &str = "project_root";
 
 fn buffer_from_handle(handle: Option<i64>) -> Option<Buffer> {
     handles::buffer_from_optional(handle)
@@ -357,34 +389,6 @@ fn delete_buffer_via_command(buf_handle: i64, force: bool, wipe: bool) -> Result
     run_cmd_bang(cmd, [buf_handle.to_string()], force)
 }
 
-fn snacks_bufdelete_delete(buf_handle: i64, force: bool, wipe: bool) -> Result<()> {
-    let lua = lua::state();
-    let Some(snacks) = snacks_table(&lua) else {
-        return delete_buffer_via_command(buf_handle, force, wipe);
-    };
-    let Ok(bufdelete) = snacks.get::<mlua::Table>("bufdelete") else {
-        return delete_buffer_via_command(buf_handle, force, wipe);
-    };
-    let Ok(delete) = bufdelete.get::<mlua::Function>("delete") else {
-        return delete_buffer_via_command(buf_handle, force, wipe);
-    };
-    let opts = lua.create_table().map_err(nvim_oxi::Error::from)?;
-    opts.set("buf", buf_handle).map_err(nvim_oxi::Error::from)?;
-    if force {
-        opts.set("force", true).map_err(nvim_oxi::Error::from)?;
-    }
-    if wipe {
-        opts.set("wipe", true).map_err(nvim_oxi::Error::from)?;
-    }
-    match delete.call::<()>(opts) {
-        Ok(()) => Ok(()),
-        Err(err) => {
-            notify::warn(LOG_CONTEXT, &format!("snacks bufdelete failed: {err}"));
-            delete_buffer_via_command(buf_handle, force, wipe)
-        }
-    }
-}
-
 fn oil_util_is_oil_buffer(bufnr: i64) -> bool {
     let lua = lua::state();
     let Some(oil_util) = lua::try_require_table(&lua, "oil.util") else {
@@ -490,6 +494,131 @@ fn buf_option_string(buf: &Buffer, name: &str) -> String {
         .unwrap_or_default()
 }
 
+fn buffer_snapshots() -> Result<Vec<BufferSnapshot>> {
+    let infos: Array = api::call_function("getbufinfo", Array::new())?;
+    let mut snapshots = Vec::new();
+    for info in infos {
+        let Ok(info) = Dictionary::from_object(info) else {
+            continue;
+        };
+        let Some(buf) = dict::get_i64(&info, "bufnr") else {
+            continue;
+        };
+        let normal = handles::valid_buffer(buf)
+            .is_some_and(|buffer| buf_option_string(&buffer, "buftype").is_empty());
+        snapshots.push(BufferSnapshot {
+            buf,
+            name: dict::get_string(&info, "name").unwrap_or_default(),
+            listed: dict::get_i64(&info, "listed").is_some_and(|listed| listed != 0),
+            normal,
+            modified: dict::get_i64(&info, "changed").is_some_and(|changed| changed != 0),
+            last_used: dict::get_i64(&info, "lastused").filter(|last_used| *last_used > 0),
+        });
+    }
+    Ok(snapshots)
+}
+
+fn window_snapshots() -> Result<Vec<WindowSnapshot>> {
+    let mut snapshots = Vec::new();
+    for win in api::list_wins() {
+        let buf = i64::from(win.get_buf()?.handle());
+        let alternate = win
+            .call(|()| api::call_function::<_, i64>("bufnr", Array::from_iter(["#"])).unwrap_or(-1))
+            .ok()
+            .filter(|alternate| *alternate > 0);
+        snapshots.push(WindowSnapshot {
+            win: i64::from(win.handle()),
+            buf,
+            alternate,
+        });
+    }
+    Ok(snapshots)
+}
+
+/// Windows sent to `Replacement::Empty` share one new empty buffer.
+fn apply_window_switches(switches: &[WindowSwitch]) -> Result<()> {
+    let mut empty: Option<Buffer> = None;
+    for switch in switches {
+        let Some(mut win) = handles::valid_window(switch.win) else {
+            continue;
+        };
+        let buffer = match switch.to {
+            Replacement::Buffer(buf) => match handles::valid_buffer(buf) {
+                Some(buffer) => buffer,
+                None => continue,
+            },
+            Replacement::Empty => match &empty {
+                Some(buffer) => buffer.clone(),
+                None => {
+                    let buffer = api::create_buf(true, false)?;
+                    empty = Some(buffer.clone());
+                    buffer
+                }
+            },
+        };
+        win.set_buf(&buffer)?;
+    }
+    Ok(())
+}
+
+fn write_buffer(buf_handle: i64) -> bool {
+    let Some(buffer) = handles::valid_buffer(buf_handle) else {
+        return false;
+    };
+    buffer
+        .call(|()| api::command("write").is_ok())
+        .unwrap_or(false)
+}
+
+/// Asks what to do with unsaved changes; `None` means the user cancelled.
+fn confirm_modified_delete(buf_handle: i64) -> Result<Option<bool>> {
+    let name = buffer_name(buf_handle).unwrap_or_else(|| "[No Name]".to_string());
+    let choice: i64 = api::call_function(
+        "confirm",
+        Array::from_iter([
+            Object::from(format!("Save changes to {name}?")),
+            Object::from("&Yes\n&No\n&Cancel"),
+        ]),
+    )?;
+    match choice {
+        1 if write_buffer(buf_handle) => Ok(Some(false)),
+        1 => {
+            notify::warn(LOG_CONTEXT, &format!("write {name} failed"));
+            Ok(None)
+        }
+        2 => Ok(Some(true)),
+        _ => Ok(None),
+    }
+}
+
+/// `bdelete` that first points every window showing `buf_handle` at a
+/// replacement buffer so no window closes. Returns the switches made, or
+/// `None` when nothing was deleted.
+fn delete_buffer_preserving_layout(
+    buf_handle: i64,
+    force: bool,
+    wipe: bool,
+) -> Result<Option<Vec<WindowSwitch>>> {
+    let buffers = buffer_snapshots()?;
+    let windows = window_snapshots()?;
+    let (switches, force) = match plan_delete_buffer(buf_handle, force, &buffers, &windows) {
+        DeleteBufferOutcome::Missing => return Ok(None),
+        DeleteBufferOutcome::Delete { switches } => (switches, force),
+        DeleteBufferOutcome::Modified => {
+            let Some(force) = confirm_modified_delete(buf_handle)? else {
+                return Ok(None);
+            };
+            match plan_delete_buffer(buf_handle, true, &buffers, &windows) {
+                DeleteBufferOutcome::Delete { switches } => (switches, force),
+                DeleteBufferOutcome::Missing | DeleteBufferOutcome::Modified => return Ok(None),
+            }
+        }
+    };
+    apply_window_switches(&switches)?;
+    delete_buffer_via_command(buf_handle, force, wipe)?;
+    Ok(Some(switches))
+}
+
 fn delete_current_buffer() -> Result<()> {
     let cur_buf = api::get_current_buf();
     let cur_buf_handle = i64::from(cur_buf.handle());
@@ -502,11 +631,10 @@ fn delete_current_buffer() -> Result<()> {
         && oil_util_is_oil_buffer(oil_buf_handle)
     {
         api::set_current_buf(&oil_buffer)?;
-        snacks_bufdelete_delete(cur_buf_handle, false, false)?;
-        return Ok(());
     }
 
-    snacks_bufdelete_delete(cur_buf_handle, false, false)
+    let _ = delete_buffer_preserving_layout(cur_buf_handle, false, false)?;
+    Ok(())
 }
 
 fn is_non_floating_window(win: &Window) -> bool {
@@ -556,7 +684,7 @@ fn kill_window_and_buffer() -> Result<()> {
         return Ok(());
     }
 
-    let should_open_dashboard = match close_current_window_if_possible() {
+    let close_skipped = match close_current_window_if_possible() {
         Ok(CloseCurrentWindowOutcome::Closed) => false,
         Ok(CloseCurrentWindowOutcome::SkippedSingleNonFloatingWindow) => true,
         Err(CloseCurrentWindowError::CountNonFloatingWindows(err)) => {
@@ -575,15 +703,102 @@ fn kill_window_and_buffer() -> Result<()> {
     let buftype = buf_option_string(&buf, "buftype");
     let filetype = buf_option_string(&buf, "filetype");
     let is_terminal = buftype == "terminal" || filetype == "snacks_terminal";
-    snacks_bufdelete_delete(buf_handle, is_terminal, is_terminal)?;
+    let switches = delete_buffer_preserving_layout(buf_handle, is_terminal, is_terminal)?;
 
-    if should_open_dashboard && let Err(err) = snacks_dashboard() {
+    // The dashboard only stands in when no other buffer could fill the
+    // window that was kept.
+    let cur_win_handle = i64::from(api::get_current_win().handle());
+    let left_empty = switches.is_some_and(|switches| {
+        switches.contains(&WindowSwitch {
+            win: cur_win_handle,
+            to: Replacement::Empty,
+        })
+    });
+    if close_skipped
+        && left_empty
+        && let Err(err) = snacks_dashboard()
+    {
         notify::warn(LOG_CONTEXT, &format!("open snacks dashboard failed: {err}"));
     }
 
     Ok(())
 }
 
+fn force_opt(opts: Option<&Dictionary>) -> bool {
+    opts.and_then(|opts| dict::get_object(opts, "force"))
+        .and_then(|value| bool::from_object(value).ok())
+        .unwrap_or(false)
+}
+
+fn project_root_for_current_buffer() -> Result<String> {
+    let root = api::get_current_buf()
+        .get_var::<NvimString>(PROJECT_ROOT_VAR)
+        .ok()
+        .map(|root| root.to_string_lossy().into_owned())
+        .filter(|root| !root.is_empty());
+    match root {
+        Some(root) => Ok(root),
+        None => Ok(api::call_function::<_, NvimString>("getcwd", Array::new())?
+            .to_string_lossy()
+            .into_owned()),
+    }
+}
+
+fn delete_buffers_in_scope(scope: BulkScope<'_>, force: bool) -> Result<()> {
+    let buffers = buffer_snapshots()?;
+    let windows = window_snapshots()?;
+    let plan = plan_bulk_delete(scope, force, &buffers, &windows);
+    apply_window_switches(&plan.switches)?;
+    let mut deleted = 0_usize;
+    for buf_handle in &plan.delete {
+        match delete_buffer_via_command(*buf_handle, force, false) {
+            Ok(()) => deleted += 1,
+            Err(err) => notify::warn(
+                LOG_CONTEXT,
+                &format!("delete buffer {buf_handle} failed: {err}"),
+            ),
+        }
+    }
+    let mut message = format!(
+        "deleted {deleted} buffer{}",
+        if deleted == 1 { "" } else { "s" }
+    );
+    if !plan.kept_modified.is_empty() {
+        message.push_str(&format!(
+            ", kept {} with unsaved changes",
+            plan.kept_modified.len()
+        ));
+    }
+    notify::info(LOG_CONTEXT, &message);
+    Ok(())
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn only_other_buffers(opts: Option<Dictionary>) -> Result<()> {
+    let keep = i64::from(api::get_current_buf().handle());
+    delete_buffers_in_scope(BulkScope::AllExcept(keep), force_opt(opts.as_ref()))
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn delete_hidden_buffers(opts: Option<Dictionary>) -> Result<()> {
+    delete_buffers_in_scope(BulkScope::Hidden, force_opt(opts.as_ref()))
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn delete_buffers_outside_project(opts: Option<Dictionary>) -> Result<()> {
+    let root = project_root_for_current_buffer()?;
+    delete_buffers_in_scope(BulkScope::OutsideProject(&root), force_opt(opts.as_ref()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::normalize_oil_target;
@@ -684,7 +899,14 @@ fn vim_joinpath(lua: &mlua::Lua, lhs: &str, rhs: &str) -> Result<Option<String>>
     Ok(Some(path))
 }
 
-fn oil_select_other_window() -> Result<()> {
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn oil_select_other_window(opts: Option<Dictionary>) -> Result<()> {
+    let Some(window_opts) = window_select_opts(opts.as_ref()) else {
+        return Ok(());
+    };
     let lua = lua::state();
     let Some(oil) = lua::try_require_table(&lua, "oil") else {
         return Ok(());
@@ -715,44 +937,170 @@ fn oil_select_other_window() -> Result<()> {
     }
 
     let path = vim_joinpath(&lua, &dir, &name)?.unwrap_or_else(|| format!("{dir}/{name}"));
-    let (target, _) = get_or_create_other_window()?;
+    let Some((target, _)) = select_or_create_other_window(window_opts)? else {
+        return Ok(());
+    };
     if target.is_valid() {
         api::set_current_win(&target)?;
     }
     open_oil(Some(path))
 }
 
-fn next_window(wins: &[Window], cur: &Window) -> Option<Window> {
-    if wins.len() <= 1 {
+fn window_candidates() -> Result<Vec<WindowCandidate>> {
+    let tab = api::get_current_tabpage();
+    let mut candidates = Vec::new();
+    for win in tab.list_wins()? {
+        candidates.push(WindowCandidate {
+            win: i64::from(win.handle()),
+            floating: !is_non_floating_window(&win),
+            width: win.get_width()?,
+            height: win.get_height()?,
+        });
+    }
+    Ok(candidates)
+}
+
+fn previous_window() -> Option<i64> {
+    let winnr: i64 = api::call_function("winnr", Array::from_iter(["#"])).ok()?;
+    if winnr <= 0 {
         return None;
     }
-    next_item(wins, cur)
-        .cloned()
-        .or_else(|| wins.first().cloned())
+    let win: i64 = api::call_function("win_getid", Array::from_iter([winnr])).ok()?;
+    (win > 0).then_some(win)
 }
 
-fn other_window() -> Result<Option<Window>> {
-    let tab = api::get_current_tabpage();
-    let wins: Vec<Window> = tab.list_wins()?.collect();
-    let cur = api::get_current_win();
-    Ok(next_window(&wins, &cur))
+const ACE_LABEL_ZINDEX: u32 = 250;
+
+fn open_ace_label(label: AceLabel) -> Result<Option<Window>> {
+    let Some(target) = handles::valid_window(label.win) else {
+        return Ok(None);
+    };
+    let row = target.get_height()?.saturating_sub(1) / 2;
+    let col = target.get_width()?.saturating_sub(5) / 2;
+    let mut buffer = api::create_buf(false, true)?;
+    buffer.set_lines(.., true, [format!(" {} ", label.label)])?;
+    api::set_option_value(
+        "bufhidden",
+        "wipe",
+        &OptionOpts::builder().buf(buffer.clone()).build(),
+    )?;
+    let mut config = WindowConfig::builder();
+    config
+        .relative(WindowRelativeTo::Window(target))
+        .row(f64::from(row.saturating_sub(1)))
+        .col(f64::from(col))
+        .width(3)
+        .height(1)
+        .border(WindowBorder::Rounded)
+        .style(WindowStyle::Minimal)
+        .focusable(false)
+        .zindex(ACE_LABEL_ZINDEX)
+        .noautocmd(true);
+    api::open_win(&buffer, false, &config.build()).map(Some)
 }
 
-fn get_or_create_other_window() -> Result<(Window, bool)> {
-    let tab = api::get_current_tabpage();
-    let wins: Vec<Window> = tab.list_wins()?.collect();
-    let cur = api::get_current_win();
-    if let Some(win) = next_window(&wins, &cur)
-        && win.is_valid()
-    {
-        return Ok((win, false));
+fn close_ace_labels(floats: Vec<Window>) {
+    for float in floats {
+        if let Err(err) = float.close(true) {
+            notify::warn(LOG_CONTEXT, &format!("close window label failed: {err}"));
+        }
+    }
+}
+
+/// Draws a label over each window and waits for one key; `None` when the
+/// key matches no label.
+fn prompt_ace_window(labels: &[AceLabel]) -> Result<Option<i64>> {
+    let mut floats = Vec::new();
+    for label in labels {
+        match open_ace_label(*label) {
+            Ok(Some(float)) => floats.push(float),
+            Ok(None) => {}
+            Err(err) => {
+                close_ace_labels(floats);
+                return Err(err);
+            }
+        }
+    }
+    if let Err(err) = api::command("redraw") {
+        notify::warn(LOG_CONTEXT, &format!("redraw window labels failed: {err}"));
     }
-    run_cmd_noargs("vsplit")?;
+    // Interrupting `getcharstr()` with <C-c> errors; treat it as a cancel.
+    let key = api::call_function::<_, NvimString>("getcharstr", Array::new()).ok();
+    close_ace_labels(floats);
+    Ok(key.and_then(|key| ace_target(labels, &key.to_string_lossy())))
+}
+
+enum OtherWindowPick {
+    Window(Window),
+    Split(SplitDirection),
+    Cancelled,
+}
+
+fn pick_other_window(opts: WindowSelectOpts) -> Result<OtherWindowPick> {
+    let cur_handle = i64::from(api::get_current_win().handle());
+    let choice = plan_other_window(opts, &window_candidates()?, cur_handle, previous_window());
+    let win = match choice {
+        WindowChoice::Use(win) => win,
+        WindowChoice::Create(split) => return Ok(OtherWindowPick::Split(split)),
+        WindowChoice::Prompt(labels) => match prompt_ace_window(&labels)? {
+            Some(win) => win,
+            None => return Ok(OtherWindowPick::Cancelled),
+        },
+    };
+    Ok(handles::valid_window(win)
+        .map_or(OtherWindowPick::Split(opts.split), OtherWindowPick::Window))
+}
+
+/// The chosen window and whether it was split off for this call; `None`
+/// when the ace prompt was cancelled.
+fn select_or_create_other_window(opts: WindowSelectOpts) -> Result<Option<(Window, bool)>> {
+    let split = match pick_other_window(opts)? {
+        OtherWindowPick::Window(win) => return Ok(Some((win, false))),
+        OtherWindowPick::Cancelled => return Ok(None),
+        OtherWindowPick::Split(split) => split,
+    };
+    let cur = api::get_current_win();
+    api::command(split.command())?;
     let new_win = api::get_current_win();
     if cur.is_valid() {
         api::set_current_win(&cur)?;
     }
-    Ok((new_win, true))
+    Ok(Some((new_win, true)))
+}
+
+fn window_select_opts(opts: Option<&Dictionary>) -> Option<WindowSelectOpts> {
+    match parse_window_select_opts(opts) {
+        Ok(opts) => Some(opts),
+        Err(err) => {
+            notify::warn(LOG_CONTEXT, &err.to_string());
+            None
+        }
+    }
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn other_window(opts: Option<Dictionary>) -> Result<Option<Window>> {
+    let Some(opts) = window_select_opts(opts.as_ref()) else {
+        return Ok(None);
+    };
+    match pick_other_window(opts)? {
+        OtherWindowPick::Window(win) => Ok(Some(win)),
+        OtherWindowPick::Split(_) | OtherWindowPick::Cancelled => Ok(None),
+    }
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn get_or_create_other_window(opts: Option<Dictionary>) -> Result<Option<(Window, bool)>> {
+    let Some(opts) = window_select_opts(opts.as_ref()) else {
+        return Ok(None);
+    };
+    select_or_create_other_window(opts)
 }
 
 fn restore_current_window(cur_handle: i64) {
@@ -808,6 +1156,116 @@ fn open_definition_item_in_window(target_handle: i64, item: &DefinitionItem) ->
     Ok(())
 }
 
+/// One in-flight definition request and the windows it may touch.
+#[derive(Debug, Clone, Copy)]
+struct DefinitionJump {
+    target: DefinitionTarget,
+    multiple: MultipleItems,
+    origin_win: i64,
+    origin_buf: i64,
+    /// Window chosen up front for `OtherWindow` and whether it was created
+    /// for this request. Other targets open their window once an item is
+    /// known.
+    other_win: Option<(i64, bool)>,
+}
+
+impl DefinitionJump {
+    fn close_created_target(self) {
+        if let Some((target_handle, created)) = self.other_win {
+            close_created_target_window(target_handle, created);
+        }
+    }
+
+    fn finish(self) {
+        if self.target.returns_focus() {
+            restore_current_window(self.origin_win);
+        }
+    }
+}
+
+const PEEK_MAX_WIDTH: u32 = 100;
+const PEEK_MIN_WIDTH: u32 = 20;
+const PEEK_HEIGHT: u32 = 15;
+
+fn definition_item_buffer(item: &DefinitionItem) -> Result<Option<Buffer>> {
+    if let Some(buf) = item.bufnr().and_then(handles::valid_buffer) {
+        return Ok(Some(buf));
+    }
+    let Some(filename) = item.filename() else {
+        return Ok(None);
+    };
+    let bufnr: i64 = api::call_function("bufadd", Array::from_iter([filename]))?;
+    let _: Object = api::call_function("bufload", Array::from_iter([Object::from(bufnr)]))?;
+    Ok(handles::valid_buffer(bufnr))
+}
+
+fn close_peek_on_leave(peek_handle: i64) -> Result<()> {
+    let opts = CreateAutocmdOpts::builder()
+        .callback(move |_args: AutocmdCallbackArgs| {
+            if i64::from(api::get_current_win().handle()) != peek_handle {
+                return false;
+            }
+            nvim_oxi::schedule(move |()| {
+                if let Some(peek) = handles::valid_window(peek_handle)
+                    && let Err(err) = peek.close(false)
+                {
+                    notify::warn(LOG_CONTEXT, &format!("close definition peek failed: {err}"));
+                }
+            });
+            true
+        })
+        .build();
+    let _ = api::create_autocmd(["WinLeave"], &opts)?;
+    Ok(())
+}
+
+fn open_definition_peek(item: &DefinitionItem) -> Result<()> {
+    let Some(buffer) = definition_item_buffer(item)? else {
+        return Ok(());
+    };
+    let columns = api::get_option_value::<i64>("columns", &OptionOpts::builder().build())?;
+    let width = u32::try_from(columns.saturating_sub(8))
+        .unwrap_or(0)
+        .clamp(PEEK_MIN_WIDTH, PEEK_MAX_WIDTH);
+    let mut config = WindowConfig::builder();
+    config
+        .relative(WindowRelativeTo::Cursor)
+        .row(1.0)
+        .col(0.0)
+        .width(width)
+        .height(PEEK_HEIGHT)
+        .border(WindowBorder::Rounded)
+        .focusable(true);
+    let mut peek = api::open_win(&buffer, true, &config.build())?;
+    set_cursor_safe(&mut peek, item.lnum(), item.col());
+    close_peek_on_leave(i64::from(peek.handle()))
+}
+
+fn definition_target_window(jump: DefinitionJump) -> Result<i64> {
+    if let Some((target_handle, _)) = jump.other_win {
+        return Ok(target_handle);
+    }
+    restore_current_window(jump.origin_win);
+    match jump.target {
+        DefinitionTarget::Split => run_cmd_noargs("split")?,
+        DefinitionTarget::OtherWindow | DefinitionTarget::Vsplit => run_cmd_noargs("vsplit")?,
+        DefinitionTarget::Tab => {
+            run_cmd_noargs("split")?;
+            run_cmd("wincmd", ["T"])?;
+        }
+        DefinitionTarget::FloatPeek => {}
+    }
+    Ok(i64::from(api::get_current_win().handle()))
+}
+
+fn open_definition_item(jump: DefinitionJump, item: &DefinitionItem) -> Result<()> {
+    if jump.target == DefinitionTarget::FloatPeek {
+        return open_definition_peek(item);
+    }
+    let target_handle = definition_target_window(jump)?;
+    open_definition_item_in_window(target_handle, item)
+}
+
 fn push_definition_items_to_qflist(
     lua: &mlua::Lua,
     title: Option<&str>,
@@ -852,35 +1310,91 @@ fn push_definition_items_to_qflist(
     Ok(())
 }
 
+fn buffer_name(bufnr: i64) -> Option<String> {
+    handles::valid_buffer(bufnr)
+        .and_then(|buf| buf.get_name().ok())
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !name.is_empty())
+}
+
+fn definition_item_label(item: &DefinitionItem) -> String {
+    let path = item
+        .filename()
+        .map(str::to_string)
+        .or_else(|| item.bufnr().and_then(buffer_name))
+        .unwrap_or_else(|| "[No Name]".to_string());
+    format!("{path}:{}:{}", item.lnum(), item.col())
+}
+
+fn pick_definition_item(
+    lua: &mlua::Lua,
+    jump: DefinitionJump,
+    title: Option<&str>,
+    items: Vec<DefinitionItem>,
+) -> Result<()> {
+    let vim: mlua::Table = lua.globals().get("vim").map_err(nvim_oxi::Error::from)?;
+    let ui: mlua::Table = vim.get("ui").map_err(nvim_oxi::Error::from)?;
+    let select: mlua::Function = ui.get("select").map_err(nvim_oxi::Error::from)?;
+
+    let labels = lua.create_table().map_err(nvim_oxi::Error::from)?;
+    for (index, item) in items.iter().enumerate() {
+        labels
+            .raw_set(index + 1, definition_item_label(item))
+            .map_err(nvim_oxi::Error::from)?;
+    }
+    let opts = lua.create_table().map_err(nvim_oxi::Error::from)?;
+    if let Some(title) = title {
+        opts.set("prompt", title).map_err(nvim_oxi::Error::from)?;
+    }
+    let on_choice = lua
+        .create_function(move |_, (_, index): (mlua::Value, Option<usize>)| {
+            let chosen = index
+                .and_then(|index| index.checked_sub(1))
+                .and_then(|index| items.get(index));
+            let result = match chosen {
+                Some(item) => open_definition_item(jump, item),
+                None => {
+                    jump.close_created_target();
+                    Ok(())
+                }
+            };
+            if let Err(err) = result {
+                notify::warn(
+                    LOG_CONTEXT,
+                    &format!("open picked definition failed: {err}"),
+                );
+            }
+            jump.finish();
+            Ok(())
+        })
+        .map_err(nvim_oxi::Error::from)?;
+    select
+        .call::<()>((labels, opts, on_choice))
+        .map_err(nvim_oxi::Error::from)?;
+    Ok(())
+}
+
 fn execute_definition_actions(
     lua: &mlua::Lua,
-    target_handle: i64,
-    created: bool,
+    jump: DefinitionJump,
     actions: Vec<DefinitionAction>,
 ) -> Result<()> {
     for action in actions {
         match action {
-            DefinitionAction::CloseCreatedTarget => {
-                close_created_target_window(target_handle, created);
-            }
-            DefinitionAction::OpenPrimary(item) => {
-                open_definition_item_in_window(target_handle, &item)?;
-            }
+            DefinitionAction::CloseCreatedTarget => jump.close_created_target(),
+            DefinitionAction::OpenPrimary(item) => open_definition_item(jump, &item)?,
             DefinitionAction::PushQuickfix { title, items } => {
                 push_definition_items_to_qflist(lua, title.as_deref(), &items)?;
             }
+            DefinitionAction::PickItem { title, items } => {
+                pick_definition_item(lua, jump, title.as_deref(), items)?;
+            }
         }
     }
     Ok(())
 }
 
-fn on_lsp_definition_list(
-    lua: &mlua::Lua,
-    opts: &mlua::Table,
-    target_handle: i64,
-    created: bool,
-    cur_handle: i64,
-) -> Result<()> {
+fn on_lsp_definition_list(lua: &mlua::Lua, opts: &mlua::Table, jump: DefinitionJump) -> Result<()> {
     let result = (|| -> Result<()> {
         let items_table = match opts.get::<Option<mlua::Table>>("items") {
             Ok(items) => items,
@@ -889,7 +1403,7 @@ fn on_lsp_definition_list(
                     LOG_CONTEXT,
                     &format!("invalid LSP definition list payload: {err}"),
                 );
-                close_created_target_window(target_handle, created);
+                jump.close_created_target();
                 return Ok(());
             }
         };
@@ -901,37 +1415,58 @@ fn on_lsp_definition_list(
                         LOG_CONTEXT,
                         &format!("invalid LSP definition item; skipping jump: {err}"),
                     );
-                    close_created_target_window(target_handle, created);
+                    jump.close_created_target();
                     return Ok(());
                 }
             },
             None => Vec::new(),
         };
-        let actions = plan_definition_actions(items, parse_definition_title(opts));
-        execute_definition_actions(lua, target_handle, created, actions)?;
+        let origin_file = buffer_name(jump.origin_buf);
+        let origin = DefinitionOrigin {
+            bufnr: jump.origin_buf,
+            filename: origin_file.as_deref(),
+        };
+        let actions =
+            plan_definition_actions(items, parse_definition_title(opts), origin, jump.multiple);
+        execute_definition_actions(lua, jump, actions)?;
         Ok(())
     })();
 
-    restore_current_window(cur_handle);
+    jump.finish();
     result
 }
 
-fn goto_definition_other_window() -> Result<()> {
-    let (target, created) = get_or_create_other_window()?;
+fn run_definition_request(request: DefinitionRequest) -> Result<()> {
     let cur = api::get_current_win();
-    let target_handle = i64::from(target.handle());
-    let cur_handle = i64::from(cur.handle());
+    let origin_win = i64::from(cur.handle());
+    let origin_buf = i64::from(api::get_current_buf().handle());
+    let other_win = if request.target == DefinitionTarget::OtherWindow {
+        let Some((target, created)) = select_or_create_other_window(request.window)? else {
+            return Ok(());
+        };
+        Some((i64::from(target.handle()), created))
+    } else {
+        None
+    };
+    let jump = DefinitionJump {
+        target: request.target,
+        multiple: request.multiple,
+        origin_win,
+        origin_buf,
+        other_win,
+    };
 
     let lua = lua::state();
     let vim: mlua::Table = lua.globals().get("vim").map_err(nvim_oxi::Error::from)?;
     let lsp: mlua::Table = vim.get("lsp").map_err(nvim_oxi::Error::from)?;
     let buf: mlua::Table = lsp.get("buf").map_err(nvim_oxi::Error::from)?;
-    let definition: mlua::Function = buf.get("definition").map_err(nvim_oxi::Error::from)?;
+    let list_items: mlua::Function = buf
+        .get(request.method.lsp_buf_function())
+        .map_err(nvim_oxi::Error::from)?;
 
     let on_list = lua
         .create_function(move |lua, opts: mlua::Table| {
-            if let Err(err) = on_lsp_definition_list(lua, &opts, target_handle, created, cur_handle)
-            {
+            if let Err(err) = on_lsp_definition_list(lua, &opts, jump) {
                 notify::warn(
                     LOG_CONTEXT,
                     &format!("lsp definition callback failed: {err}"),
@@ -945,16 +1480,39 @@ fn goto_definition_other_window() -> Result<()> {
     opts.set("on_list", on_list)
         .map_err(nvim_oxi::Error::from)?;
 
-    match definition.call::<()>(opts) {
+    let called = if request.method.takes_context() {
+        list_items.call::<()>((mlua::Value::Nil, opts))
+    } else {
+        list_items.call::<()>(opts)
+    };
+    match called {
         Ok(()) => Ok(()),
         Err(err) => {
-            close_created_target_window(target_handle, created);
-            restore_current_window(cur_handle);
+            jump.close_created_target();
+            restore_current_window(origin_win);
             Err(nvim_oxi::Error::from(err))
         }
     }
 }
 
+fn goto_definition_other_window() -> Result<()> {
+    run_definition_request(DefinitionRequest::default())
+}
+
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "nvim callback signatures pass owned Lua values by value"
+)]
+fn goto_definition(opts: Option<Dictionary>) -> Result<()> {
+    match parse_definition_request(opts.as_ref()) {
+        Ok(request) => run_definition_request(request),
+        Err(err) => {
+            notify::warn(LOG_CONTEXT, &err.to_string());
+            Ok(())
+        }
+    }
+}
+
 #[nvim_oxi::plugin]
 fn nvimrs_plugin_util() -> Dictionary {
     let mut api = Dictionary::new();
@@ -979,12 +1537,16 @@ fn nvimrs_plugin_util() -> Dictionary {
     );
     api.insert(
         "oil_select_other_window",
-        Function::<(), ()>::from_fn(|()| oil_select_other_window()),
+        Function::<Option<Dictionary>, ()>::from_fn(oil_select_other_window),
     );
     api.insert(
         "goto_definition_other_window",
         Function::<(), ()>::from_fn(|()| goto_definition_other_window()),
     );
+    api.insert(
+        "goto_definition",
+        Function::<Option<Dictionary>, ()>::from_fn(goto_definition),
+    );
     api.insert(
         "delete_current_buffer",
         Function::<(), ()>::from_fn(|()| delete_current_buffer()),
@@ -993,13 +1555,25 @@ fn nvimrs_plugin_util() -> Dictionary {
         "kill_window_and_buffer",
         Function::<(), ()>::from_fn(|()| kill_window_and_buffer()),
     );
+    api.insert(
+        "only_other_buffers",
+        Function::<Option<Dictionary>, ()>::from_fn(only_other_buffers),
+    );
+    api.insert(
+        "delete_hidden_buffers",
+        Function::<Option<Dictionary>, ()>::from_fn(delete_hidden_buffers),
+    );
+    api.insert(
+        "delete_buffers_outside_project",
+        Function::<Option<Dictionary>, ()>::from_fn(delete_buffers_outside_project),
+    );
     api.insert(
         "other_window",
-        Function::<(), Option<Window>>::from_fn(|()| other_window()),
+        Function::<Option<Dictionary>, Option<Window>>::from_fn(other_window),
     );
     api.insert(
         "get_or_create_other_window",
-        Function::<(), (Window, bool)>::from_fn(|()| get_or_create_other_window()),
+        Function::<Option<Dictionary>, Option<(Window, bool)>>::from_fn(get_or_create_other_window),
     );
     api
 }
@@ -0,0 +1,391 @@
+//! How "the other window" is chosen: next in order, ace-window labels, the
+//! largest window or the previously used one, and which way a split opens
+//! when no other window exists.
+
+use std::fmt;
+
+use nvim_oxi::Dictionary;
+use nvimrs_nvim_oxi_utils::dict;
+
+const ACE_LABEL_CHARS: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowStrategy {
+    #[default]
+    Next,
+    Ace,
+    Largest,
+    Mru,
+}
+
+impl WindowStrategy {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "next" => Some(Self::Next),
+            "ace" => Some(Self::Ace),
+            "largest" => Some(Self::Largest),
+            "mru" => Some(Self::Mru),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitDirection {
+    #[default]
+    Right,
+    Left,
+    Below,
+    Above,
+}
+
+impl SplitDirection {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "right" => Some(Self::Right),
+            "left" => Some(Self::Left),
+            "below" => Some(Self::Below),
+            "above" => Some(Self::Above),
+            _ => None,
+        }
+    }
+
+    pub const fn command(self) -> &'static str {
+        match self {
+            Self::Right => "rightbelow vsplit",
+            Self::Left => "leftabove vsplit",
+            Self::Below => "rightbelow split",
+            Self::Above => "leftabove split",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowSelectOpts {
+    pub strategy: WindowStrategy,
+    pub split: SplitDirection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownWindowOption {
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for UnknownWindowOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown window {} `{}`", self.field, self.value)
+    }
+}
+
+fn parse_window_field<T>(
+    opts: &Dictionary,
+    field: &'static str,
+    parse: fn(&str) -> Option<T>,
+) -> std::result::Result<Option<T>, UnknownWindowOption> {
+    let Some(value) = dict::get_string_nonempty(opts, field) else {
+        return Ok(None);
+    };
+    match parse(&value) {
+        Some(parsed) => Ok(Some(parsed)),
+        None => Err(UnknownWindowOption { field, value }),
+    }
+}
+
+/// Reads `{ strategy?, split? }`; absent keys keep their defaults and
+/// unknown values are rejected.
+pub fn parse_window_select_opts(
+    opts: Option<&Dictionary>,
+) -> std::result::Result<WindowSelectOpts, UnknownWindowOption> {
+    let defaults = WindowSelectOpts::default();
+    let Some(opts) = opts else {
+        return Ok(defaults);
+    };
+    Ok(WindowSelectOpts {
+        strategy: parse_window_field(opts, "strategy", WindowStrategy::parse)?
+            .unwrap_or(defaults.strategy),
+        split: parse_window_field(opts, "split", SplitDirection::parse)?.unwrap_or(defaults.split),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowCandidate {
+    pub win: i64,
+    pub floating: bool,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowCandidate {
+    fn area(self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AceLabel {
+    pub label: char,
+    pub win: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowChoice {
+    Use(i64),
+    Create(SplitDirection),
+    /// Draw each label over its window and wait for a key.
+    Prompt(Vec<AceLabel>),
+}
+
+/// Picks the window other than `current` to open something in. Floating
+/// windows are never chosen. Ace labels are only drawn when more than one
+/// window could be picked.
+pub fn plan_other_window(
+    opts: WindowSelectOpts,
+    windows: &[WindowCandidate],
+    current: i64,
+    previous: Option<i64>,
+) -> WindowChoice {
+    let tiled: Vec<WindowCandidate> = windows
+        .iter()
+        .copied()
+        .filter(|window| !window.floating)
+        .collect();
+    let others = || tiled.iter().filter(move |window| window.win != current);
+    let next = || {
+        let after_current = tiled
+            .iter()
+            .position(|window| window.win == current)
+            .map_or(0, |index| index + 1);
+        tiled
+            .iter()
+            .cycle()
+            .skip(after_current)
+            .take(tiled.len())
+            .find(|window| window.win != current)
+            .map(|window| window.win)
+    };
+    let chosen = match opts.strategy {
+        WindowStrategy::Next => next(),
+        WindowStrategy::Largest => others()
+            .copied()
+            .reduce(|best, window| {
+                if window.area() > best.area() {
+                    window
+                } else {
+                    best
+                }
+            })
+            .map(|window| window.win),
+        WindowStrategy::Mru => previous
+            .filter(|previous| others().any(|window| window.win == *previous))
+            .or_else(next),
+        WindowStrategy::Ace => {
+            if others().count() > 1 {
+                return WindowChoice::Prompt(
+                    others()
+                        .zip(ACE_LABEL_CHARS.chars())
+                        .map(|(window, label)| AceLabel {
+                            label,
+                            win: window.win,
+                        })
+                        .collect(),
+                );
+            }
+            next()
+        }
+    };
+    chosen.map_or(WindowChoice::Create(opts.split), WindowChoice::Use)
+}
+
+/// Window whose label is `key`; anything else cancels the prompt.
+pub fn ace_target(labels: &[AceLabel], key: &str) -> Option<i64> {
+    let mut chars = key.chars();
+    let (Some(key), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    labels
+        .iter()
+        .find(|label| label.label == key.to_ascii_lowercase())
+        .map(|label| label.win)
+}
+
+#[cfg(test)]
+mod tests {
+    use nvim_oxi::Dictionary;
+    use nvim_oxi::Object;
+
+    use super::AceLabel;
+    use super::SplitDirection;
+    use super::UnknownWindowOption;
+    use super::WindowCandidate;
+    use super::WindowChoice;
+    use super::WindowSelectOpts;
+    use super::WindowStrategy;
+    use super::ace_target;
+    use super::parse_window_select_opts;
+    use super::plan_other_window;
+
+    fn tiled(win: i64, width: u32, height: u32) -> WindowCandidate {
+        WindowCandidate {
+            win,
+            floating: false,
+            width,
+            height,
+        }
+    }
+
+    fn opts(strategy: WindowStrategy) -> WindowSelectOpts {
+        WindowSelectOpts {
+            strategy,
+            split: SplitDirection::Below,
+        }
+    }
+
+    #[test]
+    fn next_wraps_and_skips_floats() {
+        let float = WindowCandidate {
+            floating: true,
+            ..tiled(1002, 40, 10)
+        };
+        let windows = [tiled(1000, 80, 20), tiled(1001, 80, 20), float];
+
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Next), &windows, 1001, None),
+            WindowChoice::Use(1000)
+        );
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Next), &windows, 1002, None),
+            WindowChoice::Use(1000)
+        );
+    }
+
+    #[test]
+    fn single_window_creates_split_in_preferred_direction() {
+        let windows = [tiled(1000, 80, 20)];
+
+        for strategy in [
+            WindowStrategy::Next,
+            WindowStrategy::Ace,
+            WindowStrategy::Largest,
+            WindowStrategy::Mru,
+        ] {
+            assert_eq!(
+                plan_other_window(opts(strategy), &windows, 1000, Some(1000)),
+                WindowChoice::Create(SplitDirection::Below)
+            );
+        }
+    }
+
+    #[test]
+    fn largest_prefers_first_on_ties() {
+        let windows = [
+            tiled(1000, 120, 40),
+            tiled(1001, 60, 20),
+            tiled(1002, 60, 40),
+            tiled(1003, 40, 60),
+        ];
+
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Largest), &windows, 1000, None),
+            WindowChoice::Use(1002)
+        );
+    }
+
+    #[test]
+    fn mru_uses_previous_window_when_it_is_a_candidate() {
+        let windows = [
+            tiled(1000, 80, 20),
+            tiled(1001, 80, 20),
+            tiled(1002, 80, 20),
+        ];
+
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Mru), &windows, 1000, Some(1002)),
+            WindowChoice::Use(1002)
+        );
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Mru), &windows, 1000, Some(1000)),
+            WindowChoice::Use(1001)
+        );
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Mru), &windows, 1000, Some(77)),
+            WindowChoice::Use(1001)
+        );
+    }
+
+    #[test]
+    fn ace_prompts_only_with_more_than_two_windows() {
+        let two = [tiled(1000, 80, 20), tiled(1001, 80, 20)];
+        let three = [
+            tiled(1000, 80, 20),
+            tiled(1001, 80, 20),
+            tiled(1002, 80, 20),
+        ];
+
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Ace), &two, 1000, None),
+            WindowChoice::Use(1001)
+        );
+        assert_eq!(
+            plan_other_window(opts(WindowStrategy::Ace), &three, 1001, None),
+            WindowChoice::Prompt(vec![
+                AceLabel {
+                    label: 'a',
+                    win: 1000,
+                },
+                AceLabel {
+                    label: 's',
+                    win: 1002,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn ace_target_matches_single_label_key() {
+        let labels = [
+            AceLabel {
+                label: 'a',
+                win: 1000,
+            },
+            AceLabel {
+                label: 's',
+                win: 1002,
+            },
+        ];
+
+        assert_eq!(ace_target(&labels, "s"), Some(1002));
+        assert_eq!(ace_target(&labels, "A"), Some(1000));
+        assert_eq!(ace_target(&labels, "d"), None);
+        assert_eq!(ace_target(&labels, "\u{1b}"), None);
+        assert_eq!(ace_target(&labels, "as"), None);
+    }
+
+    #[test]
+    fn parse_window_select_opts_reads_fields_and_rejects_unknown() {
+        let opts = Dictionary::from_iter([
+            ("strategy", Object::from("largest")),
+            ("split", Object::from("above")),
+        ]);
+        let bad = Dictionary::from_iter([("strategy", Object::from("random"))]);
+
+        assert_eq!(
+            parse_window_select_opts(None),
+            Ok(WindowSelectOpts::default())
+        );
+        assert_eq!(
+            parse_window_select_opts(Some(&opts)),
+            Ok(WindowSelectOpts {
+                strategy: WindowStrategy::Largest,
+                split: SplitDirection::Above,
+            })
+        );
+        assert_eq!(
+            parse_window_select_opts(Some(&bad)),
+            Err(UnknownWindowOption {
+                field: "strategy",
+                value: "random".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Visit scoring shared by the on-disk MRU stores.
+
+use std::time::SystemTime;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// Visit count weighted by how recently something was used, in the spirit
+/// of Firefox's frecency buckets. Timestamps are Unix seconds.
+pub const fn frecency(visits: u64, last_visit: u64, now: u64) -> u64 {
+    let age = now.saturating_sub(last_visit);
+    let weight = if age < 4 * HOUR {
+        100
+    } else if age < DAY {
+        80
+    } else if age < 7 * DAY {
+        60
+    } else if age < 30 * DAY {
+        40
+    } else if age < 90 * DAY {
+        20
+    } else {
+        10
+    };
+    visits.saturating_mul(weight)
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_visits_outweigh_stale_ones() {
+        let now = 1_000 * DAY;
+        assert_eq!(frecency(1, now - HOUR, now), 100);
+        assert_eq!(frecency(5, now - 200 * DAY, now), 50);
+        assert!(frecency(1, now - 3 * DAY, now) > frecency(1, now - 60 * DAY, now));
+        assert_eq!(frecency(u64::MAX, now, now), u64::MAX);
+    }
+}
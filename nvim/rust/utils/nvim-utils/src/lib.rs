@@ -3,7 +3,9 @@ use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
+pub mod frecency;
 pub mod mode;
+pub mod store;
 
 pub mod path {
     use super::Component;
@@ -64,10 +66,41 @@ pub mod path {
     pub fn has_uri_scheme(value: &str) -> bool {
         split_uri_scheme_and_rest(value).is_some()
     }
+
+    /// Match `name` against a glob where `*` spans any run of characters and `?`
+    /// exactly one.
+    pub fn glob_matches(pattern: &str, name: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        let (mut p, mut n) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    backtrack = Some((p, n));
+                    p += 1;
+                }
+                Some(&ch) if ch == '?' || ch == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => {
+                    let Some((star, matched)) = backtrack else {
+                        return false;
+                    };
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+            }
+        }
+        pattern[p..].iter().all(|ch| *ch == '*')
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::path::glob_matches;
     use super::path::has_uri_scheme;
     use super::path::normalize_path;
     use super::path::split_uri_scheme_and_rest;
@@ -91,6 +124,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn glob_matches_star_and_question_mark() {
+        assert!(glob_matches("*.cabal", "app.cabal"));
+        assert!(glob_matches("*.sln", ".sln"));
+        assert!(glob_matches("a?c*", "abcdef"));
+        assert!(glob_matches("*a*b", "xaxxb"));
+        assert!(!glob_matches("*.cabal", "app.cabal.bak"));
+        assert!(!glob_matches("a?c", "ac"));
+    }
+
     #[test]
     fn has_uri_scheme_cases() {
         let cases = [
@@ -23,8 +23,20 @@ pub fn is_visual_like_mode(mode: &str) -> bool {
     matches!(mode.as_bytes().first(), Some(b'v' | b'V' | b'\x16'))
 }
 
+/// Returns true for charwise visual mode (`v`).
+pub fn is_charwise_visual_mode(mode: &str) -> bool {
+    mode.as_bytes().first() == Some(&b'v')
+}
+
+/// Returns true for blockwise visual mode (`CTRL-V`).
+pub fn is_blockwise_visual_mode(mode: &str) -> bool {
+    mode.as_bytes().first() == Some(&b'\x16')
+}
+
 #[cfg(test)]
 mod tests {
+    use super::is_blockwise_visual_mode;
+    use super::is_charwise_visual_mode;
     use super::is_cmdline_mode;
     use super::is_insert_like_mode;
     use super::is_replace_like_mode;
@@ -71,4 +83,19 @@ mod tests {
         assert!(is_visual_like_mode("\u{16}"));
         assert!(!is_visual_like_mode("n"));
     }
+
+    #[test]
+    fn detects_blockwise_visual_mode() {
+        assert!(is_blockwise_visual_mode("\u{16}"));
+        assert!(!is_blockwise_visual_mode("v"));
+        assert!(!is_blockwise_visual_mode("V"));
+        assert!(!is_blockwise_visual_mode("n"));
+    }
+
+    #[test]
+    fn detects_charwise_visual_mode() {
+        assert!(is_charwise_visual_mode("v"));
+        assert!(!is_charwise_visual_mode("V"));
+        assert!(!is_charwise_visual_mode("\u{16}"));
+    }
 }
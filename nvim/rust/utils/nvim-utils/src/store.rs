@@ -0,0 +1,281 @@
+//! Small on-disk stores shared by Neovim instances: lock-serialised
+//! read-modify-write with atomic replacement.
+
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const LOCK_ATTEMPTS: u32 = 20;
+const LOCK_RETRY: Duration = Duration::from_millis(10);
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum StoreError {
+    CreateDirectory { path: PathBuf, message: String },
+    ReadFile { path: PathBuf, message: String },
+    WriteFile { path: PathBuf, message: String },
+    Incompatible { path: PathBuf },
+    Locked { path: PathBuf },
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CreateDirectory { path, message } => write!(
+                f,
+                "failed to create directory '{}': {message}",
+                path.display()
+            ),
+            Self::ReadFile { path, message } => {
+                write!(f, "failed to read '{}': {message}", path.display())
+            }
+            Self::WriteFile { path, message } => {
+                write!(f, "failed to write '{}': {message}", path.display())
+            }
+            Self::Incompatible { path } => write!(
+                f,
+                "'{}' has an unknown version or is corrupt; leaving it untouched",
+                path.display()
+            ),
+            Self::Locked { path } => write!(
+                f,
+                "'{}' is locked by another instance; try again later",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+pub type StoreResult<T> = std::result::Result<T, StoreError>;
+
+/// Contents of `path`, or `None` when it does not exist yet.
+pub fn read_optional(path: &Path) -> StoreResult<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(raw) => Ok(Some(raw)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(StoreError::ReadFile {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Advisory lock shared by every instance writing `path`. Removed on
+/// drop; a lock older than `LOCK_STALE_AFTER` is assumed abandoned.
+struct FileLock {
+    path: PathBuf,
+    token: String,
+}
+
+/// Contents written into a lock file, unique to this acquisition so a lock
+/// can tell whether the file on disk is still its own.
+fn lock_token() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    format!(
+        "{} {nanos} {}",
+        process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > LOCK_STALE_AFTER)
+}
+
+/// Remove the abandoned lock at `path` whose contents were `observed`.
+/// Another instance may break the same lock and take a fresh one between
+/// the staleness check and the removal, so the lock is first renamed to a
+/// private name (only one instance can win that rename) and checked again
+/// there; a lock that turns out to be live is linked back into place.
+fn break_stale_lock(path: &Path, observed: &str) {
+    let private = with_suffix(path, &format!(".{}.stale", lock_token().replace(' ', "-")));
+    if fs::rename(path, &private).is_err() {
+        return;
+    }
+    let still_abandoned = is_stale(&private)
+        && fs::read_to_string(&private).is_ok_and(|contents| contents == observed);
+    if !still_abandoned {
+        let _ = fs::hard_link(&private, path);
+    }
+    let _ = fs::remove_file(&private);
+}
+
+impl FileLock {
+    fn acquire(target: &Path) -> StoreResult<Self> {
+        let path = with_suffix(target, ".lock");
+        let token = lock_token();
+        for _ in 0..LOCK_ATTEMPTS {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = file.write_all(token.as_bytes());
+                    return Ok(Self { path, token });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    if is_stale(&path) {
+                        let observed = fs::read_to_string(&path).unwrap_or_default();
+                        break_stale_lock(&path, &observed);
+                    } else {
+                        thread::sleep(LOCK_RETRY);
+                    }
+                }
+                Err(err) => {
+                    return Err(StoreError::WriteFile {
+                        path,
+                        message: err.to_string(),
+                    });
+                }
+            }
+        }
+        Err(StoreError::Locked {
+            path: target.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // A lock held past `LOCK_STALE_AFTER` may have been broken and
+        // re-taken by another instance; leave that one alone.
+        if fs::read_to_string(&self.path).is_ok_and(|contents| contents == self.token) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn write_atomic(path: &Path, contents: &str) -> StoreResult<()> {
+    let write_error = |err: std::io::Error| StoreError::WriteFile {
+        path: path.to_path_buf(),
+        message: err.to_string(),
+    };
+    let tmp = with_suffix(path, &format!(".{}.tmp", process::id()));
+    let mut file = fs::File::create(&tmp).map_err(write_error)?;
+    let written = file
+        .write_all(contents.as_bytes())
+        .and_then(|()| file.sync_all());
+    if let Err(err) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(write_error(err));
+    }
+    Ok(())
+}
+
+/// Run `update` while holding the lock for `path` and write the contents it
+/// returns, if any. Other instances are serialised through a lock file, and
+/// the write goes through a temporary file and a rename so readers never see
+/// a partial file. Fails with [`StoreError::Locked`] rather than writing
+/// unlocked when another instance holds the lock; callers keep their update
+/// and retry later.
+pub fn locked_update<T, F>(path: &Path, update: F) -> StoreResult<T>
+where
+    F: FnOnce() -> StoreResult<(T, Option<String>)>,
+{
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| StoreError::CreateDirectory {
+            path: parent.to_path_buf(),
+            message: err.to_string(),
+        })?;
+    }
+    let _lock = FileLock::acquire(path)?;
+    let (value, contents) = update()?;
+    if let Some(contents) = contents {
+        write_atomic(path, &contents)?;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locked_update_fails_without_writing_while_another_instance_holds_the_lock() {
+        let dir = std::env::temp_dir().join(format!("nvimrs-store-lock-{}", process::id()));
+        let path = dir.join("state.json");
+        let written = locked_update(&path, || Ok(((), Some("first".to_string()))));
+        assert!(written.is_ok());
+
+        let held = FileLock::acquire(&path);
+        assert!(held.is_ok());
+        let blocked = locked_update(&path, || Ok(((), Some("second".to_string()))));
+        assert!(matches!(blocked, Err(StoreError::Locked { .. })));
+        assert_eq!(fs::read_to_string(&path).ok().as_deref(), Some("first"));
+
+        drop(held);
+        let retried = locked_update(&path, || Ok(((), Some("second".to_string()))));
+        assert!(retried.is_ok());
+        assert_eq!(fs::read_to_string(&path).ok().as_deref(), Some("second"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn age_past_stale(path: &Path) {
+        let file = OpenOptions::new().write(true).open(path);
+        let aged = file.and_then(|file| {
+            file.set_modified(SystemTime::now() - LOCK_STALE_AFTER - Duration::from_secs(60))
+        });
+        assert!(aged.is_ok());
+    }
+
+    #[test]
+    fn locked_update_breaks_an_abandoned_lock() {
+        let dir = std::env::temp_dir().join(format!("nvimrs-store-stale-{}", process::id()));
+        let path = dir.join("state.json");
+        let lock = with_suffix(&path, ".lock");
+        assert!(fs::create_dir_all(&dir).is_ok());
+        assert!(fs::write(&lock, "crashed").is_ok());
+        age_past_stale(&lock);
+
+        let written = locked_update(&path, || Ok(((), Some("fresh".to_string()))));
+        assert!(written.is_ok());
+        assert_eq!(fs::read_to_string(&path).ok().as_deref(), Some("fresh"));
+        assert!(!lock.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn breaking_a_stale_lock_spares_one_retaken_in_the_meantime() {
+        let dir = std::env::temp_dir().join(format!("nvimrs-store-retaken-{}", process::id()));
+        let path = dir.join("state.json");
+        let lock = with_suffix(&path, ".lock");
+        assert!(fs::create_dir_all(&dir).is_ok());
+
+        // Another instance saw "crashed", broke it and took the lock before
+        // this one got round to removing what it had seen.
+        let held = FileLock::acquire(&path);
+        assert!(held.is_ok());
+        break_stale_lock(&lock, "crashed");
+        assert!(lock.exists());
+        assert!(matches!(
+            locked_update(&path, || Ok(((), None))),
+            Err(StoreError::Locked { .. })
+        ));
+
+        drop(held);
+        assert!(!lock.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,613 @@
+//! Base vs candidate comparison of one summary section. Every numeric field
+//! is reported; fields with a budget gate the run. All metrics are read as
+//! higher-is-worse, so a budget is the largest candidate/base ratio allowed,
+//! and a noise tolerance is the absolute increase ignored regardless of the
+//! ratio (it also covers a zero base, where no ratio exists).
+
+use std::collections::BTreeMap;
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::error::ReportError;
+use crate::schema::Schema;
+
+const DEFAULT_SECTION: &str = "phases";
+const DEFAULT_BUDGETS: [(&str, f64); 1] = [("avg_us", 1.10)];
+const COLUMN_GAP: usize = 2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CompareOptions {
+    pub(crate) section: String,
+    /// Largest candidate/base ratio allowed per metric.
+    pub(crate) budgets: BTreeMap<String, f64>,
+    /// Absolute increase per metric that never counts as a regression.
+    pub(crate) noise: BTreeMap<String, f64>,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            section: DEFAULT_SECTION.to_owned(),
+            budgets: DEFAULT_BUDGETS
+                .iter()
+                .map(|(metric, budget)| ((*metric).to_owned(), *budget))
+                .collect(),
+            noise: BTreeMap::new(),
+        }
+    }
+}
+
+fn parse_metric_value(option: &str, raw: &str) -> Result<(String, f64), ReportError> {
+    let invalid = || ReportError::InvalidCompareOption {
+        option: option.to_owned(),
+        value: raw.to_owned(),
+    };
+    let (metric, value) = raw.split_once('=').ok_or_else(invalid)?;
+    let value = value.parse::<f64>().map_err(|_| invalid())?;
+    if metric.is_empty() || !value.is_finite() || value < 0.0 {
+        return Err(invalid());
+    }
+    Ok((metric.to_owned(), value))
+}
+
+/// Reads `--section <name>`, `--budget <metric>=<ratio>` and
+/// `--noise <metric>=<abs>`. Any `--budget` replaces the default budgets.
+pub(crate) fn parse_compare_options<I>(args: I) -> Result<CompareOptions, ReportError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = CompareOptions::default();
+    let mut budgets = BTreeMap::new();
+    let mut args = args.into_iter();
+    while let Some(option) = args.next() {
+        let Some(value) = args.next() else {
+            return Err(ReportError::InvalidCompareOption {
+                option,
+                value: String::new(),
+            });
+        };
+        match option.as_str() {
+            "--section" => options.section = value,
+            "--budget" => {
+                let (metric, budget) = parse_metric_value(&option, &value)?;
+                budgets.insert(metric, budget);
+            }
+            "--noise" => {
+                let (metric, noise) = parse_metric_value(&option, &value)?;
+                options.noise.insert(metric, noise);
+            }
+            _ => return Err(ReportError::InvalidCompareOption { option, value }),
+        }
+    }
+    if !budgets.is_empty() {
+        options.budgets = budgets;
+    }
+    Ok(options)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum DeltaStatus {
+    /// No budget for this metric; reported only.
+    Info,
+    Ok,
+    Regression,
+    /// The row, or a budgeted metric of it, exists in the base but not in
+    /// the candidate.
+    Missing,
+    /// The row exists only in the candidate.
+    New,
+}
+
+impl DeltaStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "-",
+            Self::Ok => "ok",
+            Self::Regression => "REGRESSION",
+            Self::Missing => "MISSING",
+            Self::New => "new",
+        }
+    }
+
+    fn fails(self) -> bool {
+        matches!(self, Self::Regression | Self::Missing)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MetricDelta {
+    pub(crate) row: String,
+    pub(crate) metric: String,
+    pub(crate) base: Option<f64>,
+    pub(crate) candidate: Option<f64>,
+    pub(crate) budget: Option<f64>,
+    pub(crate) status: DeltaStatus,
+}
+
+impl MetricDelta {
+    fn ratio(&self) -> Option<f64> {
+        match (self.base, self.candidate) {
+            (Some(base), Some(candidate)) if base > 0.0 => Some(candidate / base),
+            _ => None,
+        }
+    }
+
+    fn delta(&self) -> Option<f64> {
+        Some(self.candidate? - self.base?)
+    }
+}
+
+fn judge(base: f64, candidate: f64, budget: Option<f64>, noise: f64) -> DeltaStatus {
+    let Some(budget) = budget else {
+        return DeltaStatus::Info;
+    };
+    if candidate - base <= noise {
+        return DeltaStatus::Ok;
+    }
+    if base > 0.0 && candidate / base <= budget {
+        return DeltaStatus::Ok;
+    }
+    DeltaStatus::Regression
+}
+
+fn numeric_fields(entry: &Map<String, Value>) -> BTreeMap<&str, f64> {
+    entry
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.as_number()?.as_f64()?)))
+        .collect()
+}
+
+/// A keyed collection yields one row per key; a singleton yields one row
+/// named after the section. Which one `section` is comes from the schema, so
+/// an empty collection has no rows rather than one empty row.
+fn section_rows<'a>(
+    summary: &'a Value,
+    section: &'a str,
+    keyed: bool,
+) -> Result<BTreeMap<&'a str, BTreeMap<&'a str, f64>>, ReportError> {
+    let Some(entries) = summary.get(section).and_then(Value::as_object) else {
+        return Err(ReportError::MissingSection {
+            section: section.to_owned(),
+        });
+    };
+    if !keyed {
+        return Ok(BTreeMap::from([(section, numeric_fields(entries))]));
+    }
+    Ok(entries
+        .iter()
+        .filter_map(|(key, entry)| Some((key.as_str(), numeric_fields(entry.as_object()?))))
+        .collect())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Comparison {
+    pub(crate) section: String,
+    pub(crate) deltas: Vec<MetricDelta>,
+}
+
+impl Comparison {
+    pub(crate) fn regressions(&self) -> usize {
+        self.deltas
+            .iter()
+            .filter(|delta| delta.status.fails())
+            .count()
+    }
+
+    /// Markdown section in the style of `perf/current.md`: a heading, the
+    /// inputs and regression count as bullets, and an aligned `text` table.
+    pub(crate) fn render_markdown(&self, base_label: &str, candidate_label: &str) -> String {
+        let header = [
+            "row",
+            "metric",
+            "base",
+            "candidate",
+            "delta",
+            "delta_pct",
+            "ratio",
+            "budget",
+            "status",
+        ]
+        .map(str::to_owned);
+        let rows = self
+            .deltas
+            .iter()
+            .map(|delta| {
+                [
+                    delta.row.clone(),
+                    delta.metric.clone(),
+                    format_value(delta.base),
+                    format_value(delta.candidate),
+                    delta
+                        .delta()
+                        .map_or_else(|| "n/a".to_owned(), |delta| format!("{delta:+.3}")),
+                    delta.ratio().map_or_else(
+                        || "n/a".to_owned(),
+                        |ratio| format!("{:+.2}%", (ratio - 1.0) * 100.0),
+                    ),
+                    delta
+                        .ratio()
+                        .map_or_else(|| "n/a".to_owned(), |ratio| format!("{ratio:.3}")),
+                    delta
+                        .budget
+                        .map_or_else(|| "-".to_owned(), |budget| format!("{budget:.3}")),
+                    delta.status.as_str().to_owned(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let mut out = format!("## Compare ({})\n\n", self.section);
+        out.push_str(&format!("- Base: `{base_label}`\n"));
+        out.push_str(&format!("- Candidate: `{candidate_label}`\n"));
+        out.push_str(&format!("- Regressions: {}\n\n", self.regressions()));
+        out.push_str("```text\n");
+        out.push_str(&render_columns(&header, &rows));
+        out.push_str("```\n");
+        out
+    }
+}
+
+fn format_value(value: Option<f64>) -> String {
+    value.map_or_else(|| "n/a".to_owned(), |value| format!("{value:.3}"))
+}
+
+fn render_columns<const N: usize>(header: &[String; N], rows: &[[String; N]]) -> String {
+    let mut widths = header.clone().map(|cell| cell.len());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    for row in std::iter::once(header).chain(rows) {
+        let mut line = String::new();
+        for (index, (cell, width)) in row.iter().zip(widths).enumerate() {
+            if index + 1 == N {
+                line.push_str(cell);
+            } else {
+                line.push_str(&format!("{cell:<width$}", width = width + COLUMN_GAP));
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+pub(crate) fn compare_summaries(
+    schema: &Schema,
+    base: &Value,
+    candidate: &Value,
+    options: &CompareOptions,
+) -> Result<Comparison, ReportError> {
+    let Some((_, spec)) = schema.record_for_output(&options.section) else {
+        return Err(ReportError::MissingSection {
+            section: options.section.clone(),
+        });
+    };
+    let base_rows = section_rows(base, &options.section, spec.is_keyed())?;
+    let candidate_rows = section_rows(candidate, &options.section, spec.is_keyed())?;
+    let mut deltas = Vec::new();
+    for (row, base_metrics) in &base_rows {
+        let Some(candidate_metrics) = candidate_rows.get(row) else {
+            deltas.push(MetricDelta {
+                row: (*row).to_owned(),
+                metric: "*".to_owned(),
+                base: None,
+                candidate: None,
+                budget: None,
+                status: DeltaStatus::Missing,
+            });
+            continue;
+        };
+        for (metric, base_value) in base_metrics {
+            let budget = options.budgets.get(*metric).copied();
+            let noise = options.noise.get(*metric).copied().unwrap_or(0.0);
+            let candidate_value = candidate_metrics.get(metric).copied();
+            let status = match (candidate_value, budget) {
+                (Some(candidate_value), _) => judge(*base_value, candidate_value, budget, noise),
+                (None, Some(_)) => DeltaStatus::Missing,
+                (None, None) => DeltaStatus::Info,
+            };
+            deltas.push(MetricDelta {
+                row: (*row).to_owned(),
+                metric: (*metric).to_owned(),
+                base: Some(*base_value),
+                candidate: candidate_value,
+                budget,
+                status,
+            });
+        }
+    }
+    for row in candidate_rows.keys() {
+        if !base_rows.contains_key(row) {
+            deltas.push(MetricDelta {
+                row: (*row).to_owned(),
+                metric: "*".to_owned(),
+                base: None,
+                candidate: None,
+                budget: None,
+                status: DeltaStatus::New,
+            });
+        }
+    }
+    Ok(Comparison {
+        section: options.section.clone(),
+        deltas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::CompareOptions;
+    use super::Comparison;
+    use super::DeltaStatus;
+    use super::compare_summaries;
+    use super::parse_compare_options;
+    use crate::schema::Schema;
+    use crate::schema::SchemaRegistry;
+
+    fn window_switch() -> Schema {
+        SchemaRegistry::bundled()
+            .and_then(|registry| registry.get("window-switch").cloned())
+            .expect("window-switch should be bundled")
+    }
+
+    fn phase(avg_us: f64, lua_memory_kib: f64) -> serde_json::Value {
+        json!({
+            "name": "ignored",
+            "avg_us": avg_us,
+            "lua_memory_kib": lua_memory_kib,
+            "iterations": 600
+        })
+    }
+
+    fn compare(
+        base: &serde_json::Value,
+        candidate: &serde_json::Value,
+        options: &CompareOptions,
+    ) -> Comparison {
+        compare_summaries(&window_switch(), base, candidate, options)
+            .expect("summaries should compare")
+    }
+
+    fn statuses(
+        base: &serde_json::Value,
+        candidate: &serde_json::Value,
+        options: &CompareOptions,
+    ) -> Vec<(String, String, DeltaStatus)> {
+        compare(base, candidate, options)
+            .deltas
+            .into_iter()
+            .map(|delta| (delta.row, delta.metric, delta.status))
+            .collect()
+    }
+
+    #[test]
+    fn default_budget_gates_avg_us_only() {
+        let base =
+            json!({ "phases": { "baseline": phase(100.0, 10.0), "recovery": phase(100.0, 10.0) } });
+        let candidate =
+            json!({ "phases": { "baseline": phase(109.0, 50.0), "recovery": phase(111.0, 10.0) } });
+
+        let comparison = compare(&base, &candidate, &CompareOptions::default());
+
+        assert_eq!(comparison.regressions(), 1);
+        assert_eq!(
+            statuses(&base, &candidate, &CompareOptions::default()),
+            vec![
+                ("baseline".to_owned(), "avg_us".to_owned(), DeltaStatus::Ok),
+                (
+                    "baseline".to_owned(),
+                    "iterations".to_owned(),
+                    DeltaStatus::Info
+                ),
+                (
+                    "baseline".to_owned(),
+                    "lua_memory_kib".to_owned(),
+                    DeltaStatus::Info
+                ),
+                (
+                    "recovery".to_owned(),
+                    "avg_us".to_owned(),
+                    DeltaStatus::Regression
+                ),
+                (
+                    "recovery".to_owned(),
+                    "iterations".to_owned(),
+                    DeltaStatus::Info
+                ),
+                (
+                    "recovery".to_owned(),
+                    "lua_memory_kib".to_owned(),
+                    DeltaStatus::Info
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn noise_tolerance_absorbs_small_absolute_increases() {
+        let base = json!({ "phases": { "tiny": { "avg_us": 2.0, "floating_windows": 0 } } });
+        let candidate = json!({ "phases": { "tiny": { "avg_us": 3.0, "floating_windows": 1 } } });
+        let options = parse_compare_options(
+            [
+                "--budget",
+                "avg_us=1.2",
+                "--budget",
+                "floating_windows=1.0",
+                "--noise",
+                "avg_us=1.5",
+            ]
+            .map(str::to_owned),
+        )
+        .expect("options should parse");
+
+        assert_eq!(
+            statuses(&base, &candidate, &options),
+            vec![
+                ("tiny".to_owned(), "avg_us".to_owned(), DeltaStatus::Ok),
+                (
+                    "tiny".to_owned(),
+                    "floating_windows".to_owned(),
+                    DeltaStatus::Regression
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_rows_fail_and_new_rows_are_reported() {
+        let base =
+            json!({ "phases": { "baseline": phase(10.0, 1.0), "stress": phase(10.0, 1.0) } });
+        let candidate =
+            json!({ "phases": { "baseline": phase(10.0, 1.0), "warmup": phase(1.0, 1.0) } });
+
+        let comparison = compare(&base, &candidate, &CompareOptions::default());
+        let stress = comparison
+            .deltas
+            .iter()
+            .find(|delta| delta.row == "stress")
+            .map(|delta| delta.status);
+        let warmup = comparison
+            .deltas
+            .iter()
+            .find(|delta| delta.row == "warmup")
+            .map(|delta| delta.status);
+
+        assert_eq!(stress, Some(DeltaStatus::Missing));
+        assert_eq!(warmup, Some(DeltaStatus::New));
+        assert_eq!(comparison.regressions(), 1);
+    }
+
+    #[test]
+    fn dropped_budgeted_metric_fails_and_dropped_info_metric_is_reported() {
+        let base = json!({ "phases": { "baseline": phase(10.0, 1.0) } });
+        let candidate = json!({ "phases": { "baseline": { "mean_us": 10.0, "iterations": 600 } } });
+
+        let comparison = compare(&base, &candidate, &CompareOptions::default());
+
+        assert_eq!(comparison.regressions(), 1);
+        assert_eq!(
+            statuses(&base, &candidate, &CompareOptions::default()),
+            vec![
+                (
+                    "baseline".to_owned(),
+                    "avg_us".to_owned(),
+                    DeltaStatus::Missing
+                ),
+                (
+                    "baseline".to_owned(),
+                    "iterations".to_owned(),
+                    DeltaStatus::Info
+                ),
+                (
+                    "baseline".to_owned(),
+                    "lua_memory_kib".to_owned(),
+                    DeltaStatus::Info
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn singleton_section_compares_as_one_row() {
+        let base = json!({ "summary": { "avg_us": 14.0, "retargets": 100, "mode": "fixed" } });
+        let candidate = json!({ "summary": { "avg_us": 14.5, "retargets": 100, "mode": "fixed" } });
+        let options = parse_compare_options(["--section", "summary"].map(str::to_owned))
+            .expect("options should parse");
+
+        assert_eq!(
+            statuses(&base, &candidate, &options),
+            vec![
+                ("summary".to_owned(), "avg_us".to_owned(), DeltaStatus::Ok),
+                (
+                    "summary".to_owned(),
+                    "retargets".to_owned(),
+                    DeltaStatus::Info
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_keyed_section_has_no_rows() {
+        let base = json!({ "validation": {} });
+        let candidate = json!({ "validation": { "stress": { "avg_us": 1.0 } } });
+        let options = parse_compare_options(["--section", "validation"].map(str::to_owned))
+            .expect("options should parse");
+
+        assert_eq!(statuses(&base, &base, &options), Vec::new());
+        assert_eq!(
+            statuses(&base, &candidate, &options),
+            vec![("stress".to_owned(), "*".to_owned(), DeltaStatus::New)]
+        );
+    }
+
+    #[test]
+    fn undeclared_section_is_rejected() {
+        let summary = json!({ "timings": { "avg_us": 1.0 } });
+        let options = parse_compare_options(["--section", "timings"].map(str::to_owned))
+            .expect("options should parse");
+
+        let err = compare_summaries(&window_switch(), &summary, &summary, &options)
+            .expect_err("timings is not a window-switch section");
+
+        assert_eq!(err.to_string(), "summary has no timings section to compare");
+    }
+
+    #[test]
+    fn parse_compare_options_rejects_malformed_values() {
+        for args in [
+            vec!["--budget", "avg_us"],
+            vec!["--budget", "avg_us=fast"],
+            vec!["--noise", "=2"],
+            vec!["--noise", "avg_us=-1"],
+            vec!["--budget"],
+            vec!["--threshold", "avg_us=1.1"],
+        ] {
+            assert!(
+                parse_compare_options(args.into_iter().map(str::to_owned)).is_err(),
+                "expected rejection"
+            );
+        }
+        assert_eq!(
+            parse_compare_options(Vec::new()).expect("defaults should parse"),
+            CompareOptions {
+                section: "phases".to_owned(),
+                budgets: BTreeMap::from([("avg_us".to_owned(), 1.10)]),
+                noise: BTreeMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn render_markdown_aligns_columns() {
+        let base = json!({ "phases": { "baseline": { "avg_us": 10.0 } } });
+        let candidate = json!({ "phases": { "baseline": { "avg_us": 12.0 } } });
+
+        let rendered = compare(&base, &candidate, &CompareOptions::default())
+            .render_markdown("base.log", "candidate.log");
+
+        assert_eq!(
+            rendered,
+            concat!(
+                "## Compare (phases)\n",
+                "\n",
+                "- Base: `base.log`\n",
+                "- Candidate: `candidate.log`\n",
+                "- Regressions: 1\n",
+                "\n",
+                "```text\n",
+                "row       metric  base    candidate  delta   delta_pct  ratio  budget  status\n",
+                "baseline  avg_us  10.000  12.000     +2.000  +20.00%    1.200  1.100   REGRESSION\n",
+                "```\n",
+            )
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::log::Schema;
+use crate::schema::FieldType;
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum ReportError {
@@ -24,23 +24,78 @@ pub(crate) enum ReportError {
     },
     #[error("unsupported perf schema {schema}")]
     UnsupportedSchema { schema: String },
-    #[error("expected {expected} PERF_JSON events, found {found}")]
-    UnexpectedSchema { expected: Schema, found: String },
+    #[error("failed to parse perf schemas from {origin}: {source}")]
+    InvalidSchemaFile {
+        origin: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("invalid perf schema {schema} in {origin}: {reason}")]
+    InvalidSchemaSpec {
+        origin: String,
+        schema: String,
+        reason: String,
+    },
+    #[error("expected {expected} PERF_JSON events, found {found} on line {line}")]
+    UnexpectedSchema {
+        line: usize,
+        expected: String,
+        found: String,
+    },
     #[error("no PERF_JSON events found in log")]
     MissingPerfJsonEvents,
     #[error("unsupported PERF_JSON version {version} on line {line}")]
     UnsupportedVersion { line: usize, version: u32 },
-    #[error("unsupported PERF_JSON event kind {kind} for {schema}")]
-    UnsupportedEventKind { schema: Schema, kind: String },
-    #[error("duplicate singleton PERF_JSON event {kind}")]
-    DuplicateSingletonEvent { kind: &'static str },
-    #[error("duplicate {collection} PERF_JSON event for key {key}")]
+    #[error("unsupported PERF_JSON event kind {kind} for {schema} on line {line}")]
+    UnsupportedEventKind {
+        line: usize,
+        schema: String,
+        kind: String,
+    },
+    #[error("PERF_JSON {kind} event on line {line} has a non-object payload")]
+    NonObjectPayload { line: usize, kind: String },
+    #[error("PERF_JSON {kind} event on line {line} is missing field {field}")]
+    MissingPayloadField {
+        line: usize,
+        kind: String,
+        field: String,
+    },
+    #[error(
+        "PERF_JSON {kind} event on line {line} has field {field} of the wrong type (expected {expected})"
+    )]
+    InvalidPayloadField {
+        line: usize,
+        kind: String,
+        field: String,
+        expected: FieldType,
+    },
+    #[error("duplicate singleton PERF_JSON event {kind} on line {line}")]
+    DuplicateSingletonEvent { line: usize, kind: String },
+    #[error("duplicate {collection} PERF_JSON event for key {key} on line {line}")]
     DuplicateKeyedEvent {
-        collection: &'static str,
+        line: usize,
+        collection: String,
         key: String,
     },
     #[error("missing required PERF_JSON field {path}")]
     MissingField { path: String },
     #[error("PERF_JSON field {path} is not a scalar value")]
     NonScalarField { path: String },
+    #[error("invalid compare option {option} {value}")]
+    InvalidCompareOption { option: String, value: String },
+    #[error("summary has no {section} section to compare")]
+    MissingSection { section: String },
+    #[error("{count} metric(s) exceeded their regression budget")]
+    BudgetExceeded { count: usize },
+}
+
+impl ReportError {
+    /// Process exit status: 2 when `compare` found regressions, so scripts
+    /// can tell a failed gate from a usage or input error (1).
+    pub(crate) fn exit_code(&self) -> i32 {
+        match self {
+            Self::BudgetExceeded { .. } => 2,
+            _ => 1,
+        }
+    }
 }
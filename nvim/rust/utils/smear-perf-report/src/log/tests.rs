@@ -1,9 +1,14 @@
 use std::io::Cursor;
 
 use pretty_assertions::assert_eq;
+use serde_json::json;
 
-use super::Schema;
 use super::load_summary_value_from_reader;
+use crate::schema::SchemaRegistry;
+
+fn bundled() -> SchemaRegistry {
+    SchemaRegistry::bundled().expect("bundled schemas should parse")
+}
 
 #[test]
 fn window_switch_summary_merges_phase_and_diagnostics_events() {
@@ -14,16 +19,28 @@ fn window_switch_summary_merges_phase_and_diagnostics_events() {
         "PERF_JSON {\"schema\":\"window-switch\",\"version\":1,\"kind\":\"diagnostics\",\"payload\":{\"phase\":\"post_recovery\",\"raw\":\"smear_cursor perf_class=full\",\"fields\":{\"perf_class\":\"full\",\"buffer_line_count\":12000}}}\n",
         "PERF_JSON {\"schema\":\"window-switch\",\"version\":1,\"kind\":\"summary\",\"payload\":{\"baseline_avg_us\":10.0,\"recovery_avg_us\":11.0,\"recovery_ratio\":1.1,\"recovery_wait_mode\":\"fixed\",\"recovery_wait_elapsed_ms\":250.0,\"recovery_reached_cold\":true,\"recovery_timed_out\":false,\"post_wait_floating_windows\":4,\"post_wait_visible_floating_windows\":2,\"post_wait_smear_floating_windows\":2,\"post_wait_visible_smear_floating_windows\":1}}\n"
     );
+    let registry = bundled();
+    let schema = registry
+        .get("window-switch")
+        .expect("window-switch should be bundled");
 
-    let summary = load_summary_value_from_reader(Schema::WindowSwitch, Cursor::new(log.as_bytes()))
+    let summary = load_summary_value_from_reader(schema, Cursor::new(log.as_bytes()))
         .expect("window-switch summary should parse");
 
+    assert_eq!(summary["schema"], "window-switch");
     assert_eq!(summary["scenario"]["name"], "planner_heavy");
     assert_eq!(summary["phases"]["baseline"]["avg_us"], 10.0);
     assert_eq!(
-        summary["diagnostics"]["post_recovery"]["perf_class"],
-        "full"
+        summary["diagnostics"]["post_recovery"],
+        json!({
+            "raw": "smear_cursor perf_class=full",
+            "perf_class": "full",
+            "buffer_line_count": 12000
+        })
     );
+    assert_eq!(summary["validation"], json!({}));
+    assert_eq!(summary["window_counts"], json!({}));
+    assert_eq!(summary.get("recovery_wait"), None);
     assert_eq!(
         summary["summary"]["post_wait_visible_smear_floating_windows"],
         1
@@ -37,12 +54,86 @@ fn particle_toggle_summary_keeps_summary_fields() {
         "PERF_JSON {\"schema\":\"particle-toggle\",\"version\":1,\"kind\":\"config\",\"payload\":{\"warmup_iterations\":600,\"benchmark_iterations\":2400,\"retarget_interval\":24,\"particles_enabled\":true,\"time_interval_ms\":8.333,\"particle_max_num\":100,\"anchor_count\":4}}\n",
         "PERF_JSON {\"schema\":\"particle-toggle\",\"version\":1,\"kind\":\"summary\",\"payload\":{\"avg_us\":14.2,\"avg_particles\":3.5,\"max_particles\":12,\"final_particles\":2,\"retargets\":100}}\n"
     );
+    let registry = bundled();
+    let schema = registry
+        .get("particle-toggle")
+        .expect("particle-toggle should be bundled");
 
-    let summary =
-        load_summary_value_from_reader(Schema::ParticleToggle, Cursor::new(log.as_bytes()))
-            .expect("particle-toggle summary should parse");
+    let summary = load_summary_value_from_reader(schema, Cursor::new(log.as_bytes()))
+        .expect("particle-toggle summary should parse");
 
     assert_eq!(summary["scenario"]["name"], "particles_on");
     assert_eq!(summary["config"]["particles_enabled"], true);
     assert_eq!(summary["summary"]["max_particles"], 12);
 }
+
+#[test]
+fn schema_file_summarizes_new_producer_without_code_changes() {
+    let mut registry = SchemaRegistry::default();
+    registry
+        .extend_from_str(
+            r#"{"schemas":{"scroll":{"records":{
+                "run":{"required":{"name":"string"}},
+                "sample":{"output":"samples","key":"step","required":{"avg_us":"number"}}
+            }}}}"#,
+            "scroll.json",
+        )
+        .expect("schema file should parse");
+    let schema = registry.get("scroll").expect("scroll should be registered");
+    let log = concat!(
+        "PERF_JSON {\"schema\":\"scroll\",\"version\":1,\"kind\":\"run\",\"payload\":{\"name\":\"page_down\"}}\n",
+        "PERF_JSON {\"schema\":\"scroll\",\"version\":1,\"kind\":\"sample\",\"payload\":{\"step\":\"warm\",\"avg_us\":3.5}}\n"
+    );
+
+    let summary = load_summary_value_from_reader(schema, Cursor::new(log.as_bytes()))
+        .expect("scroll summary should parse");
+
+    assert_eq!(
+        summary,
+        json!({
+            "schema": "scroll",
+            "version": 1,
+            "run": { "name": "page_down" },
+            "samples": { "warm": { "step": "warm", "avg_us": 3.5 } }
+        })
+    );
+}
+
+#[test]
+fn summary_errors_name_the_offending_line() {
+    let registry = bundled();
+    let schema = registry
+        .get("particle-toggle")
+        .expect("particle-toggle should be bundled");
+    let cases = [
+        (
+            "PERF_JSON {\"schema\":\"particle-toggle\",\"version\":2,\"kind\":\"scenario\",\"payload\":{\"name\":\"a\"}}\n",
+            "unsupported PERF_JSON version 2 on line 1",
+        ),
+        (
+            "noise\nPERF_JSON {\"schema\":\"particle-toggle\",\"version\":1,\"kind\":\"trace\",\"payload\":{}}\n",
+            "unsupported PERF_JSON event kind trace for particle-toggle on line 2",
+        ),
+        (
+            "PERF_JSON {\"schema\":\"particle-toggle\",\"version\":1,\"kind\":\"summary\",\"payload\":{\"avg_us\":1.0}}\n",
+            "PERF_JSON summary event on line 1 is missing field avg_particles",
+        ),
+        (
+            concat!(
+                "PERF_JSON {\"schema\":\"particle-toggle\",\"version\":1,\"kind\":\"scenario\",\"payload\":{\"name\":\"a\"}}\n",
+                "PERF_JSON {\"schema\":\"particle-toggle\",\"version\":1,\"kind\":\"scenario\",\"payload\":{\"name\":\"b\"}}\n",
+            ),
+            "duplicate singleton PERF_JSON event scenario on line 2",
+        ),
+        (
+            "PERF_JSON {\"schema\":\"window-switch\",\"version\":1,\"kind\":\"scenario\",\"payload\":{\"name\":\"a\"}}\n",
+            "expected particle-toggle PERF_JSON events, found window-switch on line 1",
+        ),
+    ];
+
+    for (log, expected) in cases {
+        let err = load_summary_value_from_reader(schema, Cursor::new(log.as_bytes()))
+            .expect_err("malformed log should be rejected");
+        assert_eq!(err.to_string(), expected);
+    }
+}
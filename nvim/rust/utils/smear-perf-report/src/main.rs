@@ -1,32 +1,60 @@
+mod compare;
 mod error;
 mod log;
 mod query;
+mod schema;
 
 use std::path::PathBuf;
 
+use compare::compare_summaries;
+use compare::parse_compare_options;
 use error::ReportError;
-use log::Schema;
 use log::load_summary_value;
 use query::render_query_row;
+use schema::SchemaRegistry;
 
 fn main() {
     if let Err(err) = run() {
         eprintln!("{err}");
-        std::process::exit(1);
+        std::process::exit(err.exit_code());
     }
 }
 
 fn run() -> Result<(), ReportError> {
-    let mut args = std::env::args().skip(1);
+    let mut args = std::env::args().skip(1).peekable();
+    let mut registry = SchemaRegistry::bundled()?;
+    while args.next_if(|arg| arg == "--schemas").is_some() {
+        let Some(schema_path) = args.next() else {
+            return Err(usage_error(&registry));
+        };
+        registry.load_file(&PathBuf::from(schema_path))?;
+    }
     let Some(command) = args.next() else {
-        return Err(usage_error());
+        return Err(usage_error(&registry));
     };
     let Some(schema) = args.next() else {
-        return Err(usage_error());
+        return Err(usage_error(&registry));
     };
-    let schema = Schema::parse(&schema)?;
+    let schema = registry.get(&schema)?;
+    if command == "compare" {
+        let (Some(base_path), Some(candidate_path)) = (args.next(), args.next()) else {
+            return Err(usage_error(&registry));
+        };
+        let options = parse_compare_options(args)?;
+        let base = load_summary_value(schema, &PathBuf::from(&base_path))?;
+        let candidate = load_summary_value(schema, &PathBuf::from(&candidate_path))?;
+        let comparison = compare_summaries(schema, &base, &candidate, &options)?;
+        print!(
+            "{}",
+            comparison.render_markdown(&base_path, &candidate_path)
+        );
+        return match comparison.regressions() {
+            0 => Ok(()),
+            count => Err(ReportError::BudgetExceeded { count }),
+        };
+    }
     let Some(log_path) = args.next() else {
-        return Err(usage_error());
+        return Err(usage_error(&registry));
     };
     let log_path = PathBuf::from(log_path);
     let summary = load_summary_value(schema, &log_path)?;
@@ -34,7 +62,7 @@ fn run() -> Result<(), ReportError> {
     match command.as_str() {
         "summary" => {
             if args.next().is_some() {
-                return Err(usage_error());
+                return Err(usage_error(&registry));
             }
             println!(
                 "{}",
@@ -46,18 +74,18 @@ fn run() -> Result<(), ReportError> {
         "query" => {
             let field_specs = args.collect::<Vec<_>>();
             if field_specs.is_empty() {
-                return Err(usage_error());
+                return Err(usage_error(&registry));
             }
             println!("{}", render_query_row(&summary, &field_specs)?);
             Ok(())
         }
-        _ => Err(usage_error()),
+        _ => Err(usage_error(&registry)),
     }
 }
 
-fn usage_error() -> ReportError {
-    ReportError::Usage(
-        "usage: nvimrs-smear-perf-report <summary|query> <window-switch|particle-toggle> <log-file> [field[=default] ...]"
-            .to_owned(),
-    )
+fn usage_error(registry: &SchemaRegistry) -> ReportError {
+    let schemas = registry.names().collect::<Vec<_>>().join("|");
+    ReportError::Usage(format!(
+        "usage: nvimrs-smear-perf-report [--schemas <file>]... <summary|query> <{schemas}> <log-file> [field[=default] ...]\n       nvimrs-smear-perf-report [--schemas <file>]... compare <{schemas}> <base-log> <candidate-log> [--section <name>] [--budget <metric>=<ratio>]... [--noise <metric>=<abs>]..."
+    ))
 }
@@ -0,0 +1,355 @@
+//! `PERF_JSON` schemas declared as data. Each schema lists the record kinds
+//! a producer emits, whether a kind is a singleton or a collection keyed by
+//! one payload field, and the payload fields it must carry. The bundled
+//! `schemas.json` covers the in-tree perf scripts; `--schemas <file>` adds
+//! schemas or replaces bundled ones by name.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::ReportError;
+
+const BUNDLED_SCHEMAS: &str = include_str!("../schemas.json");
+const BUNDLED_ORIGIN: &str = "bundled schemas.json";
+const RESERVED_OUTPUTS: [&str; 2] = ["schema", "version"];
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FieldType {
+    String,
+    Boolean,
+    Unsigned,
+    Number,
+    Object,
+    Any,
+}
+
+impl FieldType {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Boolean => value.is_boolean(),
+            Self::Unsigned => value.is_u64(),
+            Self::Number => value.is_number(),
+            Self::Object => value.is_object(),
+            Self::Any => true,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Boolean => "boolean",
+            Self::Unsigned => "unsigned",
+            Self::Number => "number",
+            Self::Object => "object",
+            Self::Any => "any",
+        }
+    }
+}
+
+impl std::fmt::Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RecordSpec {
+    /// Summary key the record lands under; defaults to the kind.
+    #[serde(default)]
+    output: Option<String>,
+    /// Payload field whose string value keys a collection. Without it the
+    /// kind is a singleton that may appear once per log.
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    required: BTreeMap<String, FieldType>,
+    /// Payload fields left out of the summary entry.
+    #[serde(default)]
+    omit: Vec<String>,
+    /// Object payload field whose entries are lifted into the summary entry.
+    #[serde(default)]
+    flatten: Option<String>,
+}
+
+impl RecordSpec {
+    pub(crate) fn output<'a>(&'a self, kind: &'a str) -> &'a str {
+        self.output.as_deref().unwrap_or(kind)
+    }
+
+    pub(crate) fn is_keyed(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Checks the payload shape and returns the collection key, if any.
+    pub(crate) fn validate(
+        &self,
+        kind: &str,
+        line: usize,
+        payload: &Value,
+    ) -> Result<Option<String>, ReportError> {
+        let Some(fields) = payload.as_object() else {
+            return Err(ReportError::NonObjectPayload {
+                line,
+                kind: kind.to_owned(),
+            });
+        };
+        let implied = self
+            .key
+            .iter()
+            .map(|key| (key, FieldType::String))
+            .chain(self.flatten.iter().map(|field| (field, FieldType::Object)));
+        let required = self
+            .required
+            .iter()
+            .map(|(field, expected)| (field, *expected));
+        for (field, expected) in implied.chain(required) {
+            let Some(value) = fields.get(field) else {
+                return Err(ReportError::MissingPayloadField {
+                    line,
+                    kind: kind.to_owned(),
+                    field: field.clone(),
+                });
+            };
+            if !expected.accepts(value) {
+                return Err(ReportError::InvalidPayloadField {
+                    line,
+                    kind: kind.to_owned(),
+                    field: field.clone(),
+                    expected,
+                });
+            }
+        }
+        Ok(self
+            .key
+            .as_ref()
+            .and_then(|key| fields.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_owned))
+    }
+
+    /// The summary entry for a validated payload.
+    pub(crate) fn summarize(&self, payload: Value) -> Value {
+        let Value::Object(mut fields) = payload else {
+            return payload;
+        };
+        for field in &self.omit {
+            fields.remove(field);
+        }
+        if let Some(Value::Object(lifted)) =
+            self.flatten.as_ref().and_then(|field| fields.remove(field))
+        {
+            for (name, value) in lifted {
+                fields.entry(name).or_insert(value);
+            }
+        }
+        Value::Object(fields)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaFile {
+    schemas: BTreeMap<String, SchemaFileEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaFileEntry {
+    records: BTreeMap<String, RecordSpec>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Schema {
+    name: String,
+    records: BTreeMap<String, RecordSpec>,
+}
+
+impl Schema {
+    fn new(
+        name: String,
+        records: BTreeMap<String, RecordSpec>,
+        origin: &str,
+    ) -> Result<Self, ReportError> {
+        let mut outputs = BTreeSet::new();
+        for (kind, spec) in &records {
+            let output = spec.output(kind);
+            if RESERVED_OUTPUTS.contains(&output) || !outputs.insert(output) {
+                return Err(ReportError::InvalidSchemaSpec {
+                    origin: origin.to_owned(),
+                    schema: name,
+                    reason: format!("record {kind} reuses summary key {output}"),
+                });
+            }
+        }
+        Ok(Self { name, records })
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The declared kind and its spec; the kind borrows from the schema.
+    pub(crate) fn record(&self, kind: &str) -> Option<(&str, &RecordSpec)> {
+        self.records
+            .get_key_value(kind)
+            .map(|(kind, spec)| (kind.as_str(), spec))
+    }
+
+    /// The record whose summary key is `output`.
+    pub(crate) fn record_for_output(&self, output: &str) -> Option<(&str, &RecordSpec)> {
+        self.records()
+            .find(|(kind, spec)| spec.output(kind) == output)
+    }
+
+    pub(crate) fn records(&self) -> impl Iterator<Item = (&str, &RecordSpec)> {
+        self.records
+            .iter()
+            .map(|(kind, spec)| (kind.as_str(), spec))
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SchemaRegistry {
+    schemas: BTreeMap<String, Schema>,
+}
+
+impl SchemaRegistry {
+    pub(crate) fn bundled() -> Result<Self, ReportError> {
+        let mut registry = Self::default();
+        registry.extend_from_str(BUNDLED_SCHEMAS, BUNDLED_ORIGIN)?;
+        Ok(registry)
+    }
+
+    pub(crate) fn load_file(&mut self, path: &Path) -> Result<(), ReportError> {
+        let source = std::fs::read_to_string(path).map_err(|source| ReportError::ReadFile {
+            path: path.display().to_string(),
+            source,
+        })?;
+        self.extend_from_str(&source, &path.display().to_string())
+    }
+
+    pub(crate) fn extend_from_str(
+        &mut self,
+        source: &str,
+        origin: &str,
+    ) -> Result<(), ReportError> {
+        let file = serde_json::from_str::<SchemaFile>(source).map_err(|source| {
+            ReportError::InvalidSchemaFile {
+                origin: origin.to_owned(),
+                source,
+            }
+        })?;
+        for (name, entry) in file.schemas {
+            let schema = Schema::new(name.clone(), entry.records, origin)?;
+            self.schemas.insert(name, schema);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Result<&Schema, ReportError> {
+        self.schemas
+            .get(name)
+            .ok_or_else(|| ReportError::UnsupportedSchema {
+                schema: name.to_owned(),
+            })
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.schemas.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::RecordSpec;
+    use super::SchemaRegistry;
+    use crate::error::ReportError;
+
+    #[test]
+    fn bundled_schemas_cover_in_tree_producers() {
+        let registry = SchemaRegistry::bundled().expect("bundled schemas should parse");
+
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["particle-toggle", "window-switch"]
+        );
+        let window_switch = registry
+            .get("window-switch")
+            .expect("window-switch should be bundled");
+        let (kind, phase) = window_switch.record("phase").expect("phase is declared");
+        assert_eq!(phase.output(kind), "phases");
+        assert!(phase.is_keyed());
+    }
+
+    #[test]
+    fn schema_file_rejects_summary_key_collisions() {
+        let mut registry = SchemaRegistry::default();
+        let source = r#"{"schemas":{"demo":{"records":{
+            "a":{"output":"shared"},
+            "b":{"output":"shared"}
+        }}}}"#;
+
+        let err = registry
+            .extend_from_str(source, "demo.json")
+            .expect_err("colliding outputs should be rejected");
+
+        assert_eq!(
+            err.to_string(),
+            "invalid perf schema demo in demo.json: record b reuses summary key shared"
+        );
+    }
+
+    #[test]
+    fn validate_reports_missing_and_mistyped_fields() {
+        let spec = serde_json::from_value::<RecordSpec>(json!({
+            "key": "phase",
+            "required": { "avg_us": "number" }
+        }))
+        .expect("record spec should parse");
+
+        let missing = spec.validate("phase", 7, &json!({ "avg_us": 1.0 }));
+        let mistyped = spec.validate("phase", 8, &json!({ "phase": "a", "avg_us": "fast" }));
+        let key = spec.validate("phase", 9, &json!({ "phase": "a", "avg_us": 2 }));
+
+        assert!(matches!(
+            missing,
+            Err(ReportError::MissingPayloadField { line: 7, .. })
+        ));
+        assert_eq!(
+            mistyped.map_err(|err| err.to_string()),
+            Err("PERF_JSON phase event on line 8 has field avg_us of the wrong type (expected number)".to_owned())
+        );
+        assert_eq!(key.ok().flatten(), Some("a".to_owned()));
+    }
+
+    #[test]
+    fn summarize_omits_and_flattens_fields() {
+        let spec = serde_json::from_value::<RecordSpec>(json!({
+            "key": "phase",
+            "omit": ["phase"],
+            "flatten": "fields"
+        }))
+        .expect("record spec should parse");
+
+        let entry = spec.summarize(json!({
+            "phase": "post_recovery",
+            "raw": "perf_class=full",
+            "fields": { "perf_class": "full", "raw": "ignored" }
+        }));
+
+        assert_eq!(
+            entry,
+            json!({ "raw": "perf_class=full", "perf_class": "full" })
+        );
+    }
+}